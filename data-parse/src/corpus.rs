@@ -0,0 +1,54 @@
+use std::{hash::Hash, path::Path};
+
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::{GraphStats, JavaLabel, ParseResult, ParsedScopeGraph, StatixLabel};
+
+/// Set of scope graphs that are analysed together, such as the commons-* projects
+#[derive(Debug)]
+pub struct Corpus<L: Hash + Eq = JavaLabel> {
+    /// Graphs in the order their paths were given, named after their file
+    pub graphs: Vec<(String, ParsedScopeGraph<L>)>,
+    /// Statistics of all graphs combined
+    pub stats: GraphStats,
+}
+
+impl<L: StatixLabel> Corpus<L> {
+    /// Parses every file in parallel, see [`ParsedScopeGraph::from_file`].
+    ///
+    /// Graphs are named after the file without its extension, e.g. `commons-csv-scopegraph`.
+    pub fn load<P: AsRef<Path> + Send>(paths: impl IntoIterator<Item = P>) -> ParseResult<Self> {
+        let paths = paths.into_iter().collect::<Vec<_>>();
+        // ParseError is not Send, so errors are turned into strings to get them out of the threads
+        let graphs = paths
+            .into_par_iter()
+            .map(|path| {
+                let path = path.as_ref();
+                let name = path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                ParsedScopeGraph::from_file(path)
+                    .map(|graph| (name, graph))
+                    .map_err(|e| format!("{}: {e}", path.display()))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        let mut stats = GraphStats::default();
+        for (_, graph) in &graphs {
+            stats.merge(&graph.stats());
+        }
+        Ok(Self { graphs, stats })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ParsedScopeGraph<L>> {
+        self.graphs
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, graph)| graph)
+    }
+
+    pub fn into_graphs(self) -> impl Iterator<Item = (String, ParsedScopeGraph<L>)> {
+        self.graphs.into_iter()
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
+use indicatif::ProgressBar;
+
+use crate::ParseResult;
+
+/// Compression of an input file, detected from its first bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+    const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+    pub fn detect(header: &[u8]) -> Self {
+        if header.starts_with(Self::GZIP_MAGIC) {
+            Self::Gzip
+        } else if header.starts_with(Self::ZSTD_MAGIC) {
+            Self::Zstd
+        } else {
+            Self::None
+        }
+    }
+
+    /// Feature that enables decompression
+    fn feature(&self) -> &'static str {
+        match self {
+            Self::None => "",
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        }
+    }
+}
+
+/// Opens `path` for reading, decompressing it if it is gzip or zstd compressed.
+///
+/// Decompression needs the `gzip` or `zstd` feature, compressed files fail to open without it.
+pub fn open_input<P: AsRef<Path>>(path: P) -> ParseResult<Box<dyn Read + Send>> {
+    decompress(File::open(&path)?, path.as_ref())
+}
+
+/// Same as [`open_input`], counting the bytes read from the file on `bar`
+pub(crate) fn open_input_with_progress<P: AsRef<Path>>(
+    path: P,
+    bar: &ProgressBar,
+) -> ParseResult<Box<dyn Read + Send>> {
+    let file = File::open(&path)?;
+    bar.set_length(file.metadata()?.len());
+    decompress(bar.wrap_read(file), path.as_ref())
+}
+
+fn decompress(
+    reader: impl Read + Send + 'static,
+    path: &Path,
+) -> ParseResult<Box<dyn Read + Send>> {
+    let mut buf = BufReader::new(reader);
+    let compression = Compression::detect(buf.fill_buf()?);
+    match compression {
+        Compression::None => Ok(Box::new(buf)),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Ok(Box::new(BufReader::new(
+            flate2::bufread::MultiGzDecoder::new(buf),
+        ))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Ok(Box::new(BufReader::new(
+            zstd::stream::read::Decoder::with_buffer(buf)?,
+        ))),
+        #[allow(unreachable_patterns)]
+        _ => Err(format!(
+            "{} is {compression:?} compressed, enable the `{}` feature to read it",
+            path.display(),
+            compression.feature()
+        )
+        .into()),
+    }
+}
+
+/// Extension of `path` without the compression extension, e.g. `json` for `graph.json.gz`
+pub fn input_extension(path: &Path) -> Option<&str> {
+    let extension = path.extension().and_then(|e| e.to_str());
+    match extension {
+        Some("gz" | "zst") => path
+            .file_stem()
+            .map(Path::new)
+            .and_then(|stem| stem.extension())
+            .and_then(|e| e.to_str()),
+        _ => extension,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_compression() {
+        assert_eq!(Compression::detect(b"{\"data\""), Compression::None);
+        assert_eq!(Compression::detect(&[0x1f, 0x8b, 0x08]), Compression::Gzip);
+        assert_eq!(
+            Compression::detect(&[0x28, 0xb5, 0x2f, 0xfd, 0x00]),
+            Compression::Zstd
+        );
+        assert_eq!(Compression::detect(&[]), Compression::None);
+    }
+
+    #[test]
+    fn test_input_extension() {
+        assert_eq!(input_extension(Path::new("a/graph.json")), Some("json"));
+        assert_eq!(input_extension(Path::new("a/graph.json.gz")), Some("json"));
+        assert_eq!(input_extension(Path::new("graph.aterm.zst")), Some("aterm"));
+        assert_eq!(input_extension(Path::new("graph")), None);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_open_gzip() {
+        use std::io::Write;
+
+        let path =
+            std::env::temp_dir().join(format!("open-input-test-{}.json.gz", std::process::id()));
+        let mut encoder = flate2::write::GzEncoder::new(
+            File::create(&path).unwrap(),
+            flate2::Compression::fast(),
+        );
+        encoder.write_all(b"[1, 2]").unwrap();
+        encoder.finish().unwrap();
+
+        let mut contents = String::new();
+        open_input(&path)
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "[1, 2]");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,6 +1,14 @@
+mod corpus;
+pub use corpus::*;
 mod parsed;
 pub use parsed::*;
 mod error;
 pub use error::*;
+mod input;
+pub use input::*;
+mod progress;
+pub use progress::*;
 mod raw;
 pub use raw::*;
+mod replay;
+pub use replay::*;
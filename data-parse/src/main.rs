@@ -27,12 +27,15 @@ const SCOPEGRAPH_FILE: &str = "commons-io-scopegraph.json";
 
 fn main() -> ParseResult<()> {
     // queries_data()?;
+    // replay_queries()?;
+    // verify_queries()?;
+    // export_workload()?;
     // scopegraph_data()?;
     parsed_scopegraph_data()?;
     Ok(())
 }
 
-// attempt to parse query data; unsucessful
+/// Counts the distinct data wellformedness and data order predicates of the queries
 fn queries_data() -> ParseResult<()> {
     let file = File::open(format!("{}/{}", BASE_PATH, QUERIES_FILE))?;
     let mut buf = BufReader::new(file);
@@ -41,25 +44,90 @@ fn queries_data() -> ParseResult<()> {
     let mut deserializer = serde_json::Deserializer::from_reader(&mut buf);
     deserializer.disable_recursion_limit();
 
-    let mut des: Vec<RawQueryData> = Deserialize::deserialize(&mut deserializer)?;
-    let mut iter = des.iter().enumerate();
-    let (_, first) = iter.next().unwrap(); // first is trivial, wildcard = wildcard
-    let (_, eighth) = iter.nth(7).unwrap(); // eighth has 3 wildcard = 3 wildcards?
-    for (idx, d) in iter {
-        if first.dataOrd != d.dataOrd && eighth.dataOrd != d.dataOrd {
-            println!("d: {0:#?}", idx);
-        }
+    let des: Vec<RawQueryData> = Deserialize::deserialize(&mut deserializer)?;
+    println!("Deserialization took: {:?}", timer.elapsed());
+
+    let mut data_wfs = HashMap::new();
+    let mut data_ords = HashMap::new();
+    for d in &des {
+        let wf = DataWf::from(&d.dataWf);
+        // names differ for every query, so count the wellformedness without them
+        let shape = match wf.name() {
+            Some(_) => "name match".to_string(),
+            None => wf.to_string(),
+        };
+        *data_wfs.entry(shape).or_insert(0) += 1;
+        let ord = DataOrd::from(&d.dataOrd);
+        *data_ords
+            .entry((ord.to_string(), ord.equivalence()))
+            .or_insert(0) += 1;
     }
 
-    let mut d = des.get_mut(7871).unwrap();
-    // let mut d = des.get_mut(7871).unwrap();
+    for (wf, count) in data_wfs {
+        println!("{count:>6} dataWf {wf}");
+    }
+    for ((ord, equivalence), count) in data_ords {
+        println!("{count:>6} dataOrd {ord} ({equivalence:?})");
+    }
+    Ok(())
+}
 
-    d.dataWf.params.iter_mut().for_each(|wf| {
-        wf.flatten_arrs();
-    });
+/// Replays the recorded queries with and without the resolve cache
+fn replay_queries() -> ParseResult<()> {
+    let parsed_graph: ParsedScopeGraph =
+        ParsedScopeGraph::from_file(format!("{BASE_PATH}/{SCOPEGRAPH_FILE}"))?;
+    let queries = RecordedQuery::from_file(format!("{BASE_PATH}/{QUERIES_FILE}"))?;
+    for caching in [true, false] {
+        let mut replay = QueryReplay::new(&parsed_graph).with_caching(caching);
+        let timer = std::time::Instant::now();
+        let result = replay.replay(&queries);
+        println!(
+            "caching: {caching}, took {:?}, {} queries ({} skipped, {} empty), \
+            {} edges traversed, {} cache hits",
+            timer.elapsed(),
+            result.num_queries,
+            result.num_skipped,
+            result.num_empty,
+            result.stats.edges_traversed,
+            result.stats.cache_hits,
+        );
+    }
+    Ok(())
+}
 
-    println!("d: {0:#?}", d.dataWf);
+/// Writes the graph and recorded queries as workload for the `workload` benchmark of scope-graph
+fn export_workload() -> ParseResult<()> {
+    let parsed_graph: ParsedScopeGraph =
+        ParsedScopeGraph::from_file(format!("{BASE_PATH}/{SCOPEGRAPH_FILE}"))?;
+    let queries = RecordedQuery::from_file(format!("{BASE_PATH}/{QUERIES_FILE}"))?;
+    let workload = QueryReplay::new(&parsed_graph).to_workload("commons-csv", &queries);
+    std::fs::create_dir_all("./output")?;
+    workload.to_json_file("./output/workload.json")?;
+    println!(
+        "Written {} of {} queries to output/workload.json",
+        workload.queries.len(),
+        queries.len()
+    );
+    Ok(())
+}
 
+/// Compares the environments of the resolver with the ones Statix computed
+fn verify_queries() -> ParseResult<()> {
+    let parsed_graph: ParsedScopeGraph =
+        ParsedScopeGraph::from_file(format!("{BASE_PATH}/{SCOPEGRAPH_FILE}"))?;
+    let queries = RecordedQuery::from_file(format!("{BASE_PATH}/{QUERIES_FILE}"))?;
+    let results = RecordedEnv::results_from_file(format!("{BASE_PATH}/{RESULTS_FILE}"))?;
+    let verification = QueryReplay::new(&parsed_graph).verify(&queries, &results);
+    for mismatch in &verification.mismatches {
+        println!("{mismatch}");
+    }
+    println!(
+        "{} matches, {} mismatches, {} skipped, {} unmatched",
+        verification.num_matches,
+        verification.mismatches.len(),
+        verification.num_skipped,
+        verification.num_unmatched
+    );
     Ok(())
 }
 
@@ -79,8 +147,16 @@ fn parsed_scopegraph_data() -> ParseResult<()> {
     // });
     println!("parsed_graph.len(): {0:?}", parsed_graph.scopes.len());
     std::fs::create_dir_all("./output/")?;
+    let stats = parsed_graph.stats();
+    stats.to_json("./output/stats.json")?;
+    stats.to_csv("./output/stats.csv")?;
+    println!("Written statistics to output/stats.json and output/stats.csv");
     parsed_graph.to_cosmograph_csv("./output/cosmo.csv")?;
     println!("Written scope graph to output/cosmo.csv");
+    parsed_graph.to_gexf("./output/scopegraph.gexf")?;
+    println!("Written scope graph to output/scopegraph.gexf");
+    parsed_graph.to_cypher("./output/scopegraph.cypher")?;
+    println!("Written scope graph to output/scopegraph.cypher");
     Ok(())
 }
 
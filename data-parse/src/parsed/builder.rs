@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use crate::{ParseResult, ParsedEdge, ParsedScope, ParsedScopeGraph, ScopeData, StatixLabel};
+
+/// Builds a [`ParsedScopeGraph`] without a raw dump, e.g. for small test fixtures.
+///
+/// Edges refer to scopes by name, so names have to be unique across resources.
+/// Labels are added in the order they are first used.
+pub struct ParsedScopeGraphBuilder<L> {
+    scopes: Vec<(ParsedScope, ScopeData)>,
+    edges: Vec<(String, L, String)>,
+    labels: Vec<L>,
+}
+
+impl<L: StatixLabel> Default for ParsedScopeGraphBuilder<L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L: StatixLabel> ParsedScopeGraphBuilder<L> {
+    pub fn new() -> Self {
+        Self {
+            scopes: Vec::new(),
+            edges: Vec::new(),
+            labels: Vec::new(),
+        }
+    }
+
+    pub fn add_scope(self, name: impl AsRef<str>, resource: impl AsRef<str>) -> Self {
+        self.add_scope_with_data(name, resource, ScopeData::None)
+    }
+
+    pub fn add_scope_with_data(
+        mut self,
+        name: impl AsRef<str>,
+        resource: impl AsRef<str>,
+        data: ScopeData,
+    ) -> Self {
+        self.scopes.push((ParsedScope::new(name, resource), data));
+        self
+    }
+
+    /// Adds an edge between the scopes named `from` and `to`, they can be added after the edge
+    pub fn add_edge(mut self, from: impl Into<String>, label: L, to: impl Into<String>) -> Self {
+        if !self.labels.contains(&label) {
+            self.labels.push(label.clone());
+        }
+        self.edges.push((from.into(), label, to.into()));
+        self
+    }
+
+    /// Fails if an edge refers to a scope that was not added, or a name is used for multiple scopes
+    pub fn build(self) -> ParseResult<ParsedScopeGraph<L>> {
+        let mut by_name = HashMap::new();
+        for (scope, _) in &self.scopes {
+            if by_name.insert(scope.name.clone(), scope.clone()).is_some() {
+                return Err(format!("Scope name {} is used more than once", scope.name).into());
+            }
+        }
+        let lookup = |name: &str| {
+            by_name
+                .get(name)
+                .cloned()
+                .ok_or_else(|| format!("Edge refers to unknown scope {name}"))
+        };
+        let edges = self
+            .edges
+            .into_iter()
+            .map(|(from, label, to)| {
+                Ok(ParsedEdge {
+                    from: lookup(&from)?,
+                    to: lookup(&to)?,
+                    label,
+                })
+            })
+            .collect::<ParseResult<Vec<_>>>()?;
+
+        Ok(ParsedScopeGraph {
+            scopes: self.scopes.into_iter().collect(),
+            edges,
+            labels: self.labels,
+        })
+    }
+}
+
+impl<L: StatixLabel> ParsedScopeGraph<L> {
+    pub fn builder() -> ParsedScopeGraphBuilder<L> {
+        ParsedScopeGraphBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JavaLabel;
+
+    #[test]
+    fn test_builder() {
+        let graph = ParsedScopeGraph::builder()
+            .add_scope("s-1", "/./A.java")
+            .add_edge("s-1", JavaLabel::Parent, "s-2")
+            .add_edge("s-1", JavaLabel::Parent, "s-2")
+            .add_scope("s-2", "/./B.java")
+            .build()
+            .unwrap();
+        assert_eq!(graph.scopes.len(), 2);
+        assert_eq!(graph.edges.len(), 2);
+        assert_eq!(graph.edges[0].to, ParsedScope::new("s-2", "/./B.java"));
+        assert_eq!(graph.labels, vec![JavaLabel::Parent]);
+
+        let unknown = ParsedScopeGraph::builder()
+            .add_scope("s-1", "/./A.java")
+            .add_edge("s-1", JavaLabel::Parent, "s-3")
+            .build();
+        assert!(unknown.is_err());
+
+        let ambiguous = ParsedScopeGraph::<JavaLabel>::builder()
+            .add_scope("s-1", "/./A.java")
+            .add_scope("s-1", "/./B.java")
+            .build();
+        assert!(ambiguous.is_err());
+    }
+}
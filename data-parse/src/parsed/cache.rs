@@ -0,0 +1,235 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{ParseResult, report};
+
+/// Version of the parsed format, bump this when [`super::ParsedScopeGraph`] or its parsing changes
+/// so caches written by older versions are not read.
+pub const PARSER_VERSION: u32 = 1;
+
+/// Environment variable that overrides the directory of [`GraphCache::default`]
+pub const CACHE_DIR_VAR: &str = "SCOPE_GRAPH_CACHE_DIR";
+
+/// Identifies the parsed contents of a source file.
+///
+/// Hashes the contents of the file, the parser version and the type that is cached,
+/// so graphs with different labels do not share a cache.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    pub version: u32,
+    pub hash: u64,
+}
+
+impl CacheKey {
+    pub fn of<T>(source: &Path) -> ParseResult<Self> {
+        let mut hasher = Fnv1a::default();
+        hasher.write(&PARSER_VERSION.to_le_bytes());
+        hasher.write(std::any::type_name::<T>().as_bytes());
+
+        let mut file = File::open(source)?;
+        let mut buf = vec![0; 1 << 16];
+        loop {
+            let n = file.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            hasher.write(&buf[..n]);
+        }
+        Ok(Self {
+            version: PARSER_VERSION,
+            hash: hasher.0,
+        })
+    }
+}
+
+/// FNV-1a, used because the std hasher is not guaranteed to be the same across Rust versions
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+/// Cache file, the key is stored with the data to check that it belongs to the source file
+#[derive(Serialize)]
+struct CacheEntryRef<'a, T> {
+    key: CacheKey,
+    data: &'a T,
+}
+
+#[derive(Deserialize)]
+struct CacheEntry<T> {
+    key: CacheKey,
+    data: T,
+}
+
+/// Directory with parsed versions of source files, so they only have to be parsed once.
+///
+/// Cache files are named after the source file and its [`CacheKey`],
+/// a source file that changed gets a new cache file instead of reading the old one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphCache {
+    dir: PathBuf,
+}
+
+/// Uses `$SCOPE_GRAPH_CACHE_DIR` if it is set, else `scope-graph-cache` in the temp directory of the OS
+impl Default for GraphCache {
+    fn default() -> Self {
+        let dir = std::env::var_os(CACHE_DIR_VAR)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| std::env::temp_dir().join("scope-graph-cache"));
+        Self::new(dir)
+    }
+}
+
+impl GraphCache {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Reads the cached data of `source`, fails if there is none or it does not match `key`
+    pub fn read<T: DeserializeOwned>(&self, source: &Path, key: CacheKey) -> ParseResult<T> {
+        let path = self.path(source, key);
+        let file = OpenOptions::new().read(true).open(&path)?;
+        let mut buf = BufReader::new(file);
+        let timer = std::time::Instant::now();
+        let mut deserializer = serde_json::Deserializer::from_reader(&mut buf);
+        deserializer.disable_recursion_limit();
+        let entry: CacheEntry<T> = Deserialize::deserialize(&mut deserializer)?;
+        if entry.key != key {
+            return Err(format!("Cache {path:?} has key {:?}, expected {key:?}", entry.key).into());
+        }
+        report(format!(
+            "Deserialization from cache took: {:?}",
+            timer.elapsed()
+        ));
+        Ok(entry.data)
+    }
+
+    pub fn write<T: Serialize>(&self, source: &Path, key: CacheKey, data: &T) -> ParseResult<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.path(source, key);
+        report(format!("Caching graph to: {path:?}"));
+        let file = OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .create(true)
+            .open(&path)?;
+        let mut buf = BufWriter::new(file);
+        serde_json::to_writer(&mut buf, &CacheEntryRef { key, data })?;
+        buf.flush()?;
+        self.remove_stale(source, &path)?;
+        Ok(())
+    }
+
+    /// Removes the caches of older versions of `source`, all its cache files except `current`
+    fn remove_stale(&self, source: &Path, current: &Path) -> ParseResult<()> {
+        let prefix = format!("{}-v", Self::file_stem(source));
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            // `<version>-<hash>`, so the caches of sources whose name starts with the same stem are kept
+            let Some(suffix) = name
+                .strip_prefix(&prefix)
+                .and_then(|n| n.strip_suffix(".json"))
+            else {
+                continue;
+            };
+            let is_cache = suffix.split_once('-').is_some_and(|(version, hash)| {
+                !version.is_empty()
+                    && version.bytes().all(|b| b.is_ascii_digit())
+                    && hash.len() == 16
+                    && hash.bytes().all(|b| b.is_ascii_hexdigit())
+            });
+            if is_cache && path != current {
+                std::fs::remove_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the cache of `source` for `key`, a missing cache is not an error
+    pub fn remove(&self, source: &Path, key: CacheKey) -> ParseResult<()> {
+        match std::fs::remove_file(self.path(source, key)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn path(&self, source: &Path, key: CacheKey) -> PathBuf {
+        self.dir.join(format!(
+            "{}-v{}-{:016x}.json",
+            Self::file_stem(source),
+            key.version,
+            key.hash
+        ))
+    }
+
+    fn file_stem(source: &Path) -> std::borrow::Cow<'_, str> {
+        source
+            .file_stem()
+            .map(|f| f.to_string_lossy())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_invalidated_on_change() {
+        let dir =
+            std::env::temp_dir().join(format!("scope-graph-cache-test-{}", std::process::id()));
+        let cache = GraphCache::new(&dir);
+        let source = dir.join("graph.json");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        std::fs::write(&source, "old").unwrap();
+        let key = CacheKey::of::<String>(&source).unwrap();
+        cache.write(&source, key, &"parsed".to_string()).unwrap();
+        assert_eq!(cache.read::<String>(&source, key).unwrap(), "parsed");
+        // same contents, but cached as a different type
+        assert_ne!(CacheKey::of::<u32>(&source).unwrap(), key);
+
+        std::fs::write(&source, "new").unwrap();
+        let new_key = CacheKey::of::<String>(&source).unwrap();
+        assert_ne!(new_key, key);
+        assert!(cache.read::<String>(&source, new_key).is_err());
+
+        // writing the new version removes the old one, but not the caches of other sources
+        let other = dir.join("graph-v2.json");
+        std::fs::write(&other, "other").unwrap();
+        let other_key = CacheKey::of::<String>(&other).unwrap();
+        cache
+            .write(&other, other_key, &"other".to_string())
+            .unwrap();
+        cache.write(&source, new_key, &"new".to_string()).unwrap();
+        assert!(cache.read::<String>(&source, key).is_err());
+        assert_eq!(cache.read::<String>(&source, new_key).unwrap(), "new");
+        assert_eq!(cache.read::<String>(&other, other_key).unwrap(), "other");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
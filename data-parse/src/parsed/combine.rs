@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+
+use crate::{ParsedScope, ParsedScopeGraph, ScopeData, StatixLabel};
+
+/// Scopes that are replaced by a single scope
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeGroup {
+    /// Scope that replaces the members, it gets [`ScopeData::Combined`] as data
+    pub scope: ParsedScope,
+    pub members: Vec<ParsedScope>,
+}
+
+/// Decides which scopes of a graph are combined, see [`ParsedScopeGraph::combine_scopes_with`]
+pub trait CombineStrategy<L> {
+    /// Name of the strategy, used in reports
+    fn name(&self) -> &'static str;
+
+    /// Groups of scopes to combine, a scope should be a member of at most one group
+    fn groups(&self, graph: &ParsedScopeGraph<L>) -> Vec<ScopeGroup>;
+}
+
+/// Number of scopes a [`CombineStrategy`] merged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CombineReport {
+    pub strategy: &'static str,
+    /// Number of combined scopes that were added
+    pub num_groups: usize,
+    /// Number of scopes that were replaced by a combined scope
+    pub num_merged: usize,
+}
+
+/// Leaves the graph as it is
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoCombine;
+
+impl<L> CombineStrategy<L> for NoCombine {
+    fn name(&self) -> &'static str {
+        "none"
+    }
+
+    fn groups(&self, _: &ParsedScopeGraph<L>) -> Vec<ScopeGroup> {
+        Vec::new()
+    }
+}
+
+/// Combines scopes with the scope that their data refers to,
+/// such as the declaration of a class with the scope of its body.
+///
+/// The combined scope is named after the referenced scope and the name of the class or method.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DataReference;
+
+impl<L> CombineStrategy<L> for DataReference {
+    fn name(&self) -> &'static str {
+        "data reference"
+    }
+
+    fn groups(&self, graph: &ParsedScopeGraph<L>) -> Vec<ScopeGroup> {
+        // referenced scope -> (name of the class or method, scopes that refer to it)
+        let mut referenced = HashMap::<&ParsedScope, (Option<&str>, Vec<&ParsedScope>)>::new();
+        for (scope, data) in &graph.scopes {
+            let (target, name) = match data {
+                ScopeData::Ref(target) => (target, None),
+                ScopeData::ClassOrMethod(name, target) => (target, Some(name.as_str())),
+                _ => continue,
+            };
+            let entry = referenced.entry(target).or_default();
+            // make sure that the group gets a name if any of the scopes has one
+            if name.is_some() {
+                entry.0 = name;
+            }
+            entry.1.push(scope);
+        }
+
+        referenced
+            .into_iter()
+            .map(|(target, (name, scopes))| {
+                let name = match name {
+                    Some(n) => format!("{}-{}", target.name, n),
+                    None => target.name.to_string(),
+                };
+                let mut members = scopes.into_iter().cloned().collect::<Vec<_>>();
+                members.push(target.clone());
+                ScopeGroup {
+                    scope: ParsedScope::new(name, target.resource.clone()),
+                    members,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Combines scopes of the same resource whose names are equal up to the last `separator`,
+/// e.g. `d_581-0` and `d_581-1` become `d_581` with `-` as separator.
+///
+/// Scopes without the separator, or without another scope with the same prefix, are left as they are.
+#[derive(Debug, Clone, Copy)]
+pub struct NamePrefix {
+    pub separator: char,
+}
+
+impl Default for NamePrefix {
+    fn default() -> Self {
+        Self { separator: '-' }
+    }
+}
+
+impl<L> CombineStrategy<L> for NamePrefix {
+    fn name(&self) -> &'static str {
+        "name prefix"
+    }
+
+    fn groups(&self, graph: &ParsedScopeGraph<L>) -> Vec<ScopeGroup> {
+        let mut prefixes = HashMap::<(&str, &str), Vec<&ParsedScope>>::new();
+        for scope in graph.scopes.keys() {
+            if let Some((prefix, _)) = scope.name.rsplit_once(self.separator) {
+                prefixes
+                    .entry((&scope.resource, prefix))
+                    .or_default()
+                    .push(scope);
+            }
+        }
+        prefixes
+            .into_iter()
+            .filter(|(_, scopes)| scopes.len() > 1)
+            .map(|((resource, prefix), scopes)| ScopeGroup {
+                scope: ParsedScope::new(prefix, resource),
+                members: scopes.into_iter().cloned().collect(),
+            })
+            .collect()
+    }
+}
+
+/// Combines the source and target of every edge with `label`, e.g. the `withKind` edges of Java,
+/// which point from a declaration to the scope that holds its kind.
+///
+/// Scopes that are connected through multiple of these edges end up in the same group,
+/// which is named after the scope that no edge with `label` points to.
+#[derive(Debug, Clone)]
+pub struct ViaLabel<L>(pub L);
+
+impl<L: StatixLabel> CombineStrategy<L> for ViaLabel<L> {
+    fn name(&self) -> &'static str {
+        "via label"
+    }
+
+    fn groups(&self, graph: &ParsedScopeGraph<L>) -> Vec<ScopeGroup> {
+        // union-find on the scopes of the edges, the root is the source of the edges
+        let mut parent = HashMap::<&ParsedScope, &ParsedScope>::new();
+        fn root<'a>(
+            parent: &HashMap<&'a ParsedScope, &'a ParsedScope>,
+            mut s: &'a ParsedScope,
+        ) -> &'a ParsedScope {
+            while let Some(p) = parent.get(s) {
+                s = p;
+            }
+            s
+        }
+        for edge in graph.edges.iter().filter(|e| e.label == self.0) {
+            let from = root(&parent, &edge.from);
+            let to = root(&parent, &edge.to);
+            if from != to {
+                parent.insert(to, from);
+            }
+        }
+
+        let mut groups = HashMap::<&ParsedScope, Vec<&ParsedScope>>::new();
+        for &scope in parent.keys() {
+            groups.entry(root(&parent, scope)).or_default().push(scope);
+        }
+        groups
+            .into_iter()
+            .map(|(root, mut members)| {
+                members.push(root);
+                ScopeGroup {
+                    scope: root.clone(),
+                    members: members.into_iter().cloned().collect(),
+                }
+            })
+            .collect()
+    }
+}
+
+impl<L: StatixLabel> ParsedScopeGraph<L> {
+    /// Replaces the groups of `strategy` by a single scope, edges of the members are moved to that scope
+    pub fn combine_scopes_with<S: CombineStrategy<L> + ?Sized>(
+        &mut self,
+        strategy: &S,
+    ) -> CombineReport {
+        let groups = strategy.groups(self);
+        let mut replacements = HashMap::new();
+        for group in &groups {
+            for member in &group.members {
+                replacements.insert(member.clone(), group.scope.clone());
+            }
+        }
+
+        for edge in &mut self.edges {
+            if let Some(new) = replacements.get(&edge.from) {
+                edge.from = new.clone();
+            }
+            if let Some(new) = replacements.get(&edge.to) {
+                edge.to = new.clone();
+            }
+        }
+        for old in replacements.keys() {
+            self.scopes.remove(old);
+        }
+        for group in &groups {
+            self.scopes.insert(group.scope.clone(), ScopeData::Combined);
+        }
+
+        CombineReport {
+            strategy: strategy.name(),
+            num_groups: groups.len(),
+            num_merged: replacements.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JavaLabel, ParsedEdge};
+
+    fn scope(name: &str) -> ParsedScope {
+        ParsedScope::new(name, "/./A.java")
+    }
+
+    fn edge(from: &str, to: &str, label: JavaLabel) -> ParsedEdge {
+        ParsedEdge {
+            from: scope(from),
+            to: scope(to),
+            label,
+        }
+    }
+
+    /// s-1 -LEX-> d_5-0 (class A, body s-2), d_5-1 -withKind-> s-3 -withKind-> s-4
+    fn graph() -> ParsedScopeGraph {
+        let class = ScopeData::ClassOrMethod("A".to_string(), scope("s-2"));
+        ParsedScopeGraph::builder()
+            .add_scope("s-1", "/./A.java")
+            .add_scope_with_data("d_5-0", "/./A.java", class)
+            .add_scope("d_5-1", "/./A.java")
+            .add_scope("s-2", "/./A.java")
+            .add_scope("s-3", "/./A.java")
+            .add_scope("s-4", "/./A.java")
+            .add_edge("s-1", JavaLabel::Parent, "d_5-0")
+            .add_edge("d_5-1", JavaLabel::WithKind, "s-3")
+            .add_edge("s-3", JavaLabel::WithKind, "s-4")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_no_combine() {
+        let mut graph = graph();
+        let report = graph.combine_scopes_with(&NoCombine);
+        assert_eq!(report.num_merged, 0);
+        assert_eq!(graph.scopes.len(), 6);
+    }
+
+    #[test]
+    fn test_data_reference() {
+        let mut graph = graph();
+        let report = graph.combine_scopes();
+        assert_eq!(report.num_groups, 1);
+        assert_eq!(report.num_merged, 2);
+        assert!(graph.scopes.contains_key(&scope("s-2-A")));
+        assert_eq!(graph.edges[0], edge("s-1", "s-2-A", JavaLabel::Parent));
+    }
+
+    #[test]
+    fn test_name_prefix() {
+        let mut graph = graph();
+        let report = graph.combine_scopes_with(&NamePrefix::default());
+        // d_5-0 and d_5-1, the s-* scopes all share the prefix `s` as well
+        assert_eq!(report.num_groups, 2);
+        assert!(graph.scopes.contains_key(&scope("d_5")));
+        assert_eq!(graph.edges[1].from, scope("d_5"));
+    }
+
+    #[test]
+    fn test_via_label() {
+        let mut graph = graph();
+        let strategy: Box<dyn CombineStrategy<JavaLabel>> = Box::new(ViaLabel(JavaLabel::WithKind));
+        let report = graph.combine_scopes_with(strategy.as_ref());
+        assert_eq!(report.num_groups, 1);
+        assert_eq!(report.num_merged, 3);
+        assert_eq!(graph.scopes.len(), 4);
+        assert_eq!(graph.edges[2], edge("d_5-1", "d_5-1", JavaLabel::WithKind));
+    }
+}
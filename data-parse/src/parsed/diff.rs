@@ -0,0 +1,189 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Display,
+};
+
+use crate::{JavaLabel, ParsedEdge, ParsedScope, ParsedScopeGraph, StatixLabel};
+
+/// Differences between two versions of a graph, see [`ParsedScopeGraph::diff`].
+///
+/// Scopes are matched by resource and name, edges by their endpoints and label.
+/// Use [`graphing::Diagram::diff`] on [`ParsedScopeGraph::as_diagram`] to draw the differences.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphDiff<L = JavaLabel> {
+    pub added_scopes: Vec<ParsedScope>,
+    pub removed_scopes: Vec<ParsedScope>,
+    /// Scopes in both graphs with different data
+    pub changed_scopes: Vec<ParsedScope>,
+    pub added_edges: Vec<ParsedEdge<L>>,
+    pub removed_edges: Vec<ParsedEdge<L>>,
+}
+
+/// Number of differences in a single resource
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceDiff {
+    pub added_scopes: usize,
+    pub removed_scopes: usize,
+    pub changed_scopes: usize,
+    /// Edges are counted for the resource of their source
+    pub added_edges: usize,
+    pub removed_edges: usize,
+}
+
+impl<L> GraphDiff<L> {
+    pub fn is_empty(&self) -> bool {
+        self.added_scopes.is_empty()
+            && self.removed_scopes.is_empty()
+            && self.changed_scopes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+
+    pub fn per_resource(&self) -> BTreeMap<String, ResourceDiff> {
+        fn entry<'a>(
+            resources: &'a mut BTreeMap<String, ResourceDiff>,
+            scope: &ParsedScope,
+        ) -> &'a mut ResourceDiff {
+            resources.entry(scope.resource.to_string()).or_default()
+        }
+        let mut resources = BTreeMap::new();
+        for s in &self.added_scopes {
+            entry(&mut resources, s).added_scopes += 1;
+        }
+        for s in &self.removed_scopes {
+            entry(&mut resources, s).removed_scopes += 1;
+        }
+        for s in &self.changed_scopes {
+            entry(&mut resources, s).changed_scopes += 1;
+        }
+        for e in &self.added_edges {
+            entry(&mut resources, &e.from).added_edges += 1;
+        }
+        for e in &self.removed_edges {
+            entry(&mut resources, &e.from).removed_edges += 1;
+        }
+        resources
+    }
+}
+
+impl<L> Display for GraphDiff<L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "scopes: +{} -{} ~{}, edges: +{} -{}",
+            self.added_scopes.len(),
+            self.removed_scopes.len(),
+            self.changed_scopes.len(),
+            self.added_edges.len(),
+            self.removed_edges.len()
+        )
+    }
+}
+
+impl<L: StatixLabel> ParsedScopeGraph<L> {
+    /// Differences from `self` to `other`, so scopes that are only in `other` are added.
+    ///
+    /// Scopes are sorted, edges are in the order of the graph they are in. Duplicate edges are ignored.
+    pub fn diff(&self, other: &Self) -> GraphDiff<L> {
+        let mut added_scopes = other
+            .scopes
+            .keys()
+            .filter(|s| !self.scopes.contains_key(*s))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut removed_scopes = self
+            .scopes
+            .keys()
+            .filter(|s| !other.scopes.contains_key(*s))
+            .cloned()
+            .collect::<Vec<_>>();
+        // ScopeData has no PartialEq, the printed data is what is compared
+        let mut changed_scopes = self
+            .scopes
+            .iter()
+            .filter(|(s, data)| {
+                other
+                    .scopes
+                    .get(*s)
+                    .is_some_and(|d| d.to_string() != data.to_string())
+            })
+            .map(|(s, _)| s.clone())
+            .collect::<Vec<_>>();
+        added_scopes.sort();
+        removed_scopes.sort();
+        changed_scopes.sort();
+
+        let own_edges = self.edges.iter().collect::<HashSet<_>>();
+        let other_edges = other.edges.iter().collect::<HashSet<_>>();
+        let only_in = |g: &Self, edges: &HashSet<&ParsedEdge<L>>| {
+            let mut seen = HashSet::new();
+            g.edges
+                .iter()
+                .filter(|e| !edges.contains(e) && seen.insert(*e))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        GraphDiff {
+            added_scopes,
+            removed_scopes,
+            changed_scopes,
+            added_edges: only_in(other, &own_edges),
+            removed_edges: only_in(self, &other_edges),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScopeData;
+
+    #[test]
+    fn test_diff() {
+        let old = ParsedScopeGraph::builder()
+            .add_scope("s-1", "/./A.java")
+            .add_scope("s-2", "/./A.java")
+            .add_scope("s-3", "/./B.java")
+            .add_edge("s-1", JavaLabel::Parent, "s-2")
+            .add_edge("s-2", JavaLabel::Parent, "s-3")
+            .build()
+            .unwrap();
+        let data = ScopeData::Ref(ParsedScope::new("s-1", "/./A.java"));
+        let new = ParsedScopeGraph::builder()
+            .add_scope("s-1", "/./A.java")
+            .add_scope_with_data("s-2", "/./A.java", data)
+            .add_scope("s-4", "/./B.java")
+            .add_edge("s-1", JavaLabel::Parent, "s-2")
+            .add_edge("s-2", JavaLabel::Parent, "s-4")
+            .add_edge("s-2", JavaLabel::Parent, "s-4")
+            .build()
+            .unwrap();
+
+        assert!(old.diff(&old).is_empty());
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.added_scopes,
+            vec![ParsedScope::new("s-4", "/./B.java")]
+        );
+        assert_eq!(
+            diff.removed_scopes,
+            vec![ParsedScope::new("s-3", "/./B.java")]
+        );
+        assert_eq!(
+            diff.changed_scopes,
+            vec![ParsedScope::new("s-2", "/./A.java")]
+        );
+        assert_eq!(diff.added_edges.len(), 1);
+        assert_eq!(
+            diff.removed_edges[0].to,
+            ParsedScope::new("s-3", "/./B.java")
+        );
+        assert_eq!(diff.to_string(), "scopes: +1 -1 ~1, edges: +1 -1");
+
+        let resources = diff.per_resource();
+        assert_eq!(resources["/./A.java"].changed_scopes, 1);
+        assert_eq!(resources["/./A.java"].added_edges, 1);
+        assert_eq!(resources["/./B.java"].removed_scopes, 1);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::ParsedScope;
+
+/// Glob that is matched against the resource of a scope, e.g. `**/csv/**`.
+///
+/// `*` matches any part of a path segment, `?` matches one character and `**` matches any number of segments.
+/// Resources are matched without their leading `/./`, so `org/apache/**` matches `/./org/apache/Foo.java`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceGlob {
+    segments: Vec<String>,
+}
+
+impl ResourceGlob {
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            segments: normalize(pattern).split('/').map(str::to_string).collect(),
+        }
+    }
+
+    pub fn matches(&self, resource: &str) -> bool {
+        let path = normalize(resource).split('/').collect::<Vec<_>>();
+        match_segments(&self.segments, &path)
+    }
+}
+
+impl From<&str> for ResourceGlob {
+    fn from(pattern: &str) -> Self {
+        Self::new(pattern)
+    }
+}
+
+fn normalize(path: &str) -> &str {
+    path.trim_start_matches('/').trim_start_matches("./")
+}
+
+fn match_segments(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((p, rest)) if p == "**" => {
+            (0..=path.len()).any(|skip| match_segments(rest, &path[skip..]))
+        }
+        Some((p, rest)) => match path.split_first() {
+            Some((s, path)) => {
+                match_segment(p.as_bytes(), s.as_bytes()) && match_segments(rest, path)
+            }
+            None => false,
+        },
+    }
+}
+
+fn match_segment(pattern: &[u8], segment: &[u8]) -> bool {
+    match (pattern.split_first(), segment.split_first()) {
+        (None, _) => segment.is_empty(),
+        (Some((b'*', rest)), _) => {
+            (0..=segment.len()).any(|skip| match_segment(rest, &segment[skip..]))
+        }
+        (Some((b'?', rest)), Some((_, s))) => match_segment(rest, s),
+        (Some((p, rest)), Some((c, s))) => p == c && match_segment(rest, s),
+        (Some(_), None) => false,
+    }
+}
+
+/// Include and exclude lists of resource globs.
+///
+/// A scope passes if its resource matches any include glob, or there are none,
+/// and it matches none of the exclude globs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceFilter {
+    pub include: Vec<ResourceGlob>,
+    pub exclude: Vec<ResourceGlob>,
+}
+
+impl ResourceFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn include(mut self, glob: impl Into<ResourceGlob>) -> Self {
+        self.include.push(glob.into());
+        self
+    }
+
+    pub fn exclude(mut self, glob: impl Into<ResourceGlob>) -> Self {
+        self.exclude.push(glob.into());
+        self
+    }
+
+    pub fn matches(&self, scope: &ParsedScope) -> bool {
+        let included =
+            self.include.is_empty() || self.include.iter().any(|g| g.matches(&scope.resource));
+        included && !self.exclude.iter().any(|g| g.matches(&scope.resource))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_glob() {
+        let resource = "/./org/apache/commons/csv/CSVParser.java";
+        assert!(ResourceGlob::new("**/csv/**").matches(resource));
+        assert!(ResourceGlob::new("org/apache/commons/csv/*").matches(resource));
+        assert!(ResourceGlob::new("**/CSV*.java").matches(resource));
+        assert!(ResourceGlob::new("**/CSVParse?.java").matches(resource));
+        assert!(!ResourceGlob::new("org/apache/*").matches(resource));
+        assert!(!ResourceGlob::new("**/io/**").matches(resource));
+    }
+
+    #[test]
+    fn test_resource_filter() {
+        let filter = ResourceFilter::new()
+            .include("**/csv/**")
+            .exclude("**/*Test.java");
+        assert!(filter.matches(&ParsedScope::new("s-1", "/./org/csv/Lexer.java")));
+        assert!(!filter.matches(&ParsedScope::new("s-1", "/./org/csv/LexerTest.java")));
+        assert!(!filter.matches(&ParsedScope::new("s-1", "/./java/lang/Object.java")));
+
+        let everything = ResourceFilter::new();
+        assert!(everything.matches(&ParsedScope::new("s-1", "")));
+    }
+}
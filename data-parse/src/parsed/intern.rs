@@ -0,0 +1,107 @@
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, RandomState},
+    sync::{Arc, LazyLock, Mutex, Weak},
+};
+
+use serde::{Deserialize, Deserializer};
+
+/// Number of separately locked parts of the interner, so graphs that are loaded in parallel rarely wait on each other
+const NUM_SHARDS: usize = 16;
+
+static INTERNER: LazyLock<Interner> = LazyLock::new(Interner::default);
+
+/// Interned strings, split over shards by their hash.
+///
+/// Only weak references are kept, so strings are freed once the last graph that uses them is dropped.
+#[derive(Default)]
+struct Interner {
+    hasher: RandomState,
+    shards: [Mutex<Shard>; NUM_SHARDS],
+}
+
+#[derive(Default)]
+struct Shard {
+    strings: HashMap<Box<str>, Weak<str>>,
+    /// Number of strings after dead entries were last removed
+    live: usize,
+}
+
+impl Shard {
+    fn intern(&mut self, s: &str) -> Arc<str> {
+        if let Some(interned) = self.strings.get(s).and_then(Weak::upgrade) {
+            return interned;
+        }
+        let interned = Arc::<str>::from(s);
+        self.strings.insert(s.into(), Arc::downgrade(&interned));
+        // removing dead entries once the shard doubled in size keeps interning amortized O(1)
+        if self.strings.len() > 2 * self.live.max(8) {
+            self.strings.retain(|_, w| w.strong_count() > 0);
+            self.live = self.strings.len();
+        }
+        interned
+    }
+}
+
+impl Interner {
+    fn shard(&self, s: &str) -> &Mutex<Shard> {
+        &self.shards[self.hasher.hash_one(s) as usize % NUM_SHARDS]
+    }
+}
+
+/// Returns a shared copy of `s`, so equal resources are only stored once.
+///
+/// Scope names are mostly unique and are not interned.
+pub fn intern(s: &str) -> Arc<str> {
+    INTERNER.shard(s).lock().unwrap().intern(s)
+}
+
+/// Number of distinct interned strings that are still in use
+pub fn num_interned() -> usize {
+    INTERNER
+        .shards
+        .iter()
+        .map(|shard| {
+            let shard = shard.lock().unwrap();
+            shard
+                .strings
+                .values()
+                .filter(|w| w.strong_count() > 0)
+                .count()
+        })
+        .sum()
+}
+
+/// Deserializes a string with [`intern`], used with `#[serde(deserialize_with)]`
+pub(crate) fn deserialize_interned<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Arc<str>, D::Error> {
+    let s = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+    Ok(intern(&s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let a = intern("/./intern-test.java");
+        let b = intern(&String::from("/./intern-test.java"));
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &intern("/./other-intern-test.java")));
+    }
+
+    #[test]
+    fn test_intern_frees_unused() {
+        let a = intern("/./freed-intern-test.java");
+        let weak = Arc::downgrade(&a);
+        drop(a);
+        assert!(weak.upgrade().is_none());
+        // interning again after the string was freed gives a new copy
+        assert_eq!(
+            &*intern("/./freed-intern-test.java"),
+            "/./freed-intern-test.java"
+        );
+    }
+}
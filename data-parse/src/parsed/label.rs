@@ -1,10 +1,56 @@
-use std::fmt::Display;
+use std::{fmt::Display, hash::Hash};
 
-use serde::{Deserialize, Serialize};
+use deepsize::DeepSizeOf;
+use graphing::Color;
+use scope_graph::label::ScopeGraphLabel;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
-use crate::RawLabel;
+use crate::{ParseResult, RawLabel, report};
 
-#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+/// Label of a Statix specification, converted from the label names in a raw scope graph.
+///
+/// Implement this for an enum of the labels of a specification to parse its scope graphs,
+/// or use [`NamedLabel`] to keep the names as they are.
+pub trait StatixLabel:
+    Clone + Hash + Eq + Display + Serialize + DeserializeOwned + Send + Sync
+{
+    /// Converts a label name such as `java/names/Main!EXTENDS`, returns `None` for unknown labels
+    fn from_statix(name: &str) -> Option<Self>;
+}
+
+/// Converts a label name, failing on labels that `L` does not know
+pub(crate) fn parse_label<L: StatixLabel>(name: &str) -> ParseResult<L> {
+    L::from_statix(name).ok_or_else(|| {
+        report(format!("Found unknown label: {}", name));
+        "Unknown label".into()
+    })
+}
+
+/// Label of any specification, named after the part of the Statix name after the `!`,
+/// e.g. `EXTENDS` for `java/names/Main!EXTENDS`.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NamedLabel(pub String);
+
+impl StatixLabel for NamedLabel {
+    fn from_statix(name: &str) -> Option<Self> {
+        let name = name.trim_start_matches("\\").trim_end_matches("\\");
+        let short = name.rsplit_once('!').map(|(_, n)| n).unwrap_or(name);
+        match short.is_empty() {
+            true => None,
+            false => Some(Self(short.to_string())),
+        }
+    }
+}
+
+impl Display for NamedLabel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, DeepSizeOf,
+)]
 pub enum JavaLabel {
     // "java/JRE!typeName"
     TypeName,
@@ -58,42 +104,45 @@ pub enum JavaLabel {
     ElementType,
 }
 
+impl StatixLabel for JavaLabel {
+    fn from_statix(name: &str) -> Option<Self> {
+        match name.trim_start_matches("\\").trim_end_matches("\\") {
+            "java/JRE!typeName" => Some(Self::TypeName),
+            "java/names/Main!TYPE_PARAMS" => Some(Self::TypeParams),
+            "java/names/Main!IMPORT_CU" => Some(Self::ImportCu),
+            "java/names/Main!STATIC_MEMBERS" => Some(Self::StaticMember),
+            "java/names/MethodNames!return" => Some(Self::Return),
+            "java/names/PackageNames!thisPkg" => Some(Self::LocalPackage),
+            "java/names/MethodNames!mthd" => Some(Self::Method),
+            "java/names/TypeNames!thisType" => Some(Self::LocalType),
+            "java/types/Main!withKind" => Some(Self::WithKind),
+            "java/names/PackageNames!pkg" => Some(Self::Package),
+            "java/names/TypeNames!type" => Some(Self::JType),
+            "java/names/Main!STATIC_LEX" => Some(Self::StaticParent),
+            "java/names/ExpressionNames!var" => Some(Self::VarDecl),
+            "java/names/Main!IMPLEMENTS" => Some(Self::Impl),
+            "java/types/Conversions!box" => Some(Self::Boxed),
+            "java/names/Main!EXTENDS" => Some(Self::Extend),
+            "java/types/Main!withType" => Some(Self::WithType),
+            "java/names/Main!IMPORT_PKG" => Some(Self::ImportPackage),
+            "java/names/Main!STATIC_IMPORT_ONDEMAND" => Some(Self::ImportStaticOndemand),
+            "java/names/Main!SINGLE_TYPE_IMPORT" => Some(Self::ImportSingleType),
+            "java/names/Main!LEX" => Some(Self::Parent),
+            "java/names/Main!PARENT_PKG" => Some(Self::ParentPackage),
+            "java/names/Main!TYPE_IMPORT_ONDEMAND" => Some(Self::ImportTypeOndemand),
+            "java/names/Main!SINGLE_STATIC_IMPORT" => Some(Self::ImportSingleStatic),
+            "java/types/ReferenceTypes!elementType" => Some(Self::ElementType),
+
+            _ => None,
+        }
+    }
+}
+
 impl<'a> TryFrom<&'a str> for JavaLabel {
     type Error = crate::ParseError;
 
     fn try_from(value: &'a str) -> Result<Self, Self::Error> {
-        match value.trim_start_matches("\\").trim_end_matches("\\") {
-            "java/JRE!typeName" => Ok(Self::TypeName),
-            "java/names/Main!TYPE_PARAMS" => Ok(Self::TypeParams),
-            "java/names/Main!IMPORT_CU" => Ok(Self::ImportCu),
-            "java/names/Main!STATIC_MEMBERS" => Ok(Self::StaticMember),
-            "java/names/MethodNames!return" => Ok(Self::Return),
-            "java/names/PackageNames!thisPkg" => Ok(Self::LocalPackage),
-            "java/names/MethodNames!mthd" => Ok(Self::Method),
-            "java/names/TypeNames!thisType" => Ok(Self::LocalType),
-            "java/types/Main!withKind" => Ok(Self::WithKind),
-            "java/names/PackageNames!pkg" => Ok(Self::Package),
-            "java/names/TypeNames!type" => Ok(Self::JType),
-            "java/names/Main!STATIC_LEX" => Ok(Self::StaticParent),
-            "java/names/ExpressionNames!var" => Ok(Self::VarDecl),
-            "java/names/Main!IMPLEMENTS" => Ok(Self::Impl),
-            "java/types/Conversions!box" => Ok(Self::Boxed),
-            "java/names/Main!EXTENDS" => Ok(Self::Extend),
-            "java/types/Main!withType" => Ok(Self::WithType),
-            "java/names/Main!IMPORT_PKG" => Ok(Self::ImportPackage),
-            "java/names/Main!STATIC_IMPORT_ONDEMAND" => Ok(Self::ImportStaticOndemand),
-            "java/names/Main!SINGLE_TYPE_IMPORT" => Ok(Self::ImportSingleType),
-            "java/names/Main!LEX" => Ok(Self::Parent),
-            "java/names/Main!PARENT_PKG" => Ok(Self::ParentPackage),
-            "java/names/Main!TYPE_IMPORT_ONDEMAND" => Ok(Self::ImportTypeOndemand),
-            "java/names/Main!SINGLE_STATIC_IMPORT" => Ok(Self::ImportSingleStatic),
-            "java/types/ReferenceTypes!elementType" => Ok(Self::ElementType),
-
-            _ => {
-                println!("Found unknown label: {}", value);
-                Err("Unknown label".into())
-            }
-        }
+        parse_label(value)
     }
 }
 
@@ -119,47 +168,114 @@ impl Display for JavaLabel {
     }
 }
 
+/// Labels can be used in the scope graphs of this workspace, see [`crate::QueryReplay`].
+///
+/// `str` is the name of the label in the Statix specification, without its module.
+impl ScopeGraphLabel for JavaLabel {
+    fn char(&self) -> char {
+        match self {
+            Self::TypeName => 'N',
+            Self::TypeParams => 'G',
+            Self::ImportCu => 'C',
+            Self::StaticMember => 'S',
+            Self::Return => 'R',
+            Self::LocalPackage => 'k',
+            Self::Method => 'M',
+            Self::LocalType => 't',
+            Self::WithKind => 'K',
+            Self::Package => 'p',
+            Self::JType => 'T',
+            Self::StaticParent => 'Q',
+            Self::VarDecl => 'V',
+            Self::Impl => 'I',
+            Self::Boxed => 'B',
+            Self::Extend => 'E',
+            Self::WithType => 'W',
+            Self::ImportPackage => 'i',
+            Self::ImportStaticOndemand => 'o',
+            Self::ImportSingleType => 'y',
+            Self::Parent => 'P',
+            Self::ParentPackage => 'U',
+            Self::ImportTypeOndemand => 'O',
+            Self::ImportSingleStatic => 's',
+            Self::ElementType => 'A',
+        }
+    }
+
+    fn str(&self) -> &'static str {
+        match self {
+            Self::TypeName => "typeName",
+            Self::TypeParams => "TYPE_PARAMS",
+            Self::ImportCu => "IMPORT_CU",
+            Self::StaticMember => "STATIC_MEMBERS",
+            Self::Return => "return",
+            Self::LocalPackage => "thisPkg",
+            Self::Method => "mthd",
+            Self::LocalType => "thisType",
+            Self::WithKind => "withKind",
+            Self::Package => "pkg",
+            Self::JType => "type",
+            Self::StaticParent => "STATIC_LEX",
+            Self::VarDecl => "var",
+            Self::Impl => "IMPLEMENTS",
+            Self::Boxed => "box",
+            Self::Extend => "EXTENDS",
+            Self::WithType => "withType",
+            Self::ImportPackage => "IMPORT_PKG",
+            Self::ImportStaticOndemand => "STATIC_IMPORT_ONDEMAND",
+            Self::ImportSingleType => "SINGLE_TYPE_IMPORT",
+            Self::Parent => "LEX",
+            Self::ParentPackage => "PARENT_PKG",
+            Self::ImportTypeOndemand => "TYPE_IMPORT_ONDEMAND",
+            Self::ImportSingleStatic => "SINGLE_STATIC_IMPORT",
+            Self::ElementType => "elementType",
+        }
+    }
+}
+
 impl JavaLabel {
-    pub fn cosmo_color(&self) -> &'static str {
+    pub fn cosmo_color(&self) -> Color {
         match self {
-            Self::Extend | Self::Impl => "#fa98e3",
-            Self::StaticParent | Self::Parent => "#fabbeb",
+            Self::Extend | Self::Impl => Color::new_rgb_u32(0xfa98e3),
+            Self::StaticParent | Self::Parent => Color::new_rgb_u32(0xfabbeb),
 
-            Self::StaticMember | Self::Boxed | Self::VarDecl => "#c0fab9",
+            Self::StaticMember | Self::Boxed | Self::VarDecl => Color::new_rgb_u32(0xc0fab9),
 
             Self::ImportStaticOndemand
             | Self::ImportSingleType
             | Self::ImportTypeOndemand
             | Self::ImportSingleStatic
             | Self::ImportCu
-            | Self::ImportPackage => "#ebe8ebff",
+            | Self::ImportPackage => Color::new_rgba_u32(0xebe8ebff),
 
-            Self::Method | Self::Return => "#ebe9e8ff",
+            Self::Method | Self::Return => Color::new_rgba_u32(0xebe9e8ff),
 
-            Self::LocalPackage | Self::ParentPackage | Self::Package => "#ebe8e8ff",
+            Self::LocalPackage | Self::ParentPackage | Self::Package => {
+                Color::new_rgba_u32(0xebe8e8ff)
+            }
             Self::TypeName
             | Self::LocalType
             | Self::ElementType
             | Self::TypeParams
             | Self::WithKind
             | Self::WithType
-            | Self::JType => "#e8ebeaff",
+            | Self::JType => Color::new_rgba_u32(0xe8ebeaff),
         }
     }
 
-    pub fn cosmo_value(&self) -> usize {
+    pub fn cosmo_value(&self) -> f32 {
         match self {
-            JavaLabel::VarDecl => 20,
-            JavaLabel::Parent => 10,
-            JavaLabel::Extend => 5,
-            JavaLabel::Impl => 4,
-            JavaLabel::Return => 20,
-            _ => 1,
+            JavaLabel::VarDecl => 20.0,
+            JavaLabel::Parent => 10.0,
+            JavaLabel::Extend => 5.0,
+            JavaLabel::Impl => 4.0,
+            JavaLabel::Return => 20.0,
+            _ => 1.0,
         }
     }
 
-    pub fn cosmo_width(&self) -> usize {
-        1
+    pub fn cosmo_width(&self) -> f32 {
+        1.0
     }
 }
 
@@ -168,6 +284,12 @@ pub struct ParsedLabel {
     pub name: String,
 }
 
+impl ParsedLabel {
+    pub fn to_label<L: StatixLabel>(&self) -> ParseResult<L> {
+        parse_label(&self.name)
+    }
+}
+
 impl From<RawLabel> for ParsedLabel {
     fn from(raw: RawLabel) -> Self {
         ParsedLabel {
@@ -175,3 +297,25 @@ impl From<RawLabel> for ParsedLabel {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named_label() {
+        let label = NamedLabel::from_statix("statics/base!P").unwrap();
+        assert_eq!(label, NamedLabel("P".to_string()));
+        assert_eq!(NamedLabel::from_statix("\\D\\").unwrap().0, "D");
+        assert_eq!(NamedLabel::from_statix("statics/base!"), None);
+    }
+
+    #[test]
+    fn test_java_label() {
+        assert_eq!(
+            JavaLabel::from_statix("java/names/Main!EXTENDS"),
+            Some(JavaLabel::Extend)
+        );
+        assert_eq!(JavaLabel::from_statix("statics/base!P"), None);
+    }
+}
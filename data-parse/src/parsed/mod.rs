@@ -1,25 +1,53 @@
 use std::{
-    collections::HashMap,
-    fs::OpenOptions,
-    hash::Hash,
-    io::{BufReader, BufWriter, Write},
-    path::{Path, PathBuf},
+    collections::{HashMap, HashSet},
+    io::Read,
+    path::Path,
     str::FromStr,
 };
 
+use graphing::{
+    Diagram, DiagramEdge, DiagramNode, Renderer,
+    cosmo::{CosmoDiagram, CosmoStyle, CosmoStyleSheet},
+    cypher::{CypherDiagram, CypherItem},
+    gexf::{GexfDiagram, GexfItem},
+};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeSeed};
 
 use crate::{
-    ParseResult,
-    raw::{JavaType, RawEdge, RawScopeGraph, RefType},
+    ParseProgress, ParseResult, input_extension, open_input_with_progress,
+    raw::{JavaType, JavaValue, RawEdge, RawScopeGraph, RefType},
+    report,
 };
 
+mod builder;
+mod cache;
+mod combine;
+mod diff;
+mod filter;
+mod intern;
 mod label;
+mod multi_edge;
+mod predicate;
 mod scope;
-
+mod slice;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod stats;
+mod stream;
+
+pub use builder::*;
+pub use cache::*;
+pub use combine::*;
+pub use diff::*;
+pub use filter::*;
+pub use intern::*;
 pub use label::*;
+pub use multi_edge::*;
+pub use predicate::*;
 pub use scope::*;
+pub use slice::*;
+pub use stats::*;
 
 // https://stackoverflow.com/questions/51276896/how-do-i-use-serde-to-serialize-a-hashmap-with-structs-as-keys-to-json
 pub mod vectorize {
@@ -58,45 +86,60 @@ pub enum ScopeData {
     None,
 }
 
+impl From<JavaValue> for ScopeData {
+    fn from(data: JavaValue) -> Self {
+        match data.into_data() {
+            Some(JavaType::Scope(_)) => ScopeData::None, // scope declaration is not data, but good
+            Some(JavaType::Ref(RefType::ScopeRef(raw_scope))) => {
+                ScopeData::Ref(ParsedScope::from(raw_scope.arg0))
+            }
+            Some(JavaType::Ref(RefType::MethodOrClass(m))) => {
+                let (id, scope) = m.into_id_scope();
+                ScopeData::ClassOrMethod(id, ParsedScope::from(scope))
+            }
+            Some(JavaType::MethodOrClass(m)) => {
+                let (id, scope) = m.into_id_scope();
+                ScopeData::ClassOrMethod(id, ParsedScope::from(scope))
+            }
+            Some(_) => ScopeData::None,
+            None => ScopeData::None,
+        }
+    }
+}
+
+/// Short description of the data, used as attribute when exporting the graph
+impl std::fmt::Display for ScopeData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScopeData::Ref(scope) => write!(f, "ref {}", scope.id()),
+            ScopeData::ClassOrMethod(name, scope) => write!(f, "{name} ({})", scope.id()),
+            ScopeData::Combined => write!(f, "combined"),
+            ScopeData::None => Ok(()),
+        }
+    }
+}
+
+/// Scope graph with the labels of a Statix specification, see [`StatixLabel`].
+///
+/// Defaults to the labels of the Java specification.
 #[derive(Serialize, Deserialize, Debug)]
-pub struct ParsedScopeGraph {
+pub struct ParsedScopeGraph<L = JavaLabel> {
     #[serde(with = "vectorize")]
     pub scopes: HashMap<ParsedScope, ScopeData>,
-    pub edges: Vec<ParsedEdge>,
-    pub labels: Vec<JavaLabel>,
+    pub edges: Vec<ParsedEdge<L>>,
+    pub labels: Vec<L>,
 }
 
-impl TryFrom<RawScopeGraph> for ParsedScopeGraph {
+impl<L: StatixLabel> TryFrom<RawScopeGraph> for ParsedScopeGraph<L> {
     type Error = crate::ParseError;
 
     fn try_from(raw: RawScopeGraph) -> ParseResult<Self> {
-        println!(
-            "raw.data: {0:?}",
-            raw.data
-                .get("#/./org/apache/commons/csv/ExtendedBufferedReader.jav-d_581-0")
-        );
-
         let scopes = raw
             .data
             .into_iter()
             .map(|(scope_key, data)| {
                 let s = ParsedScope::from_str(&scope_key)?;
-                let d = match data.into_data() {
-                    Some(JavaType::Scope(_)) => ScopeData::None, // scope declaration is not data, but good
-                    Some(JavaType::Ref(RefType::ScopeRef(raw_scope))) => {
-                        ScopeData::Ref(ParsedScope::from(raw_scope.arg0))
-                    }
-                    Some(JavaType::Ref(RefType::MethodOrClass(m))) => {
-                        let (id, scope) = m.into_id_scope();
-                        ScopeData::ClassOrMethod(id, ParsedScope::from(scope))
-                    }
-                    Some(JavaType::MethodOrClass(m)) => {
-                        let (id, scope) = m.into_id_scope();
-                        ScopeData::ClassOrMethod(id, ParsedScope::from(scope))
-                    }
-                    Some(_) => ScopeData::None,
-                    None => ScopeData::None,
-                };
+                let d = ScopeData::from(data);
                 Ok((s, d))
             })
             .collect::<ParseResult<HashMap<_, _>>>()?;
@@ -104,14 +147,17 @@ impl TryFrom<RawScopeGraph> for ParsedScopeGraph {
         let edges = raw
             .edges
             .into_iter()
-            .flat_map(|(key, edge)| ParsedEdge::from_raw(key, RawEdge::Head(edge)).unwrap())
+            .map(|(key, edge)| ParsedEdge::from_raw(key, RawEdge::Head(edge)))
+            .collect::<ParseResult<Vec<_>>>()?
+            .into_iter()
+            .flatten()
             .collect::<Vec<_>>();
 
         let labels = raw
             .labels
             .into_iter()
             .map(ParsedLabel::from)
-            .map(JavaLabel::try_from)
+            .map(|l| l.to_label())
             .collect::<ParseResult<Vec<_>>>()?;
 
         Ok(Self {
@@ -122,32 +168,71 @@ impl TryFrom<RawScopeGraph> for ParsedScopeGraph {
     }
 }
 
-impl ParsedScopeGraph {
+impl<L: StatixLabel> ParsedScopeGraph<L> {
+    /// Parses the graph at `path`, using the [`GraphCache::default`] cache
     pub fn from_file<P: AsRef<Path>>(path: P) -> ParseResult<Self> {
-        match Self::read_cache(&path) {
+        Self::from_file_with_cache(path, &GraphCache::default())
+    }
+
+    /// Reads the graph from `cache` if the contents of `path` were parsed before,
+    /// otherwise parses the file and caches the result.
+    ///
+    /// Failing to read or write the cache is not an error, the file is parsed instead.
+    pub fn from_file_with_cache<P: AsRef<Path>>(path: P, cache: &GraphCache) -> ParseResult<Self> {
+        let path = path.as_ref();
+        let key = CacheKey::of::<Self>(path)?;
+        match cache.read(path, key) {
             Ok(graph) => return Ok(graph),
             Err(e) => {
-                println!("Cache read failed: {}", e);
-                let _ = std::fs::remove_file(Self::cache_path(&path));
+                report(format!("Cache read failed: {}", e));
+                let _ = cache.remove(path, key);
             }
         }
 
-        println!("Cache doesn't exist, reading raw file, this can take a while...");
-        let file = OpenOptions::new().read(true).open(&path)?;
-        let mut buf = BufReader::new(file);
-        let timer = std::time::Instant::now();
-        let mut deserializer = serde_json::Deserializer::from_reader(&mut buf);
-        deserializer.disable_recursion_limit();
-        let json: RawScopeGraph = Deserialize::deserialize(&mut deserializer)?;
-        println!("Deserialization took: {:?}", timer.elapsed());
-        let graph = ParsedScopeGraph::try_from(json)?;
-        if let Err(e) = graph.write_cache(&path) {
-            println!("Failed to write cache: {}", e);
+        report("Cache doesn't exist, reading raw file, this can take a while...");
+        let graph = Self::from_file_uncached(path)?;
+        if let Err(e) = cache.write(path, key, &graph) {
+            report(format!("Failed to write cache: {}", e));
         }
         Ok(graph)
     }
 
-    pub fn filter_scopes(&mut self, filter: fn(&ParsedScope) -> bool) {
+    /// Parses the graph at `path` without reading or writing a cache.
+    ///
+    /// Files with an `.aterm` or `.trm` extension are read as ATerms, see [`RawScopeGraph::from_aterm`].
+    /// Compressed files are decompressed while reading, see [`crate::open_input`].
+    /// Shows the progress of reading and converting unless [`crate::set_quiet`] is enabled.
+    pub fn from_file_uncached<P: AsRef<Path>>(path: P) -> ParseResult<Self> {
+        let mut progress = ParseProgress::new();
+        let mut input = open_input_with_progress(&path, &progress.bytes)?;
+        if matches!(input_extension(path.as_ref()), Some("aterm" | "trm")) {
+            let mut aterm = String::new();
+            input.read_to_string(&mut aterm)?;
+            progress.end_phase("Reading");
+            let raw = RawScopeGraph::from_aterm(&aterm)?;
+            progress.end_phase("ATerm parsing");
+            let graph = Self::try_from(raw)?;
+            progress.scopes.set_position(graph.scopes.len() as u64);
+            progress.edges.set_position(graph.edges.len() as u64);
+            progress.end_phase("Conversion");
+            progress.finish();
+            return Ok(graph);
+        }
+
+        let mut deserializer = serde_json::Deserializer::from_reader(input);
+        deserializer.disable_recursion_limit();
+        // converts while reading, so the raw graph is never fully in memory
+        let graph = stream::StreamingGraph::new(&progress).deserialize(&mut deserializer)?;
+        progress.end_phase("Deserialization");
+        progress.finish();
+        Ok(graph)
+    }
+
+    /// Keeps the edges from or to a scope that passes `filter`, and the scopes of those edges
+    pub fn filter_scopes<F>(&mut self, filter: F)
+    where
+        F: Fn(&ParsedScope) -> bool + Sync,
+    {
         self.edges = std::mem::take(&mut self.edges)
             .into_par_iter()
             .filter(|edge| filter(&edge.from) || filter(&edge.to))
@@ -157,9 +242,20 @@ impl ParsedScopeGraph {
         self.filter_scopes_without_edges();
     }
 
+    /// Filters the scopes on their resource, see [`ResourceGlob`], e.g. `**/csv/**`
+    pub fn filter_by_resource_glob(&mut self, pattern: &str) {
+        let glob = ResourceGlob::new(pattern);
+        self.filter_scopes(|s| glob.matches(&s.resource));
+    }
+
+    /// Filters the scopes on include and exclude lists of resource globs
+    pub fn filter_by_resources(&mut self, filter: &ResourceFilter) {
+        self.filter_scopes(|s| filter.matches(s));
+    }
+
     pub fn filter_scope_by_edge_labels<F>(&mut self, filter: F)
     where
-        F: Fn(&ParsedScope, Option<&ParsedEdge>, Option<&ParsedEdge>) -> bool,
+        F: Fn(&ParsedScope, Option<&ParsedEdge<L>>, Option<&ParsedEdge<L>>) -> bool,
     {
         self.scopes.retain(|s, _| {
             let incoming_edges = self.edges.iter().filter(|e| &e.to == s).collect::<Vec<_>>();
@@ -193,7 +289,10 @@ impl ParsedScopeGraph {
             .retain(|e| self.scopes.contains_key(&e.from) && self.scopes.contains_key(&e.to));
     }
 
-    pub fn filter_edges(&mut self, filter: fn(&ParsedEdge) -> bool) {
+    pub fn filter_edges<F>(&mut self, filter: F)
+    where
+        F: FnMut(&ParsedEdge<L>) -> bool,
+    {
         self.edges.retain(filter);
         self.filter_scopes_without_edges();
     }
@@ -209,208 +308,105 @@ impl ParsedScopeGraph {
             .collect();
     }
 
-    /// Combines scopes that refer to each other.
+    /// Combines scopes that refer to each other, see [`DataReference`].
     ///
     /// Ie if a scope exists that declares the class and another that contains the class body,
     /// they are combined.
-    pub fn combine_scopes(&mut self) {
-        let mut from_edge_map = HashMap::new();
-        let mut to_edge_map = HashMap::new();
-        for e in &mut self.edges {
-            from_edge_map
-                .entry(e.from.clone())
-                .or_insert_with(Vec::new)
-                .push(&mut e.from);
-            to_edge_map
-                .entry(e.to.clone())
-                .or_insert_with(Vec::new)
-                .push(&mut e.to);
-        }
-
-        let mut new_scopes = Vec::new();
-        let mut remove_scopes = Vec::new();
-
-        #[derive(PartialEq, Eq)]
-        struct ScopeRef<'a> {
-            scope: &'a ParsedScope,
-            name: Option<&'a str>,
-        }
-
-        impl Hash for ScopeRef<'_> {
-            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-                self.scope.hash(state);
-            }
-        }
-
-        // get <referenced scope: existing scope> pairs
-
-        let ref_scopes = self
-            .scopes
-            .iter()
-            .filter_map(|(s, d)| match d {
-                // ScopeData::Ref(rs) => Some((rs, None, s)),
-                ScopeData::Ref(rs) => Some((rs, None, s)),
-                // ScopeData::ClassOrMethod(m_name, rs) => Some((ScopeRef { scope: rs, name: Some(m_name)}, s)),
-                ScopeData::ClassOrMethod(m_name, rs) => Some((rs, Some(m_name), s)),
-                _ => None,
-            })
-            // some really dumb shit so I can make sure that every `ScopeRef` has a name
-            .fold(HashMap::new(), |mut acc, (referenced, name, orig)| {
-                let entry = acc.entry(referenced).or_insert((None, Vec::new()));
-                if let Some(n) = name {
-                    entry.0 = Some(n.as_str());
-                }
-                entry.1.push(orig);
-                acc
-            })
-            .into_iter()
-            .map(|(referenced, (name, orig))| {
-                (
-                    ScopeRef {
-                        scope: referenced,
-                        name,
-                    },
-                    orig,
-                )
-            });
-
-        for (referenced, orig) in ref_scopes {
-            let name = match &referenced.name {
-                Some(n) => format!("{}-{}", referenced.scope.name, n),
-                None => referenced.scope.name.to_string(),
-            };
-            let new_scope = ParsedScope::new(name, referenced.scope.resource.clone());
-
-            for edge_scope in from_edge_map
-                .get_mut(referenced.scope)
-                .unwrap_or(&mut Vec::new())
-            {
-                **edge_scope = new_scope.clone();
-            }
-
-            for edge_scope in to_edge_map
-                .get_mut(referenced.scope)
-                .unwrap_or(&mut Vec::new())
-            {
-                **edge_scope = new_scope.clone();
-            }
-
-            for s in orig {
-                for edge_scope in from_edge_map.get_mut(s).unwrap_or(&mut Vec::new()) {
-                    **edge_scope = new_scope.clone();
-                }
-
-                for edge_scope in to_edge_map.get_mut(s).unwrap_or(&mut Vec::new()) {
-                    **edge_scope = new_scope.clone();
-                }
-                remove_scopes.push(s.clone());
-            }
-            remove_scopes.push(referenced.scope.clone());
-            new_scopes.push(new_scope);
-        }
+    pub fn combine_scopes(&mut self) -> CombineReport {
+        self.combine_scopes_with(&DataReference)
+    }
 
-        for old in remove_scopes {
-            self.scopes.remove(&old);
+    /// Scopes are named by their name and get their kind as class, edges get their label as class
+    pub fn as_diagram(&self) -> Diagram {
+        let mut diagram = Diagram::new("Scope graph");
+        for s in self.scopes.keys() {
+            diagram.push_node(DiagramNode::new(s.name(), s.name()).add_class(s.kind_class()));
         }
-        for new in new_scopes {
-            self.scopes.insert(new, ScopeData::Combined);
+        for e in &self.edges {
+            diagram.push_edge(
+                DiagramEdge::new(e.from.name(), e.to.name(), &e.label).add_class(&e.label),
+            );
         }
+        diagram
     }
 
-    pub fn to_cosmograph_csv<P: AsRef<Path>>(&self, path: P) -> ParseResult<()> {
-        let file = OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .create(true)
-            .open(&path)?;
-        let mut buf = BufWriter::new(file);
-        buf.write_all(b"source;target;strength;label;color;width\n")?;
+    /// Writes the graph as GEXF for Gephi, with the data, resource and kind of every scope as attributes
+    pub fn to_gexf<P: AsRef<Path>>(&self, path: P) -> ParseResult<()> {
+        let mut gexf = GexfDiagram::new("Scope graph");
+        gexf.extend(self.scopes.iter().map(|(s, data)| {
+            GexfItem::node(s.name(), s.name())
+                .with_attribute("data", data)
+                .with_attribute("resource", &s.resource)
+                .with_attribute("kind", s.kind_class())
+        }));
+        gexf.extend(
+            self.edges
+                .iter()
+                .map(|e| GexfItem::edge(e.from.name(), e.to.name(), &e.label)),
+        );
+        gexf.render_to_file(&path.as_ref().to_string_lossy())?;
+        Ok(())
+    }
 
-        let mut occ = HashMap::<_, usize>::new();
+    /// Writes the graph as Cypher statements for Neo4j.
+    ///
+    /// Scopes get their kind as node label and the name, resource and data as properties,
+    /// edges get their label as relationship type.
+    pub fn to_cypher<P: AsRef<Path>>(&self, path: P) -> ParseResult<()> {
+        let mut cypher = CypherDiagram::new();
+        cypher.extend(self.scopes.iter().map(|(s, data)| {
+            CypherItem::node(s.name())
+                .with_label(s.kind_class())
+                .with_property("name", &s.name)
+                .with_property("resource", &s.resource)
+                .with_property("data", data)
+        }));
+        cypher.extend(
+            self.edges
+                .iter()
+                .map(|e| CypherItem::edge(e.from.name(), e.to.name(), &e.label)),
+        );
+        cypher.render_to_file(&path.as_ref().to_string_lossy())?;
+        Ok(())
+    }
+}
 
-        for e in &self.edges {
-            let from = e.from.name();
-            let to = e.to.name();
-
-            occ.entry(&e.from).and_modify(|c| *c += 1).or_insert(0);
-            occ.entry(&e.to).and_modify(|c| *c += 1).or_insert(0);
-
-            let _ = buf.write(
-                format!(
-                    "{};{};{};{};{};{}\n",
-                    from,
-                    to,
-                    e.label.cosmo_value(),
-                    e.label,
-                    e.label.cosmo_color(),
-                    e.label.cosmo_width()
-                )
-                .as_bytes(),
-            )?;
+/// Styling for the labels of the Java specification
+impl ParsedScopeGraph<JavaLabel> {
+    /// Styles for the classes of [`ParsedScopeGraph::as_diagram`].
+    ///
+    /// Data scopes are small, the other scopes are sized by their number of edges.
+    pub fn cosmo_style_sheet(&self) -> CosmoStyleSheet {
+        // a scope of every kind, the color only depends on the kind
+        let mut kinds = HashMap::<&str, &ParsedScope>::new();
+        for s in self.scopes.keys() {
+            kinds.entry(s.kind_class()).or_insert(s);
         }
+        let labels = self.edges.iter().map(|e| &e.label).collect::<HashSet<_>>();
 
-        let meta_fname = format!(
-            "{}-meta.csv",
-            path.as_ref().file_name().unwrap().to_str().unwrap()
-        );
-        let meta_path = path.as_ref().parent().map(|p| p.join(meta_fname)).unwrap();
-
-        let meta_file = OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .create(true)
-            .open(meta_path)?;
-        let mut meta_buf = BufWriter::new(meta_file);
-        meta_buf.write_all(b"id;color;size\n")?;
-        for (s, d) in &self.scopes {
-            let id = s.name();
-            let color = s.cosmo_color();
-            let n_edges = occ.get(s).unwrap_or(&0);
-            let size = match s.is_data() {
-                true => 1,
-                false => 5 + 2 * n_edges,
+        let mut sheet = CosmoStyleSheet::new();
+        for (kind, s) in kinds {
+            let style = match s.is_data() {
+                true => CosmoStyle::new().color(s.cosmo_color()).size(1.0),
+                false => CosmoStyle::new().color(s.cosmo_color()),
             };
-            let _ = meta_buf.write(format!("{};{};{}\n", id, color, size).as_bytes())?;
+            sheet.push(kind, style);
         }
-
-        buf.flush()?;
-        meta_buf.flush()?;
-        Ok(())
-    }
-
-    fn read_cache<P: AsRef<Path>>(path: P) -> ParseResult<Self> {
-        let path = Self::cache_path(path);
-        let file = OpenOptions::new().read(true).open(&path)?;
-        let mut buf = BufReader::new(file);
-        let timer = std::time::Instant::now();
-        let json: Self = serde_json::from_reader(&mut buf)?;
-        println!("Deserialization from cache took: {:?}", timer.elapsed());
-        Ok(json)
+        for label in labels {
+            let style = CosmoStyle::new()
+                .color(label.cosmo_color())
+                .size(label.cosmo_width())
+                .strength(label.cosmo_value());
+            sheet.push(label, style);
+        }
+        sheet
     }
 
-    fn write_cache<P: AsRef<Path>>(&self, path: P) -> ParseResult<()> {
-        let path = Self::cache_path(path);
-        println!("Caching graph to: {path:?}");
-        // if path.exists() {
-        //     println!("Cache file already exists at: {:?}", path);
-        //     return Ok(());
-        // }
-        let file = OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .create(true)
-            .open(&path)?;
-        let mut buf = BufWriter::new(file);
-        serde_json::to_writer(&mut buf, self)?;
+    /// Writes the edges to `path` and the scopes to `{file name}-meta.csv` next to it, e.g. `cosmo.csv-meta.csv`
+    pub fn to_cosmograph_csv<P: AsRef<Path>>(&self, path: P) -> ParseResult<()> {
+        let mut cosmo = CosmoDiagram::from(&self.as_diagram());
+        cosmo.set_style_sheet(self.cosmo_style_sheet());
+        cosmo.render_to_file(&path.as_ref().to_string_lossy())?;
         Ok(())
     }
-
-    fn cache_path<P: AsRef<Path>>(path: P) -> PathBuf {
-        let pathbuf = PathBuf::from(path.as_ref());
-        let file_name = pathbuf
-            .file_name()
-            .and_then(|f| f.to_str())
-            .unwrap_or_default();
-        PathBuf::from(format!("/tmp/{file_name}"))
-    }
 }
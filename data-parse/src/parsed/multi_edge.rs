@@ -0,0 +1,138 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::{JavaLabel, ParsedEdge, ParsedScope, ParsedScopeGraph, StatixLabel};
+
+/// Duplicate and parallel edges of a graph, see [`ParsedScopeGraph::multi_edges`].
+///
+/// Duplicates are edges with the same source, target and label as an earlier edge,
+/// parallel edges connect the same scopes with different labels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiEdgeReport<L: Hash + Eq = JavaLabel> {
+    /// Number of edges that are a duplicate of an earlier edge
+    pub num_duplicates: usize,
+    pub duplicates_per_label: HashMap<L, usize>,
+    /// Number of `(from, to)` pairs that are connected by edges with more than one label
+    pub num_parallel_pairs: usize,
+}
+
+impl<L: Hash + Eq> MultiEdgeReport<L> {
+    pub fn is_empty(&self) -> bool {
+        self.num_duplicates == 0 && self.num_parallel_pairs == 0
+    }
+}
+
+/// Edge with the number of times it occurs in the graph
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MultiEdge<L = JavaLabel> {
+    pub edge: ParsedEdge<L>,
+    pub multiplicity: usize,
+}
+
+impl<L: StatixLabel> ParsedScopeGraph<L> {
+    pub fn multi_edges(&self) -> MultiEdgeReport<L> {
+        let mut seen = HashSet::new();
+        let mut duplicates_per_label = HashMap::new();
+        let mut labels_per_pair = HashMap::<(&ParsedScope, &ParsedScope), HashSet<&L>>::new();
+        for edge in &self.edges {
+            if !seen.insert(edge) {
+                *duplicates_per_label.entry(edge.label.clone()).or_default() += 1;
+            }
+            labels_per_pair
+                .entry((&edge.from, &edge.to))
+                .or_default()
+                .insert(&edge.label);
+        }
+        MultiEdgeReport {
+            num_duplicates: duplicates_per_label.values().sum(),
+            duplicates_per_label,
+            num_parallel_pairs: labels_per_pair.values().filter(|l| l.len() > 1).count(),
+        }
+    }
+
+    /// Removes duplicate edges, keeping the first occurrence. Returns the number of removed edges.
+    pub fn dedup_edges(&mut self) -> usize {
+        let before = self.edges.len();
+        let mut seen = HashSet::new();
+        self.edges.retain(|edge| seen.insert(edge.clone()));
+        before - self.edges.len()
+    }
+
+    /// Edges without duplicates, with the number of times each edge occurs.
+    ///
+    /// Edges are in the order of their first occurrence.
+    pub fn merged_edges(&self) -> Vec<MultiEdge<L>> {
+        let mut index = HashMap::<&ParsedEdge<L>, usize>::new();
+        let mut merged: Vec<MultiEdge<L>> = Vec::new();
+        for edge in &self.edges {
+            match index.get(edge) {
+                Some(&i) => merged[i].multiplicity += 1,
+                None => {
+                    index.insert(edge, merged.len());
+                    merged.push(MultiEdge {
+                        edge: edge.clone(),
+                        multiplicity: 1,
+                    });
+                }
+            }
+        }
+        merged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScopeData;
+
+    fn edge(from: &str, to: &str, label: JavaLabel) -> ParsedEdge {
+        ParsedEdge {
+            from: ParsedScope::new(from, "/./A.java"),
+            to: ParsedScope::new(to, "/./A.java"),
+            label,
+        }
+    }
+
+    fn graph() -> ParsedScopeGraph {
+        ParsedScopeGraph {
+            scopes: HashMap::from([
+                (ParsedScope::new("s-1", "/./A.java"), ScopeData::None),
+                (ParsedScope::new("s-2", "/./A.java"), ScopeData::None),
+            ]),
+            edges: vec![
+                edge("s-1", "s-2", JavaLabel::Parent),
+                edge("s-1", "s-2", JavaLabel::Parent),
+                edge("s-1", "s-2", JavaLabel::StaticParent),
+                edge("s-2", "s-1", JavaLabel::Parent),
+                edge("s-1", "s-2", JavaLabel::Parent),
+            ],
+            labels: vec![JavaLabel::Parent, JavaLabel::StaticParent],
+        }
+    }
+
+    #[test]
+    fn test_multi_edges() {
+        let report = graph().multi_edges();
+        assert_eq!(report.num_duplicates, 2);
+        assert_eq!(
+            report.duplicates_per_label,
+            HashMap::from([(JavaLabel::Parent, 2)])
+        );
+        assert_eq!(report.num_parallel_pairs, 1);
+    }
+
+    #[test]
+    fn test_normalize_edges() {
+        let merged = graph().merged_edges();
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].edge, edge("s-1", "s-2", JavaLabel::Parent));
+        assert_eq!(merged[0].multiplicity, 3);
+
+        let mut graph = graph();
+        assert_eq!(graph.dedup_edges(), 2);
+        assert_eq!(graph.edges.len(), 3);
+        assert_eq!(graph.multi_edges().num_duplicates, 0);
+    }
+}
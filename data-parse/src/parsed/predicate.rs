@@ -0,0 +1,307 @@
+use std::fmt::Display;
+
+use crate::raw::{ArrParams, DataWfParams, RawDataOrd, RawDataWf};
+
+/// Pattern that data is matched against, parsed from the params of `dataWf` and `dataOrd`.
+///
+/// Statix dumps these as terms, e.g. `(Id("x"), _)` for a declaration named `x`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DataPattern {
+    /// `_`, matches anything
+    Wildcard,
+    /// Variable that is bound by the match, it is only used in the guard
+    Var(String),
+    Literal(String),
+    /// Constructor application, tuples have an empty `op`
+    Term {
+        op: String,
+        args: Vec<DataPattern>,
+    },
+}
+
+impl From<&DataWfParams> for DataPattern {
+    fn from(params: &DataWfParams) -> Self {
+        match params {
+            DataWfParams::IdMatch(id) => Self::Literal(id.value.clone()),
+            DataWfParams::Data(data) => match &data.var {
+                Some(var) if !data.wildcard => Self::Var(var.name.clone()),
+                _ => Self::Wildcard,
+            },
+            DataWfParams::Arr(ArrParams { args, common }) => Self::Term {
+                op: common.op.clone().unwrap_or_default(),
+                args: args.iter().map(Self::from).collect(),
+            },
+        }
+    }
+}
+
+impl DataPattern {
+    pub fn tuple(args: Vec<DataPattern>) -> Self {
+        Self::Term {
+            op: String::new(),
+            args,
+        }
+    }
+
+    /// True if any data matches this pattern, ie it contains no literals
+    pub fn is_wildcard(&self) -> bool {
+        match self {
+            Self::Wildcard | Self::Var(_) => true,
+            Self::Literal(_) => false,
+            Self::Term { args, .. } => args.iter().all(Self::is_wildcard),
+        }
+    }
+
+    /// Name matched by this pattern.
+    ///
+    /// This is the `x` of `Id(x)`, also when it is the first element of a tuple,
+    /// or the id of the scope for `Scope(resource, name)`.
+    /// Wildcards and variables match any name and return `None`.
+    pub fn name(&self) -> Option<String> {
+        let Self::Term { op, args } = self else {
+            return match self {
+                Self::Literal(value) => Some(value.clone()),
+                _ => None,
+            };
+        };
+        match (op.as_str(), &args[..]) {
+            ("Id", [id]) => id.name(),
+            ("Scope", [Self::Literal(res), Self::Literal(name)]) => Some(format!("{res}-{name}")),
+            // tuple, the name is the first element
+            ("", [first, ..]) => first.name(),
+            _ => None,
+        }
+    }
+
+    /// Elements of a tuple pattern, a pattern that is not a tuple is its own single element
+    pub fn fields(&self) -> &[DataPattern] {
+        match self {
+            Self::Term { op, args } if op.is_empty() => args,
+            _ => std::slice::from_ref(self),
+        }
+    }
+}
+
+impl Display for DataPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Wildcard => write!(f, "_"),
+            Self::Var(name) => write!(f, "{name}"),
+            Self::Literal(value) => write!(f, "\"{value}\""),
+            Self::Term { op, args } => {
+                let args = args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+                write!(f, "{op}({})", args.join(", "))
+            }
+        }
+    }
+}
+
+/// Rule that is applied to the variables of a pattern, e.g. `paramsEq(params, params')`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Guard {
+    /// Full name of the rule, e.g. `java/names/MethodNames!paramsEq`
+    pub rule: String,
+    /// Variables passed to the rule, arguments that are not variables are left out
+    pub vars: Vec<String>,
+}
+
+impl Guard {
+    /// Parses the body of a predicate, which is `{"name": rule, "args": [...]}` for a rule
+    /// and has no name when the predicate is always true.
+    pub fn from_body(body: &serde_json::Value) -> Option<Self> {
+        let rule = body.get("name")?.as_str()?;
+        let vars = body
+            .get("args")
+            .and_then(|a| a.as_array())
+            .into_iter()
+            .flatten()
+            .filter(|a| a.get("op").is_none())
+            .filter_map(|a| a.get("name").and_then(|n| n.as_str()))
+            .map(str::to_string)
+            .collect();
+        Some(Self {
+            rule: rule.to_string(),
+            vars,
+        })
+    }
+
+    /// Name of the rule without the module, e.g. `paramsEq`
+    pub fn short_name(&self) -> &str {
+        self.rule
+            .rsplit_once('!')
+            .map(|(_, n)| n)
+            .unwrap_or(&self.rule)
+    }
+}
+
+impl Display for Guard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}({})", self.short_name(), self.vars.join(", "))
+    }
+}
+
+/// Data wellformedness of a query: data is wellformed if it matches the pattern and the guard holds
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DataWf {
+    pub pattern: DataPattern,
+    pub guard: Option<Guard>,
+}
+
+impl From<&RawDataWf> for DataWf {
+    fn from(raw: &RawDataWf) -> Self {
+        Self {
+            pattern: raw
+                .params
+                .first()
+                .map(DataPattern::from)
+                .unwrap_or(DataPattern::Wildcard),
+            guard: Guard::from_body(&raw.body),
+        }
+    }
+}
+
+impl DataWf {
+    /// Name the data has to match, `None` if any name matches
+    pub fn name(&self) -> Option<String> {
+        self.pattern.name()
+    }
+}
+
+impl Display for DataWf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{ {}", self.pattern)?;
+        if let Some(guard) = &self.guard {
+            write!(f, " :- {guard}")?;
+        }
+        write!(f, " }}")
+    }
+}
+
+/// Data order of a query, two data are equivalent if they match `lhs` and `rhs` and the guard holds.
+///
+/// Equivalent data shadow each other according to the label order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DataOrd {
+    pub lhs: DataPattern,
+    pub rhs: DataPattern,
+    pub guard: Option<Guard>,
+}
+
+impl From<&RawDataOrd> for DataOrd {
+    fn from(raw: &RawDataOrd) -> Self {
+        let mut params = raw.params.iter().map(DataPattern::from);
+        Self {
+            lhs: params.next().unwrap_or(DataPattern::Wildcard),
+            rhs: params.next().unwrap_or(DataPattern::Wildcard),
+            guard: Guard::from_body(&raw.body),
+        }
+    }
+}
+
+/// Which data a [`DataOrd`] considers equivalent
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DataEquivalence {
+    /// All data are equivalent, only the label order decides which data shadows
+    All,
+    /// Data are equivalent if the guard holds for these tuple fields
+    Fields(Vec<usize>),
+}
+
+impl DataOrd {
+    pub fn equivalence(&self) -> DataEquivalence {
+        let Some(guard) = &self.guard else {
+            return DataEquivalence::All;
+        };
+        let fields = self
+            .lhs
+            .fields()
+            .iter()
+            .zip(self.rhs.fields())
+            .enumerate()
+            .filter(|(_, pair)| match pair {
+                (DataPattern::Var(l), DataPattern::Var(r)) => {
+                    guard.vars.contains(l) && guard.vars.contains(r)
+                }
+                _ => false,
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        DataEquivalence::Fields(fields)
+    }
+}
+
+impl Display for DataOrd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{{ {}, {}", self.lhs, self.rhs)?;
+        if let Some(guard) = &self.guard {
+            write!(f, " :- {guard}")?;
+        }
+        write!(f, " }}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(json: &str) -> DataPattern {
+        let params: DataWfParams = serde_json::from_str(json).unwrap();
+        DataPattern::from(&params)
+    }
+
+    const WILDCARD: &str = r#"{"var": null, "constructed": false, "wildcard": true}"#;
+
+    fn var(name: &str) -> String {
+        format!(
+            r#"{{"var": {{"resource": "", "name": "{name}"}}, "constructed": false, "wildcard": false}}"#
+        )
+    }
+
+    #[test]
+    fn test_data_pattern() {
+        assert_eq!(params(WILDCARD), DataPattern::Wildcard);
+
+        let id = format!(
+            r#"{{"op": "", "args": [{{"op": "Id", "args": [{{"value": "x", "constructed": true}}], "constructed": true}}, {}, {WILDCARD}], "constructed": true}}"#,
+            var("params")
+        );
+        let pattern = params(&id);
+        assert_eq!(pattern.name(), Some("x".to_string()));
+        assert!(!pattern.is_wildcard());
+        assert_eq!(pattern.to_string(), "(Id(\"x\"), params, _)");
+
+        let scope = r#"{"op": "Scope", "args": [{"value": "/./a", "constructed": true}, {"value": "s-1", "constructed": true}], "constructed": true}"#;
+        assert_eq!(params(scope).name(), Some("/./a-s-1".to_string()));
+    }
+
+    #[test]
+    fn test_data_ord_equivalence() {
+        let tuple = |v: &str| {
+            DataPattern::tuple(vec![
+                DataPattern::Wildcard,
+                DataPattern::Var(v.to_string()),
+                DataPattern::Wildcard,
+            ])
+        };
+        let body = serde_json::json!({
+            "name": "java/names/MethodNames!paramsEq",
+            "args": [{"resource": "", "name": "params"}, {"resource": "", "name": "params'"}],
+        });
+        let ord = DataOrd {
+            lhs: tuple("params"),
+            rhs: tuple("params'"),
+            guard: Guard::from_body(&body),
+        };
+        assert_eq!(ord.equivalence(), DataEquivalence::Fields(vec![1]));
+        assert_eq!(
+            ord.to_string(),
+            "{ (_, params, _), (_, params', _) :- paramsEq(params, params') }"
+        );
+
+        let trivial = DataOrd {
+            guard: Guard::from_body(&serde_json::json!({"cause": null, "vars": []})),
+            ..ord
+        };
+        assert_eq!(trivial.equivalence(), DataEquivalence::All);
+    }
+}
@@ -1,17 +1,19 @@
-use std::{hash::Hash, str::FromStr};
+use std::{hash::Hash, str::FromStr, sync::Arc};
 
-use graphing::plantuml::NodeType;
+use graphing::{Color, plantuml::NodeType};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    JavaLabel, ParseResult,
+    JavaLabel, ParseResult, StatixLabel, deserialize_interned, intern, parse_label,
     raw::{RawEdge, RawEdgeKey, RawScope},
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ParsedScope {
-    pub resource: String,
-    pub name: String,
+    /// Interned, see [`intern`]
+    #[serde(deserialize_with = "deserialize_interned")]
+    pub resource: Arc<str>,
+    pub name: Arc<str>,
 }
 
 impl FromStr for ParsedScope {
@@ -25,25 +27,22 @@ impl FromStr for ParsedScope {
             .trim_start_matches("#");
         let name = split.collect::<Vec<_>>().join("-");
 
-        Ok(Self {
-            resource: resource.to_string(),
-            name,
-        })
+        Ok(Self::new(name, resource))
     }
 }
 
 impl From<RawScope> for ParsedScope {
     fn from(raw: RawScope) -> Self {
         let (name, resource) = raw.into_name_resource();
-        ParsedScope { resource, name }
+        ParsedScope::new(name, resource)
     }
 }
 
 impl ParsedScope {
-    pub fn new(name: impl Into<String>, resource: impl Into<String>) -> Self {
+    pub fn new(name: impl AsRef<str>, resource: impl AsRef<str>) -> Self {
         ParsedScope {
-            resource: resource.into(),
-            name: name.into(),
+            resource: intern(resource.as_ref()),
+            name: Arc::from(name.as_ref()),
         }
     }
 
@@ -55,14 +54,26 @@ impl ParsedScope {
         format!("{}-{}", self.resource, self.name)
     }
 
-    pub fn cosmo_color(&self) -> &'static str {
+    /// Kind of scope, used as class when drawing the scope
+    pub fn kind_class(&self) -> &'static str {
         match self {
-            s if s.is_class() => "rgba(58, 153, 95, 1)",
-            s if s.is_data() => "rgba(144, 103, 167, 1)",
-            s if s.is_method() => "rgba(211, 95, 96, 1)",
-            s if s.is_method_body() => "rgba(201, 149, 71, 1)",
-            s if s.is_var() => "rgba(39, 84, 138, 1)",
-            _ => "#f07070",
+            s if s.is_class() => "class",
+            s if s.is_data() => "data",
+            s if s.is_method() => "method",
+            s if s.is_method_body() => "method-body",
+            s if s.is_var() => "var",
+            _ => "scope",
+        }
+    }
+
+    pub fn cosmo_color(&self) -> Color {
+        match self {
+            s if s.is_class() => Color::new_rgb(58, 153, 95),
+            s if s.is_data() => Color::new_rgb(144, 103, 167),
+            s if s.is_method() => Color::new_rgb(211, 95, 96),
+            s if s.is_method_body() => Color::new_rgb(201, 149, 71),
+            s if s.is_var() => Color::new_rgb(39, 84, 138),
+            _ => Color::new_rgb_u32(0xf07070),
         }
     }
 
@@ -95,13 +106,13 @@ impl ParsedScope {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
-pub struct ParsedEdge {
+pub struct ParsedEdge<L = JavaLabel> {
     pub from: ParsedScope,
     pub to: ParsedScope,
-    pub label: JavaLabel,
+    pub label: L,
 }
 
-impl ParsedEdge {
+impl<L: StatixLabel> ParsedEdge<L> {
     pub fn from_raw(key: RawEdgeKey, head: RawEdge) -> ParseResult<Vec<Self>> {
         let mut to = Vec::new();
         let mut cur_head = head;
@@ -117,7 +128,7 @@ impl ParsedEdge {
             .into_iter()
             .map(|h| {
                 let to = ParsedScope::from(h);
-                let label = JavaLabel::try_from(key.label.as_str())?;
+                let label = parse_label(&key.label)?;
                 Ok(ParsedEdge {
                     from: from.clone(),
                     to,
@@ -0,0 +1,120 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{JavaLabel, ParsedEdge, ParsedScopeGraph, StatixLabel};
+
+/// Scope graph split into one subgraph per source file, see [`ParsedScopeGraph::split_by_resource`]
+#[derive(Debug)]
+pub struct ResourceSlices<L = JavaLabel> {
+    /// Subgraph of every resource, with the scopes of that resource and the edges between them
+    pub files: BTreeMap<String, ParsedScopeGraph<L>>,
+    /// Edges between scopes of different resources
+    pub inter_file_edges: Vec<ParsedEdge<L>>,
+}
+
+impl<L> ResourceSlices<L> {
+    pub fn get(&self, resource: &str) -> Option<&ParsedScopeGraph<L>> {
+        self.files.get(resource)
+    }
+
+    /// Edges that leave `resource`
+    pub fn outgoing(&self, resource: &str) -> impl Iterator<Item = &ParsedEdge<L>> {
+        self.inter_file_edges
+            .iter()
+            .filter(move |e| &*e.from.resource == resource)
+    }
+
+    /// Edges that enter `resource`
+    pub fn incoming(&self, resource: &str) -> impl Iterator<Item = &ParsedEdge<L>> {
+        self.inter_file_edges
+            .iter()
+            .filter(move |e| &*e.to.resource == resource)
+    }
+}
+
+impl<L: StatixLabel> ParsedScopeGraph<L> {
+    /// Splits the graph into a subgraph per resource, ie per compilation unit.
+    ///
+    /// Every scope ends up in the subgraph of its resource, edges between two resources
+    /// are not part of any subgraph and are kept in [`ResourceSlices::inter_file_edges`].
+    pub fn split_by_resource(self) -> ResourceSlices<L> {
+        let mut files = BTreeMap::new();
+        let new_slice = || ParsedScopeGraph {
+            scopes: HashMap::new(),
+            edges: Vec::new(),
+            labels: self.labels.clone(),
+        };
+
+        for (scope, data) in self.scopes {
+            files
+                .entry(scope.resource.to_string())
+                .or_insert_with(new_slice)
+                .scopes
+                .insert(scope, data);
+        }
+
+        let mut inter_file_edges = Vec::new();
+        for edge in self.edges {
+            if edge.from.resource != edge.to.resource {
+                inter_file_edges.push(edge);
+                continue;
+            }
+            files
+                .entry(edge.from.resource.to_string())
+                .or_insert_with(new_slice)
+                .edges
+                .push(edge);
+        }
+
+        ResourceSlices {
+            files,
+            inter_file_edges,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ParsedScope, ScopeData};
+
+    fn scope(name: &str, resource: &str) -> ParsedScope {
+        ParsedScope::new(name, resource)
+    }
+
+    fn edge(from: ParsedScope, to: ParsedScope) -> ParsedEdge {
+        ParsedEdge {
+            from,
+            to,
+            label: JavaLabel::Parent,
+        }
+    }
+
+    #[test]
+    fn test_split_by_resource() {
+        let a1 = scope("s-1", "/./A.java");
+        let a2 = scope("s-2", "/./A.java");
+        let b1 = scope("s-1", "/./B.java");
+        let graph = ParsedScopeGraph {
+            scopes: HashMap::from([
+                (a1.clone(), ScopeData::None),
+                (a2.clone(), ScopeData::None),
+                (b1.clone(), ScopeData::None),
+            ]),
+            edges: vec![edge(a2.clone(), a1.clone()), edge(b1.clone(), a1.clone())],
+            labels: vec![JavaLabel::Parent],
+        };
+
+        let slices = graph.split_by_resource();
+        assert_eq!(slices.files.len(), 2);
+        let a = slices.get("/./A.java").unwrap();
+        assert_eq!(a.scopes.len(), 2);
+        assert_eq!(a.edges, vec![edge(a2, a1.clone())]);
+        let b = slices.get("/./B.java").unwrap();
+        assert_eq!(b.scopes.len(), 1);
+        assert!(b.edges.is_empty());
+
+        assert_eq!(slices.inter_file_edges, vec![edge(b1, a1)]);
+        assert_eq!(slices.outgoing("/./B.java").count(), 1);
+        assert_eq!(slices.incoming("/./B.java").count(), 0);
+    }
+}
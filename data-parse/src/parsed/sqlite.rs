@@ -0,0 +1,128 @@
+use std::{collections::HashMap, path::Path};
+
+use rusqlite::{Connection, params};
+
+use crate::{ParseResult, ParsedScope, ParsedScopeGraph, StatixLabel};
+
+const SCHEMA: &str = "
+CREATE TABLE scopes (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    resource TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    data TEXT
+);
+CREATE TABLE edges (
+    id INTEGER PRIMARY KEY,
+    source INTEGER NOT NULL REFERENCES scopes(id),
+    target INTEGER NOT NULL REFERENCES scopes(id),
+    label TEXT NOT NULL
+);
+CREATE INDEX scopes_resource ON scopes(resource);
+CREATE INDEX scopes_name ON scopes(name);
+CREATE INDEX edges_source ON edges(source);
+CREATE INDEX edges_target ON edges(target);
+CREATE INDEX edges_label ON edges(label);
+";
+
+impl<L: StatixLabel> ParsedScopeGraph<L> {
+    /// Writes the graph to an SQLite database at `path`, replacing the file if it exists.
+    ///
+    /// Scopes are stored in the `scopes` table and edges in the `edges` table, which refers to scopes by id.
+    /// Endpoints of edges that are not in [`ParsedScopeGraph::scopes`] are added without data.
+    pub fn to_sqlite<P: AsRef<Path>>(&self, path: P) -> ParseResult<()> {
+        if path.as_ref().exists() {
+            std::fs::remove_file(&path)?;
+        }
+        let mut conn = Connection::open(&path)?;
+        conn.execute_batch(SCHEMA)?;
+
+        let tx = conn.transaction()?;
+        {
+            let mut insert_scope = tx.prepare(
+                "INSERT INTO scopes (id, name, resource, kind, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            let endpoints = self
+                .edges
+                .iter()
+                .flat_map(|e| [&e.from, &e.to])
+                .filter(|s| !self.scopes.contains_key(*s))
+                .map(|s| (s, None));
+            let scopes = self
+                .scopes
+                .iter()
+                .map(|(s, data)| (s, Some(data.to_string())))
+                .chain(endpoints);
+            let mut ids = HashMap::<&ParsedScope, i64>::new();
+            for (scope, data) in scopes {
+                if ids.contains_key(scope) {
+                    continue;
+                }
+                let id = ids.len() as i64;
+                insert_scope.execute(params![
+                    id,
+                    scope.name,
+                    scope.resource,
+                    scope.kind_class(),
+                    data
+                ])?;
+                ids.insert(scope, id);
+            }
+
+            let mut insert_edge =
+                tx.prepare("INSERT INTO edges (source, target, label) VALUES (?1, ?2, ?3)")?;
+            for edge in &self.edges {
+                insert_edge.execute(params![
+                    ids[&edge.from],
+                    ids[&edge.to],
+                    edge.label.to_string()
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JavaLabel, ScopeData};
+
+    #[test]
+    fn test_to_sqlite() {
+        let mut graph = ParsedScopeGraph::builder()
+            .add_scope("s-1", "/./A.java")
+            .add_scope_with_data(
+                "d-2",
+                "/./A.java",
+                ScopeData::Ref(ParsedScope::new("s-1", "/./A.java")),
+            )
+            .add_scope("s-3", "/./B.java")
+            .add_edge("s-1", JavaLabel::Parent, "d-2")
+            .add_edge("s-1", JavaLabel::Parent, "s-3")
+            .build()
+            .unwrap();
+        // endpoint that is not in the scopes
+        graph.scopes.remove(&ParsedScope::new("s-3", "/./B.java"));
+
+        let path = std::env::temp_dir().join(format!("sqlite-test-{}.db", std::process::id()));
+        graph.to_sqlite(&path).unwrap();
+        // overwrites the existing database
+        graph.to_sqlite(&path).unwrap();
+
+        let conn = Connection::open(&path).unwrap();
+        let count = |sql: &str| -> i64 { conn.query_row(sql, [], |r| r.get(0)).unwrap() };
+        assert_eq!(count("SELECT COUNT(*) FROM scopes"), 3);
+        assert_eq!(count("SELECT COUNT(*) FROM scopes WHERE data IS NULL"), 1);
+        assert_eq!(
+            count(
+                "SELECT COUNT(*) FROM edges e JOIN scopes s ON e.target = s.id \
+                 WHERE s.resource = '/./B.java'"
+            ),
+            1
+        );
+        drop(conn);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use graphing::{create_file, csv_field};
+use serde::{Deserialize, Serialize};
+
+use crate::{ParseResult, ParsedScopeGraph, StatixLabel};
+
+/// Statistics of a scope graph, see [`ParsedScopeGraph::stats`].
+///
+/// Labels and resources are keyed by name, so the statistics of graphs with different labels have the same shape.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct GraphStats {
+    pub num_scopes: usize,
+    pub num_data_scopes: usize,
+    pub num_edges: usize,
+    /// Number of edges with each label
+    pub label_frequencies: BTreeMap<String, usize>,
+    /// Fraction of the edges with each label
+    pub label_ratios: BTreeMap<String, f64>,
+    /// Number of scopes with each number of outgoing edges
+    pub out_degrees: BTreeMap<usize, usize>,
+    /// Number of scopes with each number of incoming edges
+    pub in_degrees: BTreeMap<usize, usize>,
+    pub scopes_per_resource: BTreeMap<String, usize>,
+}
+
+impl<L: StatixLabel> ParsedScopeGraph<L> {
+    pub fn stats(&self) -> GraphStats {
+        let mut label_frequencies = BTreeMap::new();
+        let mut out_edges = HashMap::<_, usize>::new();
+        let mut in_edges = HashMap::<_, usize>::new();
+        for edge in &self.edges {
+            *label_frequencies.entry(edge.label.to_string()).or_default() += 1;
+            *out_edges.entry(&edge.from).or_default() += 1;
+            *in_edges.entry(&edge.to).or_default() += 1;
+        }
+        let label_ratios = label_frequencies
+            .iter()
+            .map(|(label, count)| (label.clone(), *count as f64 / self.edges.len() as f64))
+            .collect();
+
+        let mut out_degrees = BTreeMap::new();
+        let mut in_degrees = BTreeMap::new();
+        let mut scopes_per_resource = BTreeMap::new();
+        for scope in self.scopes.keys() {
+            let out_degree = out_edges.get(scope).copied().unwrap_or(0);
+            let in_degree = in_edges.get(scope).copied().unwrap_or(0);
+            *out_degrees.entry(out_degree).or_default() += 1;
+            *in_degrees.entry(in_degree).or_default() += 1;
+            *scopes_per_resource
+                .entry(scope.resource.to_string())
+                .or_default() += 1;
+        }
+
+        GraphStats {
+            num_scopes: self.scopes.len(),
+            num_data_scopes: self.scopes.keys().filter(|s| s.is_data()).count(),
+            num_edges: self.edges.len(),
+            label_frequencies,
+            label_ratios,
+            out_degrees,
+            in_degrees,
+            scopes_per_resource,
+        }
+    }
+}
+
+impl GraphStats {
+    /// Adds the counts of `other` to these statistics, e.g. to combine the graphs of a [`crate::Corpus`]
+    pub fn merge(&mut self, other: &Self) {
+        self.num_scopes += other.num_scopes;
+        self.num_data_scopes += other.num_data_scopes;
+        self.num_edges += other.num_edges;
+        fn add<K: Ord + Clone>(into: &mut BTreeMap<K, usize>, from: &BTreeMap<K, usize>) {
+            for (key, count) in from {
+                *into.entry(key.clone()).or_default() += count;
+            }
+        }
+        add(&mut self.label_frequencies, &other.label_frequencies);
+        add(&mut self.out_degrees, &other.out_degrees);
+        add(&mut self.in_degrees, &other.in_degrees);
+        add(&mut self.scopes_per_resource, &other.scopes_per_resource);
+        self.label_ratios = self
+            .label_frequencies
+            .iter()
+            .map(|(label, count)| (label.clone(), *count as f64 / self.num_edges as f64))
+            .collect();
+    }
+
+    pub fn to_json<P: AsRef<Path>>(&self, path: P) -> ParseResult<()> {
+        let file = create_file(path)?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    /// Writes the statistics as `;`-separated `section;key;value` rows,
+    /// e.g. `label_frequencies;PARENT;1234` or `out_degrees;2;5678`.
+    pub fn to_csv<P: AsRef<Path>>(&self, path: P) -> ParseResult<()> {
+        let mut writer = BufWriter::new(create_file(path)?);
+        self.write_csv(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writeln!(writer, "section;key;value")?;
+        writeln!(writer, "totals;scopes;{}", self.num_scopes)?;
+        writeln!(writer, "totals;data_scopes;{}", self.num_data_scopes)?;
+        writeln!(writer, "totals;edges;{}", self.num_edges)?;
+
+        fn rows<W: Write, K: ToString, V: ToString>(
+            writer: &mut W,
+            section: &str,
+            map: &BTreeMap<K, V>,
+        ) -> std::io::Result<()> {
+            for (key, value) in map {
+                writeln!(
+                    writer,
+                    "{section};{};{}",
+                    csv_field(&key.to_string(), ';'),
+                    value.to_string()
+                )?;
+            }
+            Ok(())
+        }
+        rows(writer, "label_frequencies", &self.label_frequencies)?;
+        rows(writer, "label_ratios", &self.label_ratios)?;
+        rows(writer, "out_degrees", &self.out_degrees)?;
+        rows(writer, "in_degrees", &self.in_degrees)?;
+        rows(writer, "scopes_per_resource", &self.scopes_per_resource)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JavaLabel, ParsedEdge, ParsedScope, ScopeData};
+
+    #[test]
+    fn test_stats() {
+        let a = ParsedScope::new("s-1", "/./A.java");
+        let b = ParsedScope::new("s-2", "/./A.java");
+        let c = ParsedScope::new("s-1", "/./B.java");
+        let edge = |from: &ParsedScope, to: &ParsedScope, label| ParsedEdge {
+            from: from.clone(),
+            to: to.clone(),
+            label,
+        };
+        let graph = ParsedScopeGraph {
+            scopes: HashMap::from([
+                (a.clone(), ScopeData::None),
+                (b.clone(), ScopeData::None),
+                (c.clone(), ScopeData::None),
+            ]),
+            edges: vec![
+                edge(&a, &b, JavaLabel::Parent),
+                edge(&a, &c, JavaLabel::Parent),
+                edge(&b, &c, JavaLabel::StaticMember),
+            ],
+            labels: vec![JavaLabel::Parent, JavaLabel::StaticMember],
+        };
+
+        let stats = graph.stats();
+        assert_eq!(stats.num_scopes, 3);
+        assert_eq!(stats.num_edges, 3);
+        assert_eq!(stats.out_degrees, BTreeMap::from([(0, 1), (1, 1), (2, 1)]));
+        assert_eq!(stats.in_degrees, BTreeMap::from([(0, 1), (1, 1), (2, 1)]));
+        assert_eq!(
+            stats.scopes_per_resource,
+            BTreeMap::from([("/./A.java".to_string(), 2), ("/./B.java".to_string(), 1)])
+        );
+        let parent = JavaLabel::Parent.to_string();
+        assert_eq!(stats.label_frequencies[&parent], 2);
+        assert!((stats.label_ratios[&parent] - 2.0 / 3.0).abs() < 1e-9);
+
+        let mut csv = Vec::new();
+        stats.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.contains(&format!("label_frequencies;{parent};2")));
+        assert!(csv.contains("scopes_per_resource;/./B.java;1"));
+
+        let mut merged = GraphStats::default();
+        merged.merge(&stats);
+        merged.merge(&stats);
+        assert_eq!(merged.num_edges, 6);
+        assert_eq!(merged.label_frequencies[&parent], 4);
+        assert_eq!(merged.out_degrees, BTreeMap::from([(0, 2), (1, 2), (2, 2)]));
+        assert!((merged.label_ratios[&parent] - 2.0 / 3.0).abs() < 1e-9);
+    }
+}
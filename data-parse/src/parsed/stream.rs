@@ -0,0 +1,135 @@
+use std::{collections::HashMap, fmt, marker::PhantomData, str::FromStr};
+
+use indicatif::ProgressBar;
+use serde::{
+    Deserializer,
+    de::{DeserializeSeed, Error, IgnoredAny, MapAccess, Visitor},
+};
+
+use crate::{
+    ParseResult, ParsedEdge, ParsedLabel, ParsedScope, ParsedScopeGraph, ScopeData, StatixLabel,
+    progress::ParseProgress,
+    raw::{JavaValue, RawEdge, RawEdgeHead, RawEdgeKey, RawLabel},
+};
+
+/// Builds a [`ParsedScopeGraph`] while deserializing a raw Statix dump.
+///
+/// Every scope and edge is converted as soon as it is read,
+/// so the [`RawScopeGraph`](crate::RawScopeGraph) is never in memory as a whole.
+/// Converted scopes and edges are counted on `progress`.
+pub(super) struct StreamingGraph<'a, L> {
+    progress: &'a ParseProgress,
+    label: PhantomData<L>,
+}
+
+impl<'a, L> StreamingGraph<'a, L> {
+    pub fn new(progress: &'a ParseProgress) -> Self {
+        Self {
+            progress,
+            label: PhantomData,
+        }
+    }
+}
+
+impl<'de, L: StatixLabel> DeserializeSeed<'de> for StreamingGraph<'_, L> {
+    type Value = ParsedScopeGraph<L>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, L: StatixLabel> Visitor<'de> for StreamingGraph<'_, L> {
+    type Value = ParsedScopeGraph<L>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a Statix scope graph")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut graph = ParsedScopeGraph {
+            scopes: HashMap::new(),
+            edges: Vec::new(),
+            labels: Vec::new(),
+        };
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "data" => {
+                    map.next_value_seed(DataSeed(&mut graph.scopes, &self.progress.scopes))?
+                }
+                "edges" => {
+                    map.next_value_seed(EdgesSeed(&mut graph.edges, &self.progress.edges))?
+                }
+                "labels" => {
+                    let labels = map.next_value::<Vec<RawLabel>>()?;
+                    graph.labels = labels
+                        .into_iter()
+                        .map(ParsedLabel::from)
+                        .map(|l| l.to_label())
+                        .collect::<ParseResult<Vec<_>>>()
+                        .map_err(A::Error::custom)?;
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(graph)
+    }
+}
+
+/// Inserts the entries of the `data` map into the scopes
+struct DataSeed<'a>(&'a mut HashMap<ParsedScope, ScopeData>, &'a ProgressBar);
+
+impl<'de> DeserializeSeed<'de> for DataSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for DataSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map from scope to data")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some((scope_key, data)) = map.next_entry::<String, JavaValue>()? {
+            let scope = ParsedScope::from_str(&scope_key).map_err(A::Error::custom)?;
+            self.0.insert(scope, ScopeData::from(data));
+            self.1.inc(1);
+        }
+        Ok(())
+    }
+}
+
+/// Converts the entries of the `edges` map and adds them to the edges
+struct EdgesSeed<'a, L>(&'a mut Vec<ParsedEdge<L>>, &'a ProgressBar);
+
+impl<'de, L: StatixLabel> DeserializeSeed<'de> for EdgesSeed<'_, L> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, L: StatixLabel> Visitor<'de> for EdgesSeed<'_, L> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map from scope and label to edges")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some((key, edge)) = map.next_entry::<RawEdgeKey, RawEdgeHead>()? {
+            let edges = ParsedEdge::from_raw(key, RawEdge::Head(edge)).map_err(A::Error::custom)?;
+            self.1.inc(edges.len() as u64);
+            self.0.extend(edges);
+        }
+        Ok(())
+    }
+}
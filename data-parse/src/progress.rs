@@ -0,0 +1,104 @@
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Hides the progress bars and messages of parsing, for using the crate as a library
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Prints `msg` unless [`set_quiet`] is enabled
+pub(crate) fn report(msg: impl Display) {
+    if !is_quiet() {
+        println!("{msg}");
+    }
+}
+
+/// Progress of parsing a raw dump: bytes read, scopes and edges converted, and the time every phase took
+pub(crate) struct ParseProgress {
+    multi: MultiProgress,
+    pub bytes: ProgressBar,
+    pub scopes: ProgressBar,
+    pub edges: ProgressBar,
+    phase_start: Instant,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl ParseProgress {
+    pub fn new() -> Self {
+        let target = match is_quiet() {
+            true => ProgressDrawTarget::hidden(),
+            false => ProgressDrawTarget::stderr(),
+        };
+        let multi = MultiProgress::with_draw_target(target);
+        let bytes = multi.add(ProgressBar::new(0)).with_style(
+            ProgressStyle::with_template(
+                "[{elapsed_precise}] {bar:40.cyan/blue} {bytes:>10}/{total_bytes:10} read",
+            )
+            .unwrap()
+            .progress_chars("##-"),
+        );
+        let counter = |name: &str| {
+            multi.add(ProgressBar::no_length()).with_style(
+                ProgressStyle::with_template(&format!("{{human_pos:>10}} {name} converted"))
+                    .unwrap(),
+            )
+        };
+        let scopes = counter("scopes");
+        let edges = counter("edges");
+        Self {
+            multi,
+            bytes,
+            scopes,
+            edges,
+            phase_start: Instant::now(),
+            phases: Vec::new(),
+        }
+    }
+
+    /// Ends the current phase, which started at the end of the previous phase
+    pub fn end_phase(&mut self, name: &'static str) {
+        let elapsed = self.phase_start.elapsed();
+        self.phases.push((name, elapsed));
+        self.multi.suspend(|| report(format!("{name} took: {elapsed:?}")));
+        self.phase_start = Instant::now();
+    }
+
+    /// Stops the bars and reports the total time of all phases
+    pub fn finish(&self) {
+        self.bytes.finish();
+        self.scopes.finish();
+        self.edges.finish();
+        if self.phases.len() > 1 {
+            let total = self.phases.iter().map(|(_, d)| *d).sum::<Duration>();
+            report(format!("Parsing took: {total:?}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases() {
+        let mut progress = ParseProgress::new();
+        progress.scopes.inc(2);
+        progress.end_phase("reading");
+        progress.end_phase("conversion");
+        progress.finish();
+        let names = progress.phases.iter().map(|(n, _)| *n).collect::<Vec<_>>();
+        assert_eq!(names, ["reading", "conversion"]);
+        assert_eq!(progress.scopes.position(), 2);
+    }
+}
@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+use crate::{
+    ParseResult,
+    raw::{RawEdgeHead, RawEdgeKey, RawEdgeTail, RawLabel, RawScope, RawScopeGraph},
+};
+
+/// Term in the ATerm text format, e.g. `Scope("/./A.java", "s-1")`.
+///
+/// Annotations (`{...}`) are parsed but dropped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ATerm {
+    Appl(String, Vec<ATerm>),
+    Tuple(Vec<ATerm>),
+    List(Vec<ATerm>),
+    Str(String),
+    Int(i64),
+}
+
+impl ATerm {
+    pub fn parse(input: &str) -> ParseResult<Self> {
+        let mut parser = ATermParser { input, pos: 0 };
+        let term = parser.term()?;
+        parser.skip_whitespace();
+        match parser.pos == input.len() {
+            true => Ok(term),
+            false => Err(parser.error("end of input")),
+        }
+    }
+
+    /// Arguments of an application with constructor `op`
+    pub fn appl_args(&self, op: &str) -> Option<&[ATerm]> {
+        match self {
+            ATerm::Appl(name, args) if name == op => Some(args),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ATerm::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Converts the term to the JSON that Statix writes for it, so it can be read by the raw types.
+    ///
+    /// Applications are `{"op", "arg0", .., "args", "arity"}` objects, tuples have an empty `op`,
+    /// strings and integers are `{"value"}` objects.
+    /// Scopes also get their `resource` and `name` fields, like in the JSON dumps.
+    pub fn to_json(&self) -> Value {
+        match self {
+            ATerm::Appl(op, args) => appl_json(op, args),
+            ATerm::Tuple(args) => appl_json("", args),
+            ATerm::List(items) => Value::Array(items.iter().map(ATerm::to_json).collect()),
+            ATerm::Str(s) => serde_json::json!({ "value": s }),
+            ATerm::Int(i) => serde_json::json!({ "value": i.to_string() }),
+        }
+    }
+}
+
+fn appl_json(op: &str, args: &[ATerm]) -> Value {
+    let args = args.iter().map(ATerm::to_json).collect::<Vec<_>>();
+    let mut obj = Map::new();
+    obj.insert("op".to_string(), Value::from(op));
+    for (i, arg) in args.iter().enumerate() {
+        obj.insert(format!("arg{i}"), arg.clone());
+    }
+    if let ("Scope", [resource, name]) = (op, &args[..]) {
+        obj.insert("resource".to_string(), resource["value"].clone());
+        obj.insert("name".to_string(), name["value"].clone());
+    }
+    obj.insert("arity".to_string(), Value::from(args.len()));
+    obj.insert("args".to_string(), Value::Array(args));
+    Value::Object(obj)
+}
+
+struct ATermParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl ATermParser<'_> {
+    fn error(&self, expected: &str) -> crate::ParseError {
+        format!("Invalid ATerm at byte {}: expected {expected}", self.pos).into()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, b: u8) -> ParseResult<()> {
+        self.skip_whitespace();
+        match self.peek() == Some(b) {
+            true => {
+                self.pos += 1;
+                Ok(())
+            }
+            false => Err(self.error(&format!("'{}'", b as char))),
+        }
+    }
+
+    fn term(&mut self) -> ParseResult<ATerm> {
+        self.skip_whitespace();
+        let term = match self.peek() {
+            Some(b'"') => ATerm::Str(self.string()?),
+            Some(b'[') => ATerm::List(self.terms(b'[', b']')?),
+            Some(b'(') => ATerm::Tuple(self.terms(b'(', b')')?),
+            Some(b) if b == b'-' || b.is_ascii_digit() => self.int()?,
+            Some(b) if b.is_ascii_alphabetic() || b == b'_' => {
+                let name = self.constructor();
+                self.skip_whitespace();
+                let args = match self.peek() {
+                    Some(b'(') => self.terms(b'(', b')')?,
+                    _ => Vec::new(),
+                };
+                ATerm::Appl(name, args)
+            }
+            _ => return Err(self.error("a term")),
+        };
+        self.skip_whitespace();
+        if self.peek() == Some(b'{') {
+            // annotations are not used
+            self.terms(b'{', b'}')?;
+        }
+        Ok(term)
+    }
+
+    /// Comma separated terms between `open` and `close`
+    fn terms(&mut self, open: u8, close: u8) -> ParseResult<Vec<ATerm>> {
+        self.expect(open)?;
+        let mut terms = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(close) {
+            self.pos += 1;
+            return Ok(terms);
+        }
+        loop {
+            terms.push(self.term()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b) if b == close => {
+                    self.pos += 1;
+                    return Ok(terms);
+                }
+                _ => return Err(self.error(&format!("',' or '{}'", close as char))),
+            }
+        }
+    }
+
+    fn constructor(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-' || b == b'\'')
+        {
+            self.pos += 1;
+        }
+        self.input[start..self.pos].to_string()
+    }
+
+    fn int(&mut self) -> ParseResult<ATerm> {
+        let start = self.pos;
+        self.pos += 1;
+        while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let int = self.input[start..self.pos]
+            .parse()
+            .map_err(|_| self.error("an integer"))?;
+        Ok(ATerm::Int(int))
+    }
+
+    fn string(&mut self) -> ParseResult<String> {
+        self.expect(b'"')?;
+        let mut s = String::new();
+        let mut chars = self.input[self.pos..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(s);
+                }
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some('r') => s.push('\r'),
+                    Some(c) => s.push(c),
+                    None => break,
+                },
+                c => s.push(c),
+            }
+        }
+        self.pos = self.input.len();
+        Err(self.error("'\"'"))
+    }
+}
+
+/// Key of a scope in the `data` and `edges` maps of the JSON dumps, e.g. `#/./A.java-s-1`
+fn scope_key(scope: &ATerm) -> ParseResult<String> {
+    match scope.appl_args("Scope") {
+        Some([ATerm::Str(resource), ATerm::Str(name)]) => Ok(format!("#{resource}-{name}")),
+        _ => Err(format!("Expected Scope(resource, name), found {scope:?}").into()),
+    }
+}
+
+fn from_term<T: serde::de::DeserializeOwned>(term: &ATerm) -> ParseResult<T> {
+    Ok(serde_json::from_value(term.to_json())?)
+}
+
+impl RawScopeGraph {
+    /// Reads a scope graph in the ATerm format of older Spoofax pipelines:
+    ///
+    /// ```text
+    /// ScopeGraph(
+    ///   [Label("java/names/Main!EXTENDS"), ..],
+    ///   [(Scope("/./A.java", "s-1"), Label("java/names/Main!EXTENDS"), [Scope("/./B.java", "s-1"), ..]), ..],
+    ///   [(Scope("/./A.java", "d-1"), (Id("A"), Scope("/./A.java", "s-2"))), ..]
+    /// )
+    /// ```
+    ///
+    /// with the labels, the edges grouped by source scope and label, and the data of the scopes.
+    pub fn from_aterm(input: &str) -> ParseResult<Self> {
+        let term = ATerm::parse(input)?;
+        let Some([labels, edges, data]) = term.appl_args("ScopeGraph") else {
+            return Err("Expected ScopeGraph(labels, edges, data)".into());
+        };
+        let (ATerm::List(labels), ATerm::List(edges), ATerm::List(data)) = (labels, edges, data)
+        else {
+            return Err("Expected lists of labels, edges and data".into());
+        };
+
+        let labels = labels
+            .iter()
+            .map(from_term::<RawLabel>)
+            .collect::<ParseResult<Vec<_>>>()?;
+
+        let edges = edges
+            .iter()
+            .filter_map(|edge| {
+                let ATerm::Tuple(edge) = edge else {
+                    return Some(Err(format!(
+                        "Expected (scope, label, targets), found {edge:?}"
+                    )
+                    .into()));
+                };
+                let [source, label, ATerm::List(targets)] = &edge[..] else {
+                    return Some(Err(format!(
+                        "Expected (scope, label, targets), found {edge:?}"
+                    )
+                    .into()));
+                };
+                edge_entry(source, label, targets).transpose()
+            })
+            .collect::<ParseResult<HashMap<_, _>>>()?;
+
+        let data = data
+            .iter()
+            .map(|entry| match entry {
+                ATerm::Tuple(pair) if pair.len() == 2 => {
+                    Ok((scope_key(&pair[0])?, from_term(&pair[1])?))
+                }
+                _ => Err(format!("Expected (scope, data), found {entry:?}").into()),
+            })
+            .collect::<ParseResult<HashMap<_, _>>>()?;
+
+        Ok(Self {
+            data,
+            labels,
+            edges,
+        })
+    }
+}
+
+/// Edges from `source` with `label` as the linked list of the JSON dumps, `None` without targets
+fn edge_entry(
+    source: &ATerm,
+    label: &ATerm,
+    targets: &[ATerm],
+) -> ParseResult<Option<(RawEdgeKey, RawEdgeHead)>> {
+    let label = match label.appl_args("Label") {
+        Some([ATerm::Str(label)]) => label.clone(),
+        _ => return Err(format!("Expected Label(name), found {label:?}").into()),
+    };
+    let key = RawEdgeKey {
+        s1: scope_key(source)?,
+        label,
+    };
+
+    let mut targets = targets
+        .iter()
+        .map(from_term::<RawScope>)
+        .collect::<ParseResult<Vec<_>>>()?;
+    if targets.is_empty() {
+        return Ok(None);
+    }
+    let head = targets.remove(0);
+    // the list ends with an empty tail
+    let tail = targets.into_iter().rev().fold(
+        RawEdgeTail {
+            head: None,
+            tail: None,
+        },
+        |tail, head| RawEdgeTail {
+            head: Some(head),
+            tail: Some(Box::new(tail)),
+        },
+    );
+    Ok(Some((
+        key,
+        RawEdgeHead {
+            head,
+            tail: Box::new(tail),
+        },
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JavaLabel, ParsedScope, ParsedScopeGraph, ScopeData};
+
+    #[test]
+    fn test_parse_aterm() {
+        let term = ATerm::parse(r#"Cons("a\"b", [1, -2], (), Nil){Anno}"#).unwrap();
+        assert_eq!(
+            term,
+            ATerm::Appl(
+                "Cons".to_string(),
+                vec![
+                    ATerm::Str("a\"b".to_string()),
+                    ATerm::List(vec![ATerm::Int(1), ATerm::Int(-2)]),
+                    ATerm::Tuple(Vec::new()),
+                    ATerm::Appl("Nil".to_string(), Vec::new()),
+                ]
+            )
+        );
+        assert!(ATerm::parse("Cons(").is_err());
+        assert!(ATerm::parse("Cons() trailing").is_err());
+    }
+
+    #[test]
+    fn test_scope_graph_from_aterm() {
+        let input = r#"ScopeGraph(
+            [Label("java/names/Main!LEX"), Label("java/names/TypeNames!type")],
+            [
+                (Scope("/./A.java", "s-2"), Label("java/names/Main!LEX"), [Scope("/./A.java", "s-1")]),
+                (Scope("/./A.java", "s-1"), Label("java/names/TypeNames!type"),
+                    [Scope("/./A.java", "d-1"), Scope("/./A.java", "d-2")])
+            ],
+            [
+                (Scope("/./A.java", "d-1"), (Id("A"), Scope("/./A.java", "s-2"))),
+                (Scope("/./A.java", "d-2"), REF(Scope("/./B.java", "s-1")))
+            ]
+        )"#;
+        let raw = RawScopeGraph::from_aterm(input).unwrap();
+        assert_eq!(raw.labels.len(), 2);
+        assert_eq!(raw.edges.len(), 2);
+
+        let graph = ParsedScopeGraph::<JavaLabel>::try_from(raw).unwrap();
+        assert_eq!(graph.edges.len(), 3);
+        let d1 = ParsedScope::new("d-1", "/./A.java");
+        assert!(matches!(
+            &graph.scopes[&d1],
+            ScopeData::ClassOrMethod(name, scope) if name == "A" && scope.name() == "s-2"
+        ));
+        let d2 = ParsedScope::new("d-2", "/./A.java");
+        assert!(
+            matches!(&graph.scopes[&d2], ScopeData::Ref(scope) if &*scope.resource == "/./B.java")
+        );
+    }
+
+    #[test]
+    fn test_malformed_aterm_edge_is_an_error() {
+        let input = r#"ScopeGraph(
+            [Label("java/names/Main!LEX")],
+            [(Scope("/./A.java", "s-2"), Label("java/names/Main!NOT_A_LABEL"), [Scope("/./A.java", "s-1")])],
+            []
+        )"#;
+        let raw = RawScopeGraph::from_aterm(input).unwrap();
+        assert!(ParsedScopeGraph::<JavaLabel>::try_from(raw).is_err());
+    }
+}
@@ -1,7 +1,9 @@
+mod aterm;
 mod graph;
 mod java;
 mod query;
 
+pub use aterm::*;
 pub use graph::*;
 pub use java::*;
 pub use query::*;
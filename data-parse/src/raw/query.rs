@@ -1,16 +1,104 @@
 use serde::Deserialize;
 
-use crate::raw::RawScope;
+use crate::raw::{RawLabel, RawScope};
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct RawQueryData {
-    pub dataOrd: serde_json::Value,
+    pub dataOrd: RawDataOrd,
     pub dataWf: RawDataWf,
     pub labelOrd: serde_json::Value,
     pub pathWf: PathWf,
     pub scope: RawScope,
 }
 
+impl RawQueryData {
+    /// Label names of the label order as `(lhs, rhs)` for `lhs < rhs`.
+    ///
+    /// Statix dumps the order as a hash trie that maps every label to the labels it is less than,
+    /// orderings with the end of the path (`<data>`) are left out.
+    pub fn label_order(&self) -> Vec<(String, String)> {
+        let mut order = Vec::new();
+        if let Some(root) = self.labelOrd.pointer("/entries/fwd/rootNode") {
+            collect_label_order(root, &mut order);
+        }
+        order
+    }
+
+    /// True if the path wellformedness accepts the empty path,
+    /// ie the query is resolved in its start scope.
+    pub fn accepts_empty_path(&self) -> bool {
+        self.pathWf.accepting
+    }
+}
+
+/// Walks the nodes of a trie, which contain `label, [labels]` or `label, label` pairs and sub-tries.
+fn collect_label_order(node: &serde_json::Value, order: &mut Vec<(String, String)>) {
+    fn label_name(v: &serde_json::Value) -> Option<&str> {
+        v.pointer("/label/arg0/value").and_then(|n| n.as_str())
+    }
+
+    let Some(nodes) = node.get("nodes").and_then(|n| n.as_array()) else {
+        return;
+    };
+    let mut iter = nodes.iter();
+    while let Some(n) = iter.next() {
+        let Some(lhs) = label_name(n) else {
+            collect_label_order(n, order);
+            continue;
+        };
+        let Some(rhs) = iter.next() else {
+            break;
+        };
+        let rhs = match rhs.as_array() {
+            Some(arr) => arr.iter().filter_map(label_name).collect::<Vec<_>>(),
+            None => label_name(rhs).into_iter().collect(),
+        };
+        order.extend(rhs.into_iter().map(|r| (lhs.to_string(), r.to_string())));
+    }
+}
+
+/// Environments that Statix computed for a query, the results are in the same order as the queries
+#[derive(Deserialize, Debug)]
+pub struct RawQueryResult {
+    pub paths: Vec<RawResultEnv>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawResultEnv {
+    // the datum is the data of the target scope, so it is not needed
+    pub path: RawResultPath,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawResultPath {
+    pub source: RawScope,
+    /// Last step of the path, `None` for the empty path
+    pub path: Option<RawPathStep>,
+    pub target: RawScope,
+}
+
+impl RawResultPath {
+    /// Labels of the path, in the order they are traversed
+    pub fn labels(&self) -> Vec<&RawLabel> {
+        let mut labels = Vec::new();
+        let mut step = self.path.as_ref();
+        while let Some(s) = step {
+            labels.push(&s.label);
+            step = s.prefix.as_deref();
+        }
+        labels.reverse();
+        labels
+    }
+}
+
+/// Step of a path, stored as linked list from the last step to the first
+#[derive(Deserialize, Debug)]
+pub struct RawPathStep {
+    pub prefix: Option<Box<RawPathStep>>,
+    pub label: RawLabel,
+    pub target: RawScope,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct RawDataWf {
     pub body: serde_json::Value,
@@ -28,7 +116,7 @@ pub struct RawDataOrd {
     pub freeVars: serde_json::Value,
     pub label: String,
     pub name: String,
-    pub params: serde_json::Value,
+    pub params: Vec<DataWfParams>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -83,37 +171,37 @@ impl DataWfParams {
 }
 
 #[derive(Deserialize, Debug, Clone)]
-struct DataWfCommon {
-    constructed: bool,
-    op: Option<String>,
+pub struct DataWfCommon {
+    pub constructed: bool,
+    pub op: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct ArrParams {
-    args: Vec<DataWfParams>,
+    pub args: Vec<DataWfParams>,
     #[serde(flatten)]
-    common: DataWfCommon,
+    pub common: DataWfCommon,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct IdMatchWf {
-    value: String,
+    pub value: String,
 
     #[serde(flatten)]
-    common: DataWfCommon,
+    pub common: DataWfCommon,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct RawData {
-    var: Option<RawDataVar>,
-    wildcard: bool,
+    pub var: Option<RawDataVar>,
+    pub wildcard: bool,
 
     #[serde(flatten)]
-    common: DataWfCommon,
+    pub common: DataWfCommon,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct RawDataVar {
-    name: String,
-    resource: String,
+    pub name: String,
+    pub resource: String,
 }
@@ -0,0 +1,628 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    path::Path,
+    sync::Arc,
+};
+
+pub use scope_graph::bench_util::workload::ReplayProjection;
+use scope_graph::{
+    SgData,
+    bench_util::workload::{Workload, WorkloadQuery},
+    graph::{CachedScopeGraph, QueryResult, QueryStats, ScopeGraph},
+    label::ScopeGraphLabel,
+    order::{LabelOrder, LabelOrderBuilder},
+    regex::{LabelClass, Regex, dfs::RegexAutomaton},
+    scope::Scope,
+};
+use serde::Deserialize;
+
+use crate::{
+    DataOrd, DataWf, ParseResult, ParsedScope, ParsedScopeGraph, RawQueryData, RawQueryResult,
+    RawResultPath, ScopeData, StatixLabel, open_input, parse_label,
+};
+
+/// Query recorded by Statix, with the labels of a specification.
+///
+/// Statix only dumps the state of the path wellformedness automaton, not its regex,
+/// see [`RecordedQuery::inferred_regex`] for the regex that is used instead.
+/// Only the name that the data wellformedness matches is replayed, data orders are not:
+/// shadowing follows the label order, as for [`crate::DataEquivalence::All`].
+#[derive(Debug, Clone)]
+pub struct RecordedQuery<L> {
+    pub scope: ParsedScope,
+    /// `(lhs, rhs)` for `lhs < rhs`
+    pub label_order: Vec<(L, L)>,
+    pub data_wf: DataWf,
+    pub data_ord: DataOrd,
+    /// Whether the empty path is wellformed
+    pub accepts_empty_path: bool,
+}
+
+impl<L: StatixLabel> TryFrom<&RawQueryData> for RecordedQuery<L> {
+    type Error = crate::ParseError;
+
+    fn try_from(raw: &RawQueryData) -> ParseResult<Self> {
+        let label_order = raw
+            .label_order()
+            .iter()
+            .map(|(lhs, rhs)| Ok((parse_label(lhs)?, parse_label(rhs)?)))
+            .collect::<ParseResult<Vec<_>>>()?;
+        Ok(Self {
+            scope: ParsedScope::from(raw.scope.clone()),
+            label_order,
+            data_wf: DataWf::from(&raw.dataWf),
+            data_ord: DataOrd::from(&raw.dataOrd),
+            accepts_empty_path: raw.accepts_empty_path(),
+        })
+    }
+}
+
+impl<L> RecordedQuery<L> {
+    /// Name the data has to match, `None` if any data matches
+    pub fn name(&self) -> Option<String> {
+        self.data_wf.name()
+    }
+}
+
+impl<L: StatixLabel> RecordedQuery<L> {
+    /// Reads the queries of a Statix query dump, eg `commons-csv.queries.json`
+    pub fn from_file<P: AsRef<Path>>(path: P) -> ParseResult<Vec<Self>> {
+        let mut deserializer = serde_json::Deserializer::from_reader(open_input(path)?);
+        deserializer.disable_recursion_limit();
+        let raw: Vec<RawQueryData> = Deserialize::deserialize(&mut deserializer)?;
+        raw.iter().map(Self::try_from).collect()
+    }
+}
+
+impl<L: StatixLabel + ScopeGraphLabel> RecordedQuery<L> {
+    pub fn to_label_order(&self) -> LabelOrder<L> {
+        self.label_order
+            .iter()
+            .fold(LabelOrderBuilder::new(), |builder, (lhs, rhs)| {
+                builder.push(lhs.clone(), rhs.clone())
+            })
+            .build()
+    }
+
+    /// Path regex based on the labels of the label order.
+    ///
+    /// Labels that are not greater than any other label are the relation that is queried,
+    /// the other labels are traversed to get there: `[traversed]*[relation]`.
+    /// If the label order is empty, the regex is unknown, unless the empty path is wellformed:
+    /// then this returns `ε`, which only finds the data of the start scope.
+    pub fn inferred_regex(&self) -> Option<Regex<L>> {
+        let greater = self
+            .label_order
+            .iter()
+            .map(|(_, rhs)| rhs)
+            .collect::<HashSet<_>>();
+        let (traversed, relation): (Vec<_>, Vec<_>) = self
+            .label_order
+            .iter()
+            .flat_map(|(lhs, rhs)| [lhs, rhs])
+            .cloned()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .partition(|l| greater.contains(l));
+
+        match (traversed.is_empty(), relation.is_empty()) {
+            (_, true) => self.accepts_empty_path.then_some(Regex::EmptyString),
+            (true, false) => Some(LabelClass::new("relation", relation).into()),
+            (false, false) => Some(Regex::concat(
+                Regex::kleene(LabelClass::new("traversed", traversed)),
+                LabelClass::new("relation", relation),
+            )),
+        }
+    }
+}
+
+/// Environment of a query: the labels of its path and the scope it resolves to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RecordedEnv<L> {
+    /// In the order they are traversed
+    pub labels: Vec<L>,
+    pub target: ParsedScope,
+}
+
+impl<L: StatixLabel> TryFrom<&RawResultPath> for RecordedEnv<L> {
+    type Error = crate::ParseError;
+
+    fn try_from(raw: &RawResultPath) -> ParseResult<Self> {
+        let labels = raw
+            .labels()
+            .into_iter()
+            .map(|l| parse_label(&l.arg0.value))
+            .collect::<ParseResult<Vec<_>>>()?;
+        Ok(Self {
+            labels,
+            target: ParsedScope::from(raw.target.clone()),
+        })
+    }
+}
+
+impl<L: StatixLabel> RecordedEnv<L> {
+    /// Reads the environments of a Statix results dump, eg `commons-csv.results.json`.
+    ///
+    /// Returns the environments of every query, in the same order as the queries.
+    pub fn results_from_file<P: AsRef<Path>>(path: P) -> ParseResult<Vec<Vec<Self>>> {
+        let mut deserializer = serde_json::Deserializer::from_reader(open_input(path)?);
+        deserializer.disable_recursion_limit();
+        let raw: Vec<RawQueryResult> = Deserialize::deserialize(&mut deserializer)?;
+        raw.iter()
+            .map(|result| {
+                result
+                    .paths
+                    .iter()
+                    .map(|env| Self::try_from(&env.path))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl<L: Display> Display for RecordedEnv<L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for label in &self.labels {
+            write!(f, "{label} ")?;
+        }
+        write!(f, "-> {}", self.target.id())
+    }
+}
+
+/// Query for which the resolver does not find the same environments as Statix
+#[derive(Debug, Clone)]
+pub struct Mismatch<L> {
+    /// Index of the query in the dump
+    pub index: usize,
+    pub query: RecordedQuery<L>,
+    /// Found by Statix, but not by the resolver
+    pub missing: Vec<RecordedEnv<L>>,
+    /// Found by the resolver, but not by Statix
+    pub unexpected: Vec<RecordedEnv<L>>,
+}
+
+impl<L: Display> Display for Mismatch<L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "query {} in {}", self.index, self.query.scope.id())?;
+        if let Some(name) = self.query.name() {
+            write!(f, " for {name}")?;
+        }
+        for env in &self.missing {
+            write!(f, "\n  missing:    {env}")?;
+        }
+        for env in &self.unexpected {
+            write!(f, "\n  unexpected: {env}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of [`QueryReplay::verify`]
+#[derive(Debug)]
+pub struct Verification<L> {
+    pub num_matches: usize,
+    /// Queries that could not be replayed, see [`QueryReplay::run`]
+    pub num_skipped: usize,
+    /// Queries without results or results without a query, when the dumps have a different length
+    pub num_unmatched: usize,
+    pub mismatches: Vec<Mismatch<L>>,
+}
+
+// derived Default would require `L: Default`
+impl<L> Default for Verification<L> {
+    fn default() -> Self {
+        Self {
+            num_matches: 0,
+            num_skipped: 0,
+            num_unmatched: 0,
+            mismatches: Vec::new(),
+        }
+    }
+}
+
+impl<L> Verification<L> {
+    /// True if every replayed query found the same environments as Statix and every query had results
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty() && self.num_unmatched == 0
+    }
+}
+
+/// Result of replaying a set of queries
+#[derive(Debug, Default)]
+pub struct ReplayStats {
+    pub num_queries: usize,
+    /// Queries whose start scope is not in the graph or whose regex is not known
+    pub num_skipped: usize,
+    /// Queries that did not resolve to anything
+    pub num_empty: usize,
+    /// Sum of the stats of all queries that were replayed
+    pub stats: QueryStats,
+}
+
+/// Replays recorded Statix queries on a [`CachedScopeGraph`] converted from a parsed scope graph,
+/// to measure how well the cache performs on a real workload.
+///
+/// Scopes with a class or method as data get its name as data,
+/// references to scopes get the id of the scope they refer to as name.
+pub struct QueryReplay<L: ScopeGraphLabel> {
+    graph: CachedScopeGraph<L, SgData>,
+    scopes: HashMap<ParsedScope, Scope>,
+    /// Indexed by the id of the scope in `graph`
+    parsed_scopes: Vec<ParsedScope>,
+    caching_enabled: bool,
+    // queries share a handful of orders and regexes, which are expensive to build
+    orders: HashMap<Vec<(L, L)>, LabelOrder<L>>,
+    automata: HashMap<Regex<L>, RegexAutomaton<L>>,
+}
+
+impl<L: StatixLabel + ScopeGraphLabel> QueryReplay<L> {
+    pub fn new(parsed: &ParsedScopeGraph<L>) -> Self {
+        let mut replay = Self {
+            graph: CachedScopeGraph::new(),
+            scopes: HashMap::new(),
+            parsed_scopes: Vec::new(),
+            caching_enabled: true,
+            orders: HashMap::new(),
+            automata: HashMap::new(),
+        };
+        for (scope, data) in &parsed.scopes {
+            replay.add_scope(scope, Self::convert_data(data));
+        }
+        for edge in &parsed.edges {
+            let from = replay.get_or_add_scope(&edge.from);
+            let to = replay.get_or_add_scope(&edge.to);
+            replay.graph.add_edge(from, to, edge.label.clone());
+        }
+        replay
+    }
+
+    /// Replays with the resolve cache enabled (default) or disabled, to get a baseline
+    pub fn with_caching(mut self, enabled: bool) -> Self {
+        self.caching_enabled = enabled;
+        self
+    }
+
+    pub fn graph(&self) -> &CachedScopeGraph<L, SgData> {
+        &self.graph
+    }
+
+    /// Scope in the converted graph that belongs to a parsed scope
+    pub fn scope(&self, scope: &ParsedScope) -> Option<Scope> {
+        self.scopes.get(scope).copied()
+    }
+
+    /// Parsed scope that a scope in the converted graph was created for
+    pub fn parsed_scope(&self, scope: Scope) -> Option<&ParsedScope> {
+        self.parsed_scopes.get(scope.id())
+    }
+
+    /// Empties the resolve cache, so the next queries start cold
+    pub fn reset_cache(&mut self) {
+        self.graph.reset_cache();
+    }
+
+    /// Resolves a query with its [inferred regex](RecordedQuery::inferred_regex).
+    ///
+    /// Returns `None` if the start scope is not in the graph or the regex is not known.
+    pub fn run(
+        &mut self,
+        query: &RecordedQuery<L>,
+    ) -> Option<(Vec<QueryResult<L, SgData>>, QueryStats)> {
+        let regex = query.inferred_regex()?;
+        self.run_with_regex(query, regex)
+    }
+
+    /// Resolves a query with the given path regex instead of the inferred one
+    pub fn run_with_regex(
+        &mut self,
+        query: &RecordedQuery<L>,
+        regex: Regex<L>,
+    ) -> Option<(Vec<QueryResult<L, SgData>>, QueryStats)> {
+        let start = self.scope(&query.scope)?;
+        let order = self
+            .orders
+            .entry(query.label_order.clone())
+            .or_insert_with(|| query.to_label_order())
+            .clone();
+        let automaton = self
+            .automata
+            .entry(regex)
+            .or_insert_with_key(|r| r.clone().compile())
+            .clone();
+        let (proj, wfd) = match query.name() {
+            Some(name) => (ReplayProjection::Name, Some(Arc::from(name))),
+            None => (ReplayProjection::Any, Some(Arc::from(""))),
+        };
+        Some(self.graph.query_proj_stats(
+            start,
+            &automaton,
+            &order,
+            proj,
+            wfd,
+            self.caching_enabled,
+        ))
+    }
+
+    /// Resolves all queries in order, without resetting the cache in between
+    pub fn replay<'a>(
+        &mut self,
+        queries: impl IntoIterator<Item = &'a RecordedQuery<L>>,
+    ) -> ReplayStats
+    where
+        L: 'a,
+    {
+        let mut result = ReplayStats::default();
+        for query in queries {
+            result.num_queries += 1;
+            match self.run(query) {
+                Some((envs, stats)) => {
+                    result.num_empty += envs.is_empty() as usize;
+                    result.stats = std::mem::take(&mut result.stats) + stats;
+                }
+                None => result.num_skipped += 1,
+            }
+        }
+        result
+    }
+
+    /// Compares the environments of every query to the environments that Statix computed.
+    ///
+    /// `results` are in the same order as `queries`, see [`RecordedEnv::results_from_file`].
+    /// Environments are compared as sets of paths, duplicates are ignored.
+    /// If the dumps have a different length, the queries or results past the shorter one are counted as unmatched.
+    pub fn verify(
+        &mut self,
+        queries: &[RecordedQuery<L>],
+        results: &[Vec<RecordedEnv<L>>],
+    ) -> Verification<L> {
+        let mut verification = Verification {
+            num_unmatched: queries.len().abs_diff(results.len()),
+            ..Default::default()
+        };
+        for (index, (query, expected)) in queries.iter().zip(results).enumerate() {
+            let Some((envs, _)) = self.run(query) else {
+                verification.num_skipped += 1;
+                continue;
+            };
+            let actual = envs
+                .iter()
+                .map(|env| RecordedEnv {
+                    labels: env.path.labels(),
+                    target: self.parsed_scopes[env.path.target().id()].clone(),
+                })
+                .collect::<HashSet<_>>();
+            let expected = expected.iter().cloned().collect::<HashSet<_>>();
+            if actual == expected {
+                verification.num_matches += 1;
+                continue;
+            }
+            verification.mismatches.push(Mismatch {
+                index,
+                query: query.clone(),
+                missing: expected.difference(&actual).cloned().collect(),
+                unexpected: actual.difference(&expected).cloned().collect(),
+            });
+        }
+        verification
+    }
+
+    /// Converts the graph and queries to a [`Workload`] for the benchmarks of `scope_graph::bench_util`.
+    ///
+    /// Queries whose start scope is not in the graph or whose regex is not known are left out.
+    /// Labels are stored as their [`ScopeGraphLabel::char`], so the workload can be read without `L`.
+    pub fn to_workload(&self, name: &str, queries: &[RecordedQuery<L>]) -> Workload<char> {
+        let scopes = (0..self.parsed_scopes.len())
+            .map(|id| self.graph.get_scope(Scope(id)).unwrap())
+            .collect::<Vec<_>>();
+        let edges = scopes
+            .iter()
+            .enumerate()
+            .flat_map(|(id, s)| {
+                s.outgoing()
+                    .iter()
+                    .map(move |e| (id, e.target().id(), e.lbl().clone()))
+            })
+            .collect();
+        let queries = queries
+            .iter()
+            .filter_map(|q| {
+                Some(WorkloadQuery {
+                    start: self.scope(&q.scope)?.id(),
+                    regex: q.inferred_regex()?,
+                    order: q.label_order.clone(),
+                    wfd: q.name(),
+                })
+            })
+            .collect();
+        Workload {
+            name: name.to_string(),
+            scopes: scopes.into_iter().map(|s| s.data.clone()).collect(),
+            edges,
+            queries,
+        }
+        .map_labels(|l| l.char())
+    }
+
+    fn add_scope(&mut self, scope: &ParsedScope, data: SgData) -> Scope {
+        let s = Scope(self.parsed_scopes.len());
+        self.graph.add_scope(s, data);
+        self.parsed_scopes.push(scope.clone());
+        self.scopes.insert(scope.clone(), s);
+        s
+    }
+
+    fn get_or_add_scope(&mut self, scope: &ParsedScope) -> Scope {
+        match self.scopes.get(scope) {
+            Some(s) => *s,
+            None => self.add_scope(scope, SgData::NoData),
+        }
+    }
+
+    fn convert_data(data: &ScopeData) -> SgData {
+        match data {
+            ScopeData::ClassOrMethod(name, scope) => SgData::var(name, scope.id()),
+            ScopeData::Ref(scope) => SgData::var(scope.id(), "ref"),
+            ScopeData::Combined | ScopeData::None => SgData::NoData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataPattern, JavaLabel, ParsedEdge};
+    use scope_graph::bench_util::workload::WorkloadRunner;
+
+    fn scope(name: &str) -> ParsedScope {
+        ParsedScope::new(name, "/./test")
+    }
+
+    fn edge(from: &str, to: &str, label: JavaLabel) -> ParsedEdge {
+        ParsedEdge {
+            from: scope(from),
+            to: scope(to),
+            label,
+        }
+    }
+
+    /// s_cu -LEX-> s_prj -pkg-> d-1 ("java"), s_prj -pkg-> d-2 ("org")
+    fn graph() -> ParsedScopeGraph {
+        let scopes = HashMap::from([
+            (scope("s_cu"), ScopeData::None),
+            (scope("s_prj"), ScopeData::None),
+            (
+                scope("d-1"),
+                ScopeData::ClassOrMethod("java".to_string(), scope("s_pkg-1")),
+            ),
+            (
+                scope("d-2"),
+                ScopeData::ClassOrMethod("org".to_string(), scope("s_pkg-2")),
+            ),
+        ]);
+        ParsedScopeGraph {
+            scopes,
+            edges: vec![
+                edge("s_cu", "s_prj", JavaLabel::Parent),
+                edge("s_prj", "d-1", JavaLabel::Package),
+                edge("s_prj", "d-2", JavaLabel::Package),
+            ],
+            labels: vec![JavaLabel::Parent, JavaLabel::Package],
+        }
+    }
+
+    fn query(name: Option<&str>) -> RecordedQuery<JavaLabel> {
+        RecordedQuery {
+            scope: scope("s_cu"),
+            label_order: vec![
+                (JavaLabel::Package, JavaLabel::StaticParent),
+                (JavaLabel::Package, JavaLabel::Parent),
+            ],
+            data_wf: DataWf {
+                pattern: match name {
+                    Some(name) => DataPattern::tuple(vec![
+                        DataPattern::Literal(name.to_string()),
+                        DataPattern::Wildcard,
+                    ]),
+                    None => DataPattern::Wildcard,
+                },
+                guard: None,
+            },
+            data_ord: DataOrd {
+                lhs: DataPattern::Wildcard,
+                rhs: DataPattern::Wildcard,
+                guard: None,
+            },
+            accepts_empty_path: false,
+        }
+    }
+
+    #[test]
+    fn test_inferred_regex() {
+        let regex = query(None).inferred_regex().unwrap().compile();
+        assert!(regex.is_match(&[JavaLabel::Parent, JavaLabel::Package]));
+        assert!(regex.is_match(&[JavaLabel::Package]));
+        assert!(!regex.is_match(&[JavaLabel::Package, JavaLabel::Parent]));
+
+        let unknown = RecordedQuery::<JavaLabel> {
+            label_order: Vec::new(),
+            ..query(None)
+        };
+        assert_eq!(unknown.inferred_regex(), None);
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut replay = QueryReplay::new(&graph());
+        let (envs, _) = replay.run(&query(Some("java"))).unwrap();
+        assert_eq!(envs.len(), 1);
+        assert_eq!(envs[0].data.name(), "java");
+
+        let (envs, _) = replay.run(&query(None)).unwrap();
+        assert_eq!(envs.len(), 2);
+
+        let stats = replay.replay(&[query(Some("java")), query(Some("java"))]);
+        assert_eq!(stats.num_queries, 2);
+        assert_eq!(stats.num_empty, 0);
+        assert!(stats.stats.cache_hits > 0);
+    }
+
+    #[test]
+    fn test_to_workload() {
+        let replay = QueryReplay::new(&graph());
+        let unknown = RecordedQuery {
+            scope: scope("s_missing"),
+            ..query(None)
+        };
+        let workload = replay.to_workload("test", &[query(Some("java")), unknown]);
+        assert_eq!(workload.scopes.len(), graph().scopes.len());
+        assert_eq!(workload.queries.len(), 1);
+        assert_eq!(workload.queries[0].wfd.as_deref(), Some("java"));
+        assert!(
+            workload
+                .edges
+                .iter()
+                .any(|(_, _, l)| *l == JavaLabel::Package.char())
+        );
+
+        let mut runner = WorkloadRunner::new(&workload);
+        assert!(runner.run(true).edges_traversed > 0);
+    }
+
+    #[test]
+    fn test_verify() {
+        let mut replay = QueryReplay::new(&graph());
+        let found = RecordedEnv {
+            labels: vec![JavaLabel::Parent, JavaLabel::Package],
+            target: scope("d-1"),
+        };
+        let other = RecordedEnv {
+            labels: vec![JavaLabel::Package],
+            target: scope("d-1"),
+        };
+        let queries = [query(Some("java")), query(Some("java"))];
+        let verification = replay.verify(&queries, &[vec![found.clone()], vec![other.clone()]]);
+        assert_eq!(verification.num_matches, 1);
+        assert_eq!(verification.mismatches.len(), 1);
+
+        let mismatch = &verification.mismatches[0];
+        assert_eq!(mismatch.index, 1);
+        assert_eq!(mismatch.missing, vec![other]);
+        assert_eq!(mismatch.unexpected, vec![found]);
+        assert!(!verification.is_ok());
+    }
+
+    #[test]
+    fn test_verify_different_lengths() {
+        let mut replay = QueryReplay::new(&graph());
+        let found = RecordedEnv {
+            labels: vec![JavaLabel::Parent, JavaLabel::Package],
+            target: scope("d-1"),
+        };
+        let queries = [query(Some("java")), query(Some("java"))];
+        let verification = replay.verify(&queries, &[vec![found]]);
+        assert_eq!(verification.num_matches, 1);
+        assert_eq!(verification.num_unmatched, 1);
+        assert!(!verification.is_ok());
+    }
+}
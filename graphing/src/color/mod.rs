@@ -0,0 +1,191 @@
+#[cfg(any(feature = "plantuml", feature = "mermaid"))]
+use crate::CssProperty;
+
+mod names;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Color {
+    r: u8,
+    g: u8,
+    b: u8,
+    a: u8,
+}
+
+impl Color {
+    pub const BLACK: Self = Self::new_rgb_u32(0x000000);
+    pub const WHITE: Self = Self::new_rgb_u32(0xFFFFFF);
+    pub const RED: Self = Self::new_rgb_u32(0xFF0000);
+    pub const GREEN: Self = Self::new_rgb_u32(0x12d812);
+    pub const BLUE: Self = Self::new_rgb_u32(0x0000FF);
+    pub const PURPLE: Self = Self::new_rgb_u32(0x800080);
+    pub const ORANGE: Self = Self::new_rgb_u32(0xFFA500);
+    pub const YELLOW: Self = Self::new_rgb_u32(0xc0bd22);
+    pub const CYAN: Self = Self::new_rgb_u32(0x00FFFF);
+
+    pub const DARK_GRAY: Self = Self::new_rgb_u32(0xB6B6B6);
+    pub const LIGHT_GRAY: Self = Self::new_rgb_u32(0xE6E6E6);
+    pub const LIGHT_RED: Self = Self::new_rgb_u32(0xFFF1F1);
+    pub const LIGHT_GREEN: Self = Self::new_rgb_u32(0xF1FFF1);
+    pub const LIGHT_BLUE: Self = Self::new_rgb_u32(0xF1F1FF);
+    pub const LIGHT_PURPLE: Self = Self::new_rgb_u32(0xFFF1FB);
+    pub const LIGHT_ORANGE: Self = Self::new_rgb_u32(0xFFFBF1);
+    pub const LIGHT_YELLOW: Self = Self::new_rgb_u32(0xFEFFF1);
+    pub const LIGHT_CYAN: Self = Self::new_rgb_u32(0xF1FFFF);
+
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub const fn new_rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    pub const fn new_rgba_u32(clr: u32) -> Self {
+        let r = (clr >> 24) as u8;
+        let g = (clr >> 16) as u8;
+        let b = (clr >> 8) as u8;
+        let a = (clr) as u8;
+        Self::new(r, g, b, a)
+    }
+
+    pub const fn new_rgb_u32(clr: u32) -> Self {
+        Self::new_rgba_u32(clr << 8)
+    }
+
+    /// Creates a color from hue (degrees), saturation and lightness (both 0.0..=1.0)
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        let h = hue.rem_euclid(360.0) / 60.0;
+        let s = saturation.clamp(0.0, 1.0);
+        let l = lightness.clamp(0.0, 1.0);
+
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as usize {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = l - c / 2.0;
+        let to_u8 = |v: f32| ((v + m) * 255.0).round() as u8;
+        Self::new_rgb(to_u8(r), to_u8(g), to_u8(b))
+    }
+
+    /// Parses `#rgb`, `#rrggbb` or `#rrggbbaa`, the leading `#` is optional
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim();
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        match hex.len() {
+            // each digit is repeated, so 0xabc becomes 0xaabbcc
+            3 => {
+                let expand = |d: u32| (d & 0xF) * 0x11;
+                Some(Self::new_rgb(
+                    expand(value >> 8) as u8,
+                    expand(value >> 4) as u8,
+                    expand(value) as u8,
+                ))
+            }
+            6 => Some(Self::new_rgb_u32(value)),
+            8 => Some(Self::new_rgba_u32(value)),
+            _ => None,
+        }
+    }
+
+    /// Looks up one of the CSS named colors, ignoring case (e.g. `"IndianRed"`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase();
+        names::CSS_COLORS
+            .binary_search_by(|(n, _)| n.cmp(&name.as_str()))
+            .ok()
+            .map(|idx| Self::new_rgb_u32(names::CSS_COLORS[idx].1))
+    }
+
+    pub const fn rgb(&self) -> (u8, u8, u8) {
+        (self.r, self.g, self.b)
+    }
+
+    pub fn hex_string(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+#[cfg(any(feature = "plantuml", feature = "mermaid"))]
+impl CssProperty for Color {
+    fn write(&self, writer: &mut impl std::io::Write) -> crate::RenderResult<()> {
+        write!(writer, "{}", self.hex_string()).map_err(Into::into)
+    }
+}
+
+#[derive(derive_more::Display, Debug, Clone, PartialEq, Eq)]
+#[display("Invalid color: {_0}")]
+pub struct ParseColorError(String);
+
+impl std::error::Error for ParseColorError {}
+
+/// Parses either a hex color or a CSS color name, see [`Color::from_hex`] and [`Color::from_name`]
+impl std::str::FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+            .or_else(|| Self::from_name(s))
+            .ok_or_else(|| ParseColorError(s.to_string()))
+    }
+}
+
+/// Palettes for when a fixed set of color constants is not enough
+pub struct Palette;
+
+impl Palette {
+    /// Okabe-Ito palette, which is distinguishable for all common forms of colorblindness
+    pub const OKABE_ITO: [Color; 8] = [
+        Color::new_rgb_u32(0xE69F00),
+        Color::new_rgb_u32(0x56B4E9),
+        Color::new_rgb_u32(0x009E73),
+        Color::new_rgb_u32(0xF0E442),
+        Color::new_rgb_u32(0x0072B2),
+        Color::new_rgb_u32(0xD55E00),
+        Color::new_rgb_u32(0xCC79A7),
+        Color::new_rgb_u32(0x000000),
+    ];
+
+    /// `n` shades of the same hue, from dark to light
+    pub fn hsl_ramp(hue: f32, n: usize) -> Vec<Color> {
+        let (min, max) = (0.25, 0.9);
+        (0..n)
+            .map(|i| {
+                let t = match n {
+                    1 => 0.5,
+                    _ => i as f32 / (n - 1) as f32,
+                };
+                Color::from_hsl(hue, 0.65, min + t * (max - min))
+            })
+            .collect()
+    }
+
+    /// `n` colors with hues that are as far apart as possible.
+    ///
+    /// Hues are spaced by the golden angle, so the first colors stay distinct when `n` grows.
+    pub fn distinct(n: usize, saturation: f32, lightness: f32) -> Vec<Color> {
+        const GOLDEN_ANGLE: f32 = 137.507_77;
+        (0..n)
+            .map(|i| Color::from_hsl(i as f32 * GOLDEN_ANGLE, saturation, lightness))
+            .collect()
+    }
+
+    /// Distinct colors that are saturated enough to be used for lines and text
+    pub fn distinct_foreground(n: usize) -> Vec<Color> {
+        Self::distinct(n, 0.75, 0.4)
+    }
+
+    /// Distinct colors that are light enough to be used as backgrounds
+    pub fn distinct_background(n: usize) -> Vec<Color> {
+        Self::distinct(n, 0.8, 0.92)
+    }
+}
@@ -0,0 +1,289 @@
+use std::{
+    collections::HashMap,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use indexmap::IndexMap;
+
+use crate::{
+    Color, Diagram, RenderResult, Renderer, csv_field,
+    error::{FileContext, create_file},
+};
+
+/// Style of a node or edge in Cosmograph
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CosmoStyle {
+    color: Option<Color>,
+    /// Size for nodes, width for edges
+    size: Option<f32>,
+    /// How strongly an edge pulls its nodes together in the force layout
+    strength: Option<f32>,
+}
+
+impl CosmoStyle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Size of a node, or the width of an edge
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Strength of an edge in the force layout, ignored for nodes
+    pub fn strength(mut self, strength: f32) -> Self {
+        self.strength = Some(strength);
+        self
+    }
+
+    /// Sets the properties that are set in `other`, keeping the rest
+    fn apply(&mut self, other: &Self) {
+        self.color = other.color.or(self.color);
+        self.size = other.size.or(self.size);
+        self.strength = other.strength.or(self.strength);
+    }
+}
+
+/// Cosmograph has no classes, so the style of every item is resolved using this stylesheet when rendering.
+#[derive(Default, Debug, Clone)]
+pub struct CosmoStyleSheet {
+    styles: IndexMap<String, CosmoStyle>,
+}
+
+impl CosmoStyleSheet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_class(mut self, class: impl ToString, style: CosmoStyle) -> Self {
+        self.push(class, style);
+        self
+    }
+
+    /// Adds a style, replacing the existing style with the same name
+    pub fn push(&mut self, class: impl ToString, style: CosmoStyle) {
+        self.styles.insert(class.to_string(), style);
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        self.styles.extend(other.styles);
+    }
+
+    pub fn contains(&self, class: &str) -> bool {
+        self.styles.contains_key(class)
+    }
+
+    /// Combines the styles of `classes`, later classes take precedence
+    fn resolve(&self, classes: &[String]) -> CosmoStyle {
+        let mut style = CosmoStyle::default();
+        for class in classes {
+            match self.styles.get(class) {
+                Some(s) => style.apply(s),
+                None => tracing::warn!("Class {} does not exist in the stylesheet", class),
+            }
+        }
+        style
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CosmoItemKind {
+    Node {
+        id: String,
+        label: String,
+    },
+    Edge {
+        from: String,
+        to: String,
+        label: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CosmoItem {
+    kind: CosmoItemKind,
+    classes: Vec<String>,
+}
+
+impl CosmoItem {
+    pub fn new(kind: CosmoItemKind) -> Self {
+        Self {
+            kind,
+            classes: Vec::new(),
+        }
+    }
+
+    pub fn node(id: impl ToString, label: impl ToString) -> Self {
+        Self::new(CosmoItemKind::Node {
+            id: id.to_string(),
+            label: label.to_string(),
+        })
+    }
+
+    pub fn edge(from: impl ToString, to: impl ToString, label: impl ToString) -> Self {
+        Self::new(CosmoItemKind::Edge {
+            from: from.to_string(),
+            to: to.to_string(),
+            label: label.to_string(),
+        })
+    }
+
+    pub fn add_class(mut self, class: impl ToString) -> Self {
+        self.classes.push(class.to_string());
+        self
+    }
+}
+
+fn csv_color(color: Option<Color>) -> String {
+    color.map(|c| c.hex_string()).unwrap_or_default()
+}
+
+/// Graph for [Cosmograph](https://cosmograph.app), which can show graphs that are too large for the other backends.
+///
+/// Rendered as two `;`-separated CSV files: the edges and the metadata of the nodes.
+/// Nodes without a size in the stylesheet are sized by their number of edges.
+#[derive(Clone, Debug)]
+pub struct CosmoDiagram {
+    style: CosmoStyleSheet,
+    items: Vec<CosmoItem>,
+}
+
+impl CosmoDiagram {
+    pub fn new() -> Self {
+        Self {
+            style: CosmoStyleSheet::new(),
+            items: Vec::new(),
+        }
+    }
+
+    /// Returns number of items in the diagram.
+    pub fn num_items(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn set_style_sheet(&mut self, style: CosmoStyleSheet) {
+        self.style = style;
+    }
+
+    pub fn push(&mut self, item: CosmoItem) {
+        self.items.push(item);
+    }
+
+    pub fn extend(&mut self, items: impl IntoIterator<Item = CosmoItem>) {
+        self.items.extend(items);
+    }
+
+    /// Path of the metadata file that belongs to the edges in `path`, e.g. `output/graph.csv-meta.csv` for `output/graph.csv`
+    pub fn metadata_path(path: &Path) -> PathBuf {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        path.with_file_name(format!("{name}-meta.csv"))
+    }
+
+    /// Writes the metadata of the nodes, `id;color;size`.
+    ///
+    /// Cosmograph shows the id of a node as its label, the labels of the nodes are not written.
+    pub fn render_metadata_to_writer(&self, writer: &mut impl Write) -> RenderResult<()> {
+        let mut degrees = HashMap::<&str, usize>::new();
+        for item in &self.items {
+            if let CosmoItemKind::Edge { from, to, .. } = &item.kind {
+                *degrees.entry(from).or_default() += 1;
+                *degrees.entry(to).or_default() += 1;
+            }
+        }
+
+        writeln!(writer, "id;color;size")?;
+        for item in &self.items {
+            let CosmoItemKind::Node { id, .. } = &item.kind else {
+                continue;
+            };
+            let style = self.style.resolve(&item.classes);
+            let size = style.size.unwrap_or_else(|| {
+                5.0 + 2.0 * degrees.get(id.as_str()).copied().unwrap_or(0) as f32
+            });
+            writeln!(
+                writer,
+                "{};{};{}",
+                csv_field(id, ';'),
+                csv_color(style.color),
+                size
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for CosmoDiagram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Renderer for CosmoDiagram {
+    /// Writes the edges, `source;target;strength;label;color;width`
+    fn render_to_writer(&self, writer: &mut impl Write) -> RenderResult<()> {
+        writeln!(writer, "source;target;strength;label;color;width")?;
+        for item in &self.items {
+            let CosmoItemKind::Edge { from, to, label } = &item.kind else {
+                continue;
+            };
+            let style = self.style.resolve(&item.classes);
+            writeln!(
+                writer,
+                "{};{};{};{};{};{}",
+                csv_field(from, ';'),
+                csv_field(to, ';'),
+                style.strength.unwrap_or(1.0),
+                csv_field(label, ';'),
+                csv_color(style.color),
+                style.size.unwrap_or(1.0)
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes the edges to `path` and the metadata next to it, see [`CosmoDiagram::metadata_path`]
+    fn render_to_file(&self, path: &str) -> RenderResult<()> {
+        let path = Path::new(path);
+        let mut buf = BufWriter::new(create_file(path)?);
+        self.render_to_writer(&mut buf).context("write", path)?;
+        buf.flush().context("write", path)?;
+
+        let meta_path = Self::metadata_path(path);
+        let mut buf = BufWriter::new(create_file(&meta_path)?);
+        self.render_metadata_to_writer(&mut buf)
+            .context("write", &meta_path)?;
+        buf.flush().context("write", &meta_path)
+    }
+
+    fn source_extension(&self) -> &'static str {
+        "csv"
+    }
+}
+
+/// Groups are not supported, their nodes are exported ungrouped
+impl From<&Diagram> for CosmoDiagram {
+    fn from(diagram: &Diagram) -> Self {
+        let mut cosmo = CosmoDiagram::new();
+        cosmo.extend(diagram.nodes.iter().map(|node| {
+            node.classes
+                .iter()
+                .fold(CosmoItem::node(&node.id, &node.label), |item, class| {
+                    item.add_class(class)
+                })
+        }));
+        cosmo.extend(diagram.edges.iter().map(|edge| {
+            edge.classes.iter().fold(
+                CosmoItem::edge(&edge.from, &edge.to, &edge.label),
+                |item, class| item.add_class(class),
+            )
+        }));
+        cosmo
+    }
+}
@@ -0,0 +1,9 @@
+use std::borrow::Cow;
+
+/// Quotes a field if it contains the `delimiter`, a quote or a newline
+pub fn csv_field(s: &str, delimiter: char) -> Cow<'_, str> {
+    match s.contains([delimiter, '"', '\n']) {
+        true => Cow::Owned(format!("\"{}\"", s.replace('"', "\"\""))),
+        false => Cow::Borrowed(s),
+    }
+}
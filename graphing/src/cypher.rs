@@ -0,0 +1,208 @@
+use std::io::Write;
+
+use crate::{Diagram, RenderResult, Renderer};
+
+/// Quotes a string as Cypher string literal
+fn cypher_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Quotes a node label or relationship type, so it can contain any character
+fn cypher_name(s: &str) -> String {
+    format!("`{}`", s.replace('`', "``"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CypherItemKind {
+    Node {
+        id: String,
+        /// Node labels, besides the `Node` label that every node has
+        labels: Vec<String>,
+        properties: Vec<(String, String)>,
+    },
+    Edge {
+        from: String,
+        to: String,
+        /// Relationship type
+        label: String,
+        properties: Vec<(String, String)>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CypherItem {
+    kind: CypherItemKind,
+}
+
+impl CypherItem {
+    pub fn new(kind: CypherItemKind) -> Self {
+        Self { kind }
+    }
+
+    pub fn node(id: impl ToString) -> Self {
+        Self::new(CypherItemKind::Node {
+            id: id.to_string(),
+            labels: Vec::new(),
+            properties: Vec::new(),
+        })
+    }
+
+    pub fn edge(from: impl ToString, to: impl ToString, label: impl ToString) -> Self {
+        Self::new(CypherItemKind::Edge {
+            from: from.to_string(),
+            to: to.to_string(),
+            label: label.to_string(),
+            properties: Vec::new(),
+        })
+    }
+
+    /// Adds a label to a node, does nothing for edges
+    pub fn with_label(mut self, label: impl ToString) -> Self {
+        if let CypherItemKind::Node { labels, .. } = &mut self.kind {
+            labels.push(label.to_string());
+        }
+        self
+    }
+
+    /// Adds a property, which is written as string
+    pub fn with_property(mut self, name: impl ToString, value: impl ToString) -> Self {
+        let (CypherItemKind::Node { properties, .. } | CypherItemKind::Edge { properties, .. }) =
+            &mut self.kind;
+        properties.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// Graph as Cypher statements, which can be run in [Neo4j](https://neo4j.com) with `cypher-shell -f`.
+///
+/// Every node gets the `Node` label and an `id` property, which is indexed
+/// so edges can find their nodes.
+#[derive(Clone, Debug, Default)]
+pub struct CypherDiagram {
+    items: Vec<CypherItem>,
+}
+
+impl CypherDiagram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns number of items in the diagram.
+    pub fn num_items(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn push(&mut self, item: CypherItem) {
+        self.items.push(item);
+    }
+
+    pub fn extend(&mut self, items: impl IntoIterator<Item = CypherItem>) {
+        self.items.extend(items);
+    }
+}
+
+fn write_properties(
+    writer: &mut impl Write,
+    properties: &[(String, String)],
+    id: Option<&str>,
+) -> RenderResult<()> {
+    let id = id.map(|id| ("id", id));
+    let properties = id
+        .into_iter()
+        .chain(properties.iter().map(|(n, v)| (n.as_str(), v.as_str())))
+        .map(|(name, value)| format!("{}: {}", cypher_name(name), cypher_string(value)))
+        .collect::<Vec<_>>();
+    if !properties.is_empty() {
+        write!(writer, " {{{}}}", properties.join(", "))?;
+    }
+    Ok(())
+}
+
+impl Renderer for CypherDiagram {
+    fn render_to_writer(&self, writer: &mut impl Write) -> RenderResult<()> {
+        writeln!(
+            writer,
+            "CREATE INDEX node_id IF NOT EXISTS FOR (n:Node) ON (n.id);"
+        )?;
+        for item in &self.items {
+            let CypherItemKind::Node {
+                id,
+                labels,
+                properties,
+            } = &item.kind
+            else {
+                continue;
+            };
+            write!(writer, "CREATE (:Node")?;
+            for label in labels {
+                write!(writer, ":{}", cypher_name(label))?;
+            }
+            write_properties(writer, properties, Some(id))?;
+            writeln!(writer, ");")?;
+        }
+
+        for item in &self.items {
+            let CypherItemKind::Edge {
+                from,
+                to,
+                label,
+                properties,
+            } = &item.kind
+            else {
+                continue;
+            };
+            write!(
+                writer,
+                "MATCH (a:Node {{id: {}}}), (b:Node {{id: {}}}) CREATE (a)-[:{}",
+                cypher_string(from),
+                cypher_string(to),
+                cypher_name(label)
+            )?;
+            write_properties(writer, properties, None)?;
+            writeln!(writer, "]->(b);")?;
+        }
+        Ok(())
+    }
+
+    fn source_extension(&self) -> &'static str {
+        "cypher"
+    }
+}
+
+/// Classes become node labels, groups are written as a `group` property
+impl From<&Diagram> for CypherDiagram {
+    fn from(diagram: &Diagram) -> Self {
+        let mut cypher = CypherDiagram::new();
+        cypher.extend(diagram.nodes.iter().map(|node| {
+            let item = node
+                .classes
+                .iter()
+                .fold(CypherItem::node(&node.id), |item, class| {
+                    item.with_label(class)
+                })
+                .with_property("label", &node.label);
+            match diagram.group_of(&node.id) {
+                Some(group) => item.with_property("group", &group.title),
+                None => item,
+            }
+        }));
+        cypher.extend(
+            diagram
+                .edges
+                .iter()
+                .map(|edge| CypherItem::edge(&edge.from, &edge.to, &edge.label)),
+        );
+        cypher
+    }
+}
@@ -0,0 +1,114 @@
+use std::io::Write;
+
+use crate::RenderResult;
+
+use super::{json_string, theme::CytoscapeStyleSheet};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CytoscapeItemKind {
+    Node {
+        id: String,
+        label: String,
+    },
+    Edge {
+        from: String,
+        to: String,
+        label: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CytoscapeItem {
+    kind: CytoscapeItemKind,
+    classes: Vec<String>,
+}
+
+impl CytoscapeItem {
+    pub fn new(kind: CytoscapeItemKind) -> Self {
+        Self {
+            kind,
+            classes: Vec::new(),
+        }
+    }
+
+    pub fn node(id: impl ToString, label: impl ToString) -> Self {
+        Self::new(CytoscapeItemKind::Node {
+            id: id.to_string(),
+            label: label.to_string(),
+        })
+    }
+
+    pub fn edge(from: impl ToString, to: impl ToString, label: impl ToString) -> Self {
+        Self::new(CytoscapeItemKind::Edge {
+            from: from.to_string(),
+            to: to.to_string(),
+            label: label.to_string(),
+        })
+    }
+
+    /// Returns the ID of the node this item represents or is connected to.
+    ///
+    /// Edges use their 'from' node
+    pub fn node_id(&self) -> &str {
+        match &self.kind {
+            CytoscapeItemKind::Node { id, .. } => id,
+            CytoscapeItemKind::Edge { from, .. } => from,
+        }
+    }
+
+    pub fn add_class(mut self, class: impl ToString) -> Self {
+        self.classes.push(class.to_string());
+        self
+    }
+
+    pub(crate) fn find_nonexistant_class(&self, sheet: &CytoscapeStyleSheet) -> Option<&str> {
+        self.classes
+            .iter()
+            .find(|class| !sheet.contains(class))
+            .map(String::as_str)
+    }
+
+    /// Writes the item as a cytoscape element, `idx` is used to give edges a unique id.
+    ///
+    /// The color from the stylesheet is added to the data, for tools that do not support cytoscape styles.
+    pub(crate) fn write(
+        &self,
+        writer: &mut impl Write,
+        idx: usize,
+        sheet: &CytoscapeStyleSheet,
+    ) -> RenderResult<()> {
+        let is_node = matches!(self.kind, CytoscapeItemKind::Node { .. });
+        match &self.kind {
+            CytoscapeItemKind::Node { id, label } => {
+                write!(
+                    writer,
+                    "{{\"group\": \"nodes\", \"data\": {{\"id\": {}, \"label\": {}",
+                    json_string(id),
+                    json_string(label)
+                )?;
+            }
+            CytoscapeItemKind::Edge { from, to, label } => {
+                write!(
+                    writer,
+                    "{{\"group\": \"edges\", \"data\": {{\"id\": \"edge{}\", \"source\": {}, \"target\": {}, \"label\": {}",
+                    idx,
+                    json_string(from),
+                    json_string(to),
+                    json_string(label)
+                )?;
+            }
+        }
+        if let Some(color) = sheet.element_color(&self.classes, is_node) {
+            write!(writer, ", \"color\": {}", json_string(color.hex_string()))?;
+        }
+        write!(writer, "}}")?;
+        let classes = self
+            .classes
+            .iter()
+            .map(json_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(writer, ", \"classes\": [{classes}]}}")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+use std::io::Write;
+
+pub use item::*;
+use theme::CytoscapeStyleSheet;
+
+use crate::{Diagram, RenderResult, Renderer};
+
+mod item;
+pub mod theme;
+
+const CYTOSCAPE_URL: &str = "https://unpkg.com/cytoscape@3.30.2/dist/cytoscape.min.js";
+
+/// Default styling, classes in the stylesheet are applied on top of this
+const BASE_STYLE: &str = r##"  {"selector": "node", "style": {"label": "data(label)", "text-valign": "center", "text-wrap": "wrap", "background-color": "#f2e8e6", "border-width": 1, "border-color": "#000000", "font-family": "monospace"}},
+  {"selector": "edge", "style": {"label": "data(label)", "curve-style": "bezier", "target-arrow-shape": "triangle", "width": 1.5, "line-color": "#000000", "target-arrow-color": "#000000", "font-family": "monospace"}},
+  {"selector": ".search-match", "style": {"underlay-color": "#ffa500", "underlay-opacity": 0.6, "underlay-padding": 8}}"##;
+
+/// Search box highlights all nodes whose label contains the query and zooms in on them
+const SCRIPT: &str = r#"
+const cy = cytoscape({container: document.getElementById("cy"), elements, style, layout});
+document.getElementById("search").addEventListener("input", (e) => {
+  const query = e.target.value.toLowerCase();
+  cy.elements().removeClass("search-match");
+  if (query.length === 0) return;
+  const matches = cy.nodes().filter((n) => n.data("label").toLowerCase().includes(query));
+  matches.addClass("search-match");
+  if (matches.length > 0) cy.animate({fit: {eles: matches, padding: 50}});
+});"#;
+
+/// Escapes a string and wraps it in quotes, so it can be used as a JSON (and javascript) string
+pub(crate) fn json_string(s: impl ToString) -> String {
+    let mut out = String::from("\"");
+    for c in s.to_string().chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            // prevents </script> in labels from ending the script tag
+            '<' => out.push_str("\\u003c"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Layout algorithms that are built into cytoscape.js
+#[derive(derive_more::Display, Debug, Clone, Copy)]
+pub enum CytoscapeLayout {
+    #[display("breadthfirst")]
+    BreadthFirst,
+    #[display("cose")]
+    Cose,
+    #[display("circle")]
+    Circle,
+    #[display("grid")]
+    Grid,
+}
+
+/// Interactive diagram, rendered as a single HTML page that can be opened in a browser.
+///
+/// The page loads cytoscape.js from a CDN, everything else is embedded in the file.
+/// Every edge must point to a node that is in the diagram, otherwise cytoscape refuses to render.
+#[derive(Clone, Debug)]
+pub struct CytoscapeDiagram {
+    style: CytoscapeStyleSheet,
+    items: Vec<CytoscapeItem>,
+    title: String,
+    layout: CytoscapeLayout,
+}
+
+impl CytoscapeDiagram {
+    pub fn new(title: impl ToString) -> Self {
+        Self {
+            style: CytoscapeStyleSheet::new(),
+            items: Vec::new(),
+            title: title.to_string(),
+            layout: CytoscapeLayout::BreadthFirst,
+        }
+    }
+
+    pub fn set_title(&mut self, title: impl ToString) {
+        self.title = title.to_string();
+    }
+
+    pub fn set_layout(&mut self, layout: CytoscapeLayout) {
+        self.layout = layout;
+    }
+
+    /// Returns number of items in the diagram.
+    pub fn num_items(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn set_style_sheet(&mut self, style: CytoscapeStyleSheet) {
+        self.style = style;
+    }
+
+    pub fn push(&mut self, item: CytoscapeItem) {
+        self.items.push(item);
+    }
+
+    pub fn extend(&mut self, items: impl IntoIterator<Item = CytoscapeItem>) {
+        self.items.extend(items);
+    }
+}
+
+impl CytoscapeDiagram {
+    /// Writes the nodes and edges as a JSON array of cytoscape elements
+    pub(crate) fn write_elements(&self, writer: &mut impl Write) -> RenderResult<()> {
+        writeln!(writer, "[")?;
+        for (idx, item) in self.items.iter().enumerate() {
+            if let Some(dne_class) = item.find_nonexistant_class(&self.style) {
+                tracing::warn!(
+                    "Class {} does not exist in the stylesheet (found in {})",
+                    dne_class,
+                    item.node_id()
+                );
+            }
+            if idx > 0 {
+                writeln!(writer, ",")?;
+            }
+            write!(writer, "  ")?;
+            item.write(writer, idx, &self.style)?;
+        }
+        write!(writer, "\n]")?;
+        Ok(())
+    }
+
+    /// Writes the base style and the stylesheet as a JSON array
+    pub(crate) fn write_style(&self, writer: &mut impl Write) -> RenderResult<()> {
+        write!(writer, "[\n{BASE_STYLE}")?;
+        self.style.write(writer)?;
+        write!(writer, "\n]")?;
+        Ok(())
+    }
+
+    pub(crate) fn write_layout(&self, writer: &mut impl Write) -> RenderResult<()> {
+        write!(
+            writer,
+            "{{\"name\": \"{}\", \"directed\": true, \"animate\": false}}",
+            self.layout
+        )?;
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn title(&self) -> &str {
+        &self.title
+    }
+}
+
+impl Renderer for CytoscapeDiagram {
+    fn render_to_writer(&self, writer: &mut impl Write) -> RenderResult<()> {
+        let title = self
+            .title
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        writeln!(
+            writer,
+            "<!DOCTYPE html>\n\
+            <html>\n\
+            <head>\n\
+            <meta charset=\"utf-8\">\n\
+            <title>{title}</title>\n\
+            <script src=\"{CYTOSCAPE_URL}\"></script>\n\
+            <style>\n\
+            body {{ margin: 0; font-family: monospace; }}\n\
+            #header {{ padding: 8px; border-bottom: 1px solid #b6b6b6; }}\n\
+            #cy {{ position: absolute; top: 48px; bottom: 0; left: 0; right: 0; }}\n\
+            </style>\n\
+            </head>\n\
+            <body>\n\
+            <div id=\"header\"><b>{title}</b> <input id=\"search\" placeholder=\"search nodes\"></div>\n\
+            <div id=\"cy\"></div>\n\
+            <script>"
+        )?;
+
+        write!(writer, "const elements = ")?;
+        self.write_elements(writer)?;
+        write!(writer, ";\nconst style = ")?;
+        self.write_style(writer)?;
+        write!(writer, ";\nconst layout = ")?;
+        self.write_layout(writer)?;
+        writeln!(writer, ";")?;
+
+        writeln!(writer, "{SCRIPT}")?;
+        write!(writer, "</script>\n</body>\n</html>")?;
+        Ok(())
+    }
+}
+
+/// Shapes are set by the stylesheet and groups are not supported, their nodes are drawn ungrouped
+impl From<&Diagram> for CytoscapeDiagram {
+    fn from(diagram: &Diagram) -> Self {
+        let mut cytoscape = CytoscapeDiagram::new(&diagram.title);
+        cytoscape.extend(diagram.nodes.iter().map(|node| {
+            node.classes
+                .iter()
+                .fold(CytoscapeItem::node(&node.id, &node.label), |item, class| {
+                    item.add_class(class)
+                })
+        }));
+        cytoscape.extend(diagram.edges.iter().map(|edge| {
+            edge.classes.iter().fold(
+                CytoscapeItem::edge(&edge.from, &edge.to, &edge.label),
+                |item, class| item.add_class(class),
+            )
+        }));
+        cytoscape
+    }
+}
@@ -0,0 +1,262 @@
+use std::io::Write;
+
+use crate::{Color, RenderResult};
+
+use super::json_string;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum LineStyle {
+    #[display("solid")]
+    Solid,
+    #[display("dotted")]
+    Dotted,
+    #[display("dashed")]
+    Dashed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum NodeShape {
+    #[display("ellipse")]
+    Ellipse,
+    #[display("rectangle")]
+    Rectangle,
+    #[display("round-rectangle")]
+    RoundRectangle,
+}
+
+/// Cytoscape.js style properties of a node or edge.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct CytoscapeStyle {
+    /// line-color for edges, border-color for nodes
+    line_color: Option<Color>,
+    /// line-style for edges, border-style for nodes
+    line_style: Option<LineStyle>,
+    /// width for edges, border-width for nodes
+    line_thickness: Option<f32>,
+    /// background-color
+    background_color: Option<Color>,
+    /// color
+    font_color: Option<Color>,
+    /// font-size, in px
+    font_size: Option<usize>,
+    /// font-weight: bold
+    bold: bool,
+    /// shape
+    shape: Option<NodeShape>,
+}
+
+impl CytoscapeStyle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::new()
+    }
+
+    pub fn line_color(mut self, color: Color) -> Self {
+        self.line_color = Some(color);
+        self
+    }
+
+    pub fn line_style(mut self, style: LineStyle) -> Self {
+        self.line_style = Some(style);
+        self
+    }
+
+    pub fn line_thickness(mut self, thickness: f32) -> Self {
+        self.line_thickness = Some(thickness);
+        self
+    }
+
+    pub fn background_color(mut self, color: Color) -> Self {
+        self.background_color = Some(color);
+        self
+    }
+
+    pub fn font_color(mut self, color: Color) -> Self {
+        self.font_color = Some(color);
+        self
+    }
+
+    pub fn font_size(mut self, size: usize) -> Self {
+        self.font_size = Some(size);
+        self
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub fn shape(mut self, shape: NodeShape) -> Self {
+        self.shape = Some(shape);
+        self
+    }
+
+    /// Writes the style as a JSON object.
+    ///
+    /// Line properties are written for both nodes (border-*) and edges (line-*),
+    /// since a class can be used for both.
+    #[allow(unused_assignments)] // macro magic
+    pub(crate) fn write(&self, writer: &mut impl Write) -> RenderResult<()> {
+        let mut is_first = true;
+        macro_rules! write_prop {
+            ($key:literal, $value:expr) => {
+                if !is_first {
+                    write!(writer, ", ")?;
+                }
+                write!(writer, "\"{}\": {}", $key, $value)?;
+                is_first = false;
+            };
+        }
+
+        write!(writer, "{{")?;
+        if let Some(x) = self.line_color {
+            let clr = json_string(x.hex_string());
+            write_prop!("line-color", clr);
+            write_prop!("target-arrow-color", clr);
+            write_prop!("border-color", clr);
+        }
+        if let Some(x) = self.line_style {
+            let style = json_string(x);
+            write_prop!("line-style", style);
+            write_prop!("border-style", style);
+        }
+        if let Some(x) = self.line_thickness {
+            write_prop!("width", x);
+            write_prop!("border-width", x);
+        }
+        if let Some(x) = self.background_color {
+            write_prop!("background-color", json_string(x.hex_string()));
+        }
+        if let Some(x) = self.font_color {
+            write_prop!("color", json_string(x.hex_string()));
+        }
+        if let Some(x) = self.font_size {
+            write_prop!("font-size", x);
+        }
+        if self.bold {
+            write_prop!("font-weight", "\"bold\"");
+        }
+        if let Some(x) = self.shape {
+            write_prop!("shape", json_string(x));
+        }
+        write!(writer, "}}")?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "plantuml")]
+impl From<&crate::plantuml::theme::ElementCss> for CytoscapeStyle {
+    fn from(css: &crate::plantuml::theme::ElementCss) -> Self {
+        use crate::plantuml::theme::{FontStyle, LineStyle as UmlLineStyle};
+
+        Self {
+            line_color: css.line_color,
+            line_style: css.line_style.map(|s| match s {
+                UmlLineStyle::Solid => LineStyle::Solid,
+                UmlLineStyle::Dotted => LineStyle::Dotted,
+                UmlLineStyle::Dashed | UmlLineStyle::LongDashed => LineStyle::Dashed,
+            }),
+            line_thickness: css.line_thickness,
+            background_color: css.background_color,
+            font_color: css.font_color,
+            font_size: css.font_size,
+            bold: matches!(css.font_style, Some(FontStyle::Bold | FontStyle::Underline)),
+            shape: css.round_corner.map(|r| match r {
+                // plantuml uses huge values to get circles
+                100.. => NodeShape::Ellipse,
+                _ => NodeShape::RoundRectangle,
+            }),
+        }
+    }
+}
+
+/// Cytoscape.js stylesheet, every class is matched with a `.class` selector.
+#[derive(Default, Debug, Clone)]
+pub struct CytoscapeStyleSheet {
+    styles: Vec<(String, CytoscapeStyle)>,
+}
+
+impl CytoscapeStyleSheet {
+    pub fn new() -> Self {
+        Self { styles: Vec::new() }
+    }
+
+    pub fn with_class(mut self, class: impl ToString, style: CytoscapeStyle) -> Self {
+        self.push(class, style);
+        self
+    }
+
+    /// Adds a style, replacing the existing style with the same name
+    pub fn push(&mut self, class: impl ToString, style: CytoscapeStyle) {
+        let class = class.to_string();
+        match self.styles.iter_mut().find(|(c, _)| *c == class) {
+            Some((_, s)) => *s = style,
+            None => self.styles.push((class, style)),
+        }
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        for (class, style) in other.styles {
+            self.push(class, style);
+        }
+    }
+
+    pub fn contains(&self, class: &str) -> bool {
+        self.styles.iter().any(|(c, _)| c == class)
+    }
+
+    /// Color of an element with `classes`, which is the background color for nodes
+    /// and the line color for edges. Later classes take precedence, same as in cytoscape.
+    pub(crate) fn element_color(&self, classes: &[String], is_node: bool) -> Option<Color> {
+        self.styles
+            .iter()
+            .filter(|(class, _)| classes.contains(class))
+            .filter_map(|(_, style)| match is_node {
+                true => style.background_color,
+                false => style.line_color,
+            })
+            .next_back()
+    }
+
+    /// Writes the entries of the stylesheet, without the surrounding brackets
+    pub(crate) fn write(&self, writer: &mut impl Write) -> RenderResult<()> {
+        for (class, style) in &self.styles {
+            write!(
+                writer,
+                ",\n  {{\"selector\": {}, \"style\": ",
+                json_string(format!(".{class}"))
+            )?;
+            style.write(writer)?;
+            write!(writer, "}}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromIterator<(String, CytoscapeStyle)> for CytoscapeStyleSheet {
+    fn from_iter<T: IntoIterator<Item = (String, CytoscapeStyle)>>(iter: T) -> Self {
+        let mut sheet = Self::new();
+        for (class, style) in iter {
+            sheet.push(class, style);
+        }
+        sheet
+    }
+}
+
+/// Converts a PlantUML style sheet, so the same styling can be used for both.
+///
+/// Selectors are skipped, only classes are converted.
+#[cfg(feature = "plantuml")]
+impl From<&crate::plantuml::theme::PlantUmlStyleSheet> for CytoscapeStyleSheet {
+    fn from(sheet: &crate::plantuml::theme::PlantUmlStyleSheet) -> Self {
+        sheet
+            .classes
+            .iter()
+            .filter(|class| !class.is_selector)
+            .map(|class| (class.name.clone(), CytoscapeStyle::from(&class.element)))
+            .collect()
+    }
+}
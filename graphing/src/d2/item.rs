@@ -0,0 +1,121 @@
+use std::io::Write;
+
+use crate::RenderResult;
+
+use super::{
+    escape,
+    theme::{D2StyleSheet, NodeShape},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum D2ItemKind {
+    Node {
+        id: String,
+        contents: String,
+    },
+    Edge {
+        from: String,
+        to: String,
+        label: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct D2Item {
+    kind: D2ItemKind,
+    classes: Vec<String>,
+    /// Overrides the shape set by the classes
+    shape: Option<NodeShape>,
+}
+
+impl D2Item {
+    pub fn new(kind: D2ItemKind) -> Self {
+        Self {
+            kind,
+            classes: Vec::new(),
+            shape: None,
+        }
+    }
+
+    pub fn node(id: impl ToString, contents: impl ToString) -> Self {
+        Self::new(D2ItemKind::Node {
+            id: id.to_string(),
+            contents: contents.to_string(),
+        })
+    }
+
+    pub fn edge(from: impl ToString, to: impl ToString, label: impl ToString) -> Self {
+        Self::new(D2ItemKind::Edge {
+            from: from.to_string(),
+            to: to.to_string(),
+            label: label.to_string(),
+        })
+    }
+
+    /// Returns the ID of the node this item represents or is connected to.
+    ///
+    /// Edges use their 'from' node
+    pub fn node_id(&self) -> &str {
+        match &self.kind {
+            D2ItemKind::Node { id, .. } => id,
+            D2ItemKind::Edge { from, .. } => from,
+        }
+    }
+
+    /// Sets the shape of a node, does nothing for edges
+    pub fn with_shape(mut self, shape: NodeShape) -> Self {
+        if let D2ItemKind::Node { .. } = self.kind {
+            self.shape = Some(shape);
+        }
+        self
+    }
+
+    pub fn add_class(mut self, class: impl ToString) -> Self {
+        self.classes.push(class.to_string());
+        self
+    }
+
+    pub(crate) fn find_nonexistant_class(&self, sheet: &D2StyleSheet) -> Option<&str> {
+        self.classes
+            .iter()
+            .find(|class| !sheet.contains(class))
+            .map(String::as_str)
+    }
+
+    pub(crate) fn write(&self, writer: &mut impl Write) -> RenderResult<()> {
+        match &self.kind {
+            // "{id}": "{contents}" {shape: {shape}; class: [{classes}]}
+            D2ItemKind::Node { id, contents } => {
+                write!(writer, "{}: {}", escape(id), escape(contents))?;
+            }
+            // "{from}" -> "{to}": "{label}" {class: [{classes}]}
+            D2ItemKind::Edge { from, to, label } => {
+                write!(writer, "{} -> {}", escape(from), escape(to))?;
+                if !label.is_empty() {
+                    write!(writer, ": {}", escape(label))?;
+                }
+            }
+        }
+        self.write_attributes(writer)
+    }
+
+    fn write_attributes(&self, writer: &mut impl Write) -> RenderResult<()> {
+        let mut attributes = Vec::new();
+        if let Some(shape) = self.shape {
+            attributes.push(format!("shape: {shape}"));
+        }
+        if !self.classes.is_empty() {
+            let classes = self
+                .classes
+                .iter()
+                .map(escape)
+                .collect::<Vec<_>>()
+                .join("; ");
+            attributes.push(format!("class: [{classes}]"));
+        }
+        if !attributes.is_empty() {
+            write!(writer, " {{{}}}", attributes.join("; "))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use std::io::Write;
+
+pub use item::*;
+use theme::D2StyleSheet;
+
+use crate::{Diagram, ImageFormat, ImageTools, RenderResult, Renderer};
+
+mod item;
+pub mod theme;
+
+/// Escapes a string and wraps it in double quotes, so it can be used as a D2 key or label
+fn escape(s: impl ToString) -> String {
+    let escaped = s
+        .to_string()
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n");
+    format!("\"{escaped}\"")
+}
+
+#[derive(derive_more::Display, Debug, Clone, Copy)]
+pub enum D2Direction {
+    #[display("down")]
+    Down,
+    #[display("up")]
+    Up,
+    #[display("right")]
+    Right,
+    #[display("left")]
+    Left,
+}
+
+/// Diagram in the D2 language, render with `d2 input.d2 output.svg`.
+#[derive(Clone, Debug)]
+pub struct D2Diagram {
+    style: D2StyleSheet,
+    items: Vec<D2Item>,
+    title: String,
+    direction: D2Direction,
+}
+
+impl D2Diagram {
+    pub fn new(title: impl ToString) -> Self {
+        Self {
+            style: D2StyleSheet::new(),
+            items: Vec::new(),
+            title: title.to_string(),
+            direction: D2Direction::Down,
+        }
+    }
+
+    pub fn set_title(&mut self, title: impl ToString) {
+        self.title = title.to_string();
+    }
+
+    pub fn set_direction(&mut self, direction: D2Direction) {
+        self.direction = direction;
+    }
+
+    /// Returns number of items in the diagram.
+    pub fn num_items(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn set_style_sheet(&mut self, style: D2StyleSheet) {
+        self.style = style;
+    }
+
+    pub fn push(&mut self, item: D2Item) {
+        self.items.push(item);
+    }
+
+    pub fn extend(&mut self, items: impl IntoIterator<Item = D2Item>) {
+        self.items.extend(items);
+    }
+}
+
+impl Renderer for D2Diagram {
+    fn render_to_writer(&self, writer: &mut impl Write) -> RenderResult<()> {
+        writeln!(writer, "direction: {}", self.direction)?;
+        // d2 has no titles, this is the recommended replacement
+        writeln!(
+            writer,
+            "diagram-title: {} {{near: top-center; shape: text; style.font-size: 24; style.bold: true}}",
+            escape(&self.title)
+        )?;
+        self.style.write(writer)?;
+
+        for item in &self.items {
+            if let Some(dne_class) = item.find_nonexistant_class(&self.style) {
+                tracing::warn!(
+                    "Class {} does not exist in the stylesheet (found in {})",
+                    dne_class,
+                    item.node_id()
+                );
+            }
+            item.write(writer)?;
+            let _ = writer.write(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn source_extension(&self) -> &'static str {
+        "d2"
+    }
+
+    /// d2 derives the format from the extension of `output`
+    fn image_command(
+        &self,
+        tools: &ImageTools,
+        source: &std::path::Path,
+        output: &std::path::Path,
+        _format: ImageFormat,
+    ) -> RenderResult<Option<std::process::Command>> {
+        let mut command = std::process::Command::new(&tools.d2);
+        command.arg(source).arg(output);
+        Ok(Some(command))
+    }
+}
+
+/// Groups are not supported, their nodes are drawn ungrouped
+impl From<&Diagram> for D2Diagram {
+    fn from(diagram: &Diagram) -> Self {
+        let mut d2 = D2Diagram::new(&diagram.title);
+        d2.extend(diagram.nodes.iter().map(|node| {
+            node.classes.iter().fold(
+                D2Item::node(&node.id, &node.label).with_shape(node.shape.into()),
+                |item, class| item.add_class(class),
+            )
+        }));
+        d2.extend(diagram.edges.iter().map(|edge| {
+            edge.classes.iter().fold(
+                D2Item::edge(&edge.from, &edge.to, &edge.label),
+                |item, class| item.add_class(class),
+            )
+        }));
+        d2
+    }
+}
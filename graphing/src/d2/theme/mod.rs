@@ -0,0 +1,302 @@
+use std::io::Write;
+
+use crate::{Color, RenderResult, Shape};
+
+use super::escape;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineStyle {
+    Solid,
+    Dotted,
+    Dashed,
+    LongDashed,
+}
+
+impl LineStyle {
+    /// Value of `stroke-dash`
+    pub fn as_num(&self) -> usize {
+        match self {
+            LineStyle::Solid => 0,
+            LineStyle::Dotted => 2,
+            LineStyle::Dashed => 4,
+            LineStyle::LongDashed => 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum NodeShape {
+    #[display("circle")]
+    Circle,
+    #[display("oval")]
+    Oval,
+    #[display("rectangle")]
+    Rectangle,
+    #[display("page")]
+    Page,
+    #[display("text")]
+    Text,
+    #[display("hexagon")]
+    Hexagon,
+    /// Horizontal cylinder
+    #[display("queue")]
+    Queue,
+    /// Vertical cylinder
+    #[display("cylinder")]
+    Cylinder,
+}
+
+impl From<Shape> for NodeShape {
+    fn from(shape: Shape) -> Self {
+        match shape {
+            Shape::Circle => NodeShape::Circle,
+            Shape::Rounded | Shape::Card => NodeShape::Rectangle,
+            Shape::Hexagon => NodeShape::Hexagon,
+            Shape::Cylinder => NodeShape::Queue,
+            Shape::Database => NodeShape::Cylinder,
+            Shape::Note => NodeShape::Page,
+        }
+    }
+}
+
+/// Style of a D2 class.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct D2Style {
+    /// stroke
+    line_color: Option<Color>,
+    /// stroke-dash
+    line_style: Option<LineStyle>,
+    /// stroke-width
+    line_thickness: Option<usize>,
+    /// fill
+    background_color: Option<Color>,
+    /// font-color
+    font_color: Option<Color>,
+    /// font-size
+    font_size: Option<usize>,
+    bold: bool,
+    italic: bool,
+    /// border-radius
+    round_corner: Option<usize>,
+    /// shadow
+    shadow: bool,
+    /// shape, not part of `style` in D2
+    shape: Option<NodeShape>,
+}
+
+impl D2Style {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::new()
+    }
+
+    pub fn line_color(mut self, color: Color) -> Self {
+        self.line_color = Some(color);
+        self
+    }
+
+    pub fn line_style(mut self, style: LineStyle) -> Self {
+        self.line_style = Some(style);
+        self
+    }
+
+    /// D2 only accepts whole numbers between 1 and 15
+    pub fn line_thickness(mut self, thickness: usize) -> Self {
+        self.line_thickness = Some(thickness.clamp(1, 15));
+        self
+    }
+
+    pub fn background_color(mut self, color: Color) -> Self {
+        self.background_color = Some(color);
+        self
+    }
+
+    pub fn font_color(mut self, color: Color) -> Self {
+        self.font_color = Some(color);
+        self
+    }
+
+    pub fn font_size(mut self, size: usize) -> Self {
+        self.font_size = Some(size);
+        self
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    /// D2 only accepts values between 0 and 20
+    pub fn round_corner(mut self, radius: usize) -> Self {
+        self.round_corner = Some(radius.min(20));
+        self
+    }
+
+    pub fn shadow(mut self) -> Self {
+        self.shadow = true;
+        self
+    }
+
+    pub fn shape(mut self, shape: NodeShape) -> Self {
+        self.shape = Some(shape);
+        self
+    }
+
+    pub(crate) fn write(&self, writer: &mut impl Write, indent: &str) -> RenderResult<()> {
+        if let Some(x) = self.shape {
+            writeln!(writer, "{indent}shape: {x}")?;
+        }
+        macro_rules! write_prop {
+            ($key:literal, $value:expr) => {
+                writeln!(writer, "{indent}style.{}: {}", $key, $value)?;
+            };
+        }
+        if let Some(x) = self.line_color {
+            write_prop!("stroke", format!("\"{}\"", x.hex_string()));
+        }
+        if let Some(x) = self.line_style {
+            write_prop!("stroke-dash", x.as_num());
+        }
+        if let Some(x) = self.line_thickness {
+            write_prop!("stroke-width", x);
+        }
+        if let Some(x) = self.background_color {
+            write_prop!("fill", format!("\"{}\"", x.hex_string()));
+        }
+        if let Some(x) = self.font_color {
+            write_prop!("font-color", format!("\"{}\"", x.hex_string()));
+        }
+        if let Some(x) = self.font_size {
+            write_prop!("font-size", x);
+        }
+        if self.bold {
+            write_prop!("bold", true);
+        }
+        if self.italic {
+            write_prop!("italic", true);
+        }
+        if let Some(x) = self.round_corner {
+            write_prop!("border-radius", x);
+        }
+        if self.shadow {
+            write_prop!("shadow", true);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "plantuml")]
+impl From<&crate::plantuml::theme::ElementCss> for D2Style {
+    fn from(css: &crate::plantuml::theme::ElementCss) -> Self {
+        use crate::plantuml::theme::{FontStyle, LineStyle as UmlLineStyle};
+
+        let font_style = css.font_style.unwrap_or_default();
+        Self {
+            line_color: css.line_color,
+            line_style: css.line_style.map(|s| match s {
+                UmlLineStyle::Solid => LineStyle::Solid,
+                UmlLineStyle::Dotted => LineStyle::Dotted,
+                UmlLineStyle::Dashed => LineStyle::Dashed,
+                UmlLineStyle::LongDashed => LineStyle::LongDashed,
+            }),
+            line_thickness: css
+                .line_thickness
+                .map(|t| (t.round() as usize).clamp(1, 15)),
+            background_color: css.background_color,
+            font_color: css.font_color,
+            font_size: css.font_size,
+            bold: matches!(font_style, FontStyle::Bold | FontStyle::Underline),
+            italic: matches!(font_style, FontStyle::Italic | FontStyle::Underline),
+            round_corner: css.round_corner.filter(|r| *r < 100),
+            shadow: css.shadowing.is_some_and(|s| s > 0),
+            // plantuml uses huge values to get circles
+            shape: css
+                .round_corner
+                .filter(|r| *r >= 100)
+                .map(|_| NodeShape::Circle),
+        }
+    }
+}
+
+/// D2 classes, written in the `classes` block at the top of the diagram.
+#[derive(Default, Debug, Clone)]
+pub struct D2StyleSheet {
+    styles: Vec<(String, D2Style)>,
+}
+
+impl D2StyleSheet {
+    pub fn new() -> Self {
+        Self { styles: Vec::new() }
+    }
+
+    pub fn with_class(mut self, class: impl ToString, style: D2Style) -> Self {
+        self.push(class, style);
+        self
+    }
+
+    /// Adds a class, replacing the existing class with the same name
+    pub fn push(&mut self, class: impl ToString, style: D2Style) {
+        let class = class.to_string();
+        match self.styles.iter_mut().find(|(c, _)| *c == class) {
+            Some((_, s)) => *s = style,
+            None => self.styles.push((class, style)),
+        }
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        for (class, style) in other.styles {
+            self.push(class, style);
+        }
+    }
+
+    pub fn contains(&self, class: &str) -> bool {
+        self.styles.iter().any(|(c, _)| c == class)
+    }
+
+    pub(crate) fn write(&self, writer: &mut impl Write) -> RenderResult<()> {
+        if self.styles.is_empty() {
+            return Ok(());
+        }
+        writeln!(writer, "classes: {{")?;
+        for (class, style) in &self.styles {
+            writeln!(writer, "  {}: {{", escape(class))?;
+            style.write(writer, "    ")?;
+            writeln!(writer, "  }}")?;
+        }
+        writeln!(writer, "}}")?;
+        Ok(())
+    }
+}
+
+impl FromIterator<(String, D2Style)> for D2StyleSheet {
+    fn from_iter<T: IntoIterator<Item = (String, D2Style)>>(iter: T) -> Self {
+        let mut sheet = Self::new();
+        for (class, style) in iter {
+            sheet.push(class, style);
+        }
+        sheet
+    }
+}
+
+/// Converts a PlantUML style sheet, so the same styling can be used for both.
+///
+/// Selectors are skipped, only classes are converted.
+#[cfg(feature = "plantuml")]
+impl From<&crate::plantuml::theme::PlantUmlStyleSheet> for D2StyleSheet {
+    fn from(sheet: &crate::plantuml::theme::PlantUmlStyleSheet) -> Self {
+        sheet
+            .classes
+            .iter()
+            .filter(|class| !class.is_selector)
+            .map(|class| (class.name.clone(), D2Style::from(&class.element)))
+            .collect()
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::GridPosition;
+
+/// Class of items that are only in the new diagram, see [`Diagram::diff`]
+pub const DIFF_ADDED_CLASS: &str = "diff-added";
+/// Class of items that are only in the old diagram, see [`Diagram::diff`]
+pub const DIFF_REMOVED_CLASS: &str = "diff-removed";
+/// Class of nodes whose label, shape or classes changed, see [`Diagram::diff`]
+pub const DIFF_CHANGED_CLASS: &str = "diff-changed";
+
+/// Shape of a node, mapped to the closest shape of each backend.
+///
+/// Converts into the shape type of every backend with `From`,
+/// except for Cytoscape, where shapes are set by the stylesheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Shape {
+    #[default]
+    Rounded,
+    /// Used for scopes
+    Circle,
+    /// Used for declarations
+    Card,
+    Hexagon,
+    /// Horizontal cylinder, e.g. for caches
+    Cylinder,
+    /// Vertical cylinder
+    Database,
+    Note,
+}
+
+/// Preferred direction of an edge, only used by backends that support per-edge directions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagramNode {
+    pub id: String,
+    pub label: String,
+    pub shape: Shape,
+    pub classes: Vec<String>,
+    pub position: Option<GridPosition>,
+    pub tooltip: Option<String>,
+}
+
+impl DiagramNode {
+    pub fn new(id: impl ToString, label: impl ToString) -> Self {
+        Self {
+            id: id.to_string(),
+            label: label.to_string(),
+            shape: Shape::default(),
+            classes: Vec::new(),
+            position: None,
+            tooltip: None,
+        }
+    }
+
+    pub fn with_shape(mut self, shape: Shape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Places the node in a cell of a grid, only used by PlantUML and Mermaid
+    pub fn with_position(mut self, column: i32, row: i32) -> Self {
+        self.position = Some((column, row));
+        self
+    }
+
+    /// Text shown when hovering over the node, only used by PlantUML and Mermaid
+    pub fn with_tooltip(mut self, text: impl ToString) -> Self {
+        self.tooltip = Some(text.to_string());
+        self
+    }
+
+    pub fn add_class(mut self, class: impl ToString) -> Self {
+        self.classes.push(class.to_string());
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagramEdge {
+    pub from: String,
+    pub to: String,
+    pub label: String,
+    pub direction: Option<Direction>,
+    pub classes: Vec<String>,
+}
+
+impl DiagramEdge {
+    pub fn new(from: impl ToString, to: impl ToString, label: impl ToString) -> Self {
+        Self {
+            from: from.to_string(),
+            to: to.to_string(),
+            label: label.to_string(),
+            direction: None,
+            classes: Vec::new(),
+        }
+    }
+
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    pub fn add_class(mut self, class: impl ToString) -> Self {
+        self.classes.push(class.to_string());
+        self
+    }
+}
+
+/// Set of nodes that is drawn together, backends without groups draw the nodes ungrouped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagramGroup {
+    pub id: String,
+    pub title: String,
+    /// IDs of the nodes in this group
+    pub nodes: Vec<String>,
+}
+
+impl DiagramGroup {
+    pub fn new(id: impl ToString, title: impl ToString) -> Self {
+        Self {
+            id: id.to_string(),
+            title: title.to_string(),
+            nodes: Vec::new(),
+        }
+    }
+
+    pub fn with_nodes(mut self, nodes: impl IntoIterator<Item = impl ToString>) -> Self {
+        self.nodes.extend(nodes.into_iter().map(|n| n.to_string()));
+        self
+    }
+}
+
+/// Backend-agnostic description of a graph.
+///
+/// Every backend can be created from a diagram using `From<&Diagram>`,
+/// styling is done by giving the backend a stylesheet that defines the classes used here.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagram {
+    pub title: String,
+    pub nodes: Vec<DiagramNode>,
+    pub edges: Vec<DiagramEdge>,
+    pub groups: Vec<DiagramGroup>,
+    /// Nodes that are placed next to each other, only used by PlantUML and Mermaid
+    pub ranks: Vec<Vec<String>>,
+}
+
+impl Diagram {
+    pub fn new(title: impl ToString) -> Self {
+        Self {
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn push_node(&mut self, node: DiagramNode) {
+        self.nodes.push(node);
+    }
+
+    pub fn push_edge(&mut self, edge: DiagramEdge) {
+        self.edges.push(edge);
+    }
+
+    pub fn push_group(&mut self, group: DiagramGroup) {
+        self.groups.push(group);
+    }
+
+    pub fn same_rank(&mut self, ids: impl IntoIterator<Item = impl ToString>) {
+        self.ranks
+            .push(ids.into_iter().map(|id| id.to_string()).collect());
+    }
+
+    /// Returns the group `node_id` is part of, if any
+    pub fn group_of(&self, node_id: &str) -> Option<&DiagramGroup> {
+        self.groups
+            .iter()
+            .find(|g| g.nodes.iter().any(|n| n == node_id))
+    }
+
+    /// Nodes that are not part of any group
+    pub fn ungrouped_nodes(&self) -> impl Iterator<Item = &DiagramNode> {
+        let grouped = self
+            .groups
+            .iter()
+            .flat_map(|g| g.nodes.iter().map(String::as_str))
+            .collect::<HashSet<_>>();
+        self.nodes
+            .iter()
+            .filter(move |n| !grouped.contains(n.id.as_str()))
+    }
+
+    /// Nodes that are part of `group`, in the order they were added to the diagram
+    pub fn group_nodes<'a>(
+        &'a self,
+        group: &'a DiagramGroup,
+    ) -> impl Iterator<Item = &'a DiagramNode> {
+        let members = group
+            .nodes
+            .iter()
+            .map(String::as_str)
+            .collect::<HashSet<_>>();
+        self.nodes
+            .iter()
+            .filter(move |n| members.contains(n.id.as_str()))
+    }
+
+    /// Combines two versions of a diagram, marking the differences with
+    /// [`DIFF_ADDED_CLASS`], [`DIFF_REMOVED_CLASS`] and [`DIFF_CHANGED_CLASS`].
+    ///
+    /// Nodes are matched by ID and edges by their endpoints and label.
+    /// Removed items are kept, so both versions can be drawn in a single picture.
+    /// The stylesheet of the backend has to define the diff classes.
+    pub fn diff(old: &Diagram, new: &Diagram) -> Diagram {
+        let mut diff = Diagram::new(&new.title);
+        diff.ranks = new.ranks.clone();
+
+        let old_nodes = old
+            .nodes
+            .iter()
+            .map(|n| (n.id.as_str(), n))
+            .collect::<HashMap<_, _>>();
+        let new_ids = new
+            .nodes
+            .iter()
+            .map(|n| n.id.as_str())
+            .collect::<HashSet<_>>();
+        // edges are the same if they have the same endpoints and label
+        fn edge_key(e: &DiagramEdge) -> (&str, &str, &str) {
+            (&e.from, &e.to, &e.label)
+        }
+        let old_edges = old.edges.iter().map(edge_key).collect::<HashSet<_>>();
+        let new_edges = new.edges.iter().map(edge_key).collect::<HashSet<_>>();
+
+        for node in &new.nodes {
+            let class = match old_nodes.get(node.id.as_str()) {
+                None => Some(DIFF_ADDED_CLASS),
+                Some(old_node)
+                    if old_node.label != node.label
+                        || old_node.shape != node.shape
+                        || old_node.classes != node.classes =>
+                {
+                    Some(DIFF_CHANGED_CLASS)
+                }
+                Some(_) => None,
+            };
+            let node = node.clone();
+            diff.push_node(match class {
+                Some(class) => node.add_class(class),
+                None => node,
+            });
+        }
+        let removed_nodes = old
+            .nodes
+            .iter()
+            .filter(|node| !new_ids.contains(node.id.as_str()))
+            .map(|node| node.clone().add_class(DIFF_REMOVED_CLASS))
+            .collect::<Vec<_>>();
+        let removed_ids = removed_nodes
+            .iter()
+            .map(|n| n.id.as_str())
+            .collect::<HashSet<_>>();
+
+        for edge in &new.edges {
+            let added = !old_edges.contains(&edge_key(edge));
+            let edge = edge.clone();
+            diff.push_edge(match added {
+                true => edge.add_class(DIFF_ADDED_CLASS),
+                false => edge,
+            });
+        }
+        for edge in &old.edges {
+            if !new_edges.contains(&edge_key(edge)) {
+                diff.push_edge(edge.clone().add_class(DIFF_REMOVED_CLASS));
+            }
+        }
+
+        // removed nodes stay in the group they were in
+        diff.groups = new.groups.clone();
+        let mut group_index = diff
+            .groups
+            .iter()
+            .enumerate()
+            .map(|(i, g)| (g.id.clone(), i))
+            .collect::<HashMap<_, _>>();
+        for old_group in &old.groups {
+            let removed = old_group
+                .nodes
+                .iter()
+                .filter(|id| removed_ids.contains(id.as_str()))
+                .cloned();
+            match group_index.get(&old_group.id) {
+                Some(&i) => diff.groups[i].nodes.extend(removed),
+                None => {
+                    group_index.insert(old_group.id.clone(), diff.groups.len());
+                    diff.groups.push(
+                        DiagramGroup::new(&old_group.id, &old_group.title).with_nodes(removed),
+                    )
+                }
+            }
+        }
+        diff.nodes.extend(removed_nodes);
+        diff
+    }
+}
@@ -0,0 +1,172 @@
+use std::io::Write;
+
+use crate::{Color, RenderResult, Shape};
+
+use super::{
+    escape,
+    theme::{DotStyle, DotStyleSheet, LineStyle},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum NodeShape {
+    /// Used for scopes
+    #[display("ellipse")]
+    Ellipse,
+    /// Used for declarations
+    #[display("box")]
+    Box,
+    #[display("circle")]
+    Circle,
+    #[display("note")]
+    Note,
+    #[display("plaintext")]
+    PlainText,
+    #[display("hexagon")]
+    Hexagon,
+    #[display("cylinder")]
+    Cylinder,
+}
+
+impl From<Shape> for NodeShape {
+    fn from(shape: Shape) -> Self {
+        match shape {
+            Shape::Circle => NodeShape::Ellipse,
+            Shape::Rounded | Shape::Card => NodeShape::Box,
+            Shape::Hexagon => NodeShape::Hexagon,
+            // graphviz only has vertical cylinders
+            Shape::Cylinder | Shape::Database => NodeShape::Cylinder,
+            Shape::Note => NodeShape::Note,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DotItemKind {
+    Node {
+        id: String,
+        contents: String,
+        shape: NodeShape,
+    },
+    Edge {
+        from: String,
+        to: String,
+        label: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DotItem {
+    kind: DotItemKind,
+    classes: Vec<String>,
+    /// Attributes set on this item directly, these take precedence over the classes
+    style: DotStyle,
+}
+
+impl DotItem {
+    pub fn new(kind: DotItemKind) -> Self {
+        Self {
+            kind,
+            classes: Vec::new(),
+            style: DotStyle::new(),
+        }
+    }
+
+    pub fn node(id: impl ToString, contents: impl ToString, shape: NodeShape) -> Self {
+        Self::new(DotItemKind::Node {
+            id: id.to_string(),
+            contents: contents.to_string(),
+            shape,
+        })
+    }
+
+    pub fn edge(from: impl ToString, to: impl ToString, label: impl ToString) -> Self {
+        Self::new(DotItemKind::Edge {
+            from: from.to_string(),
+            to: to.to_string(),
+            label: label.to_string(),
+        })
+    }
+
+    /// Returns the ID of the node this item represents or is connected to.
+    ///
+    /// Edges use their 'from' node
+    pub fn node_id(&self) -> &str {
+        match &self.kind {
+            DotItemKind::Node { id, .. } => id,
+            DotItemKind::Edge { from, .. } => from,
+        }
+    }
+
+    pub fn is_node(&self) -> bool {
+        matches!(self.kind, DotItemKind::Node { .. })
+    }
+
+    pub fn add_class(mut self, class: impl ToString) -> Self {
+        self.classes.push(class.to_string());
+        self
+    }
+
+    pub fn with_line_style(mut self, style: LineStyle) -> Self {
+        self.style = self.style.line_style(style);
+        self
+    }
+
+    pub fn with_text_color(mut self, color: Color) -> Self {
+        self.style = self.style.font_color(color);
+        self
+    }
+
+    pub fn with_line_color(mut self, line_color: Color) -> Self {
+        self.style = self.style.line_color(line_color);
+        self
+    }
+
+    pub(crate) fn find_nonexistant_class(&self, sheet: &DotStyleSheet) -> Option<&str> {
+        self.classes
+            .iter()
+            .find(|class| !sheet.contains_key(*class))
+            .map(String::as_str)
+    }
+
+    /// Attributes of all classes of this item in order, followed by the attributes of the item itself
+    fn resolved_style(&self, sheet: &DotStyleSheet) -> DotStyle {
+        let mut style = DotStyle::new();
+        for class_style in self.classes.iter().filter_map(|c| sheet.get(c)) {
+            style.merge(class_style);
+        }
+        style.merge(&self.style);
+        style
+    }
+
+    pub(crate) fn write(&self, writer: &mut impl Write, sheet: &DotStyleSheet) -> RenderResult<()> {
+        match &self.kind {
+            // "{id}" [label="{contents}", shape={shape}, ...];
+            DotItemKind::Node {
+                id,
+                contents,
+                shape,
+            } => {
+                write!(
+                    writer,
+                    "\"{}\" [label=\"{}\", shape={}",
+                    escape(id),
+                    escape(contents),
+                    shape
+                )?;
+            }
+            // "{from}" -> "{to}" [label="{label}", ...];
+            DotItemKind::Edge { from, to, label } => {
+                write!(
+                    writer,
+                    "\"{}\" -> \"{}\" [label=\"{}\"",
+                    escape(from),
+                    escape(to),
+                    escape(label)
+                )?;
+            }
+        }
+        self.resolved_style(sheet).write(writer, self.is_node())?;
+        write!(writer, "];")?;
+        Ok(())
+    }
+}
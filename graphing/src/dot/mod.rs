@@ -0,0 +1,145 @@
+use std::io::Write;
+
+pub use item::*;
+use theme::DotStyleSheet;
+
+use crate::{Diagram, ImageFormat, ImageTools, RenderResult, Renderer};
+
+mod item;
+pub mod theme;
+
+/// Escapes a string so it can be used inside a quoted DOT ID
+fn escape(s: impl ToString) -> String {
+    s.to_string()
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n")
+}
+
+#[derive(derive_more::Display, Debug, Clone, Copy)]
+pub enum DotRankDir {
+    #[display("TB")]
+    TopBottom,
+    #[display("BT")]
+    BottomTop,
+    #[display("LR")]
+    LeftRight,
+    #[display("RL")]
+    RightLeft,
+}
+
+/// Graphviz diagram, which scales to much larger graphs than PlantUML.
+///
+/// Render the output with e.g. `dot -Tsvg` or `sfdp -Tsvg` for very large graphs.
+#[derive(Clone, Debug)]
+pub struct DotDiagram {
+    style: DotStyleSheet,
+    items: Vec<DotItem>,
+    title: String,
+    direction: DotRankDir,
+}
+
+impl DotDiagram {
+    pub fn new(title: impl ToString) -> Self {
+        Self {
+            style: DotStyleSheet::new(),
+            items: Vec::new(),
+            title: title.to_string(),
+            direction: DotRankDir::TopBottom,
+        }
+    }
+
+    pub fn set_title(&mut self, title: impl ToString) {
+        self.title = title.to_string();
+    }
+
+    pub fn set_direction(&mut self, direction: DotRankDir) {
+        self.direction = direction;
+    }
+
+    /// Returns number of items in the diagram.
+    pub fn num_items(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn set_style_sheet(&mut self, style: DotStyleSheet) {
+        self.style = style;
+    }
+
+    pub fn push(&mut self, item: DotItem) {
+        self.items.push(item);
+    }
+
+    pub fn extend(&mut self, items: impl IntoIterator<Item = DotItem>) {
+        self.items.extend(items);
+    }
+}
+
+impl Renderer for DotDiagram {
+    fn render_to_writer(&self, writer: &mut impl Write) -> RenderResult<()> {
+        let title = escape(&self.title);
+        writeln!(writer, "digraph \"{title}\" {{")?;
+        writeln!(
+            writer,
+            "label=\"{title}\";\nlabelloc=t;\nrankdir={};",
+            self.direction
+        )?;
+
+        // nodes first, so edges do not implicitly create nodes without attributes
+        let (nodes, edges): (Vec<_>, Vec<_>) = self.items.iter().partition(|i| i.is_node());
+        for item in nodes.into_iter().chain(edges) {
+            if let Some(dne_class) = item.find_nonexistant_class(&self.style) {
+                tracing::warn!(
+                    "Class {} does not exist in the stylesheet (found in {})",
+                    dne_class,
+                    item.node_id()
+                );
+            }
+            item.write(writer, &self.style)?;
+            let _ = writer.write(b"\n")?;
+        }
+
+        write!(writer, "}}")?;
+        Ok(())
+    }
+
+    fn source_extension(&self) -> &'static str {
+        "dot"
+    }
+
+    fn image_command(
+        &self,
+        tools: &ImageTools,
+        source: &std::path::Path,
+        output: &std::path::Path,
+        format: ImageFormat,
+    ) -> RenderResult<Option<std::process::Command>> {
+        let mut command = std::process::Command::new(&tools.dot);
+        command
+            .arg(format!("-T{format}"))
+            .arg(source)
+            .arg("-o")
+            .arg(output);
+        Ok(Some(command))
+    }
+}
+
+/// Groups are not supported, their nodes are drawn ungrouped
+impl From<&Diagram> for DotDiagram {
+    fn from(diagram: &Diagram) -> Self {
+        let mut dot = DotDiagram::new(&diagram.title);
+        dot.extend(diagram.nodes.iter().map(|node| {
+            node.classes.iter().fold(
+                DotItem::node(&node.id, &node.label, node.shape.into()),
+                |item, class| item.add_class(class),
+            )
+        }));
+        dot.extend(diagram.edges.iter().map(|edge| {
+            edge.classes.iter().fold(
+                DotItem::edge(&edge.from, &edge.to, &edge.label),
+                |item, class| item.add_class(class),
+            )
+        }));
+        dot
+    }
+}
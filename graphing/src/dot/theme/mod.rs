@@ -0,0 +1,183 @@
+use std::{collections::HashMap, io::Write, ops::Deref};
+
+use crate::{Color, RenderResult};
+
+use super::escape;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum LineStyle {
+    #[display("solid")]
+    Solid,
+    #[display("dotted")]
+    Dotted,
+    #[display("dashed")]
+    Dashed,
+    #[display("bold")]
+    Bold,
+    #[display("invis")]
+    Invisible,
+}
+
+/// Graphviz attributes of a node or edge.
+///
+/// DOT has no classes, so the attributes of every class of an item are written inline.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct DotStyle {
+    /// color
+    line_color: Option<Color>,
+    /// style
+    line_style: Option<LineStyle>,
+    /// penwidth
+    line_thickness: Option<f32>,
+    /// fillcolor, also sets style=filled for nodes
+    background_color: Option<Color>,
+    /// fontcolor
+    font_color: Option<Color>,
+    /// fontsize
+    font_size: Option<f32>,
+    /// fontname
+    font_family: Option<String>,
+}
+
+impl DotStyle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::new()
+    }
+
+    pub fn line_color(mut self, color: Color) -> Self {
+        self.line_color = Some(color);
+        self
+    }
+
+    pub fn line_style(mut self, style: LineStyle) -> Self {
+        self.line_style = Some(style);
+        self
+    }
+
+    pub fn line_thickness(mut self, thickness: f32) -> Self {
+        self.line_thickness = Some(thickness);
+        self
+    }
+
+    pub fn background_color(mut self, color: Color) -> Self {
+        self.background_color = Some(color);
+        self
+    }
+
+    pub fn font_color(mut self, color: Color) -> Self {
+        self.font_color = Some(color);
+        self
+    }
+
+    pub fn font_size(mut self, size: f32) -> Self {
+        self.font_size = Some(size);
+        self
+    }
+
+    pub fn font_family(mut self, family: impl ToString) -> Self {
+        self.font_family = Some(family.to_string());
+        self
+    }
+
+    /// Overwrites the attributes of `self` with the attributes that are set in `other`
+    pub fn merge(&mut self, other: &Self) {
+        macro_rules! merge_prop {
+            ($($prop:ident),*) => {
+                $(
+                    if let Some(x) = &other.$prop {
+                        self.$prop = Some(x.clone());
+                    }
+                )*
+            };
+        }
+        merge_prop!(
+            line_color,
+            line_style,
+            line_thickness,
+            background_color,
+            font_color,
+            font_size,
+            font_family
+        );
+    }
+
+    /// Writes the attributes as `key=value` pairs, each prefixed with ", "
+    ///
+    /// `style=filled` is only valid for nodes, edges only get a `fillcolor` (used for arrowheads).
+    pub(crate) fn write(&self, writer: &mut impl Write, is_node: bool) -> RenderResult<()> {
+        let filled = is_node && self.background_color.is_some();
+        if let Some(x) = self.line_color {
+            write!(writer, ", color=\"{}\"", x.hex_string())?;
+        }
+        match (self.line_style, filled) {
+            (Some(style), true) => write!(writer, ", style=\"{style},filled\"")?,
+            (Some(style), false) => write!(writer, ", style={style}")?,
+            (None, true) => write!(writer, ", style=filled")?,
+            (None, false) => (),
+        }
+        if let Some(x) = self.background_color {
+            write!(writer, ", fillcolor=\"{}\"", x.hex_string())?;
+        }
+        if let Some(x) = self.line_thickness {
+            write!(writer, ", penwidth={x}")?;
+        }
+        if let Some(x) = self.font_color {
+            write!(writer, ", fontcolor=\"{}\"", x.hex_string())?;
+        }
+        if let Some(x) = self.font_size {
+            write!(writer, ", fontsize={x}")?;
+        }
+        if let Some(x) = &self.font_family {
+            write!(writer, ", fontname=\"{}\"", escape(x))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct DotStyleSheet {
+    map: HashMap<String, DotStyle>,
+}
+
+impl DotStyleSheet {
+    pub fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+        }
+    }
+
+    pub fn with_class(mut self, class: impl ToString, style: DotStyle) -> Self {
+        self.map.insert(class.to_string(), style);
+        self
+    }
+
+    pub fn push(&mut self, class: impl ToString, style: DotStyle) {
+        self.map.insert(class.to_string(), style);
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        for (class, style) in other.map {
+            self.map.insert(class, style);
+        }
+    }
+}
+
+impl FromIterator<(String, DotStyle)> for DotStyleSheet {
+    fn from_iter<T: IntoIterator<Item = (String, DotStyle)>>(iter: T) -> Self {
+        Self {
+            map: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Deref for DotStyleSheet {
+    type Target = HashMap<String, DotStyle>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+
 use derive_more::{Display, From};
 
 pub type RenderResult<T> = core::result::Result<T, RenderError>;
@@ -7,8 +9,79 @@ pub enum RenderError {
     #[from]
     IoError(std::io::Error),
 
+    /// I/O error on a file or directory, with the path and what was being done to it
+    #[display("Failed to {operation} {}: {source}", path.display())]
+    FileError {
+        operation: &'static str,
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
     #[from]
     StringConversionError(std::string::FromUtf8Error),
+
+    #[display("Diagram cannot be rendered to an image")]
+    ImageNotSupported,
+
+    #[display("{tool} failed: {stderr}")]
+    ToolFailed { tool: String, stderr: String },
+}
+
+impl std::error::Error for RenderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::IoError(e) | Self::FileError { source: e, .. } => Some(e),
+            Self::StringConversionError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Adds the path and operation to I/O errors, e.g. `File::create(path).context("create", path)?`
+pub(crate) trait FileContext<T> {
+    fn context(self, operation: &'static str, path: &Path) -> RenderResult<T>;
 }
 
-impl std::error::Error for RenderError {}
+impl<T> FileContext<T> for std::io::Result<T> {
+    fn context(self, operation: &'static str, path: &Path) -> RenderResult<T> {
+        self.map_err(|source| RenderError::FileError {
+            operation,
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+/// Adds the path to the bare I/O errors of a renderer, e.g. the `write!` errors of [`crate::Renderer::render_to_writer`]
+impl<T> FileContext<T> for RenderResult<T> {
+    fn context(self, operation: &'static str, path: &Path) -> RenderResult<T> {
+        self.map_err(|e| match e {
+            RenderError::IoError(source) => RenderError::FileError {
+                operation,
+                path: path.to_path_buf(),
+                source,
+            },
+            e => e,
+        })
+    }
+}
+
+/// Keeps the kind of I/O errors, so [`create_file`] can also be used where a `std::io::Result` is returned
+impl From<RenderError> for std::io::Error {
+    fn from(e: RenderError) -> Self {
+        match e {
+            RenderError::IoError(e) => e,
+            RenderError::FileError { ref source, .. } => Self::new(source.kind(), e),
+            e => Self::other(e),
+        }
+    }
+}
+
+/// Creates the parent directories of `path` and then the file itself
+pub fn create_file(path: impl AsRef<Path>) -> RenderResult<std::fs::File> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).context("create directory", parent)?;
+    }
+    std::fs::File::create(path).context("create", path)
+}
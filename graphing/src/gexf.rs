@@ -0,0 +1,225 @@
+use std::{collections::HashMap, io::Write};
+
+use indexmap::IndexSet;
+
+use crate::{Diagram, RenderResult, Renderer};
+
+/// Escapes a string so it can be used as XML attribute value
+fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\n' => out.push_str("&#10;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GexfItemKind {
+    Node {
+        id: String,
+        label: String,
+        /// Attribute name and value, every node has a `degree` attribute as well
+        attributes: Vec<(String, String)>,
+    },
+    Edge {
+        from: String,
+        to: String,
+        label: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GexfItem {
+    kind: GexfItemKind,
+}
+
+impl GexfItem {
+    pub fn new(kind: GexfItemKind) -> Self {
+        Self { kind }
+    }
+
+    pub fn node(id: impl ToString, label: impl ToString) -> Self {
+        Self::new(GexfItemKind::Node {
+            id: id.to_string(),
+            label: label.to_string(),
+            attributes: Vec::new(),
+        })
+    }
+
+    pub fn edge(from: impl ToString, to: impl ToString, label: impl ToString) -> Self {
+        Self::new(GexfItemKind::Edge {
+            from: from.to_string(),
+            to: to.to_string(),
+            label: label.to_string(),
+        })
+    }
+
+    /// Adds an attribute to a node, which Gephi can use for filtering and partitioning.
+    /// Does nothing for edges.
+    pub fn with_attribute(mut self, name: impl ToString, value: impl ToString) -> Self {
+        if let GexfItemKind::Node { attributes, .. } = &mut self.kind {
+            attributes.push((name.to_string(), value.to_string()));
+        }
+        self
+    }
+}
+
+/// Graph in the GEXF format, which can be opened in [Gephi](https://gephi.org) for analysis.
+///
+/// Every node gets the number of edges it is part of as `degree` attribute,
+/// other attributes are added with [`GexfItem::with_attribute`] and are written as strings.
+#[derive(Clone, Debug)]
+pub struct GexfDiagram {
+    items: Vec<GexfItem>,
+    title: String,
+}
+
+impl GexfDiagram {
+    pub fn new(title: impl ToString) -> Self {
+        Self {
+            items: Vec::new(),
+            title: title.to_string(),
+        }
+    }
+
+    pub fn set_title(&mut self, title: impl ToString) {
+        self.title = title.to_string();
+    }
+
+    /// Returns number of items in the diagram.
+    pub fn num_items(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn push(&mut self, item: GexfItem) {
+        self.items.push(item);
+    }
+
+    pub fn extend(&mut self, items: impl IntoIterator<Item = GexfItem>) {
+        self.items.extend(items);
+    }
+}
+
+impl Renderer for GexfDiagram {
+    fn render_to_writer(&self, writer: &mut impl Write) -> RenderResult<()> {
+        let mut degrees = HashMap::<&str, usize>::new();
+        // attribute 0 is the degree
+        let mut attribute_names = IndexSet::from(["degree"]);
+        for item in &self.items {
+            match &item.kind {
+                GexfItemKind::Node { attributes, .. } => {
+                    attribute_names.extend(attributes.iter().map(|(name, _)| name.as_str()));
+                }
+                GexfItemKind::Edge { from, to, .. } => {
+                    *degrees.entry(from).or_default() += 1;
+                    *degrees.entry(to).or_default() += 1;
+                }
+            }
+        }
+
+        writeln!(
+            writer,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+            <gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n\
+            <meta>\n  <description>{}</description>\n</meta>\n\
+            <graph defaultedgetype=\"directed\" mode=\"static\">",
+            xml_escape(&self.title)
+        )?;
+
+        writeln!(writer, "<attributes class=\"node\">")?;
+        for (idx, name) in attribute_names.iter().enumerate() {
+            let kind = match idx {
+                0 => "integer",
+                _ => "string",
+            };
+            writeln!(
+                writer,
+                "  <attribute id=\"{idx}\" title=\"{}\" type=\"{kind}\"/>",
+                xml_escape(name)
+            )?;
+        }
+        writeln!(writer, "</attributes>")?;
+
+        writeln!(writer, "<nodes>")?;
+        for item in &self.items {
+            let GexfItemKind::Node {
+                id,
+                label,
+                attributes,
+            } = &item.kind
+            else {
+                continue;
+            };
+            writeln!(
+                writer,
+                "  <node id=\"{}\" label=\"{}\">\n    <attvalues>",
+                xml_escape(id),
+                xml_escape(label)
+            )?;
+            let degree = degrees.get(id.as_str()).copied().unwrap_or(0);
+            writeln!(writer, "      <attvalue for=\"0\" value=\"{degree}\"/>")?;
+            for (name, value) in attributes {
+                let idx = attribute_names.get_index_of(name.as_str()).unwrap_or(0);
+                writeln!(
+                    writer,
+                    "      <attvalue for=\"{idx}\" value=\"{}\"/>",
+                    xml_escape(value)
+                )?;
+            }
+            writeln!(writer, "    </attvalues>\n  </node>")?;
+        }
+        writeln!(writer, "</nodes>")?;
+
+        writeln!(writer, "<edges>")?;
+        let edges = self.items.iter().filter_map(|item| match &item.kind {
+            GexfItemKind::Edge { from, to, label } => Some((from, to, label)),
+            _ => None,
+        });
+        for (idx, (from, to, label)) in edges.enumerate() {
+            writeln!(
+                writer,
+                "  <edge id=\"{idx}\" source=\"{}\" target=\"{}\" label=\"{}\"/>",
+                xml_escape(from),
+                xml_escape(to),
+                xml_escape(label)
+            )?;
+        }
+        writeln!(writer, "</edges>")?;
+        write!(writer, "</graph>\n</gexf>")?;
+        Ok(())
+    }
+
+    fn source_extension(&self) -> &'static str {
+        "gexf"
+    }
+}
+
+/// Groups are written as a `group` attribute and classes as a space-separated `classes` attribute
+impl From<&Diagram> for GexfDiagram {
+    fn from(diagram: &Diagram) -> Self {
+        let mut gexf = GexfDiagram::new(&diagram.title);
+        gexf.extend(diagram.nodes.iter().map(|node| {
+            let item = GexfItem::node(&node.id, &node.label)
+                .with_attribute("classes", node.classes.join(" "));
+            match diagram.group_of(&node.id) {
+                Some(group) => item.with_attribute("group", &group.title),
+                None => item,
+            }
+        }));
+        gexf.extend(
+            diagram
+                .edges
+                .iter()
+                .map(|edge| GexfItem::edge(&edge.from, &edge.to, &edge.label)),
+        );
+        gexf
+    }
+}
@@ -0,0 +1,33 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum ImageFormat {
+    #[display("svg")]
+    Svg,
+    #[display("png")]
+    Png,
+}
+
+/// Paths of the external tools used to convert diagrams to images.
+///
+/// The defaults are read from the environment (`JAVA`, `PLANTUML_JAR`, `MMDC`, `DOT`, `D2`),
+/// falling back to the executable name, which is then looked up in `PATH`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageTools {
+    pub java: String,
+    pub plantuml_jar: String,
+    pub mmdc: String,
+    pub dot: String,
+    pub d2: String,
+}
+
+impl Default for ImageTools {
+    fn default() -> Self {
+        let var = |key: &str, default: &str| std::env::var(key).unwrap_or(default.to_string());
+        Self {
+            java: var("JAVA", "java"),
+            plantuml_jar: var("PLANTUML_JAR", "plantuml.jar"),
+            mmdc: var("MMDC", "mmdc"),
+            dot: var("DOT", "dot"),
+            d2: var("D2", "d2"),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::{
+    io::Write,
+    ops::{Deref, DerefMut},
+};
+
+use crate::{
+    Diagram, RenderResult, Renderer,
+    cytoscape::{CytoscapeDiagram, json_string},
+};
+
+/// Diagram as a single JSON object in the cytoscape.js format, for web visualizations and notebooks.
+///
+/// The object contains the `elements`, `style` and `layout` options of cytoscape.js,
+/// so it can be passed to `cytoscape({container, ...json})` directly.
+/// Every element has its classes and, if the stylesheet defines one, its color in `data.color`.
+///
+/// Items are added the same way as for a [`CytoscapeDiagram`], which this dereferences to.
+#[derive(Clone, Debug)]
+pub struct JsonDiagram(CytoscapeDiagram);
+
+impl JsonDiagram {
+    pub fn new(title: impl ToString) -> Self {
+        Self(CytoscapeDiagram::new(title))
+    }
+}
+
+impl Deref for JsonDiagram {
+    type Target = CytoscapeDiagram;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for JsonDiagram {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<CytoscapeDiagram> for JsonDiagram {
+    fn from(diagram: CytoscapeDiagram) -> Self {
+        Self(diagram)
+    }
+}
+
+impl From<&Diagram> for JsonDiagram {
+    fn from(diagram: &Diagram) -> Self {
+        Self(CytoscapeDiagram::from(diagram))
+    }
+}
+
+impl Renderer for JsonDiagram {
+    fn render_to_writer(&self, writer: &mut impl Write) -> RenderResult<()> {
+        writeln!(writer, "{{")?;
+        writeln!(writer, "\"title\": {},", json_string(self.0.title()))?;
+        write!(writer, "\"elements\": ")?;
+        self.0.write_elements(writer)?;
+        write!(writer, ",\n\"style\": ")?;
+        self.0.write_style(writer)?;
+        write!(writer, ",\n\"layout\": ")?;
+        self.0.write_layout(writer)?;
+        write!(writer, "\n}}")?;
+        Ok(())
+    }
+
+    fn source_extension(&self) -> &'static str {
+        "json"
+    }
+}
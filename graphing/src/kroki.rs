@@ -0,0 +1,74 @@
+use std::{io::Write, path::Path};
+
+use crate::{
+    ImageFormat, RenderError, RenderResult, Renderer,
+    error::{FileContext, create_file},
+};
+
+const DEFAULT_URL: &str = "https://kroki.io";
+
+/// Client for a [Kroki](https://kroki.io) server, which renders diagrams to images without local tools.
+///
+/// The server is read from `KROKI_URL` by default, falling back to the public instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KrokiClient {
+    url: String,
+}
+
+impl Default for KrokiClient {
+    fn default() -> Self {
+        Self::new(std::env::var("KROKI_URL").unwrap_or(DEFAULT_URL.to_string()))
+    }
+}
+
+impl KrokiClient {
+    pub fn new(url: impl ToString) -> Self {
+        Self {
+            url: url.to_string().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Kroki diagram type, based on the source format of the diagram
+    fn diagram_type(diagram: &impl Renderer) -> Option<&'static str> {
+        match diagram.source_extension() {
+            "puml" => Some("plantuml"),
+            "mmd" => Some("mermaid"),
+            "dot" => Some("graphviz"),
+            "d2" => Some("d2"),
+            _ => None,
+        }
+    }
+
+    /// Sends the diagram to the server and returns the image
+    pub fn render(&self, diagram: &impl Renderer, format: ImageFormat) -> RenderResult<Vec<u8>> {
+        let diagram_type = Self::diagram_type(diagram).ok_or(RenderError::ImageNotSupported)?;
+        let mut source = Vec::new();
+        diagram.render_source_to_writer(&mut source)?;
+
+        let url = format!("{}/{}/{}", self.url, diagram_type, format);
+        tracing::debug!("Rendering image using {}", url);
+        let to_error = |e: ureq::Error| RenderError::ToolFailed {
+            tool: url.clone(),
+            stderr: e.to_string(),
+        };
+        ureq::post(&url)
+            .header("Content-Type", "text/plain")
+            .send(&source[..])
+            .map_err(to_error)?
+            .body_mut()
+            .read_to_vec()
+            .map_err(to_error)
+    }
+
+    /// Sends the diagram to the server and writes the image to `path`
+    pub fn render_to_file(
+        &self,
+        diagram: &impl Renderer,
+        path: &str,
+        format: ImageFormat,
+    ) -> RenderResult<()> {
+        let image = self.render(diagram, format)?;
+        let path = Path::new(path);
+        create_file(path)?.write_all(&image).context("write", path)
+    }
+}
@@ -0,0 +1,35 @@
+#[cfg(any(feature = "plantuml", feature = "mermaid"))]
+use std::collections::BTreeMap;
+
+/// Position of a node as a cell in a grid, `(column, row)`.
+///
+/// None of the backends support absolute coordinates,
+/// so positions are translated to invisible edges between neighbouring cells.
+pub type GridPosition = (i32, i32);
+
+/// Nodes in the same row, from left to right, for every row from top to bottom
+#[cfg(any(feature = "plantuml", feature = "mermaid"))]
+pub(crate) fn grid_rows<'a>(positions: &[(&'a str, GridPosition)]) -> Vec<Vec<&'a str>> {
+    grid_lines(positions.iter().map(|(id, (x, y))| (*y, *x, *id)))
+}
+
+/// Nodes in the same column, from top to bottom, for every column from left to right
+#[cfg(any(feature = "plantuml", feature = "mermaid"))]
+pub(crate) fn grid_columns<'a>(positions: &[(&'a str, GridPosition)]) -> Vec<Vec<&'a str>> {
+    grid_lines(positions.iter().map(|(id, (x, y))| (*x, *y, *id)))
+}
+
+#[cfg(any(feature = "plantuml", feature = "mermaid"))]
+fn grid_lines<'a>(cells: impl Iterator<Item = (i32, i32, &'a str)>) -> Vec<Vec<&'a str>> {
+    let mut lines: BTreeMap<i32, Vec<(i32, &str)>> = BTreeMap::new();
+    for (line, idx, id) in cells {
+        lines.entry(line).or_default().push((idx, id));
+    }
+    lines
+        .into_values()
+        .map(|mut line| {
+            line.sort();
+            line.into_iter().map(|(_, id)| id).collect()
+        })
+        .collect()
+}
@@ -1,23 +1,59 @@
+#[cfg(feature = "cosmo")]
+pub mod cosmo;
+#[cfg(feature = "cypher")]
+pub mod cypher;
+#[cfg(feature = "cytoscape")]
+pub mod cytoscape;
+#[cfg(feature = "d2")]
+pub mod d2;
+#[cfg(feature = "dot")]
+pub mod dot;
+#[cfg(feature = "gexf")]
+pub mod gexf;
+#[cfg(feature = "json")]
+pub mod json;
 #[cfg(feature = "mermaid")]
 pub mod mermaid;
 #[cfg(feature = "plantuml")]
 pub mod plantuml;
+#[cfg(feature = "tikz")]
+pub mod tikz;
 
 mod color;
+#[cfg(any(feature = "plantuml", feature = "mermaid"))]
 use std::io::Write;
 
 pub use color::*;
 
+mod csv;
+pub use csv::*;
+
+mod diagram;
+pub use diagram::*;
+
 mod error;
 pub use error::*;
 
+mod image;
+pub use image::*;
+
+mod layout;
+pub use layout::GridPosition;
+
+#[cfg(feature = "kroki")]
+mod kroki;
+#[cfg(feature = "kroki")]
+pub use kroki::*;
+
 mod renderer;
 pub use renderer::*;
 
+#[cfg(any(feature = "plantuml", feature = "mermaid"))]
 pub(crate) trait CssProperty {
     fn write(&self, writer: &mut impl Write) -> RenderResult<()>;
 }
 
+#[cfg(any(feature = "plantuml", feature = "mermaid"))]
 impl<T> CssProperty for T
 where
     T: std::fmt::Display,
@@ -3,11 +3,15 @@ use std::{
     sync::atomic::{AtomicUsize, Ordering},
 };
 
-use crate::RenderResult;
+use crate::{GridPosition, RenderResult, Shape};
 
-use super::{MermaidStyleSheet, sanitise_label, theme::EdgeType};
+use super::{
+    MermaidChartDirection, MermaidStyleSheet, escape_label, sanitise_label,
+    theme::{Arrowhead, EdgeType, ElementStyle},
+};
 
 static EDGE_CTR: AtomicUsize = AtomicUsize::new(0);
+static CLASS_CTR: AtomicUsize = AtomicUsize::new(0);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
 pub enum ItemShape {
@@ -21,64 +25,189 @@ pub enum ItemShape {
     Braces,
     #[display("card")]
     Card,
+    #[display("hex")]
+    Hexagon,
+    /// Horizontal cylinder
+    #[display("h-cyl")]
+    Cylinder,
+    /// Vertical cylinder
+    #[display("cyl")]
+    Database,
+    #[display("doc")]
+    Document,
+}
+
+impl From<Shape> for ItemShape {
+    fn from(shape: Shape) -> Self {
+        match shape {
+            Shape::Rounded => ItemShape::Rounded,
+            Shape::Circle => ItemShape::Circle,
+            Shape::Card => ItemShape::Card,
+            Shape::Hexagon => ItemShape::Hexagon,
+            Shape::Cylinder => ItemShape::Cylinder,
+            Shape::Database => ItemShape::Database,
+            Shape::Note => ItemShape::Document,
+        }
+    }
 }
 
+#[derive(Clone)]
 pub struct MermaidNode {
     label: String,
     shape: ItemShape,
+    tooltip: Option<String>,
 }
 
+#[derive(Clone)]
 pub struct MermaidEdge {
     from: String,
     to: String,
     label: String,
     line_type: EdgeType,
+    head: Arrowhead,
+    bidirectional: bool,
 }
 
+impl MermaidEdge {
+    /// Writes the link between the two nodes, e.g. `-- "label" -->`
+    fn write_link(&self, writer: &mut impl Write, raw: bool) -> RenderResult<()> {
+        let head = self.head.head_str();
+        let tail = match self.bidirectional {
+            true => self.head.tail_str(),
+            false => "",
+        };
+        // open links need an extra line character instead of the head
+        let (solid_head, thick_head) = match self.head {
+            Arrowhead::None => ("-", "="),
+            _ => (head, head),
+        };
+        match self.label.as_str() {
+            // no label (line length depends on number of dashes)
+            "" => match self.line_type {
+                EdgeType::Solid => write!(writer, "{tail}--{solid_head}"),
+                EdgeType::Dotted => write!(writer, "{tail}-.-{head}"),
+                EdgeType::Thick => write!(writer, "{tail}=={thick_head}"),
+                EdgeType::Invisible => write!(writer, "~~~"),
+            },
+            // with label
+            lbl => {
+                let lbl = label_text(lbl, raw);
+                match self.line_type {
+                    EdgeType::Solid => write!(writer, "{tail}-- \"{lbl}\" --{solid_head}"),
+                    EdgeType::Dotted => write!(writer, "{tail}-. \"{lbl}\" .-{head}"),
+                    EdgeType::Thick => write!(writer, "{tail}== \"{lbl}\" =={thick_head}"),
+                    EdgeType::Invisible => write!(writer, "~~~"),
+                }
+            }
+        }?;
+        Ok(())
+    }
+}
+
+/// Group of items, rendered as a `subgraph ... end` block.
+#[derive(Clone)]
+pub struct MermaidSubgraph {
+    title: String,
+    direction: Option<MermaidChartDirection>,
+    items: Vec<MermaidItem>,
+}
+
+impl MermaidSubgraph {
+    pub fn new(title: impl ToString) -> Self {
+        Self {
+            title: title.to_string(),
+            direction: None,
+            items: Vec::new(),
+        }
+    }
+
+    /// Overrides the direction of the chart inside this subgraph
+    pub fn set_direction(&mut self, direction: MermaidChartDirection) {
+        self.direction = Some(direction);
+    }
+
+    pub fn push(&mut self, item: MermaidItem) {
+        self.items.push(item);
+    }
+
+    pub fn extend(&mut self, items: impl IntoIterator<Item = MermaidItem>) {
+        self.items.extend(items);
+    }
+}
+
+#[derive(Clone)]
 pub enum MermaidItemKind {
     Node(MermaidNode),
     Edge(MermaidEdge),
+    Subgraph(MermaidSubgraph),
+}
+
+/// Labels are escaped, unless the item was marked as raw
+fn label_text(label: &str, raw: bool) -> std::borrow::Cow<'_, str> {
+    match raw {
+        true => label.into(),
+        false => escape_label(label).into(),
+    }
 }
 
 impl MermaidItemKind {
-    pub fn write(&self, writer: &mut impl Write, id: &str) -> RenderResult<()> {
+    /// Writes this item, `raw` labels are written without escaping them
+    pub fn write(&self, writer: &mut impl Write, id: &str, raw: bool) -> RenderResult<()> {
         match self {
-            // {id}@{{ shape: {shape}, label: \"<span>{label}</span>\" }};
+            // {id}@{{ shape: {shape}, label: \"<span title='{tooltip}'>{label}</span>\" }};
             MermaidItemKind::Node(node) => {
+                let title = match &node.tooltip {
+                    Some(tooltip) => format!(" title='{}'", tooltip),
+                    None => String::new(),
+                };
                 write!(
                     writer,
-                    "{}@{{ shape: {}, label: \"<span>{}</span>\" }};",
-                    id, node.shape, node.label
+                    "{}@{{ shape: {}, label: \"<span{}>{}</span>\" }};",
+                    id,
+                    node.shape,
+                    title,
+                    label_text(&node.label, raw)
                 )?;
             }
-            // {from} {id}@{line_type} {to};
+            // {from} {id}@{link} {to};
             MermaidItemKind::Edge(edge) => {
                 write!(writer, "{} {}@", edge.from, id)?;
-                match edge.label.as_str() {
-                    // no label (line length depends on number of dashes)
-                    "" => match edge.line_type {
-                        EdgeType::Solid => write!(writer, "-->"),
-                        EdgeType::Dotted => write!(writer, "-.->"),
-                        EdgeType::Thick => write!(writer, "==>"),
-                    },
-                    // with label
-                    lbl => match edge.line_type {
-                        EdgeType::Solid => write!(writer, "-- {} -->", lbl),
-                        EdgeType::Dotted => write!(writer, "-. {} .->", lbl),
-                        EdgeType::Thick => write!(writer, "== {} ==>", lbl),
-                    },
-                }?;
+                edge.write_link(writer, raw)?;
                 write!(writer, " {};", edge.to)?;
             }
+            // subgraph {id} ["{title}"]\n{items}\nend
+            MermaidItemKind::Subgraph(subgraph) => {
+                writeln!(
+                    writer,
+                    "subgraph {} [\"{}\"]",
+                    id,
+                    label_text(&subgraph.title, raw)
+                )?;
+                if let Some(dir) = subgraph.direction {
+                    writeln!(writer, "direction {}", dir)?;
+                }
+                for item in &subgraph.items {
+                    item.write(writer)?;
+                }
+                write!(writer, "end")?;
+            }
         }
         Ok(())
     }
 }
 
+#[derive(Clone)]
 pub struct MermaidItem {
     id: String,
     kind: MermaidItemKind,
     classes: Vec<String>,
+    /// url that is opened when clicking on the item
+    link: Option<String>,
+    /// Inline style, turned into a generated class when pushed to a diagram
+    style: Option<ElementStyle>,
+    position: Option<GridPosition>,
+    /// Write the label without escaping it, see [`MermaidItem::raw`]
+    raw: bool,
 }
 
 impl MermaidItem {
@@ -94,10 +223,16 @@ impl MermaidItem {
             kind: MermaidItemKind::Edge(MermaidEdge {
                 from: from.to_string(),
                 to: to.to_string(),
-                label: sanitise_label(label),
+                label: label.to_string(),
                 line_type,
+                head: Arrowhead::default(),
+                bidirectional: false,
             }),
             classes: Vec::new(),
+            link: None,
+            style: None,
+            position: None,
+            raw: false,
         }
     }
 
@@ -105,10 +240,28 @@ impl MermaidItem {
         Self {
             id: id.to_string(),
             kind: MermaidItemKind::Node(MermaidNode {
-                label: sanitise_label(label),
+                label: label.to_string(),
                 shape,
+                tooltip: None,
             }),
             classes: Vec::new(),
+            link: None,
+            style: None,
+            position: None,
+            raw: false,
+        }
+    }
+
+    /// Groups `subgraph` under `id`, edges can point to the subgraph as a whole using this id.
+    pub fn subgraph(id: impl ToString, subgraph: MermaidSubgraph) -> Self {
+        Self {
+            id: id.to_string(),
+            kind: MermaidItemKind::Subgraph(subgraph),
+            classes: Vec::new(),
+            link: None,
+            style: None,
+            position: None,
+            raw: false,
         }
     }
 
@@ -117,26 +270,178 @@ impl MermaidItem {
         self
     }
 
+    /// Sets the arrowhead of an edge, does nothing for other items
+    pub fn with_arrowhead(mut self, arrowhead: Arrowhead) -> Self {
+        if let MermaidItemKind::Edge(edge) = &mut self.kind {
+            edge.head = arrowhead;
+        }
+        self
+    }
+
+    /// Draws the arrowhead on both ends of an edge, does nothing for other items
+    pub fn bidirectional(mut self) -> Self {
+        if let MermaidItemKind::Edge(edge) = &mut self.kind {
+            edge.bidirectional = true;
+        }
+        self
+    }
+
+    /// Writes the label of this item as is, so it can contain HTML such as `<b>bold</b>`.
+    ///
+    /// Labels are escaped by default, use [`escape_label`] for the parts that should not be interpreted.
+    pub fn raw(mut self) -> Self {
+        self.raw = true;
+        self
+    }
+
+    /// Styles only this item, without having to add a class to the stylesheet
+    pub fn with_style(mut self, style: ElementStyle) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    /// Returns a class for the inline style of this item and its children, and adds the class to the items.
+    pub(crate) fn class_defs(&mut self) -> Vec<(String, ElementStyle)> {
+        let mut defs = match &mut self.kind {
+            MermaidItemKind::Subgraph(subgraph) => subgraph
+                .items
+                .iter_mut()
+                .flat_map(MermaidItem::class_defs)
+                .collect(),
+            _ => Vec::new(),
+        };
+        if let Some(style) = self.style.take() {
+            let class_name = format!("gen-class-{}", CLASS_CTR.fetch_add(1, Ordering::Relaxed));
+            self.classes.push(class_name.clone());
+            defs.push((class_name, style));
+        }
+        defs
+    }
+
+    /// Opens `url` when the node is clicked. Mermaid does not support this on edges.
+    pub fn with_link(mut self, url: impl ToString) -> Self {
+        self.link = Some(url.to_string());
+        self
+    }
+
+    /// Shows `text` when hovering over the node, does nothing for other items.
+    ///
+    /// Newlines are kept, so this can hold more than fits in a label.
+    pub fn with_tooltip(mut self, text: impl ToString) -> Self {
+        if let MermaidItemKind::Node(node) = &mut self.kind {
+            node.tooltip = Some(escape_tooltip(&text.to_string()));
+        }
+        self
+    }
+
+    /// Places this node in a cell of a grid, relative to the other positioned nodes.
+    ///
+    /// Rows are placed next to each other in a subgraph, so this is ignored for nodes
+    /// that are inside a subgraph already. Also ignored for edges.
+    pub fn with_position(mut self, column: i32, row: i32) -> Self {
+        self.position = Some((column, row));
+        self
+    }
+
     pub(crate) fn id(&self) -> &str {
         &self.id
     }
 
     pub(crate) fn find_nonexistant_class(&self, sheet: &MermaidStyleSheet) -> Option<&str> {
-        self.classes.iter().find_map(|class| {
+        let own = self.classes.iter().find_map(|class| {
             if !sheet.contains_key(class) {
                 Some(class.as_str())
             } else {
                 None
             }
-        })
+        });
+        match &self.kind {
+            MermaidItemKind::Subgraph(subgraph) => own.or_else(|| {
+                subgraph
+                    .items
+                    .iter()
+                    .find_map(|item| item.find_nonexistant_class(sheet))
+            }),
+            _ => own,
+        }
+    }
+
+    /// Returns true if this item or any of its children has a link
+    pub(crate) fn has_link(&self) -> bool {
+        match &self.kind {
+            MermaidItemKind::Subgraph(subgraph) => {
+                self.link.is_some() || subgraph.items.iter().any(MermaidItem::has_link)
+            }
+            _ => self.link.is_some(),
+        }
+    }
+
+    /// Adds the positions of this item and its children to `positions`
+    pub(crate) fn collect_positions<'a>(&'a self, positions: &mut Vec<(&'a str, GridPosition)>) {
+        match &self.kind {
+            MermaidItemKind::Edge(_) => return,
+            MermaidItemKind::Subgraph(subgraph) => {
+                for item in &subgraph.items {
+                    item.collect_positions(positions);
+                }
+            }
+            MermaidItemKind::Node(_) => (),
+        }
+        if let Some(pos) = self.position {
+            positions.push((&self.id, pos));
+        }
+    }
+
+    /// Adds the classes of this item and its children to `classes`
+    pub(crate) fn collect_classes<'a>(&'a self, classes: &mut Vec<&'a str>) {
+        classes.extend(self.classes.iter().map(String::as_str));
+        if let MermaidItemKind::Subgraph(subgraph) = &self.kind {
+            for item in &subgraph.items {
+                item.collect_classes(classes);
+            }
+        }
     }
 
     pub(crate) fn write(&self, writer: &mut impl Write) -> RenderResult<()> {
-        self.kind.write(writer, &self.id)?;
+        self.kind.write(writer, &self.id, self.raw)?;
         let _ = writer.write(b"\n")?;
         for class in &self.classes {
             writeln!(writer, "class {} {}", self.id, class)?;
         }
+        match (&self.link, &self.kind) {
+            (Some(_), MermaidItemKind::Edge(_)) => {
+                tracing::warn!("Links on edges are not supported (found in {})", self.id);
+            }
+            // click {id} href "{url}" "{tooltip}"
+            (
+                Some(url),
+                MermaidItemKind::Node(MermaidNode {
+                    tooltip: Some(tooltip),
+                    ..
+                }),
+            ) => writeln!(
+                writer,
+                "click {} href \"{}\" \"{}\"",
+                self.id,
+                sanitise_label(url),
+                tooltip
+            )?,
+            // click {id} href "{url}"
+            (Some(url), _) => {
+                writeln!(writer, "click {} href \"{}\"", self.id, sanitise_label(url))?
+            }
+            (None, _) => (),
+        }
         Ok(())
     }
 }
+
+/// Tooltips end up in an HTML attribute inside a quoted label, so quotes and newlines are written as entities
+fn escape_tooltip(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', "&#10;")
+}
@@ -1,9 +1,14 @@
-use std::{collections::HashMap, io::Write, ops::Deref};
+use std::{io::Write, ops::Deref};
 
-use item::MermaidItem;
-use theme::ElementStyle;
+use indexmap::IndexMap;
 
-use crate::Renderer;
+use item::{MermaidItem, MermaidSubgraph};
+use theme::{EdgeType, ElementStyle};
+
+use crate::{
+    Diagram, DiagramNode, ImageFormat, ImageTools, Renderer,
+    layout::{grid_columns, grid_rows},
+};
 
 pub mod item;
 pub mod theme;
@@ -12,15 +17,82 @@ fn sanitise_label(label: impl ToString) -> String {
     label.to_string().replace(r#"""#, r#"\""#)
 }
 
-#[derive(Default, Debug)]
+/// Escapes `label` so it is shown as is, using mermaid's entity codes.
+///
+/// Newlines become line breaks. This is applied to all labels, unless [`MermaidItem::raw`] is used.
+pub fn escape_label(label: &str) -> String {
+    let mut out = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '#' => out.push_str("#35;"),
+            '"' => out.push_str("#quot;"),
+            '&' => out.push_str("#amp;"),
+            '<' => out.push_str("#lt;"),
+            '>' => out.push_str("#gt;"),
+            '\n' => out.push_str("<br>"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Nested options of the front matter, written as YAML
+#[derive(Default)]
+struct ConfigTree(IndexMap<String, ConfigEntry>);
+
+enum ConfigEntry {
+    Value(String),
+    Table(ConfigTree),
+}
+
+impl ConfigTree {
+    /// Sets `key`, where nested keys are separated by dots, replacing any previous value
+    fn insert(&mut self, key: &str, value: impl ToString) {
+        match key.split_once('.') {
+            None => {
+                self.0
+                    .insert(key.to_string(), ConfigEntry::Value(value.to_string()));
+            }
+            Some((head, rest)) => {
+                let entry = self
+                    .0
+                    .entry(head.to_string())
+                    .or_insert_with(|| ConfigEntry::Table(ConfigTree::default()));
+                if let ConfigEntry::Value(_) = entry {
+                    *entry = ConfigEntry::Table(ConfigTree::default());
+                }
+                if let ConfigEntry::Table(table) = entry {
+                    table.insert(rest, value);
+                }
+            }
+        }
+    }
+
+    fn write(&self, writer: &mut impl Write, depth: usize) -> crate::RenderResult<()> {
+        let indent = depth * 2;
+        for (key, entry) in &self.0 {
+            match entry {
+                ConfigEntry::Value(value) => writeln!(writer, "{:indent$}{key}: {value}", "")?,
+                ConfigEntry::Table(table) => {
+                    writeln!(writer, "{:indent$}{key}:", "")?;
+                    table.write(writer, depth + 1)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Classes are written in the order they were added, so rendering the same diagram twice gives the same output
+#[derive(Default, Debug, Clone)]
 pub struct MermaidStyleSheet {
-    map: HashMap<String, ElementStyle>,
+    map: IndexMap<String, ElementStyle>,
 }
 
 impl MermaidStyleSheet {
     pub fn new() -> Self {
         Self {
-            map: HashMap::new(),
+            map: IndexMap::new(),
         }
     }
 
@@ -38,7 +110,7 @@ impl MermaidStyleSheet {
 
 impl FromIterator<(String, ElementStyle)> for MermaidStyleSheet {
     fn from_iter<T: IntoIterator<Item = (String, ElementStyle)>>(iter: T) -> Self {
-        let mut map = HashMap::new();
+        let mut map = IndexMap::new();
         for (class, style) in iter {
             map.insert(class, style);
         }
@@ -47,7 +119,7 @@ impl FromIterator<(String, ElementStyle)> for MermaidStyleSheet {
 }
 
 impl Deref for MermaidStyleSheet {
-    type Target = HashMap<String, ElementStyle>;
+    type Target = IndexMap<String, ElementStyle>;
 
     fn deref(&self) -> &Self::Target {
         &self.map
@@ -66,11 +138,43 @@ pub enum MermaidChartDirection {
     RightLeft,
 }
 
+/// Strategy ELK uses to place nodes within a layer
+#[derive(derive_more::Display, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ElkNodePlacement {
+    #[default]
+    #[display("BRANDES_KOEPF")]
+    BrandesKoepf,
+    #[display("LINEAR_SEGMENTS")]
+    LinearSegments,
+    #[display("NETWORK_SIMPLEX")]
+    NetworkSimplex,
+    #[display("SIMPLE")]
+    Simple,
+}
+
+/// Options for the ELK layout engine, which handles cycles and diamonds a lot better than dagre
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ElkOptions {
+    /// Draws edges that share a node as a single line where possible
+    pub merge_edges: bool,
+    pub node_placement: ElkNodePlacement,
+}
+
+#[derive(Clone)]
 pub struct MermaidDiagram {
     style: MermaidStyleSheet,
     items: Vec<MermaidItem>,
     title: String,
     direction: MermaidChartDirection,
+    legend: bool,
+    /// Nodes that are placed next to each other, see [`MermaidDiagram::same_rank`]
+    ranks: Vec<Vec<String>>,
+    /// Layout using ELK instead of dagre
+    elk: Option<ElkOptions>,
+    /// Spacing between nodes and between ranks in pixels
+    spacing: Option<(usize, usize)>,
+    /// Options for the front matter, see [`MermaidDiagram::set_config`]
+    config: IndexMap<String, String>,
 }
 
 impl MermaidDiagram {
@@ -80,38 +184,194 @@ impl MermaidDiagram {
             items: Vec::new(),
             title: title.to_string(),
             direction: MermaidChartDirection::TopBottom,
+            legend: false,
+            ranks: Vec::new(),
+            elk: None,
+            spacing: None,
+            config: IndexMap::new(),
         }
     }
 
+    pub fn set_title(&mut self, title: impl ToString) {
+        self.title = title.to_string();
+    }
+
     pub fn set_direction(&mut self, direction: MermaidChartDirection) {
         self.direction = direction;
     }
 
+    /// Lays out the diagram using ELK, requires a mermaid version that ships with ELK
+    pub fn use_elk(&mut self, options: ElkOptions) {
+        self.elk = Some(options);
+    }
+
+    /// Sets the spacing between nodes of the same rank and between ranks, in pixels
+    pub fn set_spacing(&mut self, node_spacing: usize, rank_spacing: usize) {
+        self.spacing = Some((node_spacing, rank_spacing));
+    }
+
+    /// Sets an option in the `config` section of the front matter that is not modeled by this diagram,
+    /// e.g. `set_config("flowchart.curve", "linear")`.
+    ///
+    /// Nested keys are separated by dots. The value is written as is, so it is parsed as YAML.
+    /// Options set here take precedence over the ones generated from the diagram.
+    pub fn set_config(&mut self, key: impl ToString, value: impl ToString) {
+        self.config.insert(key.to_string(), value.to_string());
+    }
+
+    /// Adds a legend with a node for every class used in the diagram.
+    ///
+    /// Generated classes are left out, as they have no meaningful name.
+    pub fn add_legend(&mut self) {
+        self.legend = true;
+    }
+
     pub fn set_style_sheet(&mut self, style: MermaidStyleSheet) {
         self.style = style;
     }
 
-    pub fn push(&mut self, item: MermaidItem) {
+    /// Places the nodes with `ids` next to each other, perpendicular to the direction of the chart.
+    ///
+    /// The nodes are moved to an invisible subgraph, so this does not work for nodes in a subgraph.
+    /// Mermaid ignores the direction of subgraphs with nodes that are linked to the outside,
+    /// the nodes are then only kept close together.
+    pub fn same_rank(&mut self, ids: impl IntoIterator<Item = impl ToString>) {
+        let rank = ids.into_iter().map(|id| id.to_string()).collect();
+        self.ranks.push(rank);
+    }
+
+    pub fn push(&mut self, mut item: MermaidItem) {
+        for (class, style) in item.class_defs() {
+            self.style.map.insert(class, style);
+        }
         self.items.push(item);
     }
 
     pub fn extend(&mut self, items: impl IntoIterator<Item = MermaidItem>) {
-        self.items.extend(items);
+        for item in items {
+            self.push(item);
+        }
+    }
+}
+
+impl MermaidDiagram {
+    /// Writes the `config` section of the front matter, this is the same as an `%%{init: ...}%%` directive
+    fn write_config(&self, writer: &mut impl Write) -> crate::RenderResult<()> {
+        let mut config = ConfigTree::default();
+        // click handlers are disabled with the default security level
+        if self.items.iter().any(MermaidItem::has_link) {
+            config.insert("securityLevel", "loose");
+        }
+        if let Some(elk) = &self.elk {
+            config.insert("layout", "elk");
+            config.insert("elk.mergeEdges", elk.merge_edges);
+            config.insert("elk.nodePlacementStrategy", elk.node_placement);
+            // older versions of mermaid select the renderer per chart type
+            config.insert("flowchart.defaultRenderer", "elk");
+        }
+        if let Some((node_spacing, rank_spacing)) = self.spacing {
+            config.insert("flowchart.nodeSpacing", node_spacing);
+            config.insert("flowchart.rankSpacing", rank_spacing);
+        }
+        for (key, value) in &self.config {
+            config.insert(key, value);
+        }
+
+        if config.0.is_empty() {
+            return Ok(());
+        }
+        writeln!(writer, "config:")?;
+        config.write(writer, 1)
+    }
+
+    /// Writes the subgraphs and invisible links for [`MermaidDiagram::same_rank`] and [`MermaidItem::with_position`]
+    fn write_layout_hints(&self, writer: &mut impl Write) -> crate::RenderResult<()> {
+        let mut positions = Vec::new();
+        for item in &self.items {
+            item.collect_positions(&mut positions);
+        }
+
+        let rank_direction = match self.direction {
+            MermaidChartDirection::TopBottom | MermaidChartDirection::BottomTop => {
+                MermaidChartDirection::LeftRight
+            }
+            MermaidChartDirection::LeftRight | MermaidChartDirection::RightLeft => {
+                MermaidChartDirection::TopBottom
+            }
+        };
+        let ranks = self
+            .ranks
+            .iter()
+            .map(|rank| rank.iter().map(String::as_str).collect::<Vec<_>>())
+            .chain(grid_rows(&positions))
+            .filter(|rank| rank.len() > 1);
+        for (i, rank) in ranks.enumerate() {
+            writeln!(writer, "subgraph rank_hint_{i} [\" \"]")?;
+            writeln!(writer, "direction {rank_direction}")?;
+            for id in rank {
+                writeln!(writer, "{id}")?;
+            }
+            writeln!(writer, "end")?;
+            writeln!(writer, "style rank_hint_{i} fill:none,stroke:none")?;
+        }
+        for column in grid_columns(&positions) {
+            for pair in column.windows(2) {
+                writeln!(writer, "{} ~~~ {}", pair[0], pair[1])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a subgraph with a node styled by each used class
+    fn write_legend(&self, writer: &mut impl Write) -> crate::RenderResult<()> {
+        let mut used = Vec::new();
+        for item in &self.items {
+            item.collect_classes(&mut used);
+        }
+        used.sort();
+        used.dedup();
+        used.retain(|c| {
+            !c.starts_with("gen-class-") && self.style.get(*c).is_some_and(|s| !s.is_empty())
+        });
+        if used.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(writer, "subgraph legend [\"Legend\"]")?;
+        for (i, class) in used.iter().enumerate() {
+            writeln!(
+                writer,
+                "legend{i}@{{ shape: rounded, label: \"{}\" }};\nclass legend{i} {class}",
+                sanitise_label(class)
+            )?;
+        }
+        writeln!(writer, "end")?;
+        Ok(())
     }
 }
 
 impl Renderer for MermaidDiagram {
     fn render_to_writer(&self, writer: &mut impl Write) -> crate::RenderResult<()> {
+        writeln!(writer, "```mermaid")?;
+        self.render_source_to_writer(writer)?;
+        write!(writer, "\n```")?;
+        Ok(())
+    }
+
+    fn source_extension(&self) -> &'static str {
+        "mmd"
+    }
+
+    /// Writes the chart without the surrounding markdown code block
+    fn render_source_to_writer(&self, writer: &mut impl Write) -> crate::RenderResult<()> {
         writeln!(
             writer,
-            "```mermaid\n\
-            ---\n\
-            title: \"{}\"\n\
-            ---\n\
-            flowchart {}",
+            "---\n\
+            title: \"{}\"",
             sanitise_label(&self.title),
-            self.direction
         )?;
+        self.write_config(writer)?;
+        writeln!(writer, "---\nflowchart {}", self.direction)?;
 
         // write classes
         for (class_name, style_def) in self.style.iter() {
@@ -130,8 +390,94 @@ impl Renderer for MermaidDiagram {
             item.write(writer)?;
             let _ = writer.write(b"\n")?;
         }
+        self.write_layout_hints(writer)?;
 
-        write!(writer, "\n```")?;
+        if self.legend {
+            self.write_legend(writer)?;
+        }
         Ok(())
     }
+
+    fn image_command(
+        &self,
+        tools: &ImageTools,
+        source: &std::path::Path,
+        output: &std::path::Path,
+        format: ImageFormat,
+    ) -> crate::RenderResult<Option<std::process::Command>> {
+        let mut command = std::process::Command::new(&tools.mmdc);
+        command
+            .arg("-i")
+            .arg(source)
+            .arg("-o")
+            .arg(output)
+            .args(["-e", &format.to_string()]);
+        Ok(Some(command))
+    }
+}
+
+impl From<&Diagram> for MermaidDiagram {
+    fn from(diagram: &Diagram) -> Self {
+        let node = |node: &DiagramNode| {
+            let item = node.classes.iter().fold(
+                MermaidItem::node(&node.id, &node.label, node.shape.into()),
+                |item, class| item.add_class(class),
+            );
+            let item = match node.position {
+                Some((column, row)) => item.with_position(column, row),
+                None => item,
+            };
+            match &node.tooltip {
+                Some(tooltip) => item.with_tooltip(tooltip),
+                None => item,
+            }
+        };
+
+        let mut mmd = MermaidDiagram::new(&diagram.title);
+        mmd.extend(diagram.ungrouped_nodes().map(node));
+        mmd.extend(diagram.groups.iter().map(|group| {
+            let mut subgraph = MermaidSubgraph::new(&group.title);
+            subgraph.extend(diagram.group_nodes(group).map(node));
+            MermaidItem::subgraph(&group.id, subgraph)
+        }));
+        mmd.extend(diagram.edges.iter().map(|edge| {
+            edge.classes.iter().fold(
+                MermaidItem::edge(&edge.from, &edge.to, &edge.label, EdgeType::Solid),
+                |item, class| item.add_class(class),
+            )
+        }));
+        for rank in &diagram.ranks {
+            mmd.same_rank(rank);
+        }
+        mmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use item::ItemShape;
+
+    fn written(item: &MermaidItem) -> String {
+        let mut buf = Vec::new();
+        item.write(&mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("plain"), "plain");
+        assert_eq!(escape_label(r#"say "hi""#), "say #quot;hi#quot;");
+        assert_eq!(escape_label("a<b>"), "a#lt;b#gt;");
+        assert_eq!(escape_label("**bold**"), "**bold**");
+        assert_eq!(escape_label("#1 & 2"), "#35;1 #amp; 2");
+        assert_eq!(escape_label("a\nb"), "a<br>b");
+    }
+
+    #[test]
+    fn test_raw_is_not_escaped() {
+        let item = MermaidItem::node("n", "<b>#1</b>", ItemShape::Rounded);
+        assert!(written(&item).contains("#lt;b#gt;#35;1#lt;/b#gt;"));
+        assert!(written(&item.raw()).contains("<b>#1</b>"));
+    }
 }
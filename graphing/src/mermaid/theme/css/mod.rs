@@ -77,6 +77,10 @@ impl ElementStyle {
         }
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.style.is_empty()
+    }
+
     pub fn padding(mut self, padding: Size) -> Self {
         self.style.padding = Some(padding);
         self
@@ -2,8 +2,41 @@ mod css;
 
 pub use css::*;
 
+#[derive(Clone, Copy)]
 pub enum EdgeType {
     Solid,
     Dotted,
     Thick,
+    /// Link that is not drawn, but still affects the layout. Labels and arrowheads are ignored.
+    Invisible,
+}
+
+/// Shape at the end of an edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Arrowhead {
+    #[default]
+    Arrow,
+    Circle,
+    Cross,
+    /// Open link without an arrowhead
+    None,
+}
+
+impl Arrowhead {
+    pub(crate) fn head_str(&self) -> &'static str {
+        match self {
+            Arrowhead::Arrow => ">",
+            Arrowhead::Circle => "o",
+            Arrowhead::Cross => "x",
+            Arrowhead::None => "",
+        }
+    }
+
+    /// Head at the start of a bidirectional edge
+    pub(crate) fn tail_str(&self) -> &'static str {
+        match self {
+            Arrowhead::Arrow => "<",
+            _ => self.head_str(),
+        }
+    }
 }
@@ -1,11 +1,16 @@
 use std::{
+    borrow::Cow,
+    collections::HashSet,
     io::Write,
     sync::atomic::{AtomicUsize, Ordering},
 };
 
-use crate::{Color, RenderResult};
+use crate::{Color, GridPosition, RenderResult, Shape};
 
-use super::theme::{CssClass, ElementCss, LineStyle};
+use super::{
+    NodeStyle, escape_label,
+    theme::{CssClass, ElementCss, LineStyle},
+};
 
 static CLASS_CTR: AtomicUsize = AtomicUsize::new(0);
 
@@ -49,6 +54,13 @@ pub enum NodeType {
     Node,
     /// Card, used for declarations
     Card,
+    Rectangle,
+    Hexagon,
+    /// Horizontal cylinder
+    Queue,
+    Database,
+    /// Rectangle with a folded corner
+    File,
 }
 
 impl NodeType {
@@ -56,6 +68,47 @@ impl NodeType {
         match self {
             NodeType::Node => "usecase",
             NodeType::Card => "card",
+            NodeType::Rectangle => "rectangle",
+            NodeType::Hexagon => "hexagon",
+            NodeType::Queue => "queue",
+            NodeType::Database => "database",
+            NodeType::File => "file",
+        }
+    }
+}
+
+impl From<Shape> for NodeType {
+    fn from(shape: Shape) -> Self {
+        match shape {
+            // plantuml circles cannot contain a label, the stylesheet rounds the card instead
+            Shape::Circle | Shape::Card => NodeType::Card,
+            Shape::Rounded => NodeType::Rectangle,
+            Shape::Hexagon => NodeType::Hexagon,
+            Shape::Cylinder => NodeType::Queue,
+            Shape::Database => NodeType::Database,
+            Shape::Note => NodeType::File,
+        }
+    }
+}
+
+/// Block used to group items
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupType {
+    /// Titled box around the items
+    #[default]
+    Package,
+    /// Titled rectangle around the items
+    Rectangle,
+    /// Keeps the items close together without drawing anything, the title is not shown
+    Together,
+}
+
+impl GroupType {
+    pub fn uml_str(&self) -> &'static str {
+        match self {
+            GroupType::Package => "package",
+            GroupType::Rectangle => "rectangle",
+            GroupType::Together => "together",
         }
     }
 }
@@ -105,12 +158,27 @@ pub enum PlantUmlItemKind {
         to: String,
         label: String,
         dir: EdgeDirection,
+        /// Note attached to the edge, has to be written directly after it
+        note: Option<(String, EdgeDirection)>,
     },
     Note {
         to: String,
         contents: String,
         dir: EdgeDirection,
     },
+    /// Note that is not attached to anything, but can be placed next to a node using a hidden edge
+    FloatingNote {
+        id: String,
+        contents: String,
+        /// Node the note is placed next to
+        anchor: Option<(String, EdgeDirection)>,
+    },
+    Group {
+        id: String,
+        title: String,
+        group_type: GroupType,
+        items: Vec<PlantUmlItem>,
+    },
 }
 
 impl Ord for PlantUmlItemKind {
@@ -128,9 +196,10 @@ impl PartialOrd for PlantUmlItemKind {
 impl PlantUmlItemKind {
     fn num(&self) -> usize {
         match self {
-            Self::Node { .. } => 0,
+            // groups contain nodes, so must be declared before edges as well
+            Self::Node { .. } | Self::Group { .. } => 0,
             Self::Edge { .. } => 1,
-            Self::Note { .. } => 2,
+            Self::Note { .. } | Self::FloatingNote { .. } => 2,
         }
     }
 }
@@ -140,6 +209,10 @@ pub struct PlantUmlItem {
     kind: PlantUmlItemKind,
     classes: Vec<String>,
     annotation: ItemAnnotation,
+    position: Option<GridPosition>,
+    tooltip: Option<String>,
+    /// Write the contents without escaping them, see [`PlantUmlItem::raw`]
+    raw: bool,
 }
 
 impl PartialOrd for PlantUmlItem {
@@ -160,6 +233,9 @@ impl PlantUmlItem {
             kind: item,
             classes: Vec::new(),
             annotation: ItemAnnotation::default(),
+            position: None,
+            tooltip: None,
+            raw: false,
         }
     }
 
@@ -171,6 +247,8 @@ impl PlantUmlItem {
             PlantUmlItemKind::Node { id, .. } => id,
             PlantUmlItemKind::Edge { from, .. } => from,
             PlantUmlItemKind::Note { to, .. } => to,
+            PlantUmlItemKind::FloatingNote { id, .. } => id,
+            PlantUmlItemKind::Group { id, .. } => id,
         }
     }
 
@@ -179,10 +257,16 @@ impl PlantUmlItem {
             PlantUmlItemKind::Node { .. } => (),
             PlantUmlItemKind::Edge { dir, .. } => *dir = new_dir,
             PlantUmlItemKind::Note { dir, .. } => *dir = new_dir,
+            PlantUmlItemKind::FloatingNote {
+                anchor: Some((_, dir)),
+                ..
+            } => *dir = new_dir,
+            PlantUmlItemKind::FloatingNote { anchor: None, .. } => (),
+            PlantUmlItemKind::Group { .. } => (),
         }
     }
 
-    fn sanitise_id(id: impl ToString) -> String {
+    pub(crate) fn sanitise_id(id: impl ToString) -> String {
         id.to_string().chars().fold(String::new(), |mut s, c| {
             match c {
                 _ if c.is_ascii_alphanumeric() => s.push(c),
@@ -212,6 +296,7 @@ impl PlantUmlItem {
             to: Self::sanitise_id(to),
             label: label.to_string(),
             dir,
+            note: None,
         })
     }
 
@@ -223,6 +308,105 @@ impl PlantUmlItem {
         })
     }
 
+    /// Creates a note that is not attached to any node, `id` can be used to connect edges to it
+    pub fn floating_note(id: impl ToString, contents: impl ToString) -> Self {
+        Self::new(PlantUmlItemKind::FloatingNote {
+            id: Self::sanitise_id(id),
+            contents: contents.to_string(),
+            anchor: None,
+        })
+    }
+
+    /// Places a floating note on the `dir` side of `node`, does nothing for other items
+    pub fn with_anchor(mut self, node: impl ToString, dir: EdgeDirection) -> Self {
+        if let PlantUmlItemKind::FloatingNote { anchor, .. } = &mut self.kind {
+            *anchor = Some((Self::sanitise_id(node), dir));
+        }
+        self
+    }
+
+    /// Attaches a note to an edge, placed on the `dir` side of it. Does nothing for other items.
+    pub fn with_edge_note(mut self, contents: impl ToString, dir: EdgeDirection) -> Self {
+        if let PlantUmlItemKind::Edge { note, .. } = &mut self.kind {
+            *note = Some((contents.to_string(), dir));
+        }
+        self
+    }
+
+    /// Creates an empty [`GroupType::Package`], edges can point to the group as a whole using `id`.
+    pub fn group(id: impl ToString, title: impl ToString) -> Self {
+        Self::new(PlantUmlItemKind::Group {
+            id: Self::sanitise_id(id),
+            title: title.to_string(),
+            group_type: GroupType::default(),
+            items: Vec::new(),
+        })
+    }
+
+    /// Sets the type of block a group is rendered as, does nothing for other items
+    pub fn with_group_type(mut self, new_type: GroupType) -> Self {
+        if let PlantUmlItemKind::Group { group_type, .. } = &mut self.kind {
+            *group_type = new_type;
+        }
+        self
+    }
+
+    /// Adds an item to a group.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is not a group
+    pub fn push_child(&mut self, item: PlantUmlItem) {
+        match &mut self.kind {
+            PlantUmlItemKind::Group { items, .. } => items.push(item),
+            _ => panic!(
+                "Attempting to add child to non-group item {}",
+                self.node_id()
+            ),
+        }
+    }
+
+    /// Adds items to a group.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self` is not a group
+    pub fn with_children(mut self, items: impl IntoIterator<Item = PlantUmlItem>) -> Self {
+        for item in items {
+            self.push_child(item);
+        }
+        self
+    }
+
+    /// Places this item in a cell of a grid, relative to the other positioned items.
+    ///
+    /// Ignored for edges and attached notes, as they are placed by what they connect to.
+    pub fn with_position(mut self, column: i32, row: i32) -> Self {
+        self.position = Some((column, row));
+        self
+    }
+
+    /// Writes the contents of this item as is, so they can contain creole or HTML such as `<b>bold</b>`.
+    ///
+    /// Contents are escaped by default, use [`escape_label`] for the parts that should not be interpreted.
+    pub fn raw(mut self) -> Self {
+        self.raw = true;
+        self
+    }
+
+    /// Shows `text` when hovering over the node in an SVG, ignored for other items.
+    ///
+    /// Newlines are kept, so this can hold more than fits in a label.
+    pub fn with_tooltip(mut self, text: impl ToString) -> Self {
+        // '}' and ']' would close the tooltip and link early
+        let text = text
+            .to_string()
+            .replace(['}', ']'], "")
+            .replace('\n', "\\n");
+        self.tooltip = Some(text);
+        self
+    }
+
     pub fn add_class(mut self, class: impl ToString) -> Self {
         self.classes.push(class.to_string());
         self
@@ -243,8 +427,21 @@ impl PlantUmlItem {
         self
     }
 
+    /// Returns the classes for the annotations of this item and all of its children
+    pub(crate) fn class_defs(&mut self) -> Vec<CssClass> {
+        let mut defs = match &mut self.kind {
+            PlantUmlItemKind::Group { items, .. } => items
+                .iter_mut()
+                .flat_map(PlantUmlItem::class_defs)
+                .collect(),
+            _ => Vec::new(),
+        };
+        defs.extend(self.class_def());
+        defs
+    }
+
     /// Returns a CssClass if this object was not given a class and contains annotations
-    pub(crate) fn class_def(&mut self) -> Option<CssClass> {
+    fn class_def(&mut self) -> Option<CssClass> {
         if self.annotation.is_default() {
             return None;
         }
@@ -256,6 +453,36 @@ impl PlantUmlItem {
         Some(class)
     }
 
+    /// Adds the class definitions returned by `style` for this node, or for the nodes in this group, to `defs`.
+    ///
+    /// The IDs of the styled nodes are added to `styled`, which is passed to [`PlantUmlItem::write_styled`]
+    /// to add the classes to the nodes without having to modify the items.
+    pub(crate) fn collect_node_styles<'a>(
+        &'a self,
+        style: &NodeStyle,
+        defs: &mut Vec<CssClass>,
+        styled: &mut HashSet<&'a str>,
+    ) {
+        match &self.kind {
+            PlantUmlItemKind::Node { id, .. } => {
+                if let Some(css) = (style.0)(id) {
+                    defs.push(css.as_class(Self::node_style_class(id)));
+                    styled.insert(id);
+                }
+            }
+            PlantUmlItemKind::Group { items, .. } => {
+                for item in items {
+                    item.collect_node_styles(style, defs, styled);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn node_style_class(id: &str) -> String {
+        format!("gen-class-node-{id}")
+    }
+
     // pub fn as_uml(&self) -> String {
     //     let class = self
     //         .classes
@@ -268,15 +495,33 @@ impl PlantUmlItem {
     // }
 
     pub fn write(&self, writer: &mut impl Write) -> RenderResult<()> {
+        self.write_styled(writer, &HashSet::new())
+    }
+
+    /// Writes this item, adding the node style class to the nodes in `styled`
+    pub(crate) fn write_styled(
+        &self,
+        writer: &mut impl Write,
+        styled: &HashSet<&str>,
+    ) -> RenderResult<()> {
         match &self.kind {
-            // {node_type} "{contents}" as {id} <<{classes}>>
+            // {node_type} "{contents}" as {id} <<{classes}>> [[{tooltip}]]
             PlantUmlItemKind::Node {
                 id,
                 contents,
                 node_type,
             } => {
-                write!(writer, "{} \"{}\" as {}", node_type.uml_str(), contents, id,)?;
-                self.write_class(writer)?;
+                write!(
+                    writer,
+                    "{} \"{}\" as {}",
+                    node_type.uml_str(),
+                    self.label_text(contents),
+                    id,
+                )?;
+                self.write_class(writer, styled)?;
+                if let Some(tooltip) = &self.tooltip {
+                    write!(writer, " [[{{{}}}]]", tooltip)?;
+                }
             }
             // {from} -{dir}-> {to} {classes} : {label}
             PlantUmlItemKind::Edge {
@@ -284,11 +529,18 @@ impl PlantUmlItem {
                 to,
                 label,
                 dir,
+                note,
             } => {
                 write!(writer, "{} -{}-> {}", from, dir.edge_str(), to)?;
-                self.write_class(writer)?;
+                self.write_class(writer, styled)?;
                 if !label.is_empty() {
-                    write!(writer, " : {label}")?;
+                    write!(writer, " : {}", self.label_text(label))?;
+                }
+                // \nnote {dir} on link\n\t{contents}\nend note
+                if let Some((contents, dir)) = note {
+                    let formatted = self.block_text(contents);
+                    write!(writer, "\nnote {} on link", dir.note_str())?;
+                    write!(writer, "\n\t{formatted}\nend note")?;
                 }
             }
             // note left of {to} {classes}\n\t{contents}\nend note
@@ -297,19 +549,143 @@ impl PlantUmlItem {
                     true => write!(writer, "note {}", dir.note_str())?,
                     false => write!(writer, "note {} of {}", dir.note_str(), to)?,
                 }
-                self.write_class(writer)?;
-                let formatted = contents.replace("\n", "\n\t");
+                self.write_class(writer, styled)?;
+                let formatted = self.block_text(contents);
                 write!(writer, "\n\t{formatted}")?;
                 write!(writer, "\nend note")?;
             }
+            // note as {id} {classes}\n\t{contents}\nend note\n{anchor} -[hidden]{dir}- {id}
+            PlantUmlItemKind::FloatingNote {
+                id,
+                contents,
+                anchor,
+            } => {
+                write!(writer, "note as {id}")?;
+                self.write_class(writer, styled)?;
+                let formatted = self.block_text(contents);
+                write!(writer, "\n\t{formatted}\nend note")?;
+                if let Some((node, dir)) = anchor {
+                    let dir = match dir {
+                        EdgeDirection::Norank => "",
+                        _ => dir.edge_str(),
+                    };
+                    write!(writer, "\n{node} -[hidden]{dir}- {id}")?;
+                }
+            }
+            // {group_type} "{title}" as {id} {classes} {\n{items}\n}
+            PlantUmlItemKind::Group {
+                id,
+                title,
+                group_type,
+                items,
+            } => {
+                match group_type {
+                    // together does not support a title or classes
+                    GroupType::Together => write!(writer, "together")?,
+                    _ => {
+                        write!(
+                            writer,
+                            "{} \"{}\" as {}",
+                            group_type.uml_str(),
+                            self.label_text(title),
+                            id
+                        )?;
+                        self.write_class(writer, styled)?;
+                    }
+                }
+                writeln!(writer, " {{")?;
+                // nodes have to come before the edges, same as in the diagram
+                let mut items = items.iter().collect::<Vec<_>>();
+                items.sort();
+                for item in items {
+                    item.write_styled(writer, styled)?;
+                    let _ = writer.write(b"\n")?;
+                }
+                write!(writer, "}}")?;
+            }
         }
         Ok(())
     }
 
-    fn write_class(&self, writer: &mut impl Write) -> RenderResult<()> {
+    /// Adds the IDs of all nodes this item and its children refer to to `ids`
+    pub(crate) fn collect_node_ids<'a>(&'a self, ids: &mut Vec<&'a str>) {
+        match &self.kind {
+            PlantUmlItemKind::Node { id, .. } => ids.push(id),
+            PlantUmlItemKind::Edge { from, to, .. } => ids.extend([from.as_str(), to.as_str()]),
+            // floating notes are not attached to a node
+            PlantUmlItemKind::Note { to, .. } if to.is_empty() => (),
+            PlantUmlItemKind::Note { to, .. } => ids.push(to),
+            PlantUmlItemKind::FloatingNote { id, anchor, .. } => {
+                ids.push(id);
+                if let Some((node, _)) = anchor {
+                    ids.push(node);
+                }
+            }
+            PlantUmlItemKind::Group { id, items, .. } => {
+                ids.push(id);
+                for item in items {
+                    item.collect_node_ids(ids);
+                }
+            }
+        }
+    }
+
+    /// Adds the classes of this item and its children to `classes`
+    pub(crate) fn collect_classes<'a>(&'a self, classes: &mut Vec<&'a str>) {
+        classes.extend(self.classes.iter().map(String::as_str));
+        if let PlantUmlItemKind::Group { items, .. } = &self.kind {
+            for item in items {
+                item.collect_classes(classes);
+            }
+        }
+    }
+
+    /// Adds the positions of this item and its children to `positions`
+    pub(crate) fn collect_positions<'a>(&'a self, positions: &mut Vec<(&'a str, GridPosition)>) {
+        match &self.kind {
+            PlantUmlItemKind::Edge { .. } | PlantUmlItemKind::Note { .. } => return,
+            PlantUmlItemKind::Group { items, .. } => {
+                for item in items {
+                    item.collect_positions(positions);
+                }
+            }
+            _ => (),
+        }
+        if let Some(pos) = self.position {
+            positions.push((self.node_id(), pos));
+        }
+    }
+
+    /// Contents of a single line, such as a node or edge label
+    fn label_text<'a>(&self, label: &'a str) -> Cow<'a, str> {
+        match self.raw {
+            true => label.into(),
+            false => escape_label(label).into(),
+        }
+    }
+
+    /// Contents of a multiline block such as a note, indented to fit in the block
+    fn block_text(&self, contents: &str) -> String {
+        match self.raw {
+            true => contents.replace("\n", "\n\t"),
+            false => contents
+                .lines()
+                .map(escape_label)
+                .collect::<Vec<_>>()
+                .join("\n\t"),
+        }
+    }
+
+    fn write_class(&self, writer: &mut impl Write, styled: &HashSet<&str>) -> RenderResult<()> {
         for class in &self.classes {
             write!(writer, "<<{class}>>")?;
         }
+        match &self.kind {
+            PlantUmlItemKind::Node { id, .. } if styled.contains(id.as_str()) => {
+                write!(writer, "<<{}>>", Self::node_style_class(id))?;
+            }
+            _ => (),
+        }
         Ok(())
     }
 }
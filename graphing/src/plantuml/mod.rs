@@ -1,10 +1,22 @@
 mod item;
-use std::{cmp::Reverse, collections::BinaryHeap, io::Write};
+mod page;
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    collections::{BinaryHeap, HashSet},
+    io::Write,
+    sync::Arc,
+};
 
 pub use item::*;
-use theme::PlantUmlStyleSheet;
+pub use page::*;
+use theme::{ElementCss, PlantUmlStyleSheet};
 
-use crate::{RenderResult, Renderer};
+use crate::{
+    Diagram, DiagramNode, Direction, ImageFormat, ImageTools, RenderResult, Renderer,
+    error::FileContext,
+    layout::{grid_columns, grid_rows},
+};
 
 pub mod theme;
 
@@ -14,12 +26,51 @@ const HEADER_SECTION: &str = r#"
 ' this hides the <<class>> from nodes
 hide stereotype"#;
 
+/// Escapes `label` so it is shown as is, instead of being interpreted as creole or HTML.
+///
+/// Newlines are written as `\n`. This is applied to all labels, unless [`PlantUmlItem::raw`] is used.
+pub fn escape_label(label: &str) -> String {
+    let mut out = String::with_capacity(label.len());
+    let mut chars = label.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\\' => out.push_str("\\\\"),
+            '"' | '<' | '~' => out.push_str(&format!("<U+{:04X}>", c as u32)),
+            // creole markup such as **bold** or --strike--
+            '*' | '/' | '-' | '_' if chars.peek() == Some(&c) => {
+                out.push_str(&format!("<U+{:04X}>", c as u32))
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Function that derives the style of a node from its ID, see [`PlantUmlDiagram::set_node_style`]
+#[derive(Clone)]
+pub struct NodeStyle(Arc<NodeStyleFn>);
+
+type NodeStyleFn = dyn Fn(&str) -> Option<ElementCss> + Send + Sync;
+
+impl std::fmt::Debug for NodeStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NodeStyle")
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct PlantUmlDiagram {
     style: PlantUmlStyleSheet,
     // notes have to come after nodes, so must be sorted
     items: BinaryHeap<Reverse<PlantUmlItem>>,
     title: String,
+    legend: bool,
+    node_style: Option<NodeStyle>,
+    /// Nodes that are placed next to each other, see [`PlantUmlDiagram::same_rank`]
+    ranks: Vec<Vec<String>>,
+    /// Lines written as is after the header, see [`PlantUmlDiagram::push_raw_directive`]
+    directives: Vec<String>,
 }
 
 impl PlantUmlDiagram {
@@ -28,6 +79,10 @@ impl PlantUmlDiagram {
             style: PlantUmlStyleSheet::new(),
             items: BinaryHeap::new(),
             title: title.to_string(),
+            legend: false,
+            node_style: None,
+            ranks: Vec::new(),
+            directives: Vec::new(),
         }
     }
 
@@ -40,14 +95,44 @@ impl PlantUmlDiagram {
         self.items.len()
     }
 
+    /// Adds a legend showing the colors of the classes used in the diagram.
+    ///
+    /// Generated classes are left out, as they have no meaningful name.
+    pub fn add_legend(&mut self) {
+        self.legend = true;
+    }
+
     pub fn set_style_sheet(&mut self, style: PlantUmlStyleSheet) {
         self.style = style;
     }
 
+    /// Styles nodes based on their ID, e.g. to color scopes by how often they are queried.
+    ///
+    /// `style` is called for every node when rendering, nodes for which it returns `None`
+    /// only use their own classes. The returned style takes precedence over those classes.
+    pub fn set_node_style(
+        &mut self,
+        style: impl Fn(&str) -> Option<ElementCss> + Send + Sync + 'static,
+    ) {
+        self.node_style = Some(NodeStyle(Arc::new(style)));
+    }
+
+    /// Places the nodes with `ids` next to each other, from left to right.
+    ///
+    /// This connects them with hidden edges, so it works best for nodes that are not connected already.
+    pub fn same_rank(&mut self, ids: impl IntoIterator<Item = impl ToString>) {
+        let rank = ids.into_iter().map(PlantUmlItem::sanitise_id).collect();
+        self.ranks.push(rank);
+    }
+
+    /// Writes `directive` as is after the header, for options that are not modeled by [`ElementCss`],
+    /// e.g. `skinparam linetype ortho` or `skinparam nodesep 50`.
+    pub fn push_raw_directive(&mut self, directive: &str) {
+        self.directives.push(directive.to_string());
+    }
+
     pub fn push(&mut self, mut item: PlantUmlItem) {
-        if let Some(class) = item.class_def() {
-            self.style.push(class);
-        }
+        self.style.extend(item.class_defs());
         self.items.push(Reverse(item));
     }
 
@@ -58,18 +143,167 @@ impl PlantUmlDiagram {
     }
 }
 
+impl PlantUmlDiagram {
+    /// Writes the hidden edges for [`PlantUmlDiagram::same_rank`] and [`PlantUmlItem::with_position`]
+    fn write_layout_hints(&self, writer: &mut impl Write) -> RenderResult<()> {
+        let mut positions = Vec::new();
+        for item in &self.items {
+            item.0.collect_positions(&mut positions);
+        }
+
+        let ranks = self
+            .ranks
+            .iter()
+            .map(|rank| rank.iter().map(String::as_str).collect())
+            .chain(grid_rows(&positions));
+        for row in ranks {
+            for pair in row.windows(2) {
+                writeln!(writer, "{} -[hidden]r- {}", pair[0], pair[1])?;
+            }
+        }
+        for column in grid_columns(&positions) {
+            for pair in column.windows(2) {
+                writeln!(writer, "{} -[hidden]d- {}", pair[0], pair[1])?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Renderer for PlantUmlDiagram {
     fn render_to_writer(&self, writer: &mut impl Write) -> RenderResult<()> {
         writeln!(writer, "@startuml \"{}\"{}", self.title, HEADER_SECTION)?;
+        for directive in &self.directives {
+            writeln!(writer, "{directive}")?;
+        }
+        // items are written in order through references, as cloning the heap to pop them
+        // would duplicate every item of the diagram
+        let mut items = self.items.iter().map(|item| &item.0).collect::<Vec<_>>();
+        items.sort();
+
+        let mut styled = HashSet::new();
+        let style = match &self.node_style {
+            Some(node_style) => {
+                let mut defs = Vec::new();
+                for item in &items {
+                    item.collect_node_styles(node_style, &mut defs, &mut styled);
+                }
+                let mut style = self.style.clone();
+                style.extend(defs);
+                Cow::Owned(style)
+            }
+            None => Cow::Borrowed(&self.style),
+        };
         // writes <style>...</style> section
-        self.style.write(writer)?;
+        style.write(writer)?;
         let _ = writer.write(b"\n")?;
-        let items = self.items.clone();
         for item in items {
-            item.0.write(writer)?;
+            item.write_styled(writer, &styled)?;
             let _ = writer.write(b"\n")?;
         }
+        self.write_layout_hints(writer)?;
+        if self.legend {
+            let mut used = Vec::new();
+            for item in &self.items {
+                item.0.collect_classes(&mut used);
+            }
+            used.retain(|c| !c.starts_with("gen-class-"));
+            self.style.write_legend(writer, &used)?;
+        }
         write!(writer, "\n@enduml")?;
         Ok(())
     }
+
+    fn source_extension(&self) -> &'static str {
+        "puml"
+    }
+
+    fn image_command(
+        &self,
+        tools: &ImageTools,
+        source: &std::path::Path,
+        output: &std::path::Path,
+        format: ImageFormat,
+    ) -> RenderResult<Option<std::process::Command>> {
+        // piped, as plantuml names its output after the diagram title otherwise
+        let mut command = std::process::Command::new(&tools.java);
+        command
+            .args(["-jar", &tools.plantuml_jar, "-pipe", &format!("-t{format}")])
+            .stdin(std::fs::File::open(source).context("open", source)?)
+            .stdout(std::fs::File::create(output).context("create", output)?);
+        Ok(Some(command))
+    }
+}
+
+impl From<&Diagram> for PlantUmlDiagram {
+    fn from(diagram: &Diagram) -> Self {
+        let node = |node: &DiagramNode| {
+            let item = node.classes.iter().fold(
+                PlantUmlItem::node(&node.id, &node.label, node.shape.into()),
+                |item, class| item.add_class(class),
+            );
+            let item = match node.position {
+                Some((column, row)) => item.with_position(column, row),
+                None => item,
+            };
+            match &node.tooltip {
+                Some(tooltip) => item.with_tooltip(tooltip),
+                None => item,
+            }
+        };
+
+        let mut uml = PlantUmlDiagram::new(&diagram.title);
+        uml.extend(diagram.ungrouped_nodes().map(node));
+        uml.extend(diagram.groups.iter().map(|group| {
+            PlantUmlItem::group(&group.id, &group.title)
+                .with_children(diagram.group_nodes(group).map(node))
+        }));
+        uml.extend(diagram.edges.iter().map(|edge| {
+            let dir = match edge.direction {
+                None => EdgeDirection::Unspecified,
+                Some(Direction::Up) => EdgeDirection::Up,
+                Some(Direction::Down) => EdgeDirection::Bottom,
+                Some(Direction::Left) => EdgeDirection::Left,
+                Some(Direction::Right) => EdgeDirection::Right,
+            };
+            edge.classes.iter().fold(
+                PlantUmlItem::edge(&edge.from, &edge.to, &edge.label, dir),
+                |item, class| item.add_class(class),
+            )
+        }));
+        for rank in &diagram.ranks {
+            uml.same_rank(rank);
+        }
+        uml
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn written(item: &PlantUmlItem) -> String {
+        let mut buf = Vec::new();
+        item.write(&mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("plain"), "plain");
+        assert_eq!(escape_label(r#"say "hi""#), "say <U+0022>hi<U+0022>");
+        assert_eq!(escape_label("a<b>"), "a<U+003C>b>");
+        assert_eq!(escape_label("**bold**"), "<U+002A>*bold<U+002A>*");
+        assert_eq!(escape_label("a*b"), "a*b");
+        assert_eq!(escape_label("#1"), "#1");
+        assert_eq!(escape_label("a\nb"), "a\\nb");
+        assert_eq!(escape_label("a\\nb"), "a\\\\nb");
+    }
+
+    #[test]
+    fn test_raw_is_not_escaped() {
+        let item = PlantUmlItem::node("n", "<b>**x**</b>", NodeType::Node);
+        assert!(written(&item).contains("<U+003C>b><U+002A>*x<U+002A>*<U+003C>/b>"));
+        assert!(written(&item.raw()).contains("<b>**x**</b>"));
+    }
 }
@@ -0,0 +1,181 @@
+use std::{cmp::Reverse, collections::HashMap};
+
+use crate::{RenderResult, Renderer};
+
+use super::{PlantUmlDiagram, PlantUmlItem};
+
+/// How to split a diagram into pages
+pub enum PageSplit<'a> {
+    /// Keeps connected nodes on the same page.
+    ///
+    /// Components are packed together into pages of at most `max_items` items,
+    /// a component that is larger than that gets a page of its own.
+    ConnectedComponents { max_items: usize },
+    /// Puts nodes with the same key on the same page, the key is computed from the node id.
+    ///
+    /// Edges and notes are placed on the page of the node they start from or are attached to.
+    Key(&'a dyn Fn(&str) -> String),
+}
+
+/// Union-find over node ids
+struct Components<'a> {
+    index: HashMap<&'a str, usize>,
+    parent: Vec<usize>,
+}
+
+impl<'a> Components<'a> {
+    fn new() -> Self {
+        Self {
+            index: HashMap::new(),
+            parent: Vec::new(),
+        }
+    }
+
+    fn find(&mut self, id: &'a str) -> usize {
+        let mut idx = *self.index.entry(id).or_insert_with(|| {
+            self.parent.push(self.parent.len());
+            self.parent.len() - 1
+        });
+        while self.parent[idx] != idx {
+            self.parent[idx] = self.parent[self.parent[idx]];
+            idx = self.parent[idx];
+        }
+        idx
+    }
+
+    fn union(&mut self, a: &'a str, b: &'a str) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parent[b] = a;
+    }
+}
+
+impl PlantUmlDiagram {
+    /// Splits the diagram into multiple diagrams, which are small enough for PlantUML to lay out.
+    ///
+    /// Every page has the same style sheet, the title gets the page number appended.
+    /// Items that do not refer to any node (floating notes) are placed on the first page.
+    pub fn paginate(&self, split: PageSplit) -> Vec<PlantUmlDiagram> {
+        let items = self.items.iter().map(|item| &item.0).collect::<Vec<_>>();
+        let item_ids = items
+            .iter()
+            .map(|item| {
+                let mut ids = Vec::new();
+                item.collect_node_ids(&mut ids);
+                ids
+            })
+            .collect::<Vec<_>>();
+
+        // key of the page every item should go on, None for items without a node
+        let keys = match split {
+            PageSplit::ConnectedComponents { .. } => {
+                let mut components = Components::new();
+                for ids in &item_ids {
+                    for pair in ids.windows(2) {
+                        components.union(pair[0], pair[1]);
+                    }
+                }
+                item_ids
+                    .iter()
+                    .map(|ids| ids.first().map(|id| components.find(id).to_string()))
+                    .collect::<Vec<_>>()
+            }
+            PageSplit::Key(key) => item_ids
+                .iter()
+                .map(|ids| ids.first().map(|id| key(id)))
+                .collect::<Vec<_>>(),
+        };
+
+        let mut groups: Vec<Vec<&PlantUmlItem>> = Vec::new();
+        let mut group_idx = HashMap::new();
+        let mut floating = Vec::new();
+        for (item, key) in items.into_iter().zip(keys) {
+            let Some(key) = key else {
+                floating.push(item);
+                continue;
+            };
+            let idx = *group_idx.entry(key).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[idx].push(item);
+        }
+
+        let mut pages = match split {
+            PageSplit::ConnectedComponents { max_items } => {
+                // biggest components first, so smaller ones fill up the gaps
+                groups.sort_by_key(|g| Reverse(g.len()));
+                let mut pages: Vec<Vec<&PlantUmlItem>> = Vec::new();
+                for group in groups {
+                    match pages
+                        .iter_mut()
+                        .find(|p| p.len() + group.len() <= max_items)
+                    {
+                        Some(page) => page.extend(group),
+                        None => pages.push(group),
+                    }
+                }
+                pages
+            }
+            PageSplit::Key(_) => groups,
+        };
+
+        match pages.first_mut() {
+            Some(page) => page.extend(floating),
+            None if !floating.is_empty() => pages.push(floating),
+            None => (),
+        }
+
+        let num_pages = pages.len();
+        pages
+            .into_iter()
+            .enumerate()
+            .map(|(idx, page)| {
+                let mut ids = Vec::new();
+                for item in &page {
+                    item.collect_node_ids(&mut ids);
+                }
+                // hidden edges to nodes on other pages would create new nodes
+                let ranks = self
+                    .ranks
+                    .iter()
+                    .map(|rank| {
+                        rank.iter()
+                            .filter(|id| ids.contains(&id.as_str()))
+                            .cloned()
+                            .collect()
+                    })
+                    .collect();
+                PlantUmlDiagram {
+                    // class definitions were already added to the style sheet when the items were pushed
+                    style: self.style.clone(),
+                    items: page.into_iter().cloned().map(Reverse).collect(),
+                    title: format!("{} ({}/{})", self.title, idx + 1, num_pages),
+                    legend: self.legend,
+                    node_style: self.node_style.clone(),
+                    ranks,
+                    directives: self.directives.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// Paginates the diagram and renders every page to its own file.
+    ///
+    /// Pages are numbered starting at 1, e.g. `output/graph.puml` is rendered to
+    /// `output/graph_1.puml`, `output/graph_2.puml`, etc. Returns the number of pages.
+    pub fn render_pages_to_files(&self, path: &str, split: PageSplit) -> RenderResult<usize> {
+        let path = std::path::Path::new(path);
+        let stem = path.with_extension("");
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_string())
+            .unwrap_or_else(|| String::from("puml"));
+
+        let pages = self.paginate(split);
+        for (idx, page) in pages.iter().enumerate() {
+            let page_path = format!("{}_{}.{}", stem.display(), idx + 1, ext);
+            page.render_to_file(&page_path)?;
+        }
+        Ok(pages.len())
+    }
+}
@@ -6,36 +6,36 @@ pub use props::*;
 
 use crate::Color;
 
-#[derive(Default, Clone, Debug)]
+#[derive(Default, Clone, Debug, PartialEq)]
 pub struct ElementCss {
     // typography
     /// Set the font family for text elements
-    font_family: Option<FontFamily>,
-    font_color: Option<Color>,
-    font_size: Option<usize>,
-    font_style: Option<FontStyle>,
+    pub(crate) font_family: Option<FontFamily>,
+    pub(crate) font_color: Option<Color>,
+    pub(crate) font_size: Option<usize>,
+    pub(crate) font_style: Option<FontStyle>,
 
     // color and background
-    background_color: Option<Color>,
-    hyper_link_color: Option<Color>,
+    pub(crate) background_color: Option<Color>,
+    pub(crate) hyper_link_color: Option<Color>,
 
     // borders and corners
-    round_corner: Option<usize>,
-    diagonal_corner: Option<usize>,
-    line_style: Option<LineStyle>,
-    line_color: Option<Color>,
-    line_thickness: Option<f32>,
+    pub(crate) round_corner: Option<usize>,
+    pub(crate) diagonal_corner: Option<usize>,
+    pub(crate) line_style: Option<LineStyle>,
+    pub(crate) line_color: Option<Color>,
+    pub(crate) line_thickness: Option<f32>,
 
     // Spacing and sizing
-    padding: Option<usize>,
-    margin: Option<usize>,
-    maximum_width: Option<usize>,
+    pub(crate) padding: Option<usize>,
+    pub(crate) margin: Option<usize>,
+    pub(crate) maximum_width: Option<usize>,
 
     // Additional visuals and effects
-    shadowing: Option<usize>,
-    hyperlink_underline_style: Option<HyperlinkUnderlineStyle>,
-    hyperlink_underline_thickness: Option<usize>,
-    horizontal_alignment: Option<HorizontalAlignment>,
+    pub(crate) shadowing: Option<usize>,
+    pub(crate) hyperlink_underline_style: Option<HyperlinkUnderlineStyle>,
+    pub(crate) hyperlink_underline_thickness: Option<usize>,
+    pub(crate) horizontal_alignment: Option<HorizontalAlignment>,
 }
 
 impl ElementCss {
@@ -203,12 +203,12 @@ impl ElementCss {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct CssClass {
-    name: String,
-    element: ElementCss,
+    pub(crate) name: String,
+    pub(crate) element: ElementCss,
     // True if this is a selector (no '.' is needed then)
-    is_selector: bool,
+    pub(crate) is_selector: bool,
 }
 
 impl CssClass {
@@ -246,7 +246,7 @@ impl CssClass {
 
 #[derive(Default, Clone, Debug)]
 pub struct PlantUmlStyleSheet {
-    classes: Vec<CssClass>,
+    pub(crate) classes: Vec<CssClass>,
 }
 
 impl FromIterator<CssClass> for PlantUmlStyleSheet {
@@ -259,15 +259,13 @@ impl FromIterator<CssClass> for PlantUmlStyleSheet {
 
 impl From<Vec<CssClass>> for PlantUmlStyleSheet {
     fn from(value: Vec<CssClass>) -> Self {
-        PlantUmlStyleSheet { classes: value }
+        value.into_iter().collect()
     }
 }
 
 impl<const N: usize> From<[CssClass; N]> for PlantUmlStyleSheet {
     fn from(value: [CssClass; N]) -> Self {
-        PlantUmlStyleSheet {
-            classes: value.to_vec(),
-        }
+        value.into_iter().collect()
     }
 }
 
@@ -278,16 +276,75 @@ impl PlantUmlStyleSheet {
         }
     }
 
+    /// Adds a class, replacing an earlier definition with the same name.
+    ///
+    /// Identical definitions are skipped, a warning is logged if the definitions differ.
     pub fn push(&mut self, class: CssClass) {
-        self.classes.push(class);
+        let existing = self
+            .classes
+            .iter_mut()
+            .find(|c| c.name == class.name && c.is_selector == class.is_selector);
+        match existing {
+            Some(existing) if *existing == class => (),
+            Some(existing) => {
+                tracing::warn!(
+                    "Conflicting definitions for class {}, using the last one",
+                    class.name
+                );
+                *existing = class;
+            }
+            None => self.classes.push(class),
+        }
     }
 
     pub fn extend(&mut self, classes: impl IntoIterator<Item = CssClass>) {
-        self.classes.extend(classes);
+        for class in classes {
+            self.push(class);
+        }
     }
 
+    /// Adds all classes of `other`, which take precedence over the classes in `self`
     pub fn merge(&mut self, other: PlantUmlStyleSheet) {
-        self.classes.extend(other.classes);
+        self.extend(other.classes);
+    }
+
+    /// Writes a legend with a row for every class in `used` that sets a color.
+    ///
+    /// Rows show the background color as the cell color and the line color as a colored line.
+    pub(crate) fn write_legend(&self, writer: &mut impl Write, used: &[&str]) -> RenderResult<()> {
+        let mut seen = Vec::new();
+        // later definitions of a class take precedence
+        let mut rows = self
+            .classes
+            .iter()
+            .rev()
+            .filter(|c| !c.is_selector && used.contains(&c.name.as_str()))
+            .filter(|c| c.element.background_color.is_some() || c.element.line_color.is_some())
+            .filter(|c| {
+                let is_new = !seen.contains(&c.name);
+                seen.push(c.name.clone());
+                is_new
+            })
+            .collect::<Vec<_>>();
+        rows.reverse();
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(writer, "legend right")?;
+        for class in rows {
+            write!(writer, "|")?;
+            if let Some(bg) = class.element.background_color {
+                write!(writer, "<{}>", bg.hex_string())?;
+            }
+            match class.element.line_color {
+                Some(line) => write!(writer, " <color:{}>━━━</color> ", line.hex_string())?,
+                None => write!(writer, "     ")?,
+            }
+            writeln!(writer, "| {} |", class.name)?;
+        }
+        write!(writer, "endlegend")?;
+        Ok(())
     }
 
     pub(crate) fn write(&self, writer: &mut impl Write) -> RenderResult<()> {
@@ -26,7 +26,7 @@ impl CssProperty for LineStyle {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, derive_more::Display)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, derive_more::Display)]
 pub enum FontStyle {
     #[default]
     #[display("normal")]
@@ -39,7 +39,7 @@ pub enum FontStyle {
     Underline,
 }
 
-#[derive(Clone, Copy, Debug, Default, derive_more::Display)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, derive_more::Display)]
 pub enum FontFamily {
     #[default]
     #[display("Monospaced")]
@@ -50,14 +50,14 @@ pub enum FontFamily {
     SansSerif,
 }
 
-#[derive(Clone, Copy, Debug, Default, derive_more::Display)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, derive_more::Display)]
 pub enum HyperlinkUnderlineStyle {
     #[default]
     #[display("normal")]
     Normal,
 }
 
-#[derive(Clone, Copy, Debug, Default, derive_more::Display)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, derive_more::Display)]
 pub enum HorizontalAlignment {
     #[display("left")]
     Left,
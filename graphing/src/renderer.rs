@@ -1,29 +1,96 @@
-use std::io::{BufWriter, Write};
+use std::{
+    io::{BufWriter, Write},
+    path::Path,
+    process::Command,
+};
 
-use crate::RenderResult;
+use crate::{
+    ImageFormat, ImageTools, RenderError, RenderResult,
+    error::{FileContext, create_file},
+};
 
 pub trait Renderer {
     fn render_to_writer(&self, writer: &mut impl Write) -> RenderResult<()>;
 
-    fn render(&self) -> RenderResult<String> {
+    fn render_to_string(&self) -> RenderResult<String> {
         let mut buf = Vec::new();
         self.render_to_writer(&mut buf)?;
         String::from_utf8(buf).map_err(Into::into)
     }
 
+    /// Same as [`Renderer::render_to_string`]
+    fn render(&self) -> RenderResult<String> {
+        self.render_to_string()
+    }
+
+    /// Writes the diagram to `path`, creating the parent directories if needed
     fn render_to_file(&self, path: &str) -> RenderResult<()> {
-        let path = std::path::PathBuf::from(path);
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        let file = std::fs::OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(&path)?;
-        let mut buf = BufWriter::with_capacity(4096, file);
+        let path = Path::new(path);
+        let mut buf = BufWriter::with_capacity(4096, create_file(path)?);
 
         tracing::debug!("Rendering graph to file: {}", path.display());
-        self.render_to_writer(&mut buf)
+        self.render_to_writer(&mut buf).context("write", path)?;
+        buf.flush().context("write", path)
+    }
+
+    /// Extension of the file that is passed to the external tool
+    fn source_extension(&self) -> &'static str {
+        "txt"
+    }
+
+    /// Writes the input for the external tool, which is the same as [`Renderer::render_to_writer`] by default
+    fn render_source_to_writer(&self, writer: &mut impl Write) -> RenderResult<()> {
+        self.render_to_writer(writer)
+    }
+
+    /// Returns the command that converts `source` to an image at `output`,
+    /// or `None` if this diagram cannot be converted to images.
+    #[allow(unused_variables)]
+    fn image_command(
+        &self,
+        tools: &ImageTools,
+        source: &Path,
+        output: &Path,
+        format: ImageFormat,
+    ) -> RenderResult<Option<Command>> {
+        Ok(None)
+    }
+
+    /// Renders to an image using the tools in [`ImageTools::default`]
+    fn render_to_image(&self, path: &str, format: ImageFormat) -> RenderResult<()> {
+        self.render_to_image_with(path, format, &ImageTools::default())
+    }
+
+    /// Writes the diagram next to `path` and converts it to an image at `path` using an external tool.
+    ///
+    /// The source is kept, e.g. rendering a PlantUML diagram to `output/graph.svg`
+    /// also writes `output/graph.puml`.
+    fn render_to_image_with(
+        &self,
+        path: &str,
+        format: ImageFormat,
+        tools: &ImageTools,
+    ) -> RenderResult<()> {
+        let output = Path::new(path);
+        let source = output.with_extension(self.source_extension());
+        let mut buf = BufWriter::with_capacity(4096, create_file(&source)?);
+        self.render_source_to_writer(&mut buf)
+            .context("write", &source)?;
+        buf.flush().context("write", &source)?;
+
+        let Some(mut command) = self.image_command(tools, &source, output, format)? else {
+            return Err(RenderError::ImageNotSupported);
+        };
+        tracing::debug!("Rendering image: {:?}", command);
+        let tool = command.get_program().to_string_lossy().to_string();
+        let stderr = match command.output() {
+            Ok(result) if result.status.success() => return Ok(()),
+            Ok(result) => String::from_utf8_lossy(&result.stderr).to_string(),
+            // most likely the tool is not installed
+            Err(e) => e.to_string(),
+        };
+        // some tools create the output file before failing
+        let _ = std::fs::remove_file(output);
+        Err(RenderError::ToolFailed { tool, stderr })
     }
 }
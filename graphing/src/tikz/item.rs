@@ -0,0 +1,145 @@
+use std::io::Write;
+
+use crate::{RenderResult, Shape};
+
+use super::{escape, theme::TikzStyleSheet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum NodeShape {
+    /// Used for scopes
+    #[display("circle")]
+    Circle,
+    #[display("ellipse")]
+    Ellipse,
+    /// Used for declarations
+    #[display("rectangle")]
+    Rectangle,
+    #[display("regular polygon, regular polygon sides=6")]
+    Hexagon,
+    /// Horizontal cylinder
+    #[display("cylinder")]
+    Cylinder,
+    /// Vertical cylinder
+    #[display("cylinder, shape border rotate=90")]
+    Database,
+}
+
+impl From<Shape> for NodeShape {
+    fn from(shape: Shape) -> Self {
+        match shape {
+            Shape::Circle => NodeShape::Circle,
+            Shape::Rounded | Shape::Card | Shape::Note => NodeShape::Rectangle,
+            Shape::Hexagon => NodeShape::Hexagon,
+            Shape::Cylinder => NodeShape::Cylinder,
+            Shape::Database => NodeShape::Database,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TikzItemKind {
+    Node {
+        id: String,
+        contents: String,
+        shape: NodeShape,
+    },
+    Edge {
+        from: String,
+        to: String,
+        label: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TikzItem {
+    kind: TikzItemKind,
+    classes: Vec<String>,
+}
+
+impl TikzItem {
+    pub fn new(kind: TikzItemKind) -> Self {
+        Self {
+            kind,
+            classes: Vec::new(),
+        }
+    }
+
+    /// TikZ node names cannot contain most special characters
+    fn sanitise_id(id: impl ToString) -> String {
+        id.to_string()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+            .collect()
+    }
+
+    pub fn node(id: impl ToString, contents: impl ToString, shape: NodeShape) -> Self {
+        Self::new(TikzItemKind::Node {
+            id: Self::sanitise_id(id),
+            contents: contents.to_string(),
+            shape,
+        })
+    }
+
+    pub fn edge(from: impl ToString, to: impl ToString, label: impl ToString) -> Self {
+        Self::new(TikzItemKind::Edge {
+            from: Self::sanitise_id(from),
+            to: Self::sanitise_id(to),
+            label: label.to_string(),
+        })
+    }
+
+    /// Returns the ID of the node this item represents or is connected to.
+    ///
+    /// Edges use their 'from' node
+    pub fn node_id(&self) -> &str {
+        match &self.kind {
+            TikzItemKind::Node { id, .. } => id,
+            TikzItemKind::Edge { from, .. } => from,
+        }
+    }
+
+    pub fn is_node(&self) -> bool {
+        matches!(self.kind, TikzItemKind::Node { .. })
+    }
+
+    pub fn add_class(mut self, class: impl ToString) -> Self {
+        self.classes.push(class.to_string());
+        self
+    }
+
+    pub(crate) fn find_nonexistant_class(&self, sheet: &TikzStyleSheet) -> Option<&str> {
+        self.classes
+            .iter()
+            .find(|class| !sheet.contains(class))
+            .map(String::as_str)
+    }
+
+    pub(crate) fn write(&self, writer: &mut impl Write) -> RenderResult<()> {
+        match &self.kind {
+            // {id}/"{contents}" [{shape}, {classes}];
+            TikzItemKind::Node {
+                id,
+                contents,
+                shape,
+            } => {
+                write!(writer, "{}/\"{}\" [{}", id, escape(contents), shape)?;
+                self.write_classes(writer)?;
+                write!(writer, "];")?;
+            }
+            // {from} ->["{label}", {classes}] {to};
+            TikzItemKind::Edge { from, to, label } => {
+                write!(writer, "{} ->[\"{}\"", from, escape(label))?;
+                self.write_classes(writer)?;
+                write!(writer, "] {};", to)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_classes(&self, writer: &mut impl Write) -> RenderResult<()> {
+        for class in &self.classes {
+            write!(writer, ", {class}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+use std::io::Write;
+
+pub use item::*;
+use theme::TikzStyleSheet;
+
+use crate::{Diagram, RenderResult, Renderer};
+
+mod item;
+pub mod theme;
+
+const HEADER_SECTION: &str = r"% Requires LuaLaTeX and the following in the preamble:
+% \usepackage{tikz}
+% \usetikzlibrary{graphs, graphdrawing, quotes, arrows.meta, shapes.geometric}
+% \usegdlibrary{layered, trees, force}";
+
+/// Escapes LaTeX special characters, newlines become line breaks
+fn escape(s: impl ToString) -> String {
+    s.to_string().chars().fold(String::new(), |mut s, c| {
+        match c {
+            '\\' => s.push_str(r"\textbackslash{}"),
+            '~' => s.push_str(r"\textasciitilde{}"),
+            '^' => s.push_str(r"\textasciicircum{}"),
+            '{' | '}' | '$' | '&' | '#' | '%' | '_' => {
+                s.push('\\');
+                s.push(c);
+            }
+            // quotes would end the label
+            '"' => s.push_str("''"),
+            '\n' => s.push_str(r"\\"),
+            _ => s.push(c),
+        }
+        s
+    })
+}
+
+/// Graph drawing algorithm used by TikZ, all of these require LuaLaTeX
+#[derive(derive_more::Display, Debug, Clone, Copy)]
+pub enum TikzLayout {
+    #[display("layered layout")]
+    Layered,
+    #[display("tree layout")]
+    Tree,
+    #[display("spring layout")]
+    Spring,
+}
+
+/// TikZ picture that can be included directly in a LaTeX document.
+///
+/// The node placement is left to the graph drawing library of TikZ.
+#[derive(Clone, Debug)]
+pub struct TikzDiagram {
+    style: TikzStyleSheet,
+    items: Vec<TikzItem>,
+    title: String,
+    layout: TikzLayout,
+}
+
+impl TikzDiagram {
+    pub fn new(title: impl ToString) -> Self {
+        Self {
+            style: TikzStyleSheet::new(),
+            items: Vec::new(),
+            title: title.to_string(),
+            layout: TikzLayout::Layered,
+        }
+    }
+
+    pub fn set_title(&mut self, title: impl ToString) {
+        self.title = title.to_string();
+    }
+
+    pub fn set_layout(&mut self, layout: TikzLayout) {
+        self.layout = layout;
+    }
+
+    /// Returns number of items in the diagram.
+    pub fn num_items(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn set_style_sheet(&mut self, style: TikzStyleSheet) {
+        self.style = style;
+    }
+
+    pub fn push(&mut self, item: TikzItem) {
+        self.items.push(item);
+    }
+
+    pub fn extend(&mut self, items: impl IntoIterator<Item = TikzItem>) {
+        self.items.extend(items);
+    }
+}
+
+impl Renderer for TikzDiagram {
+    fn render_to_writer(&self, writer: &mut impl Write) -> RenderResult<()> {
+        // tikz has no titles, the caption of the figure should be used instead
+        writeln!(
+            writer,
+            "% {}\n{}",
+            self.title.replace('\n', " "),
+            HEADER_SECTION
+        )?;
+        writeln!(writer, r"\begin{{tikzpicture}}[")?;
+        writeln!(writer, "  >={{Stealth}},")?;
+        self.style.write(writer)?;
+        writeln!(writer, "]")?;
+        writeln!(
+            writer,
+            r"\graph [{}, nodes={{draw, align=center}}, edge quotes={{auto}}] {{",
+            self.layout
+        )?;
+
+        // nodes first, so edges do not implicitly create nodes without a style
+        let (nodes, edges): (Vec<_>, Vec<_>) = self.items.iter().partition(|i| i.is_node());
+        for item in nodes.into_iter().chain(edges) {
+            if let Some(dne_class) = item.find_nonexistant_class(&self.style) {
+                tracing::warn!(
+                    "Class {} does not exist in the stylesheet (found in {})",
+                    dne_class,
+                    item.node_id()
+                );
+            }
+            write!(writer, "  ")?;
+            item.write(writer)?;
+            let _ = writer.write(b"\n")?;
+        }
+
+        writeln!(writer, "}};")?;
+        write!(writer, r"\end{{tikzpicture}}")?;
+        Ok(())
+    }
+}
+
+/// Groups are not supported, their nodes are drawn ungrouped
+impl From<&Diagram> for TikzDiagram {
+    fn from(diagram: &Diagram) -> Self {
+        let mut tikz = TikzDiagram::new(&diagram.title);
+        tikz.extend(diagram.nodes.iter().map(|node| {
+            node.classes.iter().fold(
+                TikzItem::node(&node.id, &node.label, node.shape.into()),
+                |item, class| item.add_class(class),
+            )
+        }));
+        tikz.extend(diagram.edges.iter().map(|edge| {
+            edge.classes.iter().fold(
+                TikzItem::edge(&edge.from, &edge.to, &edge.label),
+                |item, class| item.add_class(class),
+            )
+        }));
+        tikz
+    }
+}
@@ -0,0 +1,289 @@
+use std::io::Write;
+
+use crate::{Color, RenderResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum LineStyle {
+    #[display("solid")]
+    Solid,
+    #[display("dotted")]
+    Dotted,
+    #[display("dashed")]
+    Dashed,
+    #[display("loosely dashed")]
+    LongDashed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum FontSize {
+    #[display(r"\tiny")]
+    Tiny,
+    #[display(r"\scriptsize")]
+    Script,
+    #[display(r"\footnotesize")]
+    Footnote,
+    #[display(r"\small")]
+    Small,
+    #[display(r"\normalsize")]
+    Normal,
+    #[display(r"\large")]
+    Large,
+    #[display(r"\Large")]
+    Larger,
+}
+
+impl FontSize {
+    /// Closest LaTeX size to a font size in points, assuming a 10pt document
+    pub fn from_pt(pt: usize) -> Self {
+        match pt {
+            0..=5 => Self::Tiny,
+            6..=7 => Self::Script,
+            8 => Self::Footnote,
+            9 => Self::Small,
+            10..=11 => Self::Normal,
+            12..=14 => Self::Large,
+            _ => Self::Larger,
+        }
+    }
+}
+
+/// Writes a color in xcolor's extended syntax, which does not require a `\definecolor`
+fn write_color(writer: &mut impl Write, color: Color) -> RenderResult<()> {
+    let (r, g, b) = color.rgb();
+    write!(writer, "{{rgb,255:red,{r};green,{g};blue,{b}}}")?;
+    Ok(())
+}
+
+/// TikZ options of a node or edge, written as a named style.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct TikzStyle {
+    /// draw
+    line_color: Option<Color>,
+    /// solid/dotted/dashed
+    line_style: Option<LineStyle>,
+    /// line width, in pt
+    line_thickness: Option<f32>,
+    /// fill
+    background_color: Option<Color>,
+    /// text
+    font_color: Option<Color>,
+    font_size: Option<FontSize>,
+    bold: bool,
+    italic: bool,
+    /// rounded corners, in pt
+    round_corner: Option<usize>,
+}
+
+impl TikzStyle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::new()
+    }
+
+    pub fn line_color(mut self, color: Color) -> Self {
+        self.line_color = Some(color);
+        self
+    }
+
+    pub fn line_style(mut self, style: LineStyle) -> Self {
+        self.line_style = Some(style);
+        self
+    }
+
+    pub fn line_thickness(mut self, thickness: f32) -> Self {
+        self.line_thickness = Some(thickness);
+        self
+    }
+
+    pub fn background_color(mut self, color: Color) -> Self {
+        self.background_color = Some(color);
+        self
+    }
+
+    pub fn font_color(mut self, color: Color) -> Self {
+        self.font_color = Some(color);
+        self
+    }
+
+    pub fn font_size(mut self, size: FontSize) -> Self {
+        self.font_size = Some(size);
+        self
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    pub fn round_corner(mut self, radius: usize) -> Self {
+        self.round_corner = Some(radius);
+        self
+    }
+
+    /// Writes the options separated by ", "
+    #[allow(unused_assignments)] // macro magic
+    pub(crate) fn write(&self, writer: &mut impl Write) -> RenderResult<()> {
+        let mut is_first = true;
+        macro_rules! sep {
+            () => {
+                if !is_first {
+                    write!(writer, ", ")?;
+                }
+                is_first = false;
+            };
+        }
+
+        if let Some(x) = self.line_color {
+            sep!();
+            write!(writer, "draw=")?;
+            write_color(writer, x)?;
+        }
+        if let Some(x) = self.line_style {
+            sep!();
+            write!(writer, "{x}")?;
+        }
+        if let Some(x) = self.line_thickness {
+            sep!();
+            write!(writer, "line width={x}pt")?;
+        }
+        if let Some(x) = self.background_color {
+            sep!();
+            write!(writer, "fill=")?;
+            write_color(writer, x)?;
+        }
+        if let Some(x) = self.font_color {
+            sep!();
+            write!(writer, "text=")?;
+            write_color(writer, x)?;
+        }
+        if self.font_size.is_some() || self.bold || self.italic {
+            sep!();
+            write!(writer, "font=")?;
+            if let Some(x) = self.font_size {
+                write!(writer, "{x}")?;
+            }
+            if self.bold {
+                write!(writer, r"\bfseries")?;
+            }
+            if self.italic {
+                write!(writer, r"\itshape")?;
+            }
+        }
+        if let Some(x) = self.round_corner {
+            sep!();
+            write!(writer, "rounded corners={x}pt")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "plantuml")]
+impl From<&crate::plantuml::theme::ElementCss> for TikzStyle {
+    fn from(css: &crate::plantuml::theme::ElementCss) -> Self {
+        use crate::plantuml::theme::{FontStyle, LineStyle as UmlLineStyle};
+
+        let font_style = css.font_style.unwrap_or_default();
+        Self {
+            line_color: css.line_color,
+            line_style: css.line_style.map(|s| match s {
+                UmlLineStyle::Solid => LineStyle::Solid,
+                UmlLineStyle::Dotted => LineStyle::Dotted,
+                UmlLineStyle::Dashed => LineStyle::Dashed,
+                UmlLineStyle::LongDashed => LineStyle::LongDashed,
+            }),
+            line_thickness: css.line_thickness,
+            background_color: css.background_color,
+            font_color: css.font_color,
+            font_size: css.font_size.map(FontSize::from_pt),
+            bold: matches!(font_style, FontStyle::Bold | FontStyle::Underline),
+            italic: matches!(font_style, FontStyle::Italic | FontStyle::Underline),
+            // plantuml uses huge values to get circles, which tikz does not like
+            round_corner: css.round_corner.map(|r| r.min(10)),
+        }
+    }
+}
+
+/// Named TikZ styles, which are defined in the options of the `tikzpicture`.
+#[derive(Default, Debug, Clone)]
+pub struct TikzStyleSheet {
+    styles: Vec<(String, TikzStyle)>,
+}
+
+impl TikzStyleSheet {
+    pub fn new() -> Self {
+        Self { styles: Vec::new() }
+    }
+
+    pub fn with_class(mut self, class: impl ToString, style: TikzStyle) -> Self {
+        self.push(class, style);
+        self
+    }
+
+    /// Adds a style, replacing the existing style with the same name
+    pub fn push(&mut self, class: impl ToString, style: TikzStyle) {
+        let class = class.to_string();
+        match self.styles.iter_mut().find(|(c, _)| *c == class) {
+            Some((_, s)) => *s = style,
+            None => self.styles.push((class, style)),
+        }
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        for (class, style) in other.styles {
+            self.push(class, style);
+        }
+    }
+
+    pub fn contains(&self, class: &str) -> bool {
+        self.styles.iter().any(|(c, _)| c == class)
+    }
+
+    pub(crate) fn write(&self, writer: &mut impl Write) -> RenderResult<()> {
+        for (class, style) in &self.styles {
+            write!(writer, "  {class}/.style={{")?;
+            style.write(writer)?;
+            writeln!(writer, "}},")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromIterator<(String, TikzStyle)> for TikzStyleSheet {
+    fn from_iter<T: IntoIterator<Item = (String, TikzStyle)>>(iter: T) -> Self {
+        let mut sheet = Self::new();
+        for (class, style) in iter {
+            sheet.push(class, style);
+        }
+        sheet
+    }
+}
+
+/// Converts a PlantUML style sheet, so the same styling can be used for both.
+///
+/// The `element` and `arrow` selectors become `every node` and `every edge`, other selectors are skipped.
+#[cfg(feature = "plantuml")]
+impl From<&crate::plantuml::theme::PlantUmlStyleSheet> for TikzStyleSheet {
+    fn from(sheet: &crate::plantuml::theme::PlantUmlStyleSheet) -> Self {
+        sheet
+            .classes
+            .iter()
+            .filter_map(|class| {
+                let name = match (class.is_selector, class.name.as_str()) {
+                    (false, name) => name,
+                    (true, "element") => "every node",
+                    (true, "arrow") => "every edge",
+                    (true, _) => return None,
+                };
+                Some((name.to_string(), TikzStyle::from(&class.element)))
+            })
+            .collect()
+    }
+}
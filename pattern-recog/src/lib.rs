@@ -1,19 +1,21 @@
-use std::{
-    collections::HashMap,
-    fs::OpenOptions,
-    io::{BufWriter, Write},
-    sync::LazyLock,
-};
-
-use data_parse::{JavaLabel, ParsedScopeGraph};
+use std::{collections::HashMap, io::Write, sync::LazyLock};
+
+use data_parse::{JavaLabel, ParsedScope, ParsedScopeGraph};
 use graphing::plantuml::{EdgeDirection, NodeType, PlantUmlDiagram, PlantUmlItem};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::IntoDeserializer};
 
-use crate::pattern::Pattern;
+use crate::pattern::{MatchMode, MatchedPattern, Pattern, Vf2Match};
 
+mod mapping;
 pub mod pattern;
+mod signature;
+mod sink;
 pub mod stat;
 
+pub use mapping::*;
+pub use signature::*;
+pub use sink::*;
+
 /// Use labels when matching patterns.
 const STRICT_LABELS: bool = true;
 
@@ -45,13 +47,15 @@ impl std::fmt::Display for Scope {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum MatchableLabel {
     /// VarDecl, Method etc.
     ClassMember,
     Parent,
     ExtendImpl,
     Other,
+    /// Label of the parsed graph that is not put in a bucket, see [`LabelMapping`]
+    Java(JavaLabel),
 }
 
 impl std::fmt::Display for MatchableLabel {
@@ -61,6 +65,26 @@ impl std::fmt::Display for MatchableLabel {
             MatchableLabel::Parent => write!(f, "Parent"),
             MatchableLabel::ExtendImpl => write!(f, "ExtendImpl"),
             MatchableLabel::Other => write!(f, "Other"),
+            MatchableLabel::Java(lbl) => write!(f, "Java:{lbl}"),
+        }
+    }
+}
+
+impl std::str::FromStr for MatchableLabel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ClassMember" => Ok(MatchableLabel::ClassMember),
+            "Parent" => Ok(MatchableLabel::Parent),
+            "ExtendImpl" => Ok(MatchableLabel::ExtendImpl),
+            "Other" => Ok(MatchableLabel::Other),
+            _ => match s.strip_prefix("Java:") {
+                Some(lbl) => JavaLabel::deserialize(lbl.into_deserializer())
+                    .map(MatchableLabel::Java)
+                    .map_err(|e: serde::de::value::Error| format!("Unknown label `{s}`: {e}")),
+                None => Err(format!("Unknown label `{s}`")),
+            },
         }
     }
 }
@@ -85,17 +109,75 @@ pub struct Edge {
     lbl: MatchableLabel,
 }
 
+impl Edge {
+    pub fn from(&self) -> Scope {
+        self.from
+    }
+
+    pub fn to(&self) -> Scope {
+        self.to
+    }
+
+    pub fn label(&self) -> &MatchableLabel {
+        &self.lbl
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(from = "SerializedScopeGraph")]
 pub struct ScopeGraph {
     scopes: Vec<Scope>,
     edges: Vec<Edge>,
     /// map with scopes and incoming/outgoing edges
     from_edge_map: HashMap<Scope, Vec<Edge>>,
     to_edge_map: HashMap<Scope, Vec<Edge>>,
+    /// Scopes of the graph this graph was converted from, see [`ScopeGraph::parsed_scope`]
+    parsed_scopes: HashMap<Scope, ParsedScope>,
+    /// Indices into `edges` of the edges between two scopes
+    #[serde(skip)]
+    edge_index: HashMap<(Scope, Scope), Vec<usize>>,
+    /// Indices into `edges` of the outgoing/incoming edges of a scope with a label
+    #[serde(skip)]
+    outgoing_by_label: HashMap<(Scope, MatchableLabel), Vec<usize>>,
+    #[serde(skip)]
+    incoming_by_label: HashMap<(Scope, MatchableLabel), Vec<usize>>,
+    /// Degrees of every scope, used to prune candidates when matching with vf2
+    #[serde(skip)]
+    signatures: HashMap<Scope, NodeSignature>,
+}
+
+/// Fields of a serialized [`ScopeGraph`], the indices are rebuilt when deserializing
+#[derive(Deserialize)]
+struct SerializedScopeGraph {
+    scopes: Vec<Scope>,
+    edges: Vec<Edge>,
+    #[serde(default)]
+    parsed_scopes: HashMap<Scope, ParsedScope>,
+}
+
+impl From<SerializedScopeGraph> for ScopeGraph {
+    fn from(value: SerializedScopeGraph) -> Self {
+        let mut graph = Self::new();
+        for s in value.scopes {
+            graph.add_node(s);
+        }
+        for e in value.edges {
+            graph.push_edge(e);
+        }
+        graph.parsed_scopes = value.parsed_scopes;
+        graph
+    }
 }
 
 impl From<ParsedScopeGraph> for ScopeGraph {
     fn from(value: ParsedScopeGraph) -> Self {
+        Self::from_parsed(value, &LabelMapping::default())
+    }
+}
+
+impl ScopeGraph {
+    /// Converts a parsed graph, with its labels turned into [`MatchableLabel`]s by `mapping`
+    pub fn from_parsed(value: ParsedScopeGraph, mapping: &LabelMapping) -> Self {
         // let scopes = value.scopes
         // .into_keys()
         // .map(|s| Arc::from(s.name))
@@ -109,40 +191,22 @@ impl From<ParsedScopeGraph> for ScopeGraph {
             acc
         });
 
-        let scopes = index_map.values().copied().collect::<Vec<_>>();
-
-        let edges = value
-            .edges
-            .into_iter()
-            .filter_map(|e| {
-                let from = *index_map.get(&e.from)?;
-                let to = *index_map.get(&e.to)?;
-                Some(Edge {
-                    from,
-                    to,
-                    lbl: e.label.into(),
-                })
-            })
-            .collect::<Vec<_>>();
-
-        let (from_edge_map, to_edge_map) = edges.iter().fold(
-            (HashMap::new(), HashMap::new()),
-            |(mut from_map, mut to_map), e| {
-                from_map
-                    .entry(e.from)
-                    .or_insert_with(Vec::new)
-                    .push(e.clone());
-                to_map.entry(e.to).or_insert_with(Vec::new).push(e.clone());
-                (from_map, to_map)
-            },
-        );
-
-        ScopeGraph {
-            scopes,
-            edges,
-            from_edge_map,
-            to_edge_map,
+        let mut graph = Self::new();
+        for s in index_map.values() {
+            graph.add_node(*s);
+        }
+        for e in value.edges {
+            let (Some(from), Some(to)) = (index_map.get(&e.from), index_map.get(&e.to)) else {
+                continue;
+            };
+            graph.push_edge(Edge {
+                from: *from,
+                to: *to,
+                lbl: mapping.map(&e.label),
+            });
         }
+        graph.parsed_scopes = index_map.into_iter().map(|(p, s)| (s, p)).collect();
+        graph
     }
 }
 
@@ -159,6 +223,11 @@ impl ScopeGraph {
             edges: Vec::new(),
             from_edge_map: HashMap::new(),
             to_edge_map: HashMap::new(),
+            parsed_scopes: HashMap::new(),
+            edge_index: HashMap::new(),
+            outgoing_by_label: HashMap::new(),
+            incoming_by_label: HashMap::new(),
+            signatures: HashMap::new(),
         }
     }
 
@@ -166,8 +235,15 @@ impl ScopeGraph {
         self.scopes.iter()
     }
 
+    /// Scope that `scope` was converted from, `None` if this graph was not converted from a [`ParsedScopeGraph`]
+    pub fn parsed_scope(&self, scope: Scope) -> Option<&ParsedScope> {
+        self.parsed_scopes.get(&scope)
+    }
+
     pub fn add_node<S: Into<Scope>>(&mut self, node: S) {
-        self.scopes.push(node.into());
+        let node = node.into();
+        self.signatures.entry(node).or_default();
+        self.scopes.push(node);
     }
 
     pub fn add_edge<S: Into<Scope>>(&mut self, from: S, to: S) {
@@ -180,11 +256,37 @@ impl ScopeGraph {
         to: S,
         lbl: L,
     ) {
-        let edge = Edge {
+        self.push_edge(Edge {
             from: from.into(),
             to: to.into(),
             lbl: lbl.into(),
-        };
+        });
+    }
+
+    fn push_edge(&mut self, edge: Edge) {
+        let index = self.edges.len();
+        let between = self.edge_index.entry((edge.from, edge.to)).or_default();
+        let new_neighbor = between.is_empty();
+        let new_label = between.iter().all(|i| self.edges[*i].lbl != edge.lbl);
+        between.push(index);
+        self.signatures.entry(edge.from).or_default().add_outgoing(
+            Some(&edge.lbl),
+            new_neighbor,
+            new_label,
+        );
+        self.signatures.entry(edge.to).or_default().add_incoming(
+            Some(&edge.lbl),
+            new_neighbor,
+            new_label,
+        );
+        self.outgoing_by_label
+            .entry((edge.from, edge.lbl.clone()))
+            .or_default()
+            .push(index);
+        self.incoming_by_label
+            .entry((edge.to, edge.lbl.clone()))
+            .or_default()
+            .push(index);
         self.from_edge_map
             .entry(edge.from)
             .or_default()
@@ -196,24 +298,43 @@ impl ScopeGraph {
         self.edges.push(edge);
     }
 
+    /// Indices of the edges in `index` of `s` with any of `lbls`, in the order they were added
+    fn edges_with_labels(
+        index: &HashMap<(Scope, MatchableLabel), Vec<usize>>,
+        s: Scope,
+        lbls: &[MatchableLabel],
+    ) -> Vec<usize> {
+        let mut edges = lbls
+            .iter()
+            .filter_map(|l| index.get(&(s, l.clone())))
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+        edges.dedup();
+        edges
+    }
+
+    /// Degrees of `s`, see [`NodeSignature`]
+    pub fn signature(&self, s: impl Into<Scope>) -> Option<&NodeSignature> {
+        self.signatures.get(&s.into())
+    }
+
     pub fn get_outgoing_edges_with_labels(
         &self,
         s: impl Into<Scope>,
         lbls: &[MatchableLabel],
     ) -> impl Iterator<Item = &Edge> {
         let s = s.into();
-        self.from_edge_map
-            .get(&s)
-            .into_iter()
-            // .flatten()
-            .flat_map(|edges| {
-                edges.iter().filter(|e| {
-                    match STRICT_LABELS && !lbls.is_empty() {
-                        true => lbls.contains(&e.lbl),
-                        false => true, // accept all edges
-                    }
-                })
-            })
+        let filtered = STRICT_LABELS && !lbls.is_empty();
+        let all = match filtered {
+            true => None,
+            false => self.from_edge_map.get(&s),
+        };
+        let labeled = filtered.then(|| Self::edges_with_labels(&self.outgoing_by_label, s, lbls));
+        all.into_iter()
+            .flatten()
+            .chain(labeled.into_iter().flatten().map(|i| &self.edges[i]))
     }
 
     pub fn get_incoming_edges_with_labels(
@@ -222,18 +343,15 @@ impl ScopeGraph {
         lbls: &[MatchableLabel],
     ) -> impl Iterator<Item = &Edge> {
         let s = s.into();
-        self.to_edge_map
-            .get(&s)
-            .into_iter()
-            // .flatten()
-            .flat_map(|edges| {
-                edges.iter().filter(|e| {
-                    match STRICT_LABELS {
-                        true => lbls.contains(&e.lbl),
-                        false => true, // accept all edges
-                    }
-                })
-            })
+        let filtered = STRICT_LABELS && !lbls.is_empty();
+        let all = match filtered {
+            true => None,
+            false => self.to_edge_map.get(&s),
+        };
+        let labeled = filtered.then(|| Self::edges_with_labels(&self.incoming_by_label, s, lbls));
+        all.into_iter()
+            .flatten()
+            .chain(labeled.into_iter().flatten().map(|i| &self.edges[i]))
     }
 
     pub fn from_edges<S: Into<Scope>, L: Into<MatchableLabel>>(
@@ -254,39 +372,32 @@ impl ScopeGraph {
         graph
     }
 
-    pub fn match_subgraph(&self, pattern: &Pattern, name: &str) -> Vec<Vec<vf2::NodeIndex>> {
-        let base_path = format!("output/patterns/{}/{}", name, *TIMESTAMP);
-        std::fs::create_dir_all(&base_path).unwrap();
-
-        let scope_graph_file = format!("{}/graph.json", base_path);
-        let file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(&scope_graph_file)
-            .unwrap();
-        let mut bufwriter = BufWriter::new(file);
-        serde_json::to_writer(&mut bufwriter, &self).unwrap();
-
-        let fname = format!("{}/{}.txt", base_path, pattern.file_name());
-        let file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(fname)
-            .unwrap();
-        let mut bufwriter = BufWriter::new(file);
+    /// Finds the matches of `pattern`, writing the searched graph to `graph.json`
+    /// and the scopes of every match to `<pattern>.txt` of `output`.
+    pub fn match_subgraph(
+        &self,
+        pattern: &Pattern,
+        mode: MatchMode,
+        output: &mut dyn OutputSink,
+    ) -> std::io::Result<Vec<Vf2Match>> {
+        let mut writer = output.writer("graph.json")?;
+        serde_json::to_writer(&mut writer, &self)?;
+        writer.flush()?;
+        drop(writer);
 
         let pattern_graph = pattern.subgraph();
-        let vf2 = vf2::induced_subgraph_isomorphisms(&pattern_graph, self);
+        let isomorphisms = mode.isomorphisms(&pattern_graph, self);
+        let matches = pattern
+            .prune_matches(isomorphisms)
+            .map(|iso| Vf2Match::new(&pattern_graph, self, &iso))
+            .collect::<Vec<_>>();
 
-        pattern
-            .prune_matches(vf2.iter())
-            // .inspect(|v| println!("match: {:?}", v))
-            .inspect(|v| {
-                let _ = bufwriter.write(format!("{:?},\n", v).as_bytes()).unwrap();
-            })
-            .collect()
+        let mut writer = output.writer(&format!("{}.txt", pattern.file_name()))?;
+        for m in &matches {
+            writeln!(writer, "{:?},", m.to_vec())?;
+        }
+        writer.flush()?;
+        Ok(matches)
     }
 
     pub fn diagram(&self) -> PlantUmlDiagram {
@@ -302,18 +413,14 @@ impl ScopeGraph {
 
     pub fn find_edge<S: Into<Scope>>(&self, from: S, to: S) -> Option<&Edge> {
         let (from, to) = (from.into(), to.into());
-        let from_edges = self.from_edge_map.get(&from)?;
-        let to_edges = self.to_edge_map.get(&to)?;
-
-        match from_edges.len() < to_edges.len() {
-            true => from_edges.iter().find(|e| e.to == to),
-            false => to_edges.iter().find(|e| e.from == from),
-        }
+        let first = self.edge_index.get(&(from, to))?.first()?;
+        Some(&self.edges[*first])
     }
 }
 
+/// Node indices are the ids of the scopes, node labels are their [`NodeSignature`]
 impl vf2::Graph for ScopeGraph {
-    type NodeLabel = Scope;
+    type NodeLabel = NodeSignature;
 
     type EdgeLabel = MatchableLabel;
 
@@ -326,7 +433,7 @@ impl vf2::Graph for ScopeGraph {
     }
 
     fn node_label(&self, node: vf2::NodeIndex) -> Option<&Self::NodeLabel> {
-        self.scopes.get(node)
+        self.signatures.get(&Scope(node))
     }
 
     fn neighbors(
@@ -334,15 +441,14 @@ impl vf2::Graph for ScopeGraph {
         node: vf2::NodeIndex,
         direction: vf2::Direction,
     ) -> impl Iterator<Item = vf2::NodeIndex> {
-        self.from_edge_map
-            .get(&Scope(node))
-            .into_iter()
-            .flat_map(move |edges| {
-                edges.iter().map(move |e| match direction {
-                    vf2::Direction::Outgoing => e.to.0,
-                    vf2::Direction::Incoming => e.from.0,
-                })
-            })
+        let edges = match direction {
+            vf2::Direction::Outgoing => self.from_edge_map.get(&Scope(node)),
+            vf2::Direction::Incoming => self.to_edge_map.get(&Scope(node)),
+        };
+        edges.into_iter().flatten().map(move |e| match direction {
+            vf2::Direction::Outgoing => e.to.0,
+            vf2::Direction::Incoming => e.from.0,
+        })
     }
 
     fn contains_edge(&self, source: vf2::NodeIndex, target: vf2::NodeIndex) -> bool {
@@ -354,7 +460,6 @@ impl vf2::Graph for ScopeGraph {
         source: vf2::NodeIndex,
         target: vf2::NodeIndex,
     ) -> Option<&Self::EdgeLabel> {
-        println!("edge_label: source: {}, target: {}", source, target);
         self.find_edge(source, target).map(|e| &e.lbl)
     }
 }
@@ -1,13 +1,113 @@
-use data_parse::ParsedScopeGraph;
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+use data_parse::Corpus;
 use graphing::Renderer;
 use pattern_recog::{pattern::*, *};
 
+/// Searches scope graphs for patterns and reports the matches per graph
+#[derive(Parser, Debug)]
+struct Cli {
+    /// Scope graph files to search, every file is a corpus named after the file
+    #[arg(required = true)]
+    graphs: Vec<PathBuf>,
+    /// JSON file with the matchers to use, see `PatternConfig`
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Patterns to search for, every pattern if not given
+    #[arg(long, value_enum, value_delimiter = ',')]
+    patterns: Vec<PatternArg>,
+    /// Minimum size of the matches of a pattern, e.g. `chain=5`
+    #[arg(long, value_parser = parse_size)]
+    min: Vec<(PatternArg, usize)>,
+    /// Maximum size of the matches of a pattern, e.g. `star=20`
+    #[arg(long, value_parser = parse_size)]
+    max: Vec<(PatternArg, usize)>,
+    /// Remove the scopes of Apache Commons, keeping the scopes of the standard library
+    #[arg(long)]
+    std_only: bool,
+    /// Format of the output, the per-corpus statistics are always printed
+    #[arg(long, value_enum, default_value_t = OutputFormat::Latex)]
+    format: OutputFormat,
+    /// Directory the output is written to
+    #[arg(long, default_value = "output")]
+    output: PathBuf,
+    /// Width of the buckets of the size histograms written with the csv format
+    #[arg(long, default_value_t = 1)]
+    bucket_width: usize,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum PatternArg {
+    Chain,
+    Fanout,
+    Tree,
+    Diamond,
+    DeepDiamond,
+    Circle,
+    Star,
+    Clique,
+    Biclique,
+}
+
+impl PatternArg {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Chain => ChainMatcher::NAME,
+            Self::Fanout => FanoutMatcher::NAME,
+            Self::Tree => TreeMatcher::NAME,
+            Self::Diamond => DiamondMatcher::NAME,
+            Self::DeepDiamond => DeepDiamondMatcher::NAME,
+            Self::Circle => CircleMatcher::NAME,
+            Self::Star => StarMatcher::NAME,
+            Self::Clique => CliqueMatcher::NAME,
+            Self::Biclique => BicliqueMatcher::NAME,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    /// LaTeX tables of all corpora in `patterns.tex`
+    Latex,
+    /// Every match of a corpus in `<corpus>.json`
+    Json,
+    /// Statistics of all corpora in `patterns.csv` and their size histograms in `histograms.csv`
+    Csv,
+}
+
+/// Parses `<pattern>=<size>`
+fn parse_size(s: &str) -> Result<(PatternArg, usize), String> {
+    let (pattern, size) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected <pattern>=<size>, got `{s}`"))?;
+    let pattern = PatternArg::from_str(pattern, true)?;
+    let size = size
+        .parse()
+        .map_err(|e| format!("invalid size `{size}`: {e}"))?;
+    Ok((pattern, size))
+}
+
 fn main() {
-    real_graph();
-    // test();
+    let cli = Cli::parse();
+    let mut config = match &cli.config {
+        Some(path) => PatternConfig::from_json_file(path).unwrap(),
+        None => PatternConfig::default(),
+    };
+    if !cli.patterns.is_empty() {
+        config = config.with_patterns(cli.patterns.iter().map(|p| p.name()));
+    }
+    for (pattern, min) in &cli.min {
+        config = config.with_min_size(pattern.name(), *min);
+    }
+    for (pattern, max) in &cli.max {
+        config = config.with_max_size(pattern.name(), *max);
+    }
+    run(&cli, &config);
+    // test(&config);
 }
 
-fn test() {
+fn test(config: &PatternConfig) {
     // let graph = ScopeGraph::from_edges([
     //     (1, MatchableLabel::Parent, 0),
     //     (2, MatchableLabel::Parent, 0),
@@ -55,42 +155,109 @@ fn test() {
 
     graph.diagram().render_to_file("output/graph.puml").unwrap();
 
-    // let matches = graph.match_subgraph(&pattern, "test");
+    // let matches = graph
+    //     .match_subgraph(&pattern, MatchMode::NonInduced, &mut DirectoryOutput::timestamped("test"))
+    //     .unwrap();
     // println!("found {0:?} matches", matches.len());
     let timer = std::time::Instant::now();
-    let c_matches = CircleMatcher::search(&graph)
+    let c_matches = CircleMatcher::default()
+        .search(&graph)
         .into_iter()
         .collect::<Vec<_>>();
     println!("{:?}", timer.elapsed());
     for m in c_matches {
         println!("m: {0:?}", m)
     }
+    for (len, count) in CycleEnumerator::new().with_max_len(4).length_counts(&graph) {
+        println!("{count} cycles of length {len}");
+    }
+
+    let spec = "1 -ExtendImpl-> 0; 4 -ExtendImpl-> 1; 4 -> 2; 2 -ExtendImpl-> 0"
+        .parse::<PatternSpec>()
+        .unwrap()
+        .with_max_distance(1);
+    for m in spec.search(&graph) {
+        println!("{} (distance {}): {:?}", spec.name(), m.distance(), m)
+    }
+
+    for subgraph in SubgraphMiner::new()
+        .with_max_nodes(3)
+        .mine(&graph, &mut IndicatifProgress::default())
+    {
+        println!("{subgraph}");
+    }
+
+    for significance in SignificanceTest::new()
+        .with_config(config.clone())
+        .test_all(&graph)
+    {
+        println!("{significance}");
+    }
+
+    let matches = PatternMatches::from_graph(&graph, config, &mut IndicatifProgress::default());
+    matches
+        .to_diagram(&graph)
+        .render_to_file("output/matches.puml")
+        .unwrap();
+    matches.to_json(&graph, "output/matches.json").unwrap();
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&matches.summary()).unwrap()
+    );
+    for (name, overlap) in matches.overlap_stats() {
+        println!("{name}: {overlap}");
+    }
+    matches.to_csv(&graph, "output/matches.csv").unwrap();
 }
 
-fn real_graph() {
-    fn inner(path: &str, std_only: bool) -> PatternMatches {
-        println!("Parsing graph from file...");
-        let mut graph = ParsedScopeGraph::from_file(path).unwrap();
+fn run(cli: &Cli, config: &PatternConfig) {
+    std::fs::create_dir_all(&cli.output).unwrap();
+    println!("Parsing graphs from files...");
+    let corpus = Corpus::load(&cli.graphs).unwrap();
+    println!(
+        "Parsed {} scopes and {} edges",
+        corpus.stats.num_scopes, corpus.stats.num_edges
+    );
 
-        if std_only {
+    let mut report = CorpusReport::new();
+    for (name, mut graph) in corpus.into_graphs() {
+        if cli.std_only {
             graph.filter_scopes(|s| !s.resource.contains("commons"));
         }
+        let graph = ScopeGraph::from(graph);
+        let matches = PatternMatches::from_graph(&graph, config, &mut IndicatifProgress::default());
+        if cli.format == OutputFormat::Json {
+            matches
+                .to_json(&graph, cli.output.join(format!("{name}.json")))
+                .unwrap();
+        }
+        report.push(name, matches);
+    }
 
-        graph.scopes = graph.scopes.into_iter().collect();
-        let searchable_graph = ScopeGraph::from(graph);
-        PatternMatches::from_graph(&searchable_graph)
+    match cli.format {
+        OutputFormat::Latex => {
+            let tab = report
+                .latex_table()
+                .with_columns(
+                    LatexColumn::DEFAULT
+                        .iter()
+                        .copied()
+                        .chain([LatexColumn::Coverage]),
+                )
+                .with_caption("Pattern matches per corpus")
+                .with_label("tab:pattern-matches")
+                .render();
+            let latex = format!("{}\n\n{}\n", tab, report.to_latex_table());
+            std::fs::write(cli.output.join("patterns.tex"), latex).unwrap();
+        }
+        OutputFormat::Csv => {
+            report.to_csv(cli.output.join("patterns.csv")).unwrap();
+            report
+                .histograms_to_csv(cli.bucket_width, cli.output.join("histograms.csv"))
+                .unwrap();
+        }
+        // written per corpus
+        OutputFormat::Json => (),
     }
-    let m_csv = inner("data-parse/raw/commons-csv-scopegraph.json", false);
-    let m_io = inner("data-parse/raw/commons-io-scopegraph.json", false);
-    let m_lang3 = inner("data-parse/raw/commons-lang-scopegraph.json", false);
-    // let m_std = inner("data-parse/raw/commons-csv-scopegraph.json", true);
-
-    let tab = [
-        // m_std.to_latex_table("Java Standard Library"),
-        m_csv.to_latex_table("Commons CSV"),
-        m_io.to_latex_table("Commons IO"),
-        m_lang3.to_latex_table("Commons Lang3"),
-    ]
-    .join("\n");
-    println!("{}", tab);
+    print!("{report}");
 }
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use data_parse::JavaLabel;
+use serde::{Deserialize, Serialize};
+
+use crate::MatchableLabel;
+
+/// How the labels of a [`ParsedScopeGraph`](data_parse::ParsedScopeGraph) are turned into [`MatchableLabel`]s,
+/// see [`ScopeGraph::from_parsed`](crate::ScopeGraph::from_parsed).
+///
+/// By default labels are bucketed into ClassMember, Parent, ExtendImpl and Other.
+/// Unbucketed labels are kept as [`MatchableLabel::Java`], e.g. to match WithType or LocalType edges.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct LabelMapping {
+    /// Labels that are mapped to something else than their bucket
+    overrides: HashMap<JavaLabel, MatchableLabel>,
+    /// Keep labels without an override unbucketed
+    unbucketed: bool,
+}
+
+impl LabelMapping {
+    /// Every label is put in its bucket, as by `From<JavaLabel>`
+    pub fn bucketed() -> Self {
+        Self::default()
+    }
+
+    /// Every label is kept as [`MatchableLabel::Java`]
+    pub fn unbucketed() -> Self {
+        Self {
+            overrides: HashMap::new(),
+            unbucketed: true,
+        }
+    }
+
+    /// Map `label` to `target` instead
+    pub fn with(mut self, label: JavaLabel, target: MatchableLabel) -> Self {
+        self.overrides.insert(label, target);
+        self
+    }
+
+    /// Keep `label` unbucketed, e.g. `LabelMapping::bucketed().keep(JavaLabel::WithType)`
+    pub fn keep(self, label: JavaLabel) -> Self {
+        let target = MatchableLabel::Java(label.clone());
+        self.with(label, target)
+    }
+
+    pub fn map(&self, label: &JavaLabel) -> MatchableLabel {
+        match self.overrides.get(label) {
+            Some(target) => target.clone(),
+            None if self.unbucketed => MatchableLabel::Java(label.clone()),
+            None => label.clone().into(),
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    MatchableLabel, Scope, ScopeGraph,
+    pattern::{MatchedPattern, OverlapPolicy, PatternMatcher, SizeBounds},
+};
+
+const BICLIQUE_LABELS: &[MatchableLabel] = &[MatchableLabel::ClassMember];
+
+#[derive(Serialize, Debug)]
+pub struct BicliqueMatch {
+    /// Scopes with an edge to every target
+    sources: Vec<Scope>,
+    targets: Vec<Scope>,
+}
+
+impl BicliqueMatch {
+    pub fn sources(&self) -> &[Scope] {
+        &self.sources
+    }
+
+    pub fn targets(&self) -> &[Scope] {
+        &self.targets
+    }
+}
+
+impl MatchedPattern for BicliqueMatch {
+    /// Number of sources
+    fn size(&self) -> usize {
+        self.sources.len()
+    }
+
+    fn scopes(&self) -> impl Iterator<Item = &Scope> {
+        self.sources.iter().chain(self.targets.iter())
+    }
+}
+
+/// Finds complete bipartite subgraphs, over ClassMember edges by default: several sources that all have an edge to the same targets,
+/// e.g. classes that reference the same utility scopes.
+///
+/// The default of at least two sources and two targets, `K2,2`, is also known as a butterfly.
+/// Every set of targets is reported once with all sources it has in common,
+/// so a larger biclique is reported once for every subset of its targets of the configured size.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct BicliqueMatcher {
+    labels: Vec<MatchableLabel>,
+    targets: usize,
+    min: usize,
+    max: Option<usize>,
+}
+
+impl Default for BicliqueMatcher {
+    fn default() -> Self {
+        Self {
+            labels: BICLIQUE_LABELS.to_vec(),
+            targets: 2,
+            min: 2,
+            max: None,
+        }
+    }
+}
+
+impl BicliqueMatcher {
+    /// Labels of the edges that are followed, all labels if empty
+    pub fn with_labels(mut self, labels: impl IntoIterator<Item = MatchableLabel>) -> Self {
+        self.labels = labels.into_iter().collect();
+        self
+    }
+
+    /// Number of targets that the sources of a match have in common
+    pub fn with_targets(mut self, targets: usize) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    /// Minimum number of sources of a match
+    pub fn with_min_sources(mut self, min: usize) -> Self {
+        self.min = min;
+        self
+    }
+
+    /// Maximum number of sources of a match
+    pub fn with_max_sources(mut self, max: usize) -> Self {
+        self.max = Some(max);
+        self
+    }
+}
+
+impl PatternMatcher for BicliqueMatcher {
+    type Match = BicliqueMatch;
+    const OVERLAP: OverlapPolicy = OverlapPolicy::AllowOverlap;
+    const NAME: &str = "Biclique";
+
+    /// Finds the bicliques for which `scope` is the source with the lowest index
+    fn find_pattern_for_scope(&self, graph: &ScopeGraph, scope: Scope) -> Vec<Self::Match> {
+        let mut targets = graph
+            .get_outgoing_edges_with_labels(scope, &self.labels)
+            .map(|e| e.to)
+            .filter(|t| *t != scope)
+            .collect::<Vec<_>>();
+        targets.sort_by_key(|t| t.0);
+        targets.dedup();
+
+        let mut search = BicliqueSearch {
+            graph,
+            labels: &self.labels,
+            anchor: scope,
+            targets,
+            min_sources: self.min.max(1),
+            num_targets: self.targets.max(1),
+            matches: Vec::new(),
+        };
+        search.extend(0, Vec::new(), None);
+        search.matches
+    }
+
+    fn size_bounds(&self) -> SizeBounds {
+        SizeBounds {
+            min: self.min,
+            max: self.max,
+        }
+    }
+}
+
+struct BicliqueSearch<'a> {
+    graph: &'a ScopeGraph,
+    labels: &'a [MatchableLabel],
+    anchor: Scope,
+    /// Targets of the anchor, sorted
+    targets: Vec<Scope>,
+    min_sources: usize,
+    num_targets: usize,
+    matches: Vec<BicliqueMatch>,
+}
+
+impl BicliqueSearch<'_> {
+    /// Adds targets from index `start` on to `chosen`, as long as they keep enough sources in `common`
+    fn extend(&mut self, start: usize, chosen: Vec<Scope>, common: Option<HashSet<Scope>>) {
+        if chosen.len() == self.num_targets {
+            let mut sources = common.unwrap_or_default().into_iter().collect::<Vec<_>>();
+            sources.sort_by_key(|s| s.0);
+            if sources.first() == Some(&self.anchor) {
+                self.matches.push(BicliqueMatch {
+                    sources,
+                    targets: chosen,
+                });
+            }
+            return;
+        }
+
+        for i in start..self.targets.len() {
+            let target = self.targets[i];
+            let sources = self
+                .graph
+                .get_incoming_edges_with_labels(target, self.labels)
+                .map(|e| e.from);
+            let next = match &common {
+                Some(common) => sources.filter(|s| common.contains(s)).collect(),
+                None => sources.collect::<HashSet<_>>(),
+            };
+            if next.len() < self.min_sources {
+                continue;
+            }
+            let mut next_chosen = chosen.clone();
+            next_chosen.push(target);
+            self.extend(i + 1, next_chosen, Some(next));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_butterfly() {
+        // 0, 1 and 2 share both targets, 3 only has the first
+        let graph = ScopeGraph::from_edges(
+            [
+                (0, 10),
+                (0, 11),
+                (1, 10),
+                (1, 11),
+                (2, 11),
+                (2, 10),
+                (3, 10),
+            ]
+            .map(|(a, b)| (a, MatchableLabel::ClassMember, b)),
+        );
+        let matches = BicliqueMatcher::default().search(&graph);
+        // reported once, for the source with the lowest index
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].sources(), &[Scope(0), Scope(1), Scope(2)]);
+        assert_eq!(matches[0].targets(), &[Scope(10), Scope(11)]);
+
+        let mut single = BicliqueMatcher::default().with_targets(1).search(&graph);
+        single.sort_by_key(|m| m.targets()[0].0);
+        assert_eq!(single.len(), 2);
+        assert_eq!(single[0].size(), 4);
+        assert_eq!(single[1].size(), 3);
+
+        let bounded = BicliqueMatcher::default()
+            .with_max_sources(2)
+            .search(&graph);
+        assert!(bounded.is_empty());
+    }
+}
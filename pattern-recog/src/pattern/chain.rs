@@ -1,12 +1,15 @@
 use std::rc::Rc;
 
+use serde::{Deserialize, Serialize, ser::SerializeStruct};
+
 use crate::{
     MatchableLabel, Scope, ScopeGraph,
-    pattern::{MatchedPattern, PatternMatcher},
+    pattern::{MatchedPattern, OverlapPolicy, PatternMatcher, SizeBounds},
 };
 
 const CHAIN_LABELS: &[MatchableLabel] = &[MatchableLabel::Parent, MatchableLabel::ExtendImpl];
-const MIN_SIZE: usize = 6;
+/// Minimum number of scopes of a [`ChainMatch`] when the length of [`ChainMatcher`] is not given
+pub const DEFAULT_CHAIN_LEN: usize = 7;
 
 #[derive(Clone, Debug)]
 pub(crate) struct ChainScope {
@@ -67,6 +70,15 @@ impl ChainMatch {
     }
 }
 
+/// Serialized as the list of its scopes, from the first to the last scope of the chain
+impl Serialize for ChainMatch {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("ChainMatch", 1)?;
+        s.serialize_field("scopes", &self.to_vec())?;
+        s.end()
+    }
+}
+
 impl MatchedPattern for ChainMatch {
     fn size(&self) -> usize {
         let mut count = 0;
@@ -91,28 +103,66 @@ impl MatchedPattern for ChainMatch {
     }
 }
 
-pub struct ChainMatcher;
+/// Finds chains of edges that end in a scope without outgoing edges, over Parent and ExtendImpl edges by default.
+///
+/// Every scope is part of at most one chain.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ChainMatcher {
+    labels: Vec<MatchableLabel>,
+    min: usize,
+    max: Option<usize>,
+}
+
+impl Default for ChainMatcher {
+    fn default() -> Self {
+        Self {
+            labels: CHAIN_LABELS.to_vec(),
+            min: DEFAULT_CHAIN_LEN,
+            max: None,
+        }
+    }
+}
+
+impl ChainMatcher {
+    /// Labels of the edges that are followed, all labels if empty
+    pub fn with_labels(mut self, labels: impl IntoIterator<Item = MatchableLabel>) -> Self {
+        self.labels = labels.into_iter().collect();
+        self
+    }
+
+    /// Minimum number of scopes in a chain
+    pub fn with_min_len(mut self, min: usize) -> Self {
+        self.min = min;
+        self
+    }
+
+    /// Maximum number of scopes in a chain, longer chains are left out rather than cut off
+    pub fn with_max_len(mut self, max: usize) -> Self {
+        self.max = Some(max);
+        self
+    }
+}
 
 impl PatternMatcher for ChainMatcher {
     type Match = ChainMatch;
-    const EXCLUSIVE: bool = true;
+    const OVERLAP: OverlapPolicy = OverlapPolicy::Exclusive;
     const NAME: &str = "Chain";
 
     /// Find all chains starting in `cur_scope`
-    fn find_pattern_for_scope(graph: &ScopeGraph, cur_scope: Scope) -> Vec<Self::Match> {
+    fn find_pattern_for_scope(&self, graph: &ScopeGraph, cur_scope: Scope) -> Vec<Self::Match> {
         let mut cur_matches = vec![ChainMatch::from_scope(cur_scope)];
         let mut finished = Vec::new();
 
         while let Some(m) = cur_matches.pop() {
             let mut outgoing_edges = graph
-                .get_outgoing_edges_with_labels(m.tail(), CHAIN_LABELS)
+                .get_outgoing_edges_with_labels(m.tail(), &self.labels)
                 .peekable();
 
             match outgoing_edges.peek() {
                 // leaf node
                 None => {
-                    if m.size() > MIN_SIZE {
-                        // only add matches with more than one node
+                    if self.size_bounds().contains(m.size()) {
                         finished.push(m);
                     }
                 }
@@ -127,4 +177,11 @@ impl PatternMatcher for ChainMatcher {
         }
         finished
     }
+
+    fn size_bounds(&self) -> SizeBounds {
+        SizeBounds {
+            min: self.min,
+            max: self.max,
+        }
+    }
 }
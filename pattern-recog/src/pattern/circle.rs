@@ -2,9 +2,13 @@ use std::rc::Rc;
 
 use hashbrown::HashMap;
 
+use serde::{Deserialize, Serialize, ser::SerializeStruct};
+
 use crate::{
     MatchableLabel, Scope, ScopeGraph,
-    pattern::{ChainScope, ChainScopeIter, MatchedPattern, PatternMatcher},
+    pattern::{
+        ChainScope, ChainScopeIter, MatchedPattern, OverlapPolicy, PatternMatcher, SizeBounds,
+    },
 };
 
 // const CHAIN_LABELS: &[MatchableLabel] = &[MatchableLabel::Parent, MatchableLabel::ExtendImpl];
@@ -60,6 +64,15 @@ impl CircleMatch {
     }
 }
 
+/// Serialized as the list of its scopes, from the first to the last scope of the circle
+impl Serialize for CircleMatch {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("CircleMatch", 1)?;
+        s.serialize_field("scopes", &self.to_vec())?;
+        s.end()
+    }
+}
+
 impl MatchedPattern for CircleMatch {
     fn size(&self) -> usize {
         self.size
@@ -78,32 +91,67 @@ impl MatchedPattern for CircleMatch {
     }
 }
 
-pub struct CircleMatcher;
+/// Finds paths that return to the scope they started in.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct CircleMatcher {
+    labels: Vec<MatchableLabel>,
+    min: usize,
+    max: Option<usize>,
+}
+
+impl CircleMatcher {
+    /// Labels of the edges that are followed, all labels if empty
+    pub fn with_labels(mut self, labels: impl IntoIterator<Item = MatchableLabel>) -> Self {
+        self.labels = labels.into_iter().collect();
+        self
+    }
+
+    /// Minimum number of steps in a circle, counting the first scope at both ends
+    pub fn with_min_len(mut self, min: usize) -> Self {
+        self.min = min;
+        self
+    }
+
+    /// Maximum number of steps in a circle, counting the first scope at both ends
+    pub fn with_max_len(mut self, max: usize) -> Self {
+        self.max = Some(max);
+        self
+    }
+}
 
 impl PatternMatcher for CircleMatcher {
     type Match = CircleMatch;
-    const EXCLUSIVE: bool = false;
+    const OVERLAP: OverlapPolicy = OverlapPolicy::AllowOverlap;
     const NAME: &str = "Circle";
 
     /// Find all chains starting in `cur_scope`
-    fn find_pattern_for_scope(graph: &ScopeGraph, cur_scope: Scope) -> Vec<Self::Match> {
+    fn find_pattern_for_scope(&self, graph: &ScopeGraph, cur_scope: Scope) -> Vec<Self::Match> {
         let mut cur_matches = vec![CircleMatch::from_scope(cur_scope)];
         let mut finished = Vec::new();
 
         while let Some(m) = cur_matches.pop() {
-            let outgoing_edges = graph.get_outgoing_edges_with_labels(m.tail(), CHAIN_LABELS);
+            let outgoing_edges = graph.get_outgoing_edges_with_labels(m.tail(), &self.labels);
 
             for edge in outgoing_edges {
                 let step = m.clone().step(edge.to);
                 if step.size() >= MIN_SIZE && step.is_circular() {
                     finished.push(step);
-                } else if !m.contains(&edge.to) {
+                } else if !m.contains(&edge.to) && self.max.is_none_or(|max| step.size() < max) {
+                    // a longer path can not close a circle within the maximum length
                     cur_matches.push(step);
                 }
             }
         }
         finished
     }
+
+    fn size_bounds(&self) -> SizeBounds {
+        SizeBounds {
+            min: self.min,
+            max: self.max,
+        }
+    }
 }
 
 /// Returns (nodes_in_cycles, nodes_not_in_cycles)
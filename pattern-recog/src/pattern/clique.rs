@@ -0,0 +1,317 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    MatchableLabel, Scope, ScopeGraph,
+    pattern::{MatchedPattern, OverlapPolicy, PatternMatcher, SizeBounds},
+};
+
+const CLIQUE_LABELS: &[MatchableLabel] = &[MatchableLabel::ExtendImpl];
+
+/// Minimum number of scopes of a [`CliqueMatch`] when the size of [`CliqueMatcher`] is not given
+pub const DEFAULT_CLIQUE_SIZE: usize = 3;
+
+#[derive(Serialize, Debug)]
+pub struct CliqueMatch {
+    scopes: Vec<Scope>,
+    missing_edges: usize,
+}
+
+impl CliqueMatch {
+    /// Number of pairs of scopes in this match that are not connected
+    pub fn missing_edges(&self) -> usize {
+        self.missing_edges
+    }
+
+    pub fn is_clique(&self) -> bool {
+        self.missing_edges == 0
+    }
+}
+
+impl MatchedPattern for CliqueMatch {
+    fn size(&self) -> usize {
+        self.scopes.len()
+    }
+
+    fn scopes(&self) -> impl Iterator<Item = &Scope> {
+        self.scopes.iter()
+    }
+}
+
+/// Finds groups of scopes that are (nearly) all connected in either direction, by ExtendImpl edges by default,
+/// e.g. tightly coupled interfaces.
+///
+/// With `k = 1` the matches are the maximal cliques.
+/// With a larger `k` every maximal clique is grown greedily into a k-plex,
+/// where every scope may miss edges to `k - 1` other scopes of the match.
+/// Every match is reported for its scope with the lowest index.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct CliqueMatcher {
+    labels: Vec<MatchableLabel>,
+    k: usize,
+    min: usize,
+    max: Option<usize>,
+}
+
+impl Default for CliqueMatcher {
+    fn default() -> Self {
+        Self {
+            labels: CLIQUE_LABELS.to_vec(),
+            k: 1,
+            min: DEFAULT_CLIQUE_SIZE,
+            max: None,
+        }
+    }
+}
+
+impl CliqueMatcher {
+    /// Labels of the edges that are followed, all labels if empty
+    pub fn with_labels(mut self, labels: impl IntoIterator<Item = MatchableLabel>) -> Self {
+        self.labels = labels.into_iter().collect();
+        self
+    }
+
+    /// Grow cliques into k-plexes, `1` only finds cliques
+    pub fn with_k(mut self, k: usize) -> Self {
+        self.k = k;
+        self
+    }
+
+    /// Minimum number of scopes in a match
+    pub fn with_min_size(mut self, min: usize) -> Self {
+        self.min = min;
+        self
+    }
+
+    /// Maximum number of scopes in a match
+    pub fn with_max_size(mut self, max: usize) -> Self {
+        self.max = Some(max);
+        self
+    }
+}
+
+impl PatternMatcher for CliqueMatcher {
+    type Match = CliqueMatch;
+    const OVERLAP: OverlapPolicy = OverlapPolicy::AllowOverlap;
+    const NAME: &str = "Clique";
+
+    fn find_pattern_for_scope(&self, graph: &ScopeGraph, scope: Scope) -> Vec<Self::Match> {
+        let mut neighborhoods = Neighborhoods {
+            graph,
+            labels: &self.labels,
+            cache: HashMap::new(),
+        };
+        let neighbors = neighborhoods.get(scope).clone();
+        // only extend with higher scopes, so every clique is found from its lowest scope
+        let candidates = neighbors
+            .iter()
+            .filter(|s| s.0 > scope.0)
+            .copied()
+            .collect();
+        let excluded = neighbors
+            .iter()
+            .filter(|s| s.0 < scope.0)
+            .copied()
+            .collect();
+        let mut cliques = Vec::new();
+        bron_kerbosch(
+            vec![scope],
+            candidates,
+            excluded,
+            &mut neighborhoods,
+            &mut cliques,
+        );
+
+        let mut seen = HashSet::new();
+        let mut matches = Vec::new();
+        for clique in cliques {
+            let mut scopes = match self.k > 1 {
+                true => grow_k_plex(clique, self.k, scope, &mut neighborhoods),
+                false => clique,
+            };
+            scopes.sort_by_key(|s| s.0);
+            if self.size_bounds().contains(scopes.len()) && seen.insert(scopes.clone()) {
+                matches.push(CliqueMatch {
+                    missing_edges: missing_edges(&scopes, &mut neighborhoods),
+                    scopes,
+                });
+            }
+        }
+        matches
+    }
+
+    fn size_bounds(&self) -> SizeBounds {
+        SizeBounds {
+            min: self.min,
+            max: self.max,
+        }
+    }
+}
+
+/// Undirected neighbors over the labels of the matcher, computed once per scope
+struct Neighborhoods<'a> {
+    graph: &'a ScopeGraph,
+    labels: &'a [MatchableLabel],
+    cache: HashMap<Scope, HashSet<Scope>>,
+}
+
+impl Neighborhoods<'_> {
+    fn get(&mut self, scope: Scope) -> &HashSet<Scope> {
+        let graph = self.graph;
+        let labels = self.labels;
+        self.cache.entry(scope).or_insert_with(|| {
+            let outgoing = graph
+                .get_outgoing_edges_with_labels(scope, labels)
+                .map(|e| e.to);
+            let incoming = graph
+                .get_incoming_edges_with_labels(scope, labels)
+                .map(|e| e.from);
+            outgoing.chain(incoming).filter(|s| *s != scope).collect()
+        })
+    }
+}
+
+/// Bron–Kerbosch with pivoting, adds every maximal clique that contains `clique` to `cliques`
+fn bron_kerbosch(
+    clique: Vec<Scope>,
+    mut candidates: HashSet<Scope>,
+    mut excluded: HashSet<Scope>,
+    neighborhoods: &mut Neighborhoods,
+    cliques: &mut Vec<Vec<Scope>>,
+) {
+    if candidates.is_empty() {
+        if excluded.is_empty() {
+            cliques.push(clique);
+        }
+        return;
+    }
+
+    let pivot = candidates
+        .iter()
+        .chain(excluded.iter())
+        .copied()
+        .max_by_key(|u| neighborhoods.get(*u).intersection(&candidates).count())
+        .unwrap();
+    let pivot_neighbors = neighborhoods.get(pivot).clone();
+    let mut branches = candidates
+        .difference(&pivot_neighbors)
+        .copied()
+        .collect::<Vec<_>>();
+    branches.sort_by_key(|s| s.0);
+
+    for v in branches {
+        let neighbors = neighborhoods.get(v).clone();
+        let mut next = clique.clone();
+        next.push(v);
+        bron_kerbosch(
+            next,
+            candidates.intersection(&neighbors).copied().collect(),
+            excluded.intersection(&neighbors).copied().collect(),
+            neighborhoods,
+            cliques,
+        );
+        candidates.remove(&v);
+        excluded.insert(v);
+    }
+}
+
+/// Adds scopes higher than `lowest` to `scopes` as long as every scope is connected to all but `k - 1` others
+fn grow_k_plex(
+    mut scopes: Vec<Scope>,
+    k: usize,
+    lowest: Scope,
+    neighborhoods: &mut Neighborhoods,
+) -> Vec<Scope> {
+    let mut rejected = HashSet::new();
+    loop {
+        let members = scopes.iter().copied().collect::<HashSet<_>>();
+        let mut candidates = HashSet::new();
+        for s in &scopes {
+            candidates.extend(neighborhoods.get(*s).iter().copied());
+        }
+        let best = candidates
+            .into_iter()
+            .filter(|c| c.0 > lowest.0 && !members.contains(c) && !rejected.contains(c))
+            .map(|c| (neighborhoods.get(c).intersection(&members).count(), c))
+            .max_by_key(|(degree, c)| (*degree, std::cmp::Reverse(c.0)));
+        let Some((_, candidate)) = best else {
+            return scopes;
+        };
+
+        let mut grown = members;
+        grown.insert(candidate);
+        let is_k_plex = grown.iter().all(|s| {
+            let degree = neighborhoods.get(*s).intersection(&grown).count();
+            degree + k >= grown.len()
+        });
+        match is_k_plex {
+            true => scopes.push(candidate),
+            false => {
+                rejected.insert(candidate);
+            }
+        }
+    }
+}
+
+fn missing_edges(scopes: &[Scope], neighborhoods: &mut Neighborhoods) -> usize {
+    let members = scopes.iter().copied().collect::<HashSet<_>>();
+    let connected = scopes
+        .iter()
+        .map(|s| neighborhoods.get(*s).intersection(&members).count())
+        .sum::<usize>()
+        / 2;
+    scopes.len() * (scopes.len() - 1) / 2 - connected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(usize, usize)]) -> ScopeGraph {
+        ScopeGraph::from_edges(
+            edges
+                .iter()
+                .map(|(a, b)| (*a, MatchableLabel::ExtendImpl, *b)),
+        )
+    }
+
+    #[test]
+    fn test_maximal_cliques() {
+        // clique of 4, triangle, and an edge between them
+        let graph = graph(&[
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (1, 2),
+            (3, 1),
+            (2, 3),
+            (4, 5),
+            (5, 6),
+            (6, 4),
+            (3, 6),
+        ]);
+        let mut matches = CliqueMatcher::default().search(&graph);
+        matches.sort_by_key(|m| m.size());
+        // every clique is reported once, although it is found from each of its scopes
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].scopes, vec![Scope(4), Scope(5), Scope(6)]);
+        assert_eq!(
+            matches[1].scopes,
+            vec![Scope(0), Scope(1), Scope(2), Scope(3)]
+        );
+        assert!(matches.iter().all(CliqueMatch::is_clique));
+    }
+
+    #[test]
+    fn test_k_plex() {
+        let square = graph(&[(0, 1), (1, 2), (2, 3), (3, 0)]);
+        assert!(CliqueMatcher::default().search(&square).is_empty());
+
+        let matches = CliqueMatcher::default().with_k(2).search(&square);
+        let full = matches.iter().find(|m| m.size() == 4).unwrap();
+        assert_eq!(full.missing_edges(), 2);
+        assert!(!full.is_clique());
+    }
+}
@@ -0,0 +1,189 @@
+use std::{fs::File, io::BufReader, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ScopeGraph,
+    pattern::{
+        BicliqueMatcher, ChainMatcher, CircleMatcher, CliqueMatcher, DeepDiamondMatcher,
+        DiamondMatcher, FanoutMatcher, OverlapPolicy, PatternMatcher, ProgressSink, StarMatcher,
+        TreeMatcher,
+    },
+};
+
+/// Range of match sizes that [`PatternMatcher::search`](crate::pattern::PatternMatcher::search) keeps,
+/// what the size means depends on the pattern.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SizeBounds {
+    pub min: usize,
+    /// No upper bound if `None`
+    pub max: Option<usize>,
+}
+
+impl SizeBounds {
+    pub fn at_least(min: usize) -> Self {
+        Self { min, max: None }
+    }
+
+    pub fn contains(&self, size: usize) -> bool {
+        size >= self.min && self.max.is_none_or(|max| size <= max)
+    }
+}
+
+impl std::fmt::Display for SizeBounds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.max {
+            Some(max) => write!(f, "{}..={}", self.min, max),
+            None => write!(f, "{}..", self.min),
+        }
+    }
+}
+
+/// Matchers used by [`PatternMatches::from_graph`](crate::pattern::PatternMatches::from_graph).
+///
+/// Can be read from a JSON file, so different cutoffs do not need a recompile.
+/// Missing fields keep their default, e.g. `{ "chain": { "min": 5 } }` only changes the minimum chain length.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct PatternConfig {
+    pub(crate) chain: ChainMatcher,
+    pub(crate) fanout: FanoutMatcher,
+    pub(crate) tree: TreeMatcher,
+    pub(crate) diamond: DiamondMatcher,
+    pub(crate) deep_diamond: DeepDiamondMatcher,
+    pub(crate) circle: CircleMatcher,
+    pub(crate) star: StarMatcher,
+    pub(crate) clique: CliqueMatcher,
+    pub(crate) biclique: BicliqueMatcher,
+    /// Overlap policy of every matcher, the policy of the matcher itself if `None`
+    pub(crate) overlap: Option<OverlapPolicy>,
+    /// Names of the matchers to search with, e.g. `Chain`, every matcher if `None`
+    pub(crate) patterns: Option<Vec<String>>,
+}
+
+impl PatternConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_json_file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn with_chain(mut self, chain: ChainMatcher) -> Self {
+        self.chain = chain;
+        self
+    }
+
+    pub fn with_fanout(mut self, fanout: FanoutMatcher) -> Self {
+        self.fanout = fanout;
+        self
+    }
+
+    pub fn with_tree(mut self, tree: TreeMatcher) -> Self {
+        self.tree = tree;
+        self
+    }
+
+    pub fn with_diamond(mut self, diamond: DiamondMatcher) -> Self {
+        self.diamond = diamond;
+        self
+    }
+
+    pub fn with_deep_diamond(mut self, deep_diamond: DeepDiamondMatcher) -> Self {
+        self.deep_diamond = deep_diamond;
+        self
+    }
+
+    pub fn with_circle(mut self, circle: CircleMatcher) -> Self {
+        self.circle = circle;
+        self
+    }
+
+    pub fn with_star(mut self, star: StarMatcher) -> Self {
+        self.star = star;
+        self
+    }
+
+    pub fn with_clique(mut self, clique: CliqueMatcher) -> Self {
+        self.clique = clique;
+        self
+    }
+
+    pub fn with_biclique(mut self, biclique: BicliqueMatcher) -> Self {
+        self.biclique = biclique;
+        self
+    }
+
+    /// Use `overlap` for every matcher instead of their own policy
+    pub fn with_overlap(mut self, overlap: OverlapPolicy) -> Self {
+        self.overlap = Some(overlap);
+        self
+    }
+
+    /// Only search with the matchers called `names`, e.g. `Chain`, the other patterns get no matches
+    pub fn with_patterns(mut self, names: impl IntoIterator<Item = impl ToString>) -> Self {
+        self.patterns = Some(names.into_iter().map(|n| n.to_string()).collect());
+        self
+    }
+
+    /// Whether the matcher called `name` is searched with, case insensitive
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.patterns
+            .as_ref()
+            .is_none_or(|names| names.iter().any(|n| n.eq_ignore_ascii_case(name)))
+    }
+
+    /// Sets the minimum size of the matcher called `name`, see [`PatternMatcher::size_bounds`].
+    ///
+    /// Unknown names are ignored.
+    pub fn with_min_size(mut self, name: &str, min: usize) -> Self {
+        match name.to_lowercase().as_str() {
+            "chain" => self.chain = self.chain.with_min_len(min),
+            "fanout" => self.fanout = self.fanout.with_min_leaves(min),
+            "tree" => self.tree = self.tree.with_min_leaves(min),
+            "diamond" => self.diamond = self.diamond.with_min_middle(min),
+            "deepdiamond" => self.deep_diamond = self.deep_diamond.with_min_width(min),
+            "circle" => self.circle = self.circle.with_min_len(min),
+            "star" => self.star = self.star.with_min_degree(min),
+            "clique" => self.clique = self.clique.with_min_size(min),
+            "biclique" => self.biclique = self.biclique.with_min_sources(min),
+            _ => (),
+        }
+        self
+    }
+
+    /// Sets the maximum size of the matcher called `name`, see [`PatternMatcher::size_bounds`].
+    ///
+    /// Unknown names are ignored.
+    pub fn with_max_size(mut self, name: &str, max: usize) -> Self {
+        match name.to_lowercase().as_str() {
+            "chain" => self.chain = self.chain.with_max_len(max),
+            "fanout" => self.fanout = self.fanout.with_max_leaves(max),
+            "tree" => self.tree = self.tree.with_max_leaves(max),
+            "diamond" => self.diamond = self.diamond.with_max_middle(max),
+            "deepdiamond" => self.deep_diamond = self.deep_diamond.with_max_width(max),
+            "circle" => self.circle = self.circle.with_max_len(max),
+            "star" => self.star = self.star.with_max_degree(max),
+            "clique" => self.clique = self.clique.with_max_size(max),
+            "biclique" => self.biclique = self.biclique.with_max_sources(max),
+            _ => (),
+        }
+        self
+    }
+
+    /// Searches `graph` with `matcher` using the overlap policy of this config,
+    /// no matches if the matcher is not enabled.
+    pub(crate) fn search<M: PatternMatcher>(
+        &self,
+        matcher: &M,
+        graph: &ScopeGraph,
+        progress: &mut dyn ProgressSink,
+    ) -> Vec<M::Match> {
+        if !self.is_enabled(M::NAME) {
+            return Vec::new();
+        }
+        matcher.search_with(graph, self.overlap.unwrap_or(M::OVERLAP), progress)
+    }
+}
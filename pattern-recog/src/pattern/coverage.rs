@@ -0,0 +1,134 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Scope, ScopeGraph};
+
+/// Number of scopes and edges of a graph that are part of at least one match.
+///
+/// An edge is part of a match if both its scopes are part of that same match.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Coverage {
+    pub scopes: usize,
+    pub edges: usize,
+    pub total_scopes: usize,
+    pub total_edges: usize,
+}
+
+impl Coverage {
+    pub fn scope_fraction(&self) -> f32 {
+        match self.total_scopes {
+            0 => 0.0,
+            total => self.scopes as f32 / total as f32,
+        }
+    }
+
+    pub fn edge_fraction(&self) -> f32 {
+        match self.total_edges {
+            0 => 0.0,
+            total => self.edges as f32 / total as f32,
+        }
+    }
+}
+
+impl std::fmt::Display for Coverage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.2}% of scopes ({}/{}), {:.2}% of edges ({}/{})",
+            self.scope_fraction() * 100.0,
+            self.scopes,
+            self.total_scopes,
+            self.edge_fraction() * 100.0,
+            self.edges,
+            self.total_edges
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PatternCoverage {
+    /// Name of the matcher, e.g. `Chain`
+    pub name: String,
+    pub coverage: Coverage,
+}
+
+/// Coverage of every pattern and of all patterns together
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    pub patterns: Vec<PatternCoverage>,
+    /// Scopes and edges that are part of a match of any pattern
+    pub any: Coverage,
+}
+
+impl CoverageReport {
+    /// Coverage of the matches of every pattern, given as the scopes of every match
+    pub(crate) fn new<'a>(
+        graph: &ScopeGraph,
+        patterns: impl IntoIterator<Item = (&'a str, Vec<&'a [Scope]>)>,
+    ) -> Self {
+        let total_scopes = graph.scopes.len();
+        let total_edges = graph.edges.len();
+
+        let mut any_scopes = HashSet::new();
+        let mut any_edges = HashSet::new();
+        let mut report = Self::default();
+        for (name, matches) in patterns {
+            // matches that every scope is part of
+            let mut membership = HashMap::<Scope, HashSet<usize>>::new();
+            for (i, scopes) in matches.iter().enumerate() {
+                for s in scopes.iter() {
+                    membership.entry(*s).or_default().insert(i);
+                }
+            }
+            let edges = graph
+                .edges
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| {
+                    membership
+                        .get(&e.from)
+                        .zip(membership.get(&e.to))
+                        .is_some_and(|(from, to)| !from.is_disjoint(to))
+                })
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+
+            report.patterns.push(PatternCoverage {
+                name: name.to_string(),
+                coverage: Coverage {
+                    scopes: membership.len(),
+                    edges: edges.len(),
+                    total_scopes,
+                    total_edges,
+                },
+            });
+            any_scopes.extend(membership.into_keys());
+            any_edges.extend(edges);
+        }
+
+        report.any = Coverage {
+            scopes: any_scopes.len(),
+            edges: any_edges.len(),
+            total_scopes,
+            total_edges,
+        };
+        report
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Coverage> {
+        self.patterns
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| &p.coverage)
+    }
+}
+
+impl std::fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for p in &self.patterns {
+            writeln!(f, "{}: {}", p.name, p.coverage)?;
+        }
+        write!(f, "Any pattern: {}", self.any)
+    }
+}
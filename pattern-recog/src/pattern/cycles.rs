@@ -0,0 +1,404 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{MatchableLabel, Scope, ScopeGraph, pattern::MatchedPattern};
+
+/// Simple cycle, its scopes are in the order of its edges and the last scope has an edge to the first
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    scopes: Vec<Scope>,
+}
+
+/// The size of a cycle is its number of edges, which equals its number of scopes
+impl MatchedPattern for Cycle {
+    fn size(&self) -> usize {
+        self.scopes.len()
+    }
+
+    fn scopes(&self) -> impl Iterator<Item = &Scope> {
+        self.scopes.iter()
+    }
+}
+
+/// Enumerates every simple cycle of a graph once, using Johnson's algorithm.
+///
+/// With a maximum length the search is bounded as described by Gupta and Suzumura,
+/// so short cycles can be counted in graphs that have too many cycles to enumerate.
+/// Unlike [`CircleMatcher`](crate::pattern::CircleMatcher), rotations of the same cycle are not reported again.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct CycleEnumerator {
+    labels: Vec<MatchableLabel>,
+    max_len: Option<usize>,
+}
+
+impl CycleEnumerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Labels of the edges that are followed, all labels if empty
+    pub fn with_labels(mut self, labels: impl IntoIterator<Item = MatchableLabel>) -> Self {
+        self.labels = labels.into_iter().collect();
+        self
+    }
+
+    /// Maximum number of edges in a cycle
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    pub fn enumerate(&self, graph: &ScopeGraph) -> Vec<Cycle> {
+        let mut cycles = Vec::new();
+        self.for_each_cycle(graph, |scopes| {
+            cycles.push(Cycle {
+                scopes: scopes.to_vec(),
+            })
+        });
+        cycles
+    }
+
+    /// Number of cycles of every length, without keeping the cycles themselves
+    pub fn length_counts(&self, graph: &ScopeGraph) -> BTreeMap<usize, usize> {
+        let mut counts = BTreeMap::new();
+        self.for_each_cycle(graph, |scopes| {
+            *counts.entry(scopes.len()).or_default() += 1;
+        });
+        counts
+    }
+
+    /// Calls `f` with the scopes of every simple cycle
+    pub fn for_each_cycle(&self, graph: &ScopeGraph, mut f: impl FnMut(&[Scope])) {
+        let bound = self.max_len.unwrap_or(usize::MAX);
+        if bound == 0 {
+            return;
+        }
+
+        let scopes = graph.keys().copied().collect::<Vec<_>>();
+        let index = scopes
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (*s, i))
+            .collect::<HashMap<_, _>>();
+        // self loops are cycles of their own and are left out of the adjacency lists
+        let mut adj = vec![Vec::new(); scopes.len()];
+        for (i, s) in scopes.iter().enumerate() {
+            let mut neighbors = graph
+                .get_outgoing_edges_with_labels(*s, &self.labels)
+                .filter_map(|e| index.get(&e.to).copied())
+                .collect::<Vec<_>>();
+            neighbors.sort_unstable();
+            neighbors.dedup();
+            if let Ok(pos) = neighbors.binary_search(&i) {
+                neighbors.remove(pos);
+                f(&[*s]);
+            }
+            adj[i] = neighbors;
+        }
+
+        let mut emit = |path: &[usize]| {
+            let cycle = path.iter().map(|i| scopes[*i]).collect::<Vec<_>>();
+            f(&cycle);
+        };
+
+        // every cycle lies within a strongly connected component, after searching from a start node
+        // all its cycles are found so it is removed and the rest of the component is split up again
+        let all = (0..scopes.len()).collect::<Vec<_>>();
+        let mut in_component = vec![true; scopes.len()];
+        let mut components = strongly_connected(&adj, &all, &in_component);
+        in_component.fill(false);
+        while let Some(component) = components.pop() {
+            for v in &component {
+                in_component[*v] = true;
+            }
+            let start = component[0];
+            match self.max_len {
+                Some(bound) => bounded_search(&adj, &in_component, start, bound, &mut emit),
+                None => johnson_search(&adj, &in_component, start, &mut emit),
+            }
+            in_component[start] = false;
+            components.extend(strongly_connected(&adj, &component[1..], &in_component));
+            for v in &component {
+                in_component[*v] = false;
+            }
+        }
+    }
+}
+
+/// Strongly connected components with at least two of the `nodes`, only following edges to nodes in `in_set`
+fn strongly_connected(adj: &[Vec<usize>], nodes: &[usize], in_set: &[bool]) -> Vec<Vec<usize>> {
+    let mut next_index = 0;
+    let mut index = HashMap::new();
+    let mut lowlink = HashMap::<usize, usize>::new();
+    let mut stack = Vec::new();
+    let mut on_stack = HashSet::new();
+    let mut components = Vec::new();
+
+    for &root in nodes {
+        if index.contains_key(&root) {
+            continue;
+        }
+        // node and the position of the next neighbor to visit, instead of recursion
+        let mut work = vec![(root, 0)];
+        while let Some((v, mut i)) = work.pop() {
+            if i == 0 {
+                index.insert(v, next_index);
+                lowlink.insert(v, next_index);
+                next_index += 1;
+                stack.push(v);
+                on_stack.insert(v);
+            }
+
+            let mut descended = false;
+            while i < adj[v].len() {
+                let w = adj[v][i];
+                i += 1;
+                if !in_set[w] {
+                    continue;
+                }
+                match index.get(&w) {
+                    None => {
+                        work.push((v, i));
+                        work.push((w, 0));
+                        descended = true;
+                        break;
+                    }
+                    Some(w_index) if on_stack.contains(&w) => {
+                        lowlink.insert(v, lowlink[&v].min(*w_index));
+                    }
+                    Some(_) => (),
+                }
+            }
+            if descended {
+                continue;
+            }
+
+            if lowlink[&v] == index[&v] {
+                let mut component = Vec::new();
+                while let Some(w) = stack.pop() {
+                    on_stack.remove(&w);
+                    component.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                if component.len() > 1 {
+                    components.push(component);
+                }
+            }
+            if let Some((parent, _)) = work.last() {
+                lowlink.insert(*parent, lowlink[parent].min(lowlink[&v]));
+            }
+        }
+    }
+    components
+}
+
+/// Johnson's circuit search for the cycles through `start`, within the nodes in `in_component`
+fn johnson_search(
+    adj: &[Vec<usize>],
+    in_component: &[bool],
+    start: usize,
+    emit: &mut impl FnMut(&[usize]),
+) {
+    let mut path = vec![start];
+    let mut blocked = HashSet::from([start]);
+    let mut b = HashMap::<usize, HashSet<usize>>::new();
+    // position of the next neighbor to visit and whether a cycle was found, for every node of `path`
+    let mut stack = vec![0];
+    let mut closed = vec![false];
+
+    while let Some(&pos) = stack.last() {
+        let v = path[path.len() - 1];
+        let mut i = pos;
+        let mut descended = false;
+        while i < adj[v].len() {
+            let w = adj[v][i];
+            i += 1;
+            if !in_component[w] {
+                continue;
+            }
+            if w == start {
+                emit(&path);
+                *closed.last_mut().unwrap() = true;
+            } else if !blocked.contains(&w) {
+                *stack.last_mut().unwrap() = i;
+                path.push(w);
+                stack.push(0);
+                closed.push(false);
+                blocked.insert(w);
+                descended = true;
+                break;
+            }
+        }
+        if descended {
+            continue;
+        }
+
+        stack.pop();
+        let v = path.pop().unwrap();
+        if closed.pop().unwrap() {
+            if let Some(c) = closed.last_mut() {
+                *c = true;
+            }
+            let mut unblock = vec![v];
+            while let Some(u) = unblock.pop() {
+                if !blocked.remove(&u) {
+                    continue;
+                }
+                if let Some(bu) = b.get_mut(&u) {
+                    unblock.extend(bu.drain());
+                }
+            }
+        } else {
+            for &w in &adj[v] {
+                if in_component[w] {
+                    b.entry(w).or_default().insert(v);
+                }
+            }
+        }
+    }
+}
+
+/// Length bounded variant of [`johnson_search`], nodes are locked at the length of the path that reached them
+/// and only unlocked as far as a shorter path could still close a cycle within `bound`.
+fn bounded_search(
+    adj: &[Vec<usize>],
+    in_component: &[bool],
+    start: usize,
+    bound: usize,
+    emit: &mut impl FnMut(&[usize]),
+) {
+    let mut path = vec![start];
+    let mut lock = HashMap::from([(start, 0)]);
+    let mut b = HashMap::<usize, HashSet<usize>>::new();
+    // position of the next neighbor to visit and the shortest distance back to `start`, for every node of `path`
+    let mut stack = vec![0];
+    let mut blen = vec![bound];
+
+    while let Some(&pos) = stack.last() {
+        let v = path[path.len() - 1];
+        let mut i = pos;
+        let mut descended = false;
+        while i < adj[v].len() {
+            let w = adj[v][i];
+            i += 1;
+            if !in_component[w] {
+                continue;
+            }
+            if w == start {
+                emit(&path);
+                *blen.last_mut().unwrap() = 1;
+            } else if path.len() < *lock.get(&w).unwrap_or(&bound) {
+                *stack.last_mut().unwrap() = i;
+                lock.insert(w, path.len());
+                path.push(w);
+                stack.push(0);
+                blen.push(bound);
+                descended = true;
+                break;
+            }
+        }
+        if descended {
+            continue;
+        }
+
+        stack.pop();
+        let v = path.pop().unwrap();
+        let bl = blen.pop().unwrap();
+        // the parent is one step further from `start`
+        if let Some(last) = blen.last_mut() {
+            *last = (*last).min(bl.saturating_add(1));
+        }
+        if bl < bound {
+            let mut relax = vec![(bl, v)];
+            while let Some((bl, u)) = relax.pop() {
+                if *lock.get(&u).unwrap_or(&bound) < bound - bl + 1 {
+                    lock.insert(u, bound - bl + 1);
+                    if let Some(bu) = b.get(&u) {
+                        relax.extend(
+                            bu.iter()
+                                .filter(|w| !path.contains(w))
+                                .map(|w| (bl + 1, *w)),
+                        );
+                    }
+                }
+            }
+        } else {
+            for &w in &adj[v] {
+                if in_component[w] {
+                    b.entry(w).or_default().insert(v);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Complete directed graph, with `C(n, k) * (k - 1)!` cycles of length `k`
+    fn complete(n: usize) -> ScopeGraph {
+        ScopeGraph::from_edges((0..n).flat_map(|a| {
+            (0..n)
+                .filter(move |b| *b != a)
+                .map(move |b| (a, MatchableLabel::Parent, b))
+        }))
+    }
+
+    #[test]
+    fn test_complete_graph() {
+        let graph = complete(4);
+        let counts = CycleEnumerator::new().length_counts(&graph);
+        assert_eq!(counts, BTreeMap::from([(2, 6), (3, 8), (4, 6)]));
+
+        let graph = complete(5);
+        let counts = CycleEnumerator::new().length_counts(&graph);
+        assert_eq!(counts, BTreeMap::from([(2, 10), (3, 20), (4, 30), (5, 24)]));
+        let bounded = CycleEnumerator::new().with_max_len(3).length_counts(&graph);
+        assert_eq!(bounded, BTreeMap::from([(2, 10), (3, 20)]));
+        let bounded = CycleEnumerator::new().with_max_len(5).length_counts(&graph);
+        assert_eq!(bounded, counts);
+    }
+
+    #[test]
+    fn test_cycle_with_chord() {
+        let graph = ScopeGraph::from_edges(
+            [(0, 1), (1, 2), (2, 3), (3, 4), (4, 0), (2, 0)]
+                .map(|(a, b)| (a, MatchableLabel::Parent, b)),
+        );
+        let counts = CycleEnumerator::new().length_counts(&graph);
+        assert_eq!(counts, BTreeMap::from([(3, 1), (5, 1)]));
+        let bounded = CycleEnumerator::new().with_max_len(4).length_counts(&graph);
+        assert_eq!(bounded, BTreeMap::from([(3, 1)]));
+
+        let cycles = CycleEnumerator::new().with_max_len(4).enumerate(&graph);
+        assert_eq!(cycles.len(), 1);
+        let scopes = cycles[0].scopes().copied().collect::<HashSet<_>>();
+        assert_eq!(scopes, HashSet::from([Scope(0), Scope(1), Scope(2)]));
+    }
+
+    #[test]
+    fn test_self_loops_and_labels() {
+        let graph = ScopeGraph::from_edges([
+            (0, MatchableLabel::Parent, 0),
+            (0, MatchableLabel::Parent, 1),
+            (1, MatchableLabel::ExtendImpl, 0),
+        ]);
+        let counts = CycleEnumerator::new().length_counts(&graph);
+        assert_eq!(counts, BTreeMap::from([(1, 1), (2, 1)]));
+        let parents = CycleEnumerator::new()
+            .with_labels([MatchableLabel::Parent])
+            .length_counts(&graph);
+        assert_eq!(parents, BTreeMap::from([(1, 1)]));
+        assert!(
+            CycleEnumerator::new()
+                .with_max_len(0)
+                .length_counts(&graph)
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    MatchableLabel, Scope, ScopeGraph,
+    pattern::{MatchedPattern, OverlapPolicy, PatternMatcher, SizeBounds},
+};
+
+const DEFAULT_DEPTH: usize = 2;
+
+#[derive(Serialize, Debug)]
+pub struct DeepDiamondMatch {
+    bot: Scope,
+    top: Scope,
+    /// Middle scopes of every branch, from the bottom to the top
+    branches: Vec<Vec<Scope>>,
+}
+
+impl DeepDiamondMatch {
+    pub fn new(bot: Scope, top: Scope, branches: Vec<Vec<Scope>>) -> Self {
+        Self { bot, top, branches }
+    }
+
+    /// Number of middle scopes of every branch
+    pub fn depth(&self) -> usize {
+        self.branches.first().map_or(0, Vec::len)
+    }
+
+    pub fn branches(&self) -> &[Vec<Scope>] {
+        &self.branches
+    }
+}
+
+/// The size of a deep diamond is its width, the number of branches
+impl MatchedPattern for DeepDiamondMatch {
+    fn size(&self) -> usize {
+        self.branches.len()
+    }
+
+    fn scopes(&self) -> impl Iterator<Item = &Scope> {
+        std::iter::once(&self.bot)
+            .chain(std::iter::once(&self.top))
+            .chain(self.branches.iter().flatten())
+    }
+}
+
+/// Finds pairs of scopes that are connected through several branches of `depth` middle scopes each,
+/// such as the diamonds of width × depth made by the generator of the scope-graph crate.
+///
+/// Branches of a match share no scopes, when branches overlap they are picked in the order they are found.
+/// The size of a match is its width, by default at least two branches are needed since a single one is just a chain.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct DeepDiamondMatcher {
+    labels: Vec<MatchableLabel>,
+    depth: usize,
+    min: usize,
+    max: Option<usize>,
+}
+
+impl Default for DeepDiamondMatcher {
+    fn default() -> Self {
+        Self {
+            labels: Vec::new(),
+            depth: DEFAULT_DEPTH,
+            min: 2,
+            max: None,
+        }
+    }
+}
+
+impl DeepDiamondMatcher {
+    /// Labels of the edges that are followed, all labels if empty
+    pub fn with_labels(mut self, labels: impl IntoIterator<Item = MatchableLabel>) -> Self {
+        self.labels = labels.into_iter().collect();
+        self
+    }
+
+    /// Number of middle scopes of every branch, 1 finds one-level diamonds like [`DiamondMatcher`](crate::pattern::DiamondMatcher)
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Minimum number of branches
+    pub fn with_min_width(mut self, min: usize) -> Self {
+        self.min = min;
+        self
+    }
+
+    /// Maximum number of branches
+    pub fn with_max_width(mut self, max: usize) -> Self {
+        self.max = Some(max);
+        self
+    }
+}
+
+impl PatternMatcher for DeepDiamondMatcher {
+    type Match = DeepDiamondMatch;
+    const OVERLAP: OverlapPolicy = OverlapPolicy::AllowOverlap;
+    const NAME: &str = "DeepDiamond";
+
+    fn find_pattern_for_scope(&self, graph: &ScopeGraph, scope: Scope) -> Vec<Self::Match> {
+        // top scope -> middle scopes of every path from `scope` to it
+        let mut paths = HashMap::<Scope, Vec<Vec<Scope>>>::new();
+        let mut stack = vec![(scope, Vec::new())];
+        while let Some((cur, middle)) = stack.pop() {
+            for edge in graph.get_outgoing_edges_with_labels(cur, &self.labels) {
+                let next = edge.to;
+                if next == scope || middle.contains(&next) {
+                    continue;
+                }
+                if middle.len() == self.depth {
+                    paths.entry(next).or_default().push(middle.clone());
+                } else {
+                    let mut middle = middle.clone();
+                    middle.push(next);
+                    stack.push((next, middle));
+                }
+            }
+        }
+
+        paths
+            .into_iter()
+            .map(|(top, paths)| {
+                let mut used = HashSet::new();
+                let mut branches = Vec::new();
+                for p in paths {
+                    if p.contains(&top) || p.iter().any(|s| used.contains(s)) {
+                        continue;
+                    }
+                    used.extend(p.iter().copied());
+                    branches.push(p);
+                }
+                DeepDiamondMatch::new(scope, top, branches)
+            })
+            .collect()
+    }
+
+    fn size_bounds(&self) -> SizeBounds {
+        SizeBounds {
+            min: self.min,
+            max: self.max,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(usize, usize)]) -> ScopeGraph {
+        ScopeGraph::from_edges(edges.iter().map(|(a, b)| (*a, MatchableLabel::Parent, *b)))
+    }
+
+    #[test]
+    fn test_deep_diamond() {
+        // two branches of two middle scopes from 0 to 5
+        let graph = graph(&[(0, 1), (1, 2), (2, 5), (0, 3), (3, 4), (4, 5)]);
+        let matches = DeepDiamondMatcher::default().search(&graph);
+        assert_eq!(matches.len(), 1);
+        assert_eq!((matches[0].bot, matches[0].top), (Scope(0), Scope(5)));
+        assert_eq!(matches[0].size(), 2);
+        assert_eq!(matches[0].depth(), 2);
+
+        assert!(
+            DeepDiamondMatcher::default()
+                .with_depth(1)
+                .search(&graph)
+                .is_empty()
+        );
+        assert!(
+            DeepDiamondMatcher::default()
+                .with_min_width(3)
+                .search(&graph)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_overlapping_branches() {
+        // the branch over 1 and 6 shares 1 with the branch over 1 and 2
+        let graph = graph(&[
+            (0, 1),
+            (1, 2),
+            (2, 5),
+            (0, 3),
+            (3, 4),
+            (4, 5),
+            (1, 6),
+            (6, 5),
+        ]);
+        let matches = DeepDiamondMatcher::default().search(&graph);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].size(), 2);
+        let scopes = matches[0]
+            .branches()
+            .iter()
+            .flatten()
+            .collect::<HashSet<_>>();
+        assert_eq!(scopes.len(), 4);
+    }
+}
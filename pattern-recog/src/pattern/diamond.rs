@@ -1,14 +1,16 @@
 use std::collections::HashMap;
 
+use serde::{Deserialize, Serialize};
+
 use crate::{
     MatchableLabel, Scope, ScopeGraph,
-    pattern::{MatchedPattern, PatternMatcher},
+    pattern::{MatchedPattern, OverlapPolicy, PatternMatcher, SizeBounds},
 };
 
 // const DIAMOND_LABELS: &[MatchableLabel] = &[];
 const DIAMOND_LABELS: &[MatchableLabel] = &[MatchableLabel::ExtendImpl];
 
-#[derive(Debug)]
+#[derive(Serialize, Debug)]
 pub struct DiamondMatch {
     bot: Scope,
     top: Scope,
@@ -39,28 +41,67 @@ impl MatchedPattern for DiamondMatch {
     }
 }
 
-pub struct DiamondMatcher;
+/// Finds pairs of scopes that are connected through several middle scopes, by ExtendImpl edges by default.
+///
+/// By default at least two middle scopes are needed, a single one is just a chain.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct DiamondMatcher {
+    labels: Vec<MatchableLabel>,
+    min: usize,
+    max: Option<usize>,
+}
+
+impl Default for DiamondMatcher {
+    fn default() -> Self {
+        Self {
+            labels: DIAMOND_LABELS.to_vec(),
+            min: 2,
+            max: None,
+        }
+    }
+}
+
+impl DiamondMatcher {
+    /// Labels of the edges that are followed, all labels if empty
+    pub fn with_labels(mut self, labels: impl IntoIterator<Item = MatchableLabel>) -> Self {
+        self.labels = labels.into_iter().collect();
+        self
+    }
+
+    /// Minimum number of middle scopes
+    pub fn with_min_middle(mut self, min: usize) -> Self {
+        self.min = min;
+        self
+    }
+
+    /// Maximum number of middle scopes
+    pub fn with_max_middle(mut self, max: usize) -> Self {
+        self.max = Some(max);
+        self
+    }
+}
 
 impl PatternMatcher for DiamondMatcher {
     type Match = DiamondMatch;
-    const EXCLUSIVE: bool = false;
+    const OVERLAP: OverlapPolicy = OverlapPolicy::AllowOverlap;
     const NAME: &str = "Diamond";
 
-    fn find_pattern_for_scope(graph: &ScopeGraph, scope: Scope) -> Vec<Self::Match> {
-        let outgoing_edges = graph.get_outgoing_edges_with_labels(scope, DIAMOND_LABELS);
+    fn find_pattern_for_scope(&self, graph: &ScopeGraph, scope: Scope) -> Vec<Self::Match> {
+        let outgoing_edges = graph.get_outgoing_edges_with_labels(scope, &self.labels);
         let middle_scopes = outgoing_edges.map(|edge| edge.to);
 
         let top_scopes: HashMap<Scope, Vec<Scope>> =
             middle_scopes.fold(HashMap::new(), |mut acc, middle_scope| {
                 let outgoing_edges =
-                    graph.get_outgoing_edges_with_labels(middle_scope, DIAMOND_LABELS);
+                    graph.get_outgoing_edges_with_labels(middle_scope, &self.labels);
                 // level 1 diamond
                 for top_edge in outgoing_edges {
                     acc.entry(top_edge.to).or_default().push(middle_scope);
 
                     // lvl 2 scopes, pretend that an edge from middle -> top2 exists
                     graph
-                        .get_outgoing_edges_with_labels(top_edge.to, DIAMOND_LABELS)
+                        .get_outgoing_edges_with_labels(top_edge.to, &self.labels)
                         .for_each(|next_top_edge| {
                             acc.entry(next_top_edge.to).or_default().push(middle_scope);
 
@@ -88,8 +129,14 @@ impl PatternMatcher for DiamondMatcher {
 
         top_scopes
             .into_iter()
-            .filter(|(_, middle_scopes)| middle_scopes.len() > 1)
             .map(|(top, middle_scopes)| DiamondMatch::new(scope, top, middle_scopes))
             .collect()
     }
+
+    fn size_bounds(&self) -> SizeBounds {
+        SizeBounds {
+            min: self.min,
+            max: self.max,
+        }
+    }
 }
@@ -0,0 +1,117 @@
+use std::{
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use graphing::{create_file, csv_field};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ScopeGraph,
+    pattern::{CoverageReport, PatternMatches},
+};
+
+/// Version of the layout of [`MatchExport`] and the CSV rows, increased when fields change
+pub const MATCH_EXPORT_VERSION: u32 = 2;
+
+/// Scope of a [`MatchRecord`]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ScopeRecord {
+    /// Index of the scope in the [`ScopeGraph`]
+    pub id: usize,
+    /// Resource and name of the original scope, `None` if the graph was not converted from a parsed graph
+    pub resource: Option<String>,
+    pub name: Option<String>,
+}
+
+/// Single match of a pattern
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MatchRecord {
+    /// Name of the matcher, e.g. `Chain`
+    pub pattern: String,
+    /// Number of the match within its pattern
+    pub index: usize,
+    pub size: usize,
+    pub scopes: Vec<ScopeRecord>,
+}
+
+/// All matches of a graph, as written by [`PatternMatches::to_json`]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MatchExport {
+    pub version: u32,
+    pub total_scopes: usize,
+    pub coverage: CoverageReport,
+    pub matches: Vec<MatchRecord>,
+}
+
+impl PatternMatches {
+    /// Matches of every pattern, with the original names of the scopes taken from `graph`
+    pub fn records(&self, graph: &ScopeGraph) -> Vec<MatchRecord> {
+        self.scopes_per_pattern()
+            .into_iter()
+            .flat_map(|(pattern, matches)| {
+                matches
+                    .into_iter()
+                    .enumerate()
+                    .map(move |(index, (size, scopes))| MatchRecord {
+                        pattern: pattern.to_string(),
+                        index,
+                        size,
+                        scopes: scopes
+                            .into_iter()
+                            .map(|s| {
+                                let parsed = graph.parsed_scope(s);
+                                ScopeRecord {
+                                    id: s.0,
+                                    resource: parsed.map(|p| p.resource.to_string()),
+                                    name: parsed.map(|p| p.name.to_string()),
+                                }
+                            })
+                            .collect(),
+                    })
+            })
+            .collect()
+    }
+
+    pub fn to_export(&self, graph: &ScopeGraph) -> MatchExport {
+        MatchExport {
+            version: MATCH_EXPORT_VERSION,
+            total_scopes: self.total_scopes,
+            coverage: self.coverage.clone(),
+            matches: self.records(graph),
+        }
+    }
+
+    pub fn to_json(&self, graph: &ScopeGraph, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(create_file(path)?);
+        serde_json::to_writer_pretty(&mut writer, &self.to_export(graph))?;
+        writer.flush()
+    }
+
+    /// Writes one `;`-separated row per scope of every match:
+    /// `pattern;match;size;scope;resource;name`, e.g. `Chain;0;3;12;/./Foo.java;s_ty-4`.
+    pub fn to_csv(&self, graph: &ScopeGraph, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(create_file(path)?);
+        self.write_csv(graph, &mut writer)?;
+        writer.flush()
+    }
+
+    pub fn write_csv<W: Write>(&self, graph: &ScopeGraph, writer: &mut W) -> std::io::Result<()> {
+        writeln!(writer, "pattern;match;size;scope;resource;name")?;
+        for m in self.records(graph) {
+            for s in &m.scopes {
+                writeln!(
+                    writer,
+                    "{};{};{};{};{};{}",
+                    csv_field(&m.pattern, ';'),
+                    m.index,
+                    m.size,
+                    s.id,
+                    csv_field(s.resource.as_deref().unwrap_or_default(), ';'),
+                    csv_field(s.name.as_deref().unwrap_or_default(), ';'),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,11 +1,13 @@
+use serde::{Deserialize, Serialize};
+
 use crate::{
     MatchableLabel, Scope, ScopeGraph,
-    pattern::{MatchedPattern, PatternMatcher},
+    pattern::{MatchedPattern, OverlapPolicy, PatternMatcher, SizeBounds},
 };
 
 const FANOUT_LABELS: &[MatchableLabel] = &[MatchableLabel::ClassMember];
 
-#[derive(Debug)]
+#[derive(Serialize, Debug)]
 pub struct FanoutMatch {
     root: Scope,
     leaves: Vec<Scope>,
@@ -34,23 +36,66 @@ impl MatchedPattern for FanoutMatch {
     }
 }
 
-pub struct FanoutMatcher;
+/// Finds scopes with outgoing edges to several leaves.
+///
+/// By default at least two leaves are needed, a single leaf is just a chain.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct FanoutMatcher {
+    labels: Vec<MatchableLabel>,
+    min: usize,
+    max: Option<usize>,
+}
+
+impl Default for FanoutMatcher {
+    fn default() -> Self {
+        Self {
+            labels: FANOUT_LABELS.to_vec(),
+            min: 2,
+            max: None,
+        }
+    }
+}
+
+impl FanoutMatcher {
+    /// Labels of the edges that are followed, all labels if empty
+    pub fn with_labels(mut self, labels: impl IntoIterator<Item = MatchableLabel>) -> Self {
+        self.labels = labels.into_iter().collect();
+        self
+    }
+
+    /// Minimum number of leaves
+    pub fn with_min_leaves(mut self, min: usize) -> Self {
+        self.min = min;
+        self
+    }
+
+    /// Maximum number of leaves
+    pub fn with_max_leaves(mut self, max: usize) -> Self {
+        self.max = Some(max);
+        self
+    }
+}
 
 impl PatternMatcher for FanoutMatcher {
     type Match = FanoutMatch;
-    const EXCLUSIVE: bool = false;
+    const OVERLAP: OverlapPolicy = OverlapPolicy::AllowOverlap;
     const NAME: &str = "Fanout";
 
-    fn find_pattern_for_scope(graph: &ScopeGraph, scope: Scope) -> Vec<Self::Match> {
+    fn find_pattern_for_scope(&self, graph: &ScopeGraph, scope: Scope) -> Vec<Self::Match> {
         let mut t = FanoutMatch::from_scope(scope);
-        let outgoing_edges = graph.get_outgoing_edges_with_labels(scope, FANOUT_LABELS);
+        let outgoing_edges = graph.get_outgoing_edges_with_labels(scope, &self.labels);
         for edge in outgoing_edges {
             t.push_leaf(edge.to);
         }
 
-        match t.leaves.len() {
-            0..=1 => Vec::new(),
-            2.. => vec![t],
+        vec![t]
+    }
+
+    fn size_bounds(&self) -> SizeBounds {
+        SizeBounds {
+            min: self.min,
+            max: self.max,
         }
     }
 }
@@ -0,0 +1,108 @@
+use std::{
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use graphing::{create_file, csv_field};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    pattern::{CorpusReport, PatternMatches},
+    stat::{Histogram, Stats},
+};
+
+/// Distribution of the match sizes of a single pattern
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PatternHistogram {
+    /// Name of the matcher, e.g. `Chain`
+    pub name: String,
+    pub sizes: Histogram,
+}
+
+impl PatternMatches {
+    /// Histogram of the match sizes of every pattern, see [`Stats::histogram`]
+    pub fn size_histograms(&self, bucket_width: usize) -> Vec<PatternHistogram> {
+        self.scopes_per_pattern()
+            .into_iter()
+            .map(|(name, matches)| PatternHistogram {
+                name: name.to_string(),
+                sizes: matches
+                    .iter()
+                    .map(|(size, _)| *size)
+                    .collect::<Stats>()
+                    .histogram(bucket_width as f32),
+            })
+            .collect()
+    }
+
+    pub fn histograms_to_csv(
+        &self,
+        bucket_width: usize,
+        path: impl AsRef<Path>,
+    ) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(create_file(path)?);
+        self.write_histograms_csv(bucket_width, &mut writer)?;
+        writer.flush()
+    }
+
+    /// Writes one `;`-separated row per bucket of every pattern:
+    /// `pattern;start;end;count`, where a bucket contains the sizes in `start..end`
+    pub fn write_histograms_csv<W: Write>(
+        &self,
+        bucket_width: usize,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        writeln!(writer, "pattern;start;end;count")?;
+        for h in self.size_histograms(bucket_width) {
+            for b in h.sizes.buckets {
+                writeln!(
+                    writer,
+                    "{};{};{};{}",
+                    csv_field(&h.name, ';'),
+                    b.start,
+                    b.end,
+                    b.count
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CorpusReport {
+    pub fn histograms_to_csv(
+        &self,
+        bucket_width: usize,
+        path: impl AsRef<Path>,
+    ) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(create_file(path)?);
+        self.write_histograms_csv(bucket_width, &mut writer)?;
+        writer.flush()
+    }
+
+    /// Writes the size histograms of every corpus as `corpus;pattern;start;end;count`,
+    /// see [`PatternMatches::write_histograms_csv`]
+    pub fn write_histograms_csv<W: Write>(
+        &self,
+        bucket_width: usize,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        writeln!(writer, "corpus;pattern;start;end;count")?;
+        for (corpus, matches) in self.corpora() {
+            for h in matches.size_histograms(bucket_width) {
+                for b in h.sizes.buckets {
+                    writeln!(
+                        writer,
+                        "{};{};{};{};{}",
+                        csv_field(corpus, ';'),
+                        csv_field(&h.name, ';'),
+                        b.start,
+                        b.end,
+                        b.count
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use serde::Serialize;
+
+use crate::{Edge, Scope, ScopeGraph, pattern::MatchedPattern};
+
+/// Match of a [`Pattern`](crate::pattern::Pattern) found by [`ScopeGraph::match_subgraph`]
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Vf2Match {
+    /// Scope of the pattern and the scope of the graph it is mapped onto, in the order of the scopes of the pattern
+    mapping: Vec<(Scope, Scope)>,
+    /// Edges of the graph that the edges of the pattern are mapped onto
+    edges: Vec<Edge>,
+}
+
+impl Vf2Match {
+    /// Converts a mapping from the node indices of `pattern` to the node indices of `graph`,
+    /// which are the ids of their scopes
+    pub(crate) fn new(pattern: &ScopeGraph, graph: &ScopeGraph, iso: &[vf2::NodeIndex]) -> Self {
+        let mapping = iso
+            .iter()
+            .enumerate()
+            .map(|(p, g)| (Scope(p), Scope(*g)))
+            .collect::<Vec<(Scope, Scope)>>();
+        let target = |s: Scope| {
+            mapping
+                .iter()
+                .find(|(p, _)| *p == s)
+                .map(|(_, g)| *g)
+                .expect("pattern edge between scopes of the pattern")
+        };
+        let edges = pattern
+            .edges
+            .iter()
+            .map(|e| {
+                let (from, to) = (target(e.from), target(e.to));
+                graph.find_edge(from, to).cloned().unwrap_or(Edge {
+                    from,
+                    to,
+                    lbl: e.lbl.clone(),
+                })
+            })
+            .collect();
+        Self { mapping, edges }
+    }
+
+    /// Scope of the graph that `pattern_scope` is mapped onto
+    pub fn get(&self, pattern_scope: impl Into<Scope>) -> Option<Scope> {
+        let pattern_scope = pattern_scope.into();
+        self.mapping
+            .iter()
+            .find(|(p, _)| *p == pattern_scope)
+            .map(|(_, g)| *g)
+    }
+
+    /// Every scope of the pattern together with the scope of the graph it is mapped onto
+    pub fn mapping(&self) -> impl Iterator<Item = (Scope, Scope)> + '_ {
+        self.mapping.iter().copied()
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+}
+
+impl MatchedPattern for Vf2Match {
+    fn size(&self) -> usize {
+        self.mapping.len()
+    }
+
+    fn scopes(&self) -> impl Iterator<Item = &Scope> {
+        self.mapping.iter().map(|(_, g)| g)
+    }
+}
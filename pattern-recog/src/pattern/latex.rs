@@ -0,0 +1,205 @@
+use crate::{
+    pattern::{Coverage, PatternMatches},
+    stat::Stats,
+};
+
+/// Statistic of the sizes of the matches of a pattern, shown as a column of a [`LatexTable`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LatexColumn {
+    Count,
+    Mean,
+    Median,
+    Min,
+    Max,
+    StdDev,
+    /// Percentage of the scopes of the graph that are part of at least one match
+    Coverage,
+    /// Percentage of the edges of the graph that are part of at least one match
+    EdgeCoverage,
+}
+
+impl LatexColumn {
+    /// Columns of [`PatternMatches::to_latex_table`]
+    pub const DEFAULT: &[LatexColumn] = &[
+        LatexColumn::Count,
+        LatexColumn::Mean,
+        LatexColumn::Median,
+        LatexColumn::Min,
+        LatexColumn::Max,
+    ];
+
+    pub fn header(&self) -> &'static str {
+        match self {
+            LatexColumn::Count => "Count",
+            LatexColumn::Mean => "Mean",
+            LatexColumn::Median => "Median",
+            LatexColumn::Min => "Min",
+            LatexColumn::Max => "Max",
+            LatexColumn::StdDev => "Std. dev.",
+            LatexColumn::Coverage => "Coverage",
+            LatexColumn::EdgeCoverage => "Edge coverage",
+        }
+    }
+
+    fn format(&self, stats: &Stats, coverage: &Coverage, precision: usize) -> String {
+        match self {
+            LatexColumn::Count => stats.count().to_string(),
+            LatexColumn::Mean => format!("{:.precision$}", stats.avg()),
+            LatexColumn::Median => (stats.median() as u32).to_string(),
+            LatexColumn::Min => (stats.min() as u32).to_string(),
+            LatexColumn::Max => (stats.max() as u32).to_string(),
+            LatexColumn::StdDev => format!("{:.precision$}", stats.std_dev()),
+            LatexColumn::Coverage => {
+                format!("{:.precision$}\\%", coverage.scope_fraction() * 100.0)
+            }
+            LatexColumn::EdgeCoverage => {
+                format!("{:.precision$}\\%", coverage.edge_fraction() * 100.0)
+            }
+        }
+    }
+}
+
+/// Name of a pattern in the first column of a [`LatexTable`]
+fn display_name(name: &str) -> &str {
+    match name {
+        "Chain" => "Linear Chain",
+        "DeepDiamond" => "Deep Diamond",
+        name => name,
+    }
+}
+
+/// LaTeX table of the match statistics of one or more corpora.
+///
+/// Every corpus gets a row with its name and number of scopes, followed by a row per pattern.
+pub struct LatexTable<'a> {
+    corpora: Vec<(String, &'a PatternMatches)>,
+    columns: Vec<LatexColumn>,
+    precision: usize,
+    column_format: Option<String>,
+    caption: Option<String>,
+    label: Option<String>,
+}
+
+impl Default for LatexTable<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> LatexTable<'a> {
+    pub fn new() -> Self {
+        Self {
+            corpora: Vec::new(),
+            columns: LatexColumn::DEFAULT.to_vec(),
+            precision: 2,
+            column_format: None,
+            caption: None,
+            label: None,
+        }
+    }
+
+    pub fn with_corpus(mut self, name: impl ToString, matches: &'a PatternMatches) -> Self {
+        self.corpora.push((name.to_string(), matches));
+        self
+    }
+
+    pub fn with_columns(mut self, columns: impl IntoIterator<Item = LatexColumn>) -> Self {
+        self.columns = columns.into_iter().collect();
+        self
+    }
+
+    /// Number of decimals of the mean, standard deviation and coverage
+    pub fn with_precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Column specification of the `tabular` environment, `l` followed by an `r` per column by default
+    pub fn with_column_format(mut self, column_format: impl ToString) -> Self {
+        self.column_format = Some(column_format.to_string());
+        self
+    }
+
+    pub fn with_caption(mut self, caption: impl ToString) -> Self {
+        self.caption = Some(caption.to_string());
+        self
+    }
+
+    pub fn with_label(mut self, label: impl ToString) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    /// Header row with the names of the columns
+    pub fn header(&self) -> String {
+        std::iter::once("Pattern")
+            .chain(self.columns.iter().map(|c| c.header()))
+            .collect::<Vec<_>>()
+            .join(" & ")
+            + " \\\\"
+    }
+
+    /// Rows of a single corpus
+    fn corpus_rows(&self, name: &str, matches: &PatternMatches) -> Vec<String> {
+        let blank = " &".repeat(self.columns.len().saturating_sub(1));
+        let mut rows = vec![format!("{name} & {}{blank} \\\\", matches.total_scopes)];
+        for (pattern, match_scopes) in matches.scopes_per_pattern() {
+            let stats = match_scopes
+                .iter()
+                .map(|(size, _)| *size)
+                .collect::<Stats>();
+            let coverage = matches.coverage.get(pattern).copied().unwrap_or_default();
+            let cells = self
+                .columns
+                .iter()
+                .map(|c| c.format(&stats, &coverage, self.precision));
+            rows.push(
+                std::iter::once(display_name(pattern).to_string())
+                    .chain(cells)
+                    .collect::<Vec<_>>()
+                    .join(" & ")
+                    + " \\\\",
+            );
+        }
+        rows
+    }
+
+    /// Rows of every corpus without header or surrounding environment, for pasting into an existing table
+    pub fn rows(&self) -> String {
+        self.corpora
+            .iter()
+            .flat_map(|(name, matches)| self.corpus_rows(name, matches))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Complete `table` environment with a header, the corpora separated by horizontal lines
+    pub fn render(&self) -> String {
+        let column_format = self
+            .column_format
+            .clone()
+            .unwrap_or_else(|| format!("l{}", "r".repeat(self.columns.len())));
+
+        let mut lines = vec![
+            "\\begin{table}[ht]".to_string(),
+            "\\centering".to_string(),
+            format!("\\begin{{tabular}}{{{column_format}}}"),
+            "\\hline".to_string(),
+            self.header(),
+        ];
+        for (name, matches) in &self.corpora {
+            lines.push("\\hline".to_string());
+            lines.extend(self.corpus_rows(name, matches));
+        }
+        lines.push("\\hline".to_string());
+        lines.push("\\end{tabular}".to_string());
+        if let Some(caption) = &self.caption {
+            lines.push(format!("\\caption{{{caption}}}"));
+        }
+        if let Some(label) = &self.label {
+            lines.push(format!("\\label{{{label}}}"));
+        }
+        lines.push("\\end{table}".to_string());
+        lines.join("\n")
+    }
+}
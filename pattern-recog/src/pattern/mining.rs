@@ -0,0 +1,398 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
+
+use crate::{
+    MatchableLabel, Scope, ScopeGraph,
+    pattern::{PatternSpec, ProgressEvent, ProgressSink},
+};
+
+/// `(from, label, to)`, where `from` and `to` are nodes of the subgraph
+pub type MinedEdge = (usize, MatchableLabel, usize);
+
+/// Labeled subgraph found by [`SubgraphMiner`]
+#[derive(Clone, Debug)]
+pub struct FrequentSubgraph {
+    num_nodes: usize,
+    edges: Vec<MinedEdge>,
+    support: usize,
+    num_embeddings: usize,
+}
+
+impl FrequentSubgraph {
+    pub fn num_nodes(&self) -> usize {
+        self.num_nodes
+    }
+
+    pub fn edges(&self) -> &[MinedEdge] {
+        &self.edges
+    }
+
+    /// Minimum image based support: the lowest number of distinct scopes any node of the subgraph is mapped to.
+    ///
+    /// Unlike the number of embeddings, this does not grow when the subgraph is symmetric or the occurrences overlap.
+    pub fn support(&self) -> usize {
+        self.support
+    }
+
+    pub fn num_embeddings(&self) -> usize {
+        self.num_embeddings
+    }
+
+    /// Pattern that matches this subgraph, e.g. to find its occurrences with [`PatternSpec::search`]
+    pub fn to_spec(&self, name: impl ToString) -> PatternSpec {
+        self.edges
+            .iter()
+            .fold(PatternSpec::new(name), |spec, (from, lbl, to)| {
+                spec.edge(*from, *to, [lbl.clone()])
+            })
+    }
+}
+
+impl std::fmt::Display for FrequentSubgraph {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "support {}: {}",
+            self.support,
+            self.to_spec("frequent subgraph")
+        )
+    }
+}
+
+/// Discovers labeled subgraphs that occur often in a [`ScopeGraph`], in the style of gSpan.
+///
+/// Subgraphs are grown one edge at a time starting from single edges,
+/// every subgraph is only extended if its support is at least the minimum support.
+/// Isomorphic subgraphs are recognized by a canonical code, so every subgraph is reported once.
+pub struct SubgraphMiner {
+    max_nodes: usize,
+    min_support: usize,
+    max_embeddings: usize,
+}
+
+impl Default for SubgraphMiner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Edge added to a subgraph to grow it
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Extension {
+    /// Edge between two existing nodes
+    Backward(usize, MatchableLabel, usize),
+    /// Edge from an existing node to a new node
+    Outgoing(usize, MatchableLabel),
+    /// Edge from a new node to an existing node
+    Incoming(usize, MatchableLabel),
+}
+
+/// Subgraph with all its embeddings, an embedding maps every node of the subgraph to a scope
+struct Candidate {
+    num_nodes: usize,
+    edges: Vec<MinedEdge>,
+    embeddings: Vec<Vec<Scope>>,
+}
+
+impl Candidate {
+    fn support(&self) -> usize {
+        (0..self.num_nodes)
+            .map(|node| {
+                self.embeddings
+                    .iter()
+                    .map(|e| e[node])
+                    .collect::<HashSet<_>>()
+                    .len()
+            })
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Smallest sorted edge list over all numberings of the nodes, equal for isomorphic subgraphs
+    fn canonical_code(&self, permutations: &[Vec<usize>]) -> Vec<MinedEdge> {
+        permutations
+            .iter()
+            .map(|p| {
+                let mut code = self
+                    .edges
+                    .iter()
+                    .map(|(from, lbl, to)| (p[*from], lbl.clone(), p[*to]))
+                    .collect::<Vec<_>>();
+                code.sort();
+                code
+            })
+            .min()
+            .unwrap_or_default()
+    }
+
+    fn extend(&self, ext: &Extension) -> Self {
+        let mut edges = self.edges.clone();
+        let num_nodes = match ext {
+            Extension::Backward(from, lbl, to) => {
+                edges.push((*from, lbl.clone(), *to));
+                self.num_nodes
+            }
+            Extension::Outgoing(from, lbl) => {
+                edges.push((*from, lbl.clone(), self.num_nodes));
+                self.num_nodes + 1
+            }
+            Extension::Incoming(to, lbl) => {
+                edges.push((self.num_nodes, lbl.clone(), *to));
+                self.num_nodes + 1
+            }
+        };
+        Self {
+            num_nodes,
+            edges,
+            embeddings: Vec::new(),
+        }
+    }
+}
+
+impl SubgraphMiner {
+    pub fn new() -> Self {
+        Self {
+            max_nodes: 4,
+            min_support: 2,
+            max_embeddings: 100_000,
+        }
+    }
+
+    /// Largest number of nodes of a mined subgraph, at least 2
+    pub fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes.max(2);
+        self
+    }
+
+    pub fn with_min_support(mut self, min_support: usize) -> Self {
+        self.min_support = min_support;
+        self
+    }
+
+    /// Embeddings kept per subgraph, beyond this the support of larger subgraphs is a lower bound
+    pub fn with_max_embeddings(mut self, max_embeddings: usize) -> Self {
+        self.max_embeddings = max_embeddings;
+        self
+    }
+
+    /// Mines all subgraphs with at most `max_nodes` nodes and at least `min_support` support,
+    /// ordered by descending support.
+    ///
+    /// Edges from a scope to itself are ignored.
+    /// Every round of extending the subgraphs with one more edge is reported to `progress` as a separate search.
+    pub fn mine(
+        &self,
+        graph: &ScopeGraph,
+        progress: &mut dyn ProgressSink,
+    ) -> Vec<FrequentSubgraph> {
+        let permutations = (0..=self.max_nodes).map(permutations).collect::<Vec<_>>();
+
+        let mut single_edges = HashMap::<MatchableLabel, Vec<Vec<Scope>>>::new();
+        for e in graph.edges.iter().filter(|e| e.from != e.to) {
+            let embeddings = single_edges.entry(e.lbl.clone()).or_default();
+            if embeddings.len() < self.max_embeddings {
+                embeddings.push(vec![e.from, e.to]);
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut found = Vec::new();
+        let mut frontier = Vec::new();
+        for (lbl, embeddings) in single_edges {
+            let candidate = Candidate {
+                num_nodes: 2,
+                edges: vec![(0, lbl, 1)],
+                embeddings,
+            };
+            self.accept(
+                candidate,
+                &permutations,
+                &mut seen,
+                &mut found,
+                &mut frontier,
+            );
+        }
+
+        let mut num_edges = 1;
+        while !frontier.is_empty() {
+            num_edges += 1;
+            let timer = Instant::now();
+            let name = format!("Mining subgraphs with {num_edges} edges");
+            let total = frontier.len();
+            let found_before = found.len();
+            progress.on_event(ProgressEvent::Started { name: &name, total });
+
+            let mut next = Vec::new();
+            for (done, candidate) in frontier.into_iter().enumerate() {
+                for (ext, embeddings) in self.extensions(graph, &candidate) {
+                    let mut child = candidate.extend(&ext);
+                    child.embeddings = embeddings;
+                    self.accept(child, &permutations, &mut seen, &mut found, &mut next);
+                }
+                progress.on_event(ProgressEvent::Progressed {
+                    name: &name,
+                    done: done + 1,
+                    total,
+                    matches: found.len() - found_before,
+                });
+            }
+            progress.on_event(ProgressEvent::Finished {
+                name: &name,
+                matches: found.len() - found_before,
+                elapsed: timer.elapsed(),
+            });
+            frontier = next;
+        }
+
+        found.sort_by(|a, b| {
+            b.support
+                .cmp(&a.support)
+                .then(a.edges.len().cmp(&b.edges.len()))
+        });
+        found
+    }
+
+    /// Reports `candidate` and keeps it for extending if it is frequent and not found before
+    fn accept(
+        &self,
+        candidate: Candidate,
+        permutations: &[Vec<Vec<usize>>],
+        seen: &mut HashSet<Vec<MinedEdge>>,
+        found: &mut Vec<FrequentSubgraph>,
+        frontier: &mut Vec<Candidate>,
+    ) {
+        if !seen.insert(candidate.canonical_code(&permutations[candidate.num_nodes])) {
+            return;
+        }
+        let support = candidate.support();
+        if support < self.min_support {
+            return;
+        }
+        found.push(FrequentSubgraph {
+            num_nodes: candidate.num_nodes,
+            edges: candidate.edges.clone(),
+            support,
+            num_embeddings: candidate.embeddings.len(),
+        });
+        frontier.push(candidate);
+    }
+
+    /// Embeddings of every subgraph with one more edge than `candidate`, grouped by the added edge
+    fn extensions(
+        &self,
+        graph: &ScopeGraph,
+        candidate: &Candidate,
+    ) -> HashMap<Extension, Vec<Vec<Scope>>> {
+        let can_grow = candidate.num_nodes < self.max_nodes;
+        let mut extensions = HashMap::<Extension, Vec<Vec<Scope>>>::new();
+        let mut push = |ext: Extension, embedding: Vec<Scope>| {
+            let embeddings = extensions.entry(ext).or_default();
+            if embeddings.len() < self.max_embeddings {
+                embeddings.push(embedding);
+            }
+        };
+
+        for embedding in &candidate.embeddings {
+            for (node, scope) in embedding.iter().enumerate() {
+                let outgoing = graph.from_edge_map.get(scope).into_iter().flatten();
+                for e in outgoing.filter(|e| e.to != e.from) {
+                    match embedding.iter().position(|s| *s == e.to) {
+                        Some(to) => {
+                            let edge = (node, e.lbl.clone(), to);
+                            if !candidate.edges.contains(&edge) {
+                                push(
+                                    Extension::Backward(node, e.lbl.clone(), to),
+                                    embedding.clone(),
+                                );
+                            }
+                        }
+                        None if can_grow => {
+                            let mut grown = embedding.clone();
+                            grown.push(e.to);
+                            push(Extension::Outgoing(node, e.lbl.clone()), grown);
+                        }
+                        None => (),
+                    }
+                }
+
+                // incoming edges between embedded scopes are found as outgoing edges of the other scope
+                let incoming = graph.to_edge_map.get(scope).into_iter().flatten();
+                for e in incoming.filter(|e| e.to != e.from) {
+                    if can_grow && !embedding.contains(&e.from) {
+                        let mut grown = embedding.clone();
+                        grown.push(e.from);
+                        push(Extension::Incoming(node, e.lbl.clone()), grown);
+                    }
+                }
+            }
+        }
+        extensions
+    }
+}
+
+/// All orderings of `0..n`
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![Vec::new()];
+    }
+    permutations(n - 1)
+        .into_iter()
+        .flat_map(|p| {
+            (0..n).map(move |i| {
+                let mut p = p.clone();
+                p.insert(i, n - 1);
+                p
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::SilentProgress;
+
+    #[test]
+    fn test_mine() {
+        // two fanouts of Parent edges and two chains of ExtendImpl edges
+        let graph = ScopeGraph::from_edges([
+            (0, MatchableLabel::Parent, 1),
+            (0, MatchableLabel::Parent, 2),
+            (3, MatchableLabel::Parent, 4),
+            (3, MatchableLabel::Parent, 5),
+            (6, MatchableLabel::ExtendImpl, 7),
+            (7, MatchableLabel::ExtendImpl, 8),
+            (9, MatchableLabel::ExtendImpl, 10),
+            (10, MatchableLabel::ExtendImpl, 11),
+        ]);
+        let found = SubgraphMiner::new().mine(&graph, &mut SilentProgress);
+        let summary = found
+            .iter()
+            .map(|f| (f.num_nodes(), f.edges().len(), f.support()))
+            .collect::<Vec<_>>();
+        // the chain can be grown from either of its edges, but is only reported once
+        assert_eq!(summary.len(), 4);
+        assert_eq!(summary[0], (2, 1, 4));
+        assert!(summary.contains(&(2, 1, 2)));
+        assert_eq!(summary.iter().filter(|s| **s == (3, 2, 2)).count(), 2);
+
+        let chain = found
+            .iter()
+            .find(|f| f.edges().len() == 2 && f.edges()[0].1 == MatchableLabel::ExtendImpl)
+            .unwrap();
+        assert_eq!(chain.num_embeddings(), 2);
+
+        let frequent = SubgraphMiner::new()
+            .with_min_support(3)
+            .mine(&graph, &mut SilentProgress);
+        assert_eq!(frequent.len(), 1);
+        assert_eq!(frequent[0].edges()[0].1, MatchableLabel::ExtendImpl);
+
+        let single_edges = SubgraphMiner::new()
+            .with_max_nodes(2)
+            .mine(&graph, &mut SilentProgress);
+        assert!(single_edges.iter().all(|f| f.edges().len() == 1));
+    }
+}
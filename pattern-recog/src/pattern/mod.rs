@@ -1,21 +1,63 @@
 use std::{
     collections::{HashMap, HashSet},
     io::Write,
+    time::Instant,
 };
 
-use indicatif::{ProgressBar, ProgressStyle};
+use data_parse::ParsedScope;
+use graphing::plantuml::{
+    EdgeDirection, NodeType, PlantUmlDiagram, PlantUmlItem, theme::PlantUmlStyleSheet,
+};
+use scope_graph::{BackgroundColor, ColorSet, ForeGroundColor};
+use serde::Serialize;
 
-use crate::{MatchableLabel, Scope, ScopeGraph, stat::Stats};
+use crate::{MatchableLabel, NodeSignature, Scope, ScopeGraph, stat::Stats};
 
+mod biclique;
 mod chain;
 mod circle;
+mod clique;
+mod config;
+mod coverage;
+mod cycles;
+mod deep_diamond;
 mod diamond;
+mod export;
 mod fanout;
+mod histogram;
+mod isomorphism;
+mod latex;
+mod mining;
+mod overlap;
+mod progress;
+mod report;
+mod significance;
+mod spec;
+mod star;
+mod summary;
 mod tree;
+pub use biclique::*;
 pub use chain::*;
 pub use circle::*;
+pub use clique::*;
+pub use config::*;
+pub use coverage::*;
+pub use cycles::*;
+pub use deep_diamond::*;
 pub use diamond::*;
+pub use export::*;
 pub use fanout::*;
+pub use histogram::*;
+pub use isomorphism::*;
+pub use latex::*;
+pub use mining::*;
+pub use overlap::*;
+pub use progress::*;
+pub use report::*;
+pub use significance::*;
+pub use spec::*;
+pub use star::*;
+pub use summary::*;
 pub use tree::*;
 
 macro_rules! size_stats {
@@ -24,60 +66,137 @@ macro_rules! size_stats {
     };
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Debug)]
 pub struct PatternMatches {
     total_scopes: usize,
     chain_matches: Vec<ChainMatch>,
     fanout_matches: Vec<FanoutMatch>,
     tree_matches: Vec<TreeMatch>,
     diamond_matches: Vec<DiamondMatch>,
+    deep_diamond_matches: Vec<DeepDiamondMatch>,
     circle_matches: Vec<CircleMatch>,
+    star_matches: Vec<StarMatch>,
+    clique_matches: Vec<CliqueMatch>,
+    biclique_matches: Vec<BicliqueMatch>,
+    coverage: CoverageReport,
 }
 
 impl PatternMatches {
-    pub fn from_graph(graph: &ScopeGraph) -> Self {
-        let timer = std::time::Instant::now();
-        let chain_matches = ChainMatcher::search(graph);
-        // let chain_matches = Vec::new();
-        println!("chain: {:?}", timer.elapsed());
-        let timer = std::time::Instant::now();
-        let fanout_matches = FanoutMatcher::search(graph);
-        println!("fanout: {:?}", timer.elapsed());
-        let timer = std::time::Instant::now();
-        let tree_matches = TreeMatcher::search(graph);
-        println!("tree: {:?}", timer.elapsed());
-        let timer = std::time::Instant::now();
-        let diamond_matches = DiamondMatcher::search(graph);
-        println!("diamond: {:?}", timer.elapsed());
-        let timer = std::time::Instant::now();
-        let circle_matches = CircleMatcher::search(graph);
-        println!("circle: {:?}", timer.elapsed());
-
-        Self {
+    pub fn from_graph(
+        graph: &ScopeGraph,
+        config: &PatternConfig,
+        progress: &mut dyn ProgressSink,
+    ) -> Self {
+        let chain_matches = config.search(&config.chain, graph, progress);
+        let fanout_matches = config.search(&config.fanout, graph, progress);
+        let tree_matches = config.search(&config.tree, graph, progress);
+        let diamond_matches = config.search(&config.diamond, graph, progress);
+        let deep_diamond_matches = config.search(&config.deep_diamond, graph, progress);
+        let circle_matches = config.search(&config.circle, graph, progress);
+        let star_matches = config.search(&config.star, graph, progress);
+        let clique_matches = config.search(&config.clique, graph, progress);
+        let biclique_matches = config.search(&config.biclique, graph, progress);
+
+        let mut matches = Self {
             total_scopes: graph.scopes.len(),
             chain_matches,
             fanout_matches,
             tree_matches,
             diamond_matches,
+            deep_diamond_matches,
             circle_matches,
-        }
+            star_matches,
+            clique_matches,
+            biclique_matches,
+            coverage: CoverageReport::default(),
+        };
+        let per_pattern = matches.scopes_per_pattern();
+        matches.coverage = CoverageReport::new(
+            graph,
+            per_pattern
+                .iter()
+                .map(|(name, m)| (*name, m.iter().map(|(_, s)| s.as_slice()).collect())),
+        );
+        matches
+    }
+
+    /// Scopes and edges of the graph covered by the matches
+    pub fn coverage(&self) -> &CoverageReport {
+        &self.coverage
     }
 
+    /// Rows of a [`LatexTable`] with the default columns, use [`LatexTable`] directly for other layouts
     pub fn to_latex_table(&self, name: &str) -> String {
-        let chain_stats = size_stats!(self.chain_matches);
-        let fanout_stats = size_stats!(self.fanout_matches);
-        let tree_stats = size_stats!(self.tree_matches);
-        let diamond_stats = size_stats!(self.diamond_matches);
-        let circle_stats = size_stats!(self.circle_matches);
-        [
-            format!("{name} & {} & & & & \\\\", self.total_scopes),
-            chain_stats.to_latex_table("Linear Chain"),
-            fanout_stats.to_latex_table("Fanout"),
-            tree_stats.to_latex_table("Tree"),
-            diamond_stats.to_latex_table("Diamond"),
-            circle_stats.to_latex_table("Circle"),
+        LatexTable::new().with_corpus(name, self).rows()
+    }
+}
+
+/// Size and scopes of every match of a pattern
+type MatchScopes = Vec<(usize, Vec<Scope>)>;
+
+impl PatternMatches {
+    /// Name of every type of pattern with the size and scopes of its matches
+    fn scopes_per_pattern(&self) -> Vec<(&'static str, MatchScopes)> {
+        fn scopes<M: MatchedPattern>(matches: &[M]) -> MatchScopes {
+            matches.iter().map(|m| (m.size(), m.to_vec())).collect()
+        }
+        vec![
+            (ChainMatcher::NAME, scopes(&self.chain_matches)),
+            (FanoutMatcher::NAME, scopes(&self.fanout_matches)),
+            (TreeMatcher::NAME, scopes(&self.tree_matches)),
+            (DiamondMatcher::NAME, scopes(&self.diamond_matches)),
+            (DeepDiamondMatcher::NAME, scopes(&self.deep_diamond_matches)),
+            (CircleMatcher::NAME, scopes(&self.circle_matches)),
+            (StarMatcher::NAME, scopes(&self.star_matches)),
+            (CliqueMatcher::NAME, scopes(&self.clique_matches)),
+            (BicliqueMatcher::NAME, scopes(&self.biclique_matches)),
         ]
-        .join("\n")
+    }
+
+    /// Diagram of `graph` where the scopes and edges of every match are colored by the type of pattern.
+    ///
+    /// A scope that is part of several types of patterns gets the color of the first type in the legend,
+    /// an edge is only colored if both its scopes are part of the same match.
+    pub fn to_diagram(&self, graph: &ScopeGraph) -> PlantUmlDiagram {
+        let groups = self.scopes_per_pattern();
+
+        let mut style = PlantUmlStyleSheet::new();
+        // (pattern type, match) for every match a scope is part of
+        let mut membership = HashMap::<Scope, Vec<(usize, usize)>>::new();
+        for (i, (name, matches)) in groups.iter().enumerate() {
+            style.push(BackgroundColor::get_uml_css(i).as_class(name));
+            style.push(ForeGroundColor::get_uml_css(i).as_class(format!("{name}-edge")));
+            for (j, (_, m)) in matches.iter().enumerate() {
+                for s in m {
+                    membership.entry(*s).or_default().push((i, j));
+                }
+            }
+        }
+
+        let mut diagram = PlantUmlDiagram::new("pattern matches");
+        diagram.set_style_sheet(style);
+        for node in &graph.scopes {
+            let item = PlantUmlItem::node(node, node, NodeType::Node);
+            let item = match membership.get(node).and_then(|m| m.first()) {
+                Some((i, _)) => item.add_class(groups[*i].0),
+                None => item,
+            };
+            diagram.push(item);
+        }
+        for e in &graph.edges {
+            let item = PlantUmlItem::edge(e.from, e.to, &e.lbl, EdgeDirection::Up);
+            let shared = membership
+                .get(&e.from)
+                .zip(membership.get(&e.to))
+                .and_then(|(from, to)| from.iter().find(|m| to.contains(m)));
+            let item = match shared {
+                Some((i, _)) => item.add_class(format!("{}-edge", groups[*i].0)),
+                None => item,
+            };
+            diagram.push(item);
+        }
+        diagram.add_legend();
+        diagram
     }
 }
 
@@ -87,13 +206,23 @@ impl std::fmt::Display for PatternMatches {
         let fanout_stats = size_stats!(self.fanout_matches);
         let tree_stats = size_stats!(self.tree_matches);
         let diamond_stats = size_stats!(self.diamond_matches);
+        let deep_diamond_stats = size_stats!(self.deep_diamond_matches);
         let circle_stats = size_stats!(self.circle_matches);
+        let star_stats = size_stats!(self.star_matches);
+        let clique_stats = size_stats!(self.clique_matches);
+        let biclique_stats = size_stats!(self.biclique_matches);
 
         writeln!(f, "Chain: {chain_stats}")?;
         writeln!(f, "Fanout: {fanout_stats}")?;
         writeln!(f, "Tree: {}", tree_stats)?;
         writeln!(f, "Diamond: {}", diamond_stats)?;
+        writeln!(f, "Deep diamond: {}", deep_diamond_stats)?;
         writeln!(f, "Circle: {}", circle_stats)?;
+        writeln!(f, "Star: {}", star_stats)?;
+        writeln!(f, "Clique: {}", clique_stats)?;
+        writeln!(f, "Biclique: {}", biclique_stats)?;
+        writeln!(f, "Coverage:")?;
+        writeln!(f, "{}", self.coverage)?;
 
         Ok(())
     }
@@ -107,56 +236,138 @@ pub trait MatchedPattern {
     fn to_vec(&self) -> Vec<Scope> {
         self.scopes().copied().collect()
     }
+
+    /// Scopes of this match together with the scope they were converted from, see [`ScopeGraph::parsed_scope`]
+    fn parsed_scopes<'a>(
+        &'a self,
+        graph: &'a ScopeGraph,
+    ) -> impl Iterator<Item = (Scope, Option<&'a ParsedScope>)> {
+        self.scopes().map(|s| (*s, graph.parsed_scope(*s)))
+    }
+
+    /// Id of the original scope of every scope of this match, e.g. `/./Foo.java-s_ty-4`.
+    ///
+    /// Falls back to the index of the scope if `graph` was not converted from a parsed graph.
+    fn scope_names(&self, graph: &ScopeGraph) -> Vec<String> {
+        self.parsed_scopes(graph)
+            .map(|(s, parsed)| match parsed {
+                Some(parsed) => parsed.id(),
+                None => s.0.to_string(),
+            })
+            .collect()
+    }
 }
 
 pub trait PatternMatcher {
     type Match: MatchedPattern;
-    /// If true, then a scope can only be part of one match.
-    const EXCLUSIVE: bool = false;
+    /// Overlap policy of [`PatternMatcher::search`]
+    const OVERLAP: OverlapPolicy = OverlapPolicy::AllowOverlap;
     const NAME: &str;
 
-    fn find_pattern_for_scope(graph: &ScopeGraph, scope: Scope) -> Vec<Self::Match>;
+    fn find_pattern_for_scope(&self, graph: &ScopeGraph, scope: Scope) -> Vec<Self::Match>;
+
+    /// Sizes of the matches that are kept by [`PatternMatcher::search`], all sizes by default
+    fn size_bounds(&self) -> SizeBounds {
+        SizeBounds::default()
+    }
 
-    fn search(graph: &ScopeGraph) -> Vec<Self::Match> {
+    fn search(&self, graph: &ScopeGraph) -> Vec<Self::Match> {
+        self.search_with(graph, Self::OVERLAP, &mut SilentProgress)
+    }
+    /// Searches with the given overlap policy, reporting the progress to `progress`
+    fn search_with(
+        &self,
+        graph: &ScopeGraph,
+        overlap: OverlapPolicy,
+        progress: &mut dyn ProgressSink,
+    ) -> Vec<Self::Match> {
+        let timer = Instant::now();
         let mut matches = Vec::<Self::Match>::new();
 
         let scopes = &graph.scopes;
-        let bar = ProgressBar::new(scopes.len() as u64).with_message(Self::NAME);
-
-        bar.set_style(
-            ProgressStyle::with_template(
-                "[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}",
-            )
-            .unwrap()
-            .progress_chars("##-"),
-        );
+        let total = scopes.len();
+        progress.on_event(ProgressEvent::Started {
+            name: Self::NAME,
+            total,
+        });
 
-        let mut available_scopes = scopes.iter().cloned().collect::<HashSet<_>>();
+        let exclusive = overlap == OverlapPolicy::Exclusive;
+        let mut taken_scopes = HashSet::new();
+        let bounds = self.size_bounds();
 
-        for s in scopes {
-            bar.inc(1);
-            bar.set_message(format!("{} ({} matches)", Self::NAME, matches.len()));
-            if Self::EXCLUSIVE && !available_scopes.contains(s) {
+        for (done, s) in scopes.iter().enumerate() {
+            progress.on_event(ProgressEvent::Progressed {
+                name: Self::NAME,
+                done: done + 1,
+                total,
+                matches: matches.len(),
+            });
+            if exclusive && taken_scopes.contains(s) {
                 // already found a match for this scope, skip it
                 continue;
             }
 
-            let new_matches = Self::find_pattern_for_scope(graph, *s);
+            let new_matches = self
+                .find_pattern_for_scope(graph, *s)
+                .into_iter()
+                .filter(|m| bounds.contains(m.size()));
             for m in new_matches {
-                if Self::EXCLUSIVE {
-                    for s in m.scopes() {
-                        available_scopes.remove(s);
+                if exclusive {
+                    if m.scopes().any(|s| taken_scopes.contains(s)) {
+                        continue;
                     }
+                    taken_scopes.extend(m.scopes().copied());
                 }
                 matches.push(m);
             }
         }
 
-        bar.finish();
+        let matches = match overlap {
+            OverlapPolicy::MaxCoverage => select_max_coverage(matches),
+            _ => matches,
+        };
+        progress.on_event(ProgressEvent::Finished {
+            name: Self::NAME,
+            matches: matches.len(),
+            elapsed: timer.elapsed(),
+        });
         matches
     }
 }
 
+/// How the edges between matched scopes have to correspond to the edges of a pattern
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum MatchMode {
+    /// Matched scopes have an edge exactly when the pattern has one
+    #[default]
+    Induced,
+    /// Matched scopes have at least the edges of the pattern, extra edges between them are allowed
+    NonInduced,
+}
+
+impl MatchMode {
+    /// Finds all mappings from the nodes of `pattern` to the nodes of `graph`.
+    ///
+    /// Labels are not compared, scopes with fewer neighbors than a node of the pattern are skipped as candidates for it.
+    pub fn isomorphisms(
+        self,
+        pattern: &ScopeGraph,
+        graph: &ScopeGraph,
+    ) -> Vec<Vec<vf2::NodeIndex>> {
+        let node_eq = |p: &NodeSignature, g: &NodeSignature| g.covers_degree(p);
+        match self {
+            Self::Induced => vf2::induced_subgraph_isomorphisms(pattern, graph)
+                .node_eq(node_eq)
+                .iter()
+                .collect(),
+            Self::NonInduced => vf2::subgraph_isomorphisms(pattern, graph)
+                .node_eq(node_eq)
+                .iter()
+                .collect(),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum Pattern {
     Cycle(usize),
@@ -220,22 +431,56 @@ impl Pattern {
         }
     }
 
-    /// Prunes matches to remove matches that are actually the same match.
+    /// Removes matches that map the pattern onto the same scopes and edges,
+    /// i.e. matches that only differ by an automorphism of the pattern.
     ///
-    /// Depends on the pattern on how exactly to do this.
+    /// Of every group of duplicates the smallest mapping is kept, so the result does not depend on the order of `matches`.
     pub fn prune_matches(
         &self,
         matches: impl IntoIterator<Item = Vec<vf2::NodeIndex>>,
     ) -> impl Iterator<Item = Vec<vf2::NodeIndex>> {
-        matches
-            .into_iter()
-            .fold(HashMap::new(), |mut acc, iso| {
-                // matches are all same length, so if sum is the same, then they contain the same nodes
-                // this preserves the order of the nodes in the match as well
-                let sum = iso.iter().sum::<usize>();
-                acc.insert(sum, iso);
-                acc
-            })
-            .into_values()
+        let pattern = self.subgraph();
+        let pattern_edges = pattern
+            .edges
+            .iter()
+            .map(|e| (e.from.0, e.to.0))
+            .collect::<Vec<_>>();
+        dedup_matches(matches, &pattern_edges).into_iter()
+    }
+}
+
+/// Scopes and edges that `iso` maps the pattern with `pattern_edges` onto,
+/// equal for two matches exactly if they differ by an automorphism of the pattern.
+pub(crate) fn canonical_key(
+    iso: &[vf2::NodeIndex],
+    pattern_edges: &[(usize, usize)],
+) -> (Vec<vf2::NodeIndex>, Vec<(vf2::NodeIndex, vf2::NodeIndex)>) {
+    let mut nodes = iso.to_vec();
+    nodes.sort();
+    let mut edges = pattern_edges
+        .iter()
+        .map(|(from, to)| (iso[*from], iso[*to]))
+        .collect::<Vec<_>>();
+    edges.sort();
+    (nodes, edges)
+}
+
+/// Keeps the smallest match of every [`canonical_key`], sorted
+pub(crate) fn dedup_matches(
+    matches: impl IntoIterator<Item = Vec<vf2::NodeIndex>>,
+    pattern_edges: &[(usize, usize)],
+) -> Vec<Vec<vf2::NodeIndex>> {
+    let mut unique = HashMap::new();
+    for iso in matches {
+        let key = canonical_key(&iso, pattern_edges);
+        match unique.get(&key) {
+            Some(existing) if *existing <= iso => (),
+            _ => {
+                unique.insert(key, iso);
+            }
+        }
     }
+    let mut unique = unique.into_values().collect::<Vec<_>>();
+    unique.sort();
+    unique
 }
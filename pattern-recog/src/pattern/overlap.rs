@@ -0,0 +1,112 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Scope,
+    pattern::{MatchedPattern, PatternMatches},
+};
+
+/// How [`PatternMatcher::search`](crate::pattern::PatternMatcher::search) handles matches that share scopes
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverlapPolicy {
+    /// Keep every match
+    AllowOverlap,
+    /// Scopes are visited in order and a match is only kept if none of its scopes is part of an earlier match
+    Exclusive,
+    /// Keep matches without shared scopes, greedily picking the matches with the most scopes first
+    MaxCoverage,
+}
+
+/// Greedy selection of matches that do not share scopes, larger matches first.
+///
+/// The selected matches keep their original order.
+pub(crate) fn select_max_coverage<M: MatchedPattern>(matches: Vec<M>) -> Vec<M> {
+    let scopes = matches
+        .iter()
+        .map(|m| m.scopes().copied().collect::<HashSet<_>>())
+        .collect::<Vec<_>>();
+    let mut order = (0..matches.len()).collect::<Vec<_>>();
+    // stable, so equally large matches are picked in the order they were found
+    order.sort_by_key(|i| std::cmp::Reverse(scopes[*i].len()));
+
+    let mut covered = HashSet::new();
+    let mut selected = vec![false; matches.len()];
+    for i in order {
+        if scopes[i].is_disjoint(&covered) {
+            covered.extend(scopes[i].iter().copied());
+            selected[i] = true;
+        }
+    }
+    matches
+        .into_iter()
+        .zip(selected)
+        .filter_map(|(m, selected)| selected.then_some(m))
+        .collect()
+}
+
+/// How much the matches of a pattern share scopes
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OverlapStats {
+    /// Scopes that are part of at least one match
+    pub covered_scopes: usize,
+    /// Scopes that are part of more than one match
+    pub shared_scopes: usize,
+    /// Matches that share at least one scope with another match
+    pub overlapping_matches: usize,
+    /// Highest number of matches that a single scope is part of
+    pub max_matches_per_scope: usize,
+}
+
+impl OverlapStats {
+    pub fn from_matches<'a>(matches: impl IntoIterator<Item = &'a [Scope]>) -> Self {
+        // matches that every scope is part of
+        let mut membership = HashMap::<Scope, Vec<usize>>::new();
+        for (i, scopes) in matches.into_iter().enumerate() {
+            for s in scopes.iter().collect::<HashSet<_>>() {
+                membership.entry(*s).or_default().push(i);
+            }
+        }
+
+        let shared = membership
+            .values()
+            .filter(|m| m.len() > 1)
+            .collect::<Vec<_>>();
+        Self {
+            covered_scopes: membership.len(),
+            shared_scopes: shared.len(),
+            overlapping_matches: shared
+                .iter()
+                .flat_map(|m| m.iter())
+                .collect::<HashSet<_>>()
+                .len(),
+            max_matches_per_scope: membership.values().map(|m| m.len()).max().unwrap_or(0),
+        }
+    }
+}
+
+impl std::fmt::Display for OverlapStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} scopes covered, {} shared by {} matches, at most {} matches per scope",
+            self.covered_scopes,
+            self.shared_scopes,
+            self.overlapping_matches,
+            self.max_matches_per_scope
+        )
+    }
+}
+
+impl PatternMatches {
+    /// Overlap between the matches of every pattern
+    pub fn overlap_stats(&self) -> Vec<(&'static str, OverlapStats)> {
+        self.scopes_per_pattern()
+            .into_iter()
+            .map(|(name, matches)| {
+                let stats = OverlapStats::from_matches(matches.iter().map(|(_, s)| s.as_slice()));
+                (name, stats)
+            })
+            .collect()
+    }
+}
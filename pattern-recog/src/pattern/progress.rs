@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Progress of a search of a [`PatternMatcher`](crate::pattern::PatternMatcher)
+#[derive(Clone, Copy, Debug)]
+pub enum ProgressEvent<'a> {
+    /// A search over `total` scopes started
+    Started { name: &'a str, total: usize },
+    /// `done` of the `total` scopes are searched
+    Progressed {
+        name: &'a str,
+        done: usize,
+        total: usize,
+        matches: usize,
+    },
+    /// The search found `matches` matches in `elapsed` time
+    Finished {
+        name: &'a str,
+        matches: usize,
+        elapsed: Duration,
+    },
+}
+
+/// Receives the progress of searches, see [`PatternMatcher::search_with`](crate::pattern::PatternMatcher::search_with)
+pub trait ProgressSink {
+    fn on_event(&mut self, event: ProgressEvent<'_>);
+}
+
+/// Ignores all progress
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SilentProgress;
+
+impl ProgressSink for SilentProgress {
+    fn on_event(&mut self, _event: ProgressEvent<'_>) {}
+}
+
+/// Shows a progress bar for every search
+#[derive(Debug, Default)]
+pub struct IndicatifProgress {
+    bar: Option<ProgressBar>,
+}
+
+impl ProgressSink for IndicatifProgress {
+    fn on_event(&mut self, event: ProgressEvent<'_>) {
+        match event {
+            ProgressEvent::Started { name, total } => {
+                let bar = ProgressBar::new(total as u64).with_message(name.to_string());
+                bar.set_style(
+                    ProgressStyle::with_template(
+                        "[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}",
+                    )
+                    .unwrap()
+                    .progress_chars("##-"),
+                );
+                self.bar = Some(bar);
+            }
+            ProgressEvent::Progressed {
+                name,
+                done,
+                matches,
+                ..
+            } => {
+                if let Some(bar) = &self.bar {
+                    bar.set_position(done as u64);
+                    bar.set_message(format!("{name} ({matches} matches)"));
+                }
+            }
+            ProgressEvent::Finished {
+                name,
+                matches,
+                elapsed,
+            } => {
+                if let Some(bar) = self.bar.take() {
+                    bar.finish_with_message(format!("{name} ({matches} matches) in {elapsed:?}"));
+                }
+            }
+        }
+    }
+}
+
+/// Passes every event to a closure, e.g. to report progress to another thread
+pub struct CallbackProgress<F> {
+    callback: F,
+}
+
+impl<F: FnMut(ProgressEvent<'_>)> CallbackProgress<F> {
+    pub fn new(callback: F) -> Self {
+        Self { callback }
+    }
+}
+
+impl<F: FnMut(ProgressEvent<'_>)> ProgressSink for CallbackProgress<F> {
+    fn on_event(&mut self, event: ProgressEvent<'_>) {
+        (self.callback)(event)
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use graphing::{create_file, csv_field};
+use serde::{Deserialize, Serialize};
+
+use crate::pattern::{LatexTable, PatternMatches};
+
+/// Matches of a single pattern in a single corpus
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CorpusRow {
+    pub corpus: String,
+    /// Name of the matcher, e.g. `Chain`
+    pub pattern: String,
+    pub count: usize,
+    /// Number of matches per 1000 scopes of the corpus
+    pub per_1k_scopes: f32,
+    pub avg_size: f32,
+    pub scope_coverage: f32,
+    pub edge_coverage: f32,
+}
+
+/// Comparison of the matches of several corpora, normalized by the number of scopes of every corpus
+#[derive(Default)]
+pub struct CorpusReport {
+    corpora: Vec<(String, PatternMatches)>,
+}
+
+impl CorpusReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_corpus(mut self, name: impl ToString, matches: PatternMatches) -> Self {
+        self.push(name, matches);
+        self
+    }
+
+    pub fn push(&mut self, name: impl ToString, matches: PatternMatches) {
+        self.corpora.push((name.to_string(), matches));
+    }
+
+    pub fn corpora(&self) -> impl Iterator<Item = (&str, &PatternMatches)> {
+        self.corpora.iter().map(|(name, m)| (name.as_str(), m))
+    }
+
+    /// A row for every pattern of every corpus
+    pub fn rows(&self) -> Vec<CorpusRow> {
+        self.corpora
+            .iter()
+            .flat_map(|(corpus, matches)| {
+                let summary = matches.summary();
+                summary.patterns.into_iter().map(move |p| CorpusRow {
+                    corpus: corpus.clone(),
+                    per_1k_scopes: match summary.total_scopes {
+                        0 => 0.0,
+                        total => p.sizes.count as f32 * 1000.0 / total as f32,
+                    },
+                    pattern: p.name,
+                    count: p.sizes.count,
+                    avg_size: p.sizes.avg,
+                    scope_coverage: p.coverage.scope_fraction(),
+                    edge_coverage: p.coverage.edge_fraction(),
+                })
+            })
+            .collect()
+    }
+
+    /// Names of the patterns, in the order of the rows of a corpus
+    fn patterns(&self) -> Vec<String> {
+        self.corpora
+            .first()
+            .map(|(_, m)| m.summary().patterns.into_iter().map(|p| p.name).collect())
+            .unwrap_or_default()
+    }
+
+    /// Matches per 1000 scopes of `pattern` in every corpus
+    fn per_1k_scopes(&self, rows: &[CorpusRow], pattern: &str) -> Vec<f32> {
+        self.corpora
+            .iter()
+            .map(|(corpus, _)| {
+                rows.iter()
+                    .find(|r| r.corpus == *corpus && r.pattern == pattern)
+                    .map(|r| r.per_1k_scopes)
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    /// [`LatexTable`] with the statistics of every corpus, for the layout see [`LatexTable`]
+    pub fn latex_table(&self) -> LatexTable<'_> {
+        self.corpora
+            .iter()
+            .fold(LatexTable::new(), |table, (name, matches)| {
+                table.with_corpus(name, matches)
+            })
+    }
+
+    /// Rows of a LaTeX table with a column per corpus, containing the number of matches per 1000 scopes of every pattern
+    pub fn to_latex_table(&self) -> String {
+        let rows = self.rows();
+        let mut lines = vec![
+            std::iter::once("Pattern")
+                .chain(self.corpora.iter().map(|(name, _)| name.as_str()))
+                .collect::<Vec<_>>()
+                .join(" & ")
+                + " \\\\",
+            std::iter::once("Scopes".to_string())
+                .chain(self.corpora.iter().map(|(_, m)| m.total_scopes.to_string()))
+                .collect::<Vec<_>>()
+                .join(" & ")
+                + " \\\\",
+        ];
+        for pattern in self.patterns() {
+            let cells = self
+                .per_1k_scopes(&rows, &pattern)
+                .into_iter()
+                .map(|v| format!("{v:.2}"));
+            lines.push(
+                std::iter::once(pattern)
+                    .chain(cells)
+                    .collect::<Vec<_>>()
+                    .join(" & ")
+                    + " \\\\",
+            );
+        }
+        lines.join("\n")
+    }
+
+    pub fn to_csv(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(create_file(path)?);
+        self.write_csv(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Writes [`CorpusReport::rows`] as `;`-separated rows with a header
+    pub fn write_csv<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writeln!(
+            writer,
+            "corpus;pattern;count;per_1k_scopes;avg_size;scope_coverage;edge_coverage"
+        )?;
+        for r in self.rows() {
+            writeln!(
+                writer,
+                "{};{};{};{};{};{};{}",
+                csv_field(&r.corpus, ';'),
+                csv_field(&r.pattern, ';'),
+                r.count,
+                r.per_1k_scopes,
+                r.avg_size,
+                r.scope_coverage,
+                r.edge_coverage,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Number of matches per 1000 scopes, a column per corpus
+impl std::fmt::Display for CorpusReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rows = self.rows();
+        write!(f, "{:<12}", "Pattern")?;
+        for (name, _) in &self.corpora {
+            write!(f, " {name:>16}")?;
+        }
+        writeln!(f)?;
+        write!(f, "{:<12}", "Scopes")?;
+        for (_, matches) in &self.corpora {
+            write!(f, " {:>16}", matches.total_scopes)?;
+        }
+        writeln!(f)?;
+        for pattern in self.patterns() {
+            write!(f, "{pattern:<12}")?;
+            for v in self.per_1k_scopes(&rows, &pattern) {
+                write!(f, " {v:>16.2}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::{Rng, SeedableRng, rngs::SmallRng};
+
+use crate::{
+    Scope, ScopeGraph,
+    pattern::{
+        BicliqueMatcher, ChainMatcher, CircleMatcher, CliqueMatcher, DeepDiamondMatcher,
+        DiamondMatcher, FanoutMatcher, PatternConfig, PatternMatcher, SilentProgress, StarMatcher,
+        TreeMatcher,
+    },
+    stat::Stats,
+};
+
+/// Number of matches of a pattern in a graph, compared to the number of matches in random graphs
+#[derive(Clone, Debug)]
+pub struct MotifSignificance {
+    pub name: &'static str,
+    pub observed: usize,
+    /// Match counts of the random graphs
+    pub random: Stats,
+}
+
+impl MotifSignificance {
+    /// Number of standard deviations that the observed count is above the average random count.
+    ///
+    /// Infinite if every random graph has the same count and the observed count differs.
+    pub fn z_score(&self) -> f32 {
+        let diff = self.observed as f32 - self.random.avg();
+        let std_dev = self.random.std_dev();
+        if std_dev > 0.0 {
+            diff / std_dev
+        } else if diff == 0.0 {
+            0.0
+        } else {
+            diff.signum() * f32::INFINITY
+        }
+    }
+
+    pub fn to_latex_table(&self) -> String {
+        format!(
+            "{} & {} & {:.2} & {:.2} & {:.2} \\\\",
+            self.name,
+            self.observed,
+            self.random.avg(),
+            self.random.std_dev(),
+            self.z_score(),
+        )
+    }
+}
+
+impl std::fmt::Display for MotifSignificance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} matches, random {:.2} ± {:.2}, z-score {:.2}",
+            self.name,
+            self.observed,
+            self.random.avg(),
+            self.random.std_dev(),
+            self.z_score()
+        )
+    }
+}
+
+/// Compares the match counts of patterns against degree-preserving random rewirings of a graph.
+///
+/// A rewiring repeatedly swaps the targets of two edges with the same label,
+/// so every scope keeps its number of incoming and outgoing edges per label.
+pub struct SignificanceTest {
+    num_samples: usize,
+    swaps_per_edge: usize,
+    seed: u64,
+    config: PatternConfig,
+}
+
+impl Default for SignificanceTest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SignificanceTest {
+    pub fn new() -> Self {
+        Self {
+            num_samples: 10,
+            swaps_per_edge: 10,
+            seed: 0,
+            config: PatternConfig::default(),
+        }
+    }
+
+    /// Number of random graphs to compare against
+    pub fn with_samples(mut self, num_samples: usize) -> Self {
+        self.num_samples = num_samples;
+        self
+    }
+
+    /// Attempted swaps per edge when rewiring a graph
+    pub fn with_swaps_per_edge(mut self, swaps_per_edge: usize) -> Self {
+        self.swaps_per_edge = swaps_per_edge;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Matchers used by [`SignificanceTest::test_all`]
+    pub fn with_config(mut self, config: PatternConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Random rewirings of `graph`, the same seed gives the same graphs
+    pub fn random_graphs(&self, graph: &ScopeGraph) -> impl Iterator<Item = ScopeGraph> {
+        let mut rng = SmallRng::seed_from_u64(self.seed);
+        let swaps = self.swaps_per_edge * graph.edges.len();
+        (0..self.num_samples).map(move |_| rewire(graph, swaps, &mut rng))
+    }
+
+    /// Significance of the matches of `matcher`
+    pub fn test<M: PatternMatcher>(&self, matcher: &M, graph: &ScopeGraph) -> MotifSignificance {
+        MotifSignificance {
+            name: M::NAME,
+            observed: matcher.search(graph).len(),
+            random: self
+                .random_graphs(graph)
+                .map(|g| matcher.search(&g).len())
+                .collect(),
+        }
+    }
+
+    /// Significance of the matches of all dedicated matchers, the random graphs are shared between the matchers
+    pub fn test_all(&self, graph: &ScopeGraph) -> Vec<MotifSignificance> {
+        fn counts(graph: &ScopeGraph, config: &PatternConfig) -> [usize; 9] {
+            [
+                config
+                    .search(&config.chain, graph, &mut SilentProgress)
+                    .len(),
+                config
+                    .search(&config.fanout, graph, &mut SilentProgress)
+                    .len(),
+                config
+                    .search(&config.tree, graph, &mut SilentProgress)
+                    .len(),
+                config
+                    .search(&config.diamond, graph, &mut SilentProgress)
+                    .len(),
+                config
+                    .search(&config.deep_diamond, graph, &mut SilentProgress)
+                    .len(),
+                config
+                    .search(&config.circle, graph, &mut SilentProgress)
+                    .len(),
+                config
+                    .search(&config.star, graph, &mut SilentProgress)
+                    .len(),
+                config
+                    .search(&config.clique, graph, &mut SilentProgress)
+                    .len(),
+                config
+                    .search(&config.biclique, graph, &mut SilentProgress)
+                    .len(),
+            ]
+        }
+        let names = [
+            ChainMatcher::NAME,
+            FanoutMatcher::NAME,
+            TreeMatcher::NAME,
+            DiamondMatcher::NAME,
+            DeepDiamondMatcher::NAME,
+            CircleMatcher::NAME,
+            StarMatcher::NAME,
+            CliqueMatcher::NAME,
+            BicliqueMatcher::NAME,
+        ];
+
+        let observed = counts(graph, &self.config);
+        let random = self
+            .random_graphs(graph)
+            .map(|g| counts(&g, &self.config))
+            .collect::<Vec<_>>();
+        names
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| MotifSignificance {
+                name,
+                observed: observed[i],
+                random: random.iter().map(|c| c[i]).collect(),
+            })
+            .collect()
+    }
+
+    /// LaTeX rows of [`SignificanceTest::test_all`], preceded by a row with the name of the graph
+    pub fn to_latex_table(&self, graph: &ScopeGraph, name: &str) -> String {
+        std::iter::once(format!("{name} & & & & \\\\"))
+            .chain(self.test_all(graph).iter().map(|s| s.to_latex_table()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Copy of `graph` where `swaps` times two edges `a -> b` and `c -> d` with the same label are replaced by `a -> d` and `c -> b`.
+///
+/// Swaps that would create an edge from a scope to itself or a second edge between two scopes are skipped.
+fn rewire(graph: &ScopeGraph, swaps: usize, rng: &mut impl Rng) -> ScopeGraph {
+    let mut edges = graph
+        .edges
+        .iter()
+        .map(|e| (e.from, e.to, e.lbl.clone()))
+        .collect::<Vec<_>>();
+    let mut pairs = edges
+        .iter()
+        .map(|(from, to, _)| (*from, *to))
+        .collect::<HashSet<_>>();
+    let by_label = edges.iter().enumerate().fold(
+        HashMap::<_, Vec<usize>>::new(),
+        |mut acc, (i, (_, _, lbl))| {
+            acc.entry(lbl.clone()).or_default().push(i);
+            acc
+        },
+    );
+
+    if !edges.is_empty() {
+        for _ in 0..swaps {
+            let i = rng.random_range(0..edges.len());
+            let same_label = &by_label[&edges[i].2];
+            let j = same_label[rng.random_range(0..same_label.len())];
+            let ((a, b), (c, d)) = ((edges[i].0, edges[i].1), (edges[j].0, edges[j].1));
+            if a == d || c == b || pairs.contains(&(a, d)) || pairs.contains(&(c, b)) {
+                continue;
+            }
+            pairs.remove(&(a, b));
+            pairs.remove(&(c, d));
+            pairs.insert((a, d));
+            pairs.insert((c, b));
+            edges[i].1 = d;
+            edges[j].1 = b;
+        }
+    }
+
+    let mut rewired = ScopeGraph::new();
+    for s in &graph.scopes {
+        rewired.add_node(*s);
+    }
+    for (from, to, lbl) in edges {
+        rewired.add_edge_labeled::<Scope, _>(from, to, lbl);
+    }
+    rewired
+}
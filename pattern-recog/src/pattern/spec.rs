@@ -0,0 +1,435 @@
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
+
+use crate::{
+    MatchableLabel, NodeSignature, Scope, ScopeGraph,
+    pattern::{MatchMode, MatchedPattern, dedup_matches},
+};
+
+/// Labels that an edge of a [`PatternSpec`] accepts
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LabelConstraint {
+    Any,
+    OneOf(Vec<MatchableLabel>),
+}
+
+impl LabelConstraint {
+    pub fn matches(&self, label: &MatchableLabel) -> bool {
+        match self {
+            Self::Any => true,
+            Self::OneOf(labels) => labels.contains(label),
+        }
+    }
+}
+
+impl std::fmt::Display for LabelConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Any => write!(f, "->"),
+            Self::OneOf(labels) => {
+                let labels = labels.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+                write!(f, "-{}->", labels.join("|"))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpecEdge {
+    pub from: usize,
+    pub to: usize,
+    pub constraint: LabelConstraint,
+}
+
+/// Pattern defined as a list of edges between numbered nodes, matched with vf2.
+///
+/// Can be built with [`PatternSpec::edge`] or parsed from text, where edges are separated by `;` or newlines:
+/// `0 -Parent-> 1; 1 -ExtendImpl|Parent-> 2; 2 -> 0`.
+/// An edge without labels accepts any label. Nodes are numbered from 0 without gaps.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PatternSpec {
+    name: String,
+    num_nodes: usize,
+    edges: Vec<SpecEdge>,
+    mode: MatchMode,
+    max_distance: usize,
+    /// Degrees every node needs in a match, see [`PatternSpec::push_edge`]
+    signatures: Vec<NodeSignature>,
+}
+
+impl PatternSpec {
+    pub fn new(name: impl ToString) -> Self {
+        Self {
+            name: name.to_string(),
+            num_nodes: 0,
+            edges: Vec::new(),
+            mode: MatchMode::default(),
+            max_distance: 0,
+            signatures: Vec::new(),
+        }
+    }
+
+    pub fn with_name(mut self, name: impl ToString) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Whether matches may have extra edges between their scopes, [`MatchMode::Induced`] by default
+    pub fn with_mode(mut self, mode: MatchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Also accept matches that miss up to `max_distance` edges of the pattern,
+    /// or have extra edges between their scopes in [`MatchMode::Induced`].
+    ///
+    /// Missing edges are only found if the rest of the pattern is still connected.
+    pub fn with_max_distance(mut self, max_distance: usize) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    /// Adds an edge that accepts any of `labels`, or any label if `labels` is empty
+    pub fn edge(
+        mut self,
+        from: usize,
+        to: usize,
+        labels: impl IntoIterator<Item = MatchableLabel>,
+    ) -> Self {
+        let labels = labels.into_iter().collect::<Vec<_>>();
+        let constraint = match labels.is_empty() {
+            true => LabelConstraint::Any,
+            false => LabelConstraint::OneOf(labels),
+        };
+        self.push_edge(SpecEdge {
+            from,
+            to,
+            constraint,
+        });
+        self
+    }
+
+    /// Adds `edge` and counts it in the signatures of its nodes,
+    /// its label only counts if the edge accepts a single label.
+    fn push_edge(&mut self, edge: SpecEdge) {
+        self.num_nodes = self.num_nodes.max(edge.from + 1).max(edge.to + 1);
+        self.signatures
+            .resize(self.num_nodes, NodeSignature::default());
+
+        let label = match &edge.constraint {
+            LabelConstraint::OneOf(labels) if labels.len() == 1 => Some(&labels[0]),
+            _ => None,
+        };
+        let between = self
+            .edges
+            .iter()
+            .filter(|e| e.from == edge.from && e.to == edge.to)
+            .collect::<Vec<_>>();
+        let new_neighbor = between.is_empty();
+        let new_label = !between.iter().any(|e| e.constraint == edge.constraint);
+        self.signatures[edge.from].add_outgoing(label, new_neighbor, new_label);
+        self.signatures[edge.to].add_incoming(label, new_neighbor, new_label);
+        self.edges.push(edge);
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.num_nodes
+    }
+
+    pub fn mode(&self) -> MatchMode {
+        self.mode
+    }
+
+    pub fn max_distance(&self) -> usize {
+        self.max_distance
+    }
+
+    pub fn edges(&self) -> &[SpecEdge] {
+        &self.edges
+    }
+
+    fn find_edge(&self, from: usize, to: usize) -> Option<&SpecEdge> {
+        self.edges.iter().find(|e| e.from == from && e.to == to)
+    }
+
+    fn isomorphisms(&self, graph: &ScopeGraph, mode: MatchMode) -> Vec<Vec<vf2::NodeIndex>> {
+        let node_eq = |p: &NodeSignature, g: &NodeSignature| g.covers(p);
+        let edge_eq = |c: &LabelConstraint, l: &MatchableLabel| c.matches(l);
+        match mode {
+            MatchMode::Induced => vf2::induced_subgraph_isomorphisms(self, graph)
+                .node_eq(node_eq)
+                .edge_eq(edge_eq)
+                .iter()
+                .collect(),
+            MatchMode::NonInduced => vf2::subgraph_isomorphisms(self, graph)
+                .node_eq(node_eq)
+                .edge_eq(edge_eq)
+                .iter()
+                .collect(),
+        }
+    }
+
+    /// Finds the subgraphs of `graph` that match this pattern, see [`PatternSpec::with_mode`]
+    /// and [`PatternSpec::with_max_distance`].
+    ///
+    /// Matches that only differ by an automorphism of the pattern are returned once.
+    /// With a maximum distance, matches with the same set of scopes are returned once, with the lowest distance.
+    pub fn search(&self, graph: &ScopeGraph) -> Vec<SpecMatch> {
+        if self.max_distance == 0 {
+            let pattern_edges = self
+                .edges
+                .iter()
+                .map(|e| (e.from, e.to))
+                .collect::<Vec<_>>();
+            return dedup_matches(self.isomorphisms(graph, self.mode), &pattern_edges)
+                .into_iter()
+                .map(|iso| SpecMatch {
+                    scopes: iso.into_iter().map(Scope).collect(),
+                    distance: 0,
+                })
+                .collect();
+        }
+
+        // every missing edge is an edge of the pattern that is left out of the match,
+        // so search for the pattern without every combination of at most `max_distance` edges.
+        let mut matches = HashMap::<Vec<usize>, SpecMatch>::new();
+        for removed in edge_combinations(self.edges.len(), self.max_distance) {
+            let mut reduced = PatternSpec {
+                edges: Vec::new(),
+                signatures: Vec::new(),
+                ..self.clone()
+            };
+            for (_, e) in self
+                .edges
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !removed.contains(i))
+            {
+                reduced.push_edge(e.clone());
+            }
+            // a disconnected pattern matches every combination of its components
+            if !reduced.is_connected() {
+                continue;
+            }
+
+            for iso in reduced.isomorphisms(graph, MatchMode::NonInduced) {
+                let distance = self.distance(graph, &iso);
+                if distance > self.max_distance {
+                    continue;
+                }
+                let mut key = iso.clone();
+                key.sort();
+                let m = SpecMatch {
+                    scopes: iso.into_iter().map(Scope).collect(),
+                    distance,
+                };
+                match matches.get(&key) {
+                    Some(existing) if existing.distance <= distance => (),
+                    _ => {
+                        matches.insert(key, m);
+                    }
+                }
+            }
+        }
+        matches.into_values().collect()
+    }
+
+    /// Number of edges of this pattern that are missing in the match `iso`,
+    /// plus the number of extra edges between the matched scopes if the mode is [`MatchMode::Induced`].
+    ///
+    /// An edge with a label that does not match counts as missing.
+    fn distance(&self, graph: &ScopeGraph, iso: &[vf2::NodeIndex]) -> usize {
+        let missing = self
+            .edges
+            .iter()
+            .filter(|e| {
+                !graph
+                    .find_edge(iso[e.from], iso[e.to])
+                    .is_some_and(|edge| e.constraint.matches(&edge.lbl))
+            })
+            .count();
+        let extra = match self.mode {
+            MatchMode::Induced => (0..self.num_nodes)
+                .flat_map(|from| (0..self.num_nodes).map(move |to| (from, to)))
+                .filter(|&(from, to)| {
+                    from != to
+                        && self.find_edge(from, to).is_none()
+                        && graph.find_edge(iso[from], iso[to]).is_some()
+                })
+                .count(),
+            MatchMode::NonInduced => 0,
+        };
+        missing + extra
+    }
+
+    /// True if every node can be reached from node 0, ignoring the direction of edges
+    fn is_connected(&self) -> bool {
+        let mut reached = vec![false; self.num_nodes];
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            if std::mem::replace(&mut reached[node], true) {
+                continue;
+            }
+            for e in &self.edges {
+                if e.from == node {
+                    stack.push(e.to);
+                }
+                if e.to == node {
+                    stack.push(e.from);
+                }
+            }
+        }
+        reached.into_iter().all(|r| r)
+    }
+}
+
+/// All sets of at most `max` indices below `n`, including the empty set
+fn edge_combinations(n: usize, max: usize) -> Vec<Vec<usize>> {
+    let mut combinations = vec![Vec::new()];
+    let mut last = vec![Vec::new()];
+    for _ in 0..max.min(n) {
+        last = last
+            .into_iter()
+            .flat_map(|c: Vec<usize>| {
+                let next = c.last().map_or(0, |l| l + 1);
+                (next..n).map(move |i| {
+                    let mut c = c.clone();
+                    c.push(i);
+                    c
+                })
+            })
+            .collect();
+        combinations.extend(last.iter().cloned());
+    }
+    combinations
+}
+
+impl FromStr for PatternSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut spec = PatternSpec::new("custom");
+        for edge in s
+            .split([';', '\n'])
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+        {
+            let (from, rest) = edge
+                .split_once('-')
+                .ok_or_else(|| format!("Edge `{edge}` has no arrow"))?;
+            let (labels, to) = rest
+                .split_once("->")
+                .ok_or_else(|| format!("Edge `{edge}` has no arrow"))?;
+            let node = |n: &str| {
+                n.trim()
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid node `{}` in edge `{edge}`", n.trim()))
+            };
+            let labels = labels
+                .split('|')
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(MatchableLabel::from_str)
+                .collect::<Result<Vec<_>, _>>()?;
+            spec = spec.edge(node(from)?, node(to)?, labels);
+        }
+
+        if spec.edges.is_empty() {
+            return Err("Pattern has no edges".to_string());
+        }
+        let used = spec
+            .edges
+            .iter()
+            .flat_map(|e| [e.from, e.to])
+            .collect::<HashSet<_>>();
+        if used.len() != spec.num_nodes {
+            return Err(format!(
+                "Nodes have to be numbered 0 to {} without gaps",
+                spec.num_nodes - 1
+            ));
+        }
+        Ok(spec)
+    }
+}
+
+impl std::fmt::Display for PatternSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let edges = self
+            .edges
+            .iter()
+            .map(|e| format!("{} {} {}", e.from, e.constraint, e.to))
+            .collect::<Vec<_>>();
+        write!(f, "{}", edges.join("; "))
+    }
+}
+
+impl vf2::Graph for PatternSpec {
+    type NodeLabel = NodeSignature;
+
+    type EdgeLabel = LabelConstraint;
+
+    fn is_directed(&self) -> bool {
+        true
+    }
+
+    fn node_count(&self) -> usize {
+        self.num_nodes
+    }
+
+    fn node_label(&self, node: vf2::NodeIndex) -> Option<&Self::NodeLabel> {
+        self.signatures.get(node)
+    }
+
+    fn neighbors(
+        &self,
+        node: vf2::NodeIndex,
+        direction: vf2::Direction,
+    ) -> impl Iterator<Item = vf2::NodeIndex> {
+        self.edges.iter().filter_map(move |e| match direction {
+            vf2::Direction::Outgoing => (e.from == node).then_some(e.to),
+            vf2::Direction::Incoming => (e.to == node).then_some(e.from),
+        })
+    }
+
+    fn contains_edge(&self, source: vf2::NodeIndex, target: vf2::NodeIndex) -> bool {
+        self.find_edge(source, target).is_some()
+    }
+
+    fn edge_label(
+        &self,
+        source: vf2::NodeIndex,
+        target: vf2::NodeIndex,
+    ) -> Option<&Self::EdgeLabel> {
+        self.find_edge(source, target).map(|e| &e.constraint)
+    }
+}
+
+/// Match of a [`PatternSpec`], scopes are in the order of the nodes of the pattern
+#[derive(Clone, Debug)]
+pub struct SpecMatch {
+    scopes: Vec<Scope>,
+    distance: usize,
+}
+
+impl SpecMatch {
+    /// Number of missing and extra edges compared to the pattern, 0 for an exact match
+    pub fn distance(&self) -> usize {
+        self.distance
+    }
+}
+
+impl MatchedPattern for SpecMatch {
+    fn size(&self) -> usize {
+        self.scopes.len()
+    }
+
+    fn scopes(&self) -> impl Iterator<Item = &Scope> {
+        self.scopes.iter()
+    }
+}
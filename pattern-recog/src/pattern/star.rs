@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    MatchableLabel, Scope, ScopeGraph,
+    pattern::{MatchedPattern, OverlapPolicy, PatternMatcher, SizeBounds},
+};
+
+const STAR_LABELS: &[MatchableLabel] = &[MatchableLabel::ClassMember];
+
+/// Minimum number of leaves of a [`StarMatch`] when the degree of [`StarMatcher`] is not given
+pub const DEFAULT_STAR_DEGREE: usize = 10;
+
+#[derive(Serialize, Debug)]
+pub struct StarMatch {
+    hub: Scope,
+    leaves: Vec<Scope>,
+}
+
+impl StarMatch {
+    pub fn from_scope(scope: Scope) -> Self {
+        Self {
+            hub: scope,
+            leaves: Vec::new(),
+        }
+    }
+
+    pub fn push_leaf(&mut self, leaf: Scope) {
+        self.leaves.push(leaf);
+    }
+}
+
+impl MatchedPattern for StarMatch {
+    fn size(&self) -> usize {
+        self.leaves.len()
+    }
+
+    fn scopes(&self) -> impl Iterator<Item = &Scope> {
+        std::iter::once(&self.hub).chain(self.leaves.iter())
+    }
+}
+
+/// Finds hubs with many incoming edges from leaves, e.g. the members of a god class over the default ClassMember edges.
+///
+/// Unlike [`FanoutMatcher`](crate::pattern::FanoutMatcher), only scopes without incoming edges count as leaves,
+/// so members with structure of their own are not part of the star.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct StarMatcher {
+    labels: Vec<MatchableLabel>,
+    min: usize,
+    max: Option<usize>,
+}
+
+impl Default for StarMatcher {
+    fn default() -> Self {
+        Self {
+            labels: STAR_LABELS.to_vec(),
+            min: DEFAULT_STAR_DEGREE,
+            max: None,
+        }
+    }
+}
+
+impl StarMatcher {
+    /// Labels of the edges that are followed, all labels if empty
+    pub fn with_labels(mut self, labels: impl IntoIterator<Item = MatchableLabel>) -> Self {
+        self.labels = labels.into_iter().collect();
+        self
+    }
+
+    /// Minimum number of leaves of a hub
+    pub fn with_min_degree(mut self, min: usize) -> Self {
+        self.min = min;
+        self
+    }
+
+    /// Maximum number of leaves of a hub
+    pub fn with_max_degree(mut self, max: usize) -> Self {
+        self.max = Some(max);
+        self
+    }
+}
+
+impl PatternMatcher for StarMatcher {
+    type Match = StarMatch;
+    const OVERLAP: OverlapPolicy = OverlapPolicy::AllowOverlap;
+    const NAME: &str = "Star";
+
+    fn find_pattern_for_scope(&self, graph: &ScopeGraph, scope: Scope) -> Vec<Self::Match> {
+        let mut star = StarMatch::from_scope(scope);
+        let incoming_edges = graph.get_incoming_edges_with_labels(scope, &self.labels);
+        for edge in incoming_edges {
+            let is_leaf = graph
+                .to_edge_map
+                .get(&edge.from)
+                .is_none_or(|edges| edges.is_empty());
+            if is_leaf {
+                star.push_leaf(edge.from);
+            }
+        }
+
+        match star.leaves.is_empty() {
+            true => Vec::new(),
+            false => vec![star],
+        }
+    }
+
+    fn size_bounds(&self) -> SizeBounds {
+        SizeBounds {
+            min: self.min,
+            max: self.max,
+        }
+    }
+}
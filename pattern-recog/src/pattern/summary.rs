@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    pattern::{Coverage, OverlapStats, PatternMatches},
+    stat::{Stats, StatsSummary},
+};
+
+/// Number of matches and their sizes for a single pattern
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PatternSummary {
+    /// Name of the matcher, e.g. `Chain`
+    pub name: String,
+    pub sizes: StatsSummary,
+    pub overlap: OverlapStats,
+    pub coverage: Coverage,
+}
+
+/// Overview of [`PatternMatches`] without the matched scopes
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MatchSummary {
+    pub total_scopes: usize,
+    pub patterns: Vec<PatternSummary>,
+    /// Coverage of all patterns together
+    pub coverage: Coverage,
+}
+
+impl MatchSummary {
+    pub fn get(&self, name: &str) -> Option<&PatternSummary> {
+        self.patterns.iter().find(|p| p.name == name)
+    }
+}
+
+impl PatternMatches {
+    pub fn summary(&self) -> MatchSummary {
+        let patterns = self
+            .scopes_per_pattern()
+            .into_iter()
+            .map(|(name, matches)| PatternSummary {
+                name: name.to_string(),
+                sizes: StatsSummary::from(
+                    &matches.iter().map(|(size, _)| *size).collect::<Stats>(),
+                ),
+                overlap: OverlapStats::from_matches(matches.iter().map(|(_, s)| s.as_slice())),
+                coverage: self.coverage.get(name).copied().unwrap_or_default(),
+            })
+            .collect();
+        MatchSummary {
+            total_scopes: self.total_scopes,
+            patterns,
+            coverage: self.coverage.any,
+        }
+    }
+}
@@ -1,11 +1,13 @@
+use serde::{Deserialize, Serialize};
+
 use crate::{
     MatchableLabel, Scope, ScopeGraph,
-    pattern::{MatchedPattern, PatternMatcher},
+    pattern::{MatchedPattern, OverlapPolicy, PatternMatcher, SizeBounds},
 };
 
 const TREE_LABELS: &[MatchableLabel] = &[MatchableLabel::Parent, MatchableLabel::ExtendImpl];
 
-#[derive(Debug)]
+#[derive(Serialize, Debug)]
 pub struct TreeMatch {
     root: Scope,
     leaves: Vec<Scope>,
@@ -34,24 +36,66 @@ impl MatchedPattern for TreeMatch {
     }
 }
 
-pub struct TreeMatcher;
+/// Finds scopes with incoming edges from several leaves.
+///
+/// By default at least two leaves are needed, a single leaf is just a chain.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct TreeMatcher {
+    labels: Vec<MatchableLabel>,
+    min: usize,
+    max: Option<usize>,
+}
+
+impl Default for TreeMatcher {
+    fn default() -> Self {
+        Self {
+            labels: TREE_LABELS.to_vec(),
+            min: 2,
+            max: None,
+        }
+    }
+}
+
+impl TreeMatcher {
+    /// Labels of the edges that are followed, all labels if empty
+    pub fn with_labels(mut self, labels: impl IntoIterator<Item = MatchableLabel>) -> Self {
+        self.labels = labels.into_iter().collect();
+        self
+    }
+
+    /// Minimum number of leaves
+    pub fn with_min_leaves(mut self, min: usize) -> Self {
+        self.min = min;
+        self
+    }
+
+    /// Maximum number of leaves
+    pub fn with_max_leaves(mut self, max: usize) -> Self {
+        self.max = Some(max);
+        self
+    }
+}
 
 impl PatternMatcher for TreeMatcher {
     type Match = TreeMatch;
-    const EXCLUSIVE: bool = false;
+    const OVERLAP: OverlapPolicy = OverlapPolicy::AllowOverlap;
     const NAME: &str = "Tree";
 
-    fn find_pattern_for_scope(graph: &ScopeGraph, scope: Scope) -> Vec<Self::Match> {
+    fn find_pattern_for_scope(&self, graph: &ScopeGraph, scope: Scope) -> Vec<Self::Match> {
         let mut t = TreeMatch::from_scope(scope);
-        let incoming_edges = graph.get_incoming_edges_with_labels(scope, TREE_LABELS);
+        let incoming_edges = graph.get_incoming_edges_with_labels(scope, &self.labels);
         for edge in incoming_edges {
             t.push_leaf(edge.from);
         }
 
-        match t.leaves.len() {
-            // if length is below 2, then it's just a chain
-            0..=1 => Vec::new(),
-            2.. => vec![t],
+        vec![t]
+    }
+
+    fn size_bounds(&self) -> SizeBounds {
+        SizeBounds {
+            min: self.min,
+            max: self.max,
         }
     }
 }
@@ -0,0 +1,95 @@
+use crate::MatchableLabel;
+
+/// Number of distinct neighbors of a scope, in total and per label.
+///
+/// A node of a pattern can only be mapped onto a scope with at least the same degrees,
+/// so comparing signatures rules out most candidates before vf2 tries to extend a mapping with them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NodeSignature {
+    pub outgoing: usize,
+    pub incoming: usize,
+    /// Sorted by label
+    labels: Vec<LabelDegree>,
+}
+
+/// Number of distinct neighbors of a scope over edges with `label`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LabelDegree {
+    pub label: MatchableLabel,
+    pub outgoing: usize,
+    pub incoming: usize,
+}
+
+impl NodeSignature {
+    /// Counts an outgoing edge, `new_neighbor` if there was no edge to its target yet
+    /// and `new_label` if there was no edge with `label` to its target yet.
+    ///
+    /// Edges without a label only count towards the total degree.
+    pub(crate) fn add_outgoing(
+        &mut self,
+        label: Option<&MatchableLabel>,
+        new_neighbor: bool,
+        new_label: bool,
+    ) {
+        self.outgoing += new_neighbor as usize;
+        if let Some(label) = label {
+            self.label_mut(label).outgoing += new_label as usize;
+        }
+    }
+
+    /// Counts an incoming edge, see [`NodeSignature::add_outgoing`]
+    pub(crate) fn add_incoming(
+        &mut self,
+        label: Option<&MatchableLabel>,
+        new_neighbor: bool,
+        new_label: bool,
+    ) {
+        self.incoming += new_neighbor as usize;
+        if let Some(label) = label {
+            self.label_mut(label).incoming += new_label as usize;
+        }
+    }
+
+    fn label_mut(&mut self, label: &MatchableLabel) -> &mut LabelDegree {
+        let i = match self.labels.binary_search_by(|d| d.label.cmp(label)) {
+            Ok(i) => i,
+            Err(i) => {
+                self.labels.insert(
+                    i,
+                    LabelDegree {
+                        label: label.clone(),
+                        outgoing: 0,
+                        incoming: 0,
+                    },
+                );
+                i
+            }
+        };
+        &mut self.labels[i]
+    }
+
+    pub fn label(&self, label: &MatchableLabel) -> Option<&LabelDegree> {
+        self.labels
+            .binary_search_by(|d| d.label.cmp(label))
+            .ok()
+            .map(|i| &self.labels[i])
+    }
+
+    pub fn labels(&self) -> &[LabelDegree] {
+        &self.labels
+    }
+
+    /// Whether a node with signature `pattern` can be mapped onto this scope when labels are not compared
+    pub fn covers_degree(&self, pattern: &Self) -> bool {
+        self.outgoing >= pattern.outgoing && self.incoming >= pattern.incoming
+    }
+
+    /// Whether a node with signature `pattern` can be mapped onto this scope, also comparing the degree per label
+    pub fn covers(&self, pattern: &Self) -> bool {
+        self.covers_degree(pattern)
+            && pattern.labels.iter().all(|p| {
+                self.label(&p.label)
+                    .is_some_and(|d| d.outgoing >= p.outgoing && d.incoming >= p.incoming)
+            })
+    }
+}
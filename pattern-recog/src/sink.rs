@@ -0,0 +1,82 @@
+use std::{
+    collections::BTreeMap,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use graphing::create_file;
+
+use crate::TIMESTAMP;
+
+/// Destination of the artifacts of [`ScopeGraph::match_subgraph`](crate::ScopeGraph::match_subgraph),
+/// such as the searched graph and the found matches.
+pub trait OutputSink {
+    /// Writer for the artifact `name`, e.g. `graph.json`
+    fn writer(&mut self, name: &str) -> std::io::Result<Box<dyn Write + '_>>;
+}
+
+/// Discards all artifacts
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoOutput;
+
+impl OutputSink for NoOutput {
+    fn writer(&mut self, _name: &str) -> std::io::Result<Box<dyn Write + '_>> {
+        Ok(Box::new(std::io::sink()))
+    }
+}
+
+/// Writes every artifact to a file in a directory, which is created if it does not exist
+#[derive(Clone, Debug)]
+pub struct DirectoryOutput {
+    path: PathBuf,
+}
+
+impl DirectoryOutput {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `output/patterns/<name>/<timestamp>`, with the same timestamp for the whole run
+    pub fn timestamped(name: &str) -> Self {
+        Self::new(format!("output/patterns/{}/{}", name, *TIMESTAMP))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl OutputSink for DirectoryOutput {
+    fn writer(&mut self, name: &str) -> std::io::Result<Box<dyn Write + '_>> {
+        Ok(Box::new(BufWriter::new(create_file(self.path.join(name))?)))
+    }
+}
+
+/// Keeps every artifact in memory
+#[derive(Clone, Debug, Default)]
+pub struct MemoryOutput {
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl MemoryOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&[u8]> {
+        self.files.get(name).map(Vec::as_slice)
+    }
+
+    /// Name and contents of every artifact, sorted by name
+    pub fn files(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.files.iter().map(|(n, f)| (n.as_str(), f.as_slice()))
+    }
+}
+
+impl OutputSink for MemoryOutput {
+    fn writer(&mut self, name: &str) -> std::io::Result<Box<dyn Write + '_>> {
+        let file = self.files.entry(name.to_string()).or_default();
+        file.clear();
+        Ok(Box::new(file))
+    }
+}
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 #[derive(Clone, Debug)]
 pub struct Stats {
     data_points: Vec<f32>,
@@ -33,6 +35,10 @@ impl Stats {
         Self { data_points }
     }
 
+    pub fn count(&self) -> usize {
+        self.data_points.len()
+    }
+
     pub fn avg(&self) -> f32 {
         if self.data_points.is_empty() {
             return 0.0;
@@ -62,6 +68,48 @@ impl Stats {
         }
     }
 
+    /// Population standard deviation
+    pub fn std_dev(&self) -> f32 {
+        if self.data_points.is_empty() {
+            return 0.0;
+        }
+        let avg = self.avg();
+        let variance = self
+            .data_points
+            .iter()
+            .map(|x| (x - avg).powi(2))
+            .sum::<f32>()
+            / self.data_points.len() as f32;
+        variance.sqrt()
+    }
+
+    /// Counts of the data points in buckets of `bucket_width`, starting at the bucket of the minimum.
+    ///
+    /// Empty buckets between the minimum and maximum are kept, so the histogram can be plotted directly.
+    pub fn histogram(&self, bucket_width: f32) -> Histogram {
+        assert!(bucket_width > 0.0, "bucket width must be positive");
+        if self.data_points.is_empty() {
+            return Histogram::default();
+        }
+        let first = (self.min() / bucket_width).floor();
+        let bucket_count = (self.max() / bucket_width).floor() - first + 1.0;
+        let mut buckets = (0..bucket_count as usize)
+            .map(|i| {
+                let start = (first + i as f32) * bucket_width;
+                Bucket {
+                    start,
+                    end: start + bucket_width,
+                    count: 0,
+                }
+            })
+            .collect::<Vec<_>>();
+        for x in &self.data_points {
+            let i = (x / bucket_width).floor() - first;
+            buckets[i as usize].count += 1;
+        }
+        Histogram { buckets }
+    }
+
     pub fn to_latex_table(&self, name: &str) -> String {
         format!(
             "{} & {} & {:.2} & {} & {} & {} \\\\",
@@ -75,6 +123,44 @@ impl Stats {
     }
 }
 
+/// Aggregates of [`Stats`] without the data points
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct StatsSummary {
+    pub count: usize,
+    pub avg: f32,
+    pub median: f32,
+    pub min: f32,
+    pub max: f32,
+    pub std_dev: f32,
+}
+
+impl From<&Stats> for StatsSummary {
+    fn from(stats: &Stats) -> Self {
+        Self {
+            count: stats.data_points.len(),
+            avg: stats.avg(),
+            median: stats.median(),
+            min: stats.min(),
+            max: stats.max(),
+            std_dev: stats.std_dev(),
+        }
+    }
+}
+
+/// Data points in the range `start..end`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Bucket {
+    pub start: f32,
+    pub end: f32,
+    pub count: usize,
+}
+
+/// Bucketed counts of [`Stats`], see [`Stats::histogram`]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Histogram {
+    pub buckets: Vec<Bucket>,
+}
+
 impl std::fmt::Display for Stats {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -0,0 +1,41 @@
+use scope_graph::{
+    bench_util::{
+        bench::HeadGenerator,
+        scenario::{QuerySpec, ScenarioRunner},
+    },
+    generator::GraphPattern,
+    graph::ResolverStrategy,
+};
+
+/// Strategies can be selected with arguments, e.g.
+/// `cargo bench --bench cache-strategies -- uncached bottom-up`.
+/// By default all strategies are compared.
+pub fn main() {
+    let mut strategies = std::env::args()
+        .skip(1)
+        .filter_map(|arg| arg.parse::<ResolverStrategy>().ok())
+        .collect::<Vec<_>>();
+    if strategies.is_empty() {
+        strategies = ResolverStrategy::ALL.to_vec();
+    }
+
+    let heads = [HeadGenerator::linear(50), HeadGenerator::fan_chain(25, 10)];
+    let patterns = [
+        GraphPattern::Circle(16),
+        GraphPattern::Tree(80),
+        GraphPattern::Linear(40),
+        GraphPattern::Diamond(8, 1),
+    ];
+    let queries = [1, 5, 10].map(|n| QuerySpec::new(n, ResolverStrategy::default()));
+
+    let comparison = ScenarioRunner::new()
+        .with_warmup(3)
+        .matrix("cache", &heads, patterns, 0..10, &queries, 5)
+        .compare(&strategies);
+
+    println!("{comparison}");
+    let _ = std::fs::create_dir_all("output/benches");
+    comparison
+        .to_json_file("output/benches/cache_strategies.json")
+        .unwrap();
+}
@@ -0,0 +1,30 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use scope_graph::bench_util::workload::{Workload, WorkloadRunner};
+
+/// Workload written by `data-parse`, can be changed with the `SCOPE_GRAPH_WORKLOAD` variable
+const DEFAULT_WORKLOAD: &str = "../data-parse/output/workload.json";
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let path = std::env::var("SCOPE_GRAPH_WORKLOAD").unwrap_or(DEFAULT_WORKLOAD.to_string());
+    let Ok(workload) = Workload::<char>::from_json_file(&path) else {
+        println!("No workload found at {path}, export one with data-parse first");
+        return;
+    };
+    let mut runner = WorkloadRunner::new(&workload);
+
+    let mut group = c.benchmark_group(format!("workload {}", workload.name));
+    group.sample_size(10);
+    group.bench_function("cached", |b| {
+        b.iter(|| {
+            runner.reset_cache();
+            black_box(runner.run(true))
+        })
+    });
+    group.bench_function("uncached", |b| b.iter(|| black_box(runner.run(false))));
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
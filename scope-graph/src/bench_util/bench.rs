@@ -66,6 +66,16 @@ impl HeadGenerator {
         }
     }
 
+    /// Use a fixed seed for the placement of declarations, instead of a random one
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn kind(&self) -> &HeadKind {
+        &self.kind
+    }
+
     pub fn num_scopes(&self) -> usize {
         match self.kind {
             // HeadKind::Linear(len) => len * 2, // every scope has data, so length is len*2
@@ -167,7 +177,7 @@ impl HeadGenerator {
 }
 
 #[derive(Debug)]
-struct TailIndex {
+pub(crate) struct TailIndex {
     range: Range<usize>,
     tail_size: usize,
     branches: usize,
@@ -369,7 +379,7 @@ impl<'a, Args: std::fmt::Debug + Send + Sync> PatternBencher<'a, Args> {
         results
     }
 
-    fn construct_variation<R: Rng>(
+    pub(crate) fn construct_variation<R: Rng>(
         rng: &mut R,
         pattern: GraphPattern,
         head: &HeadGenerator,
@@ -388,7 +398,7 @@ impl<'a, Args: std::fmt::Debug + Send + Sync> PatternBencher<'a, Args> {
     }
 }
 
-struct GraphParams {
+pub(crate) struct GraphParams {
     pub order: LabelOrder<SgLabel>,
     pub matcher: RegexAutomaton<SgLabel>,
     pub x: String,
@@ -0,0 +1,251 @@
+use std::io::Write;
+
+use graphing::{create_file, csv_field};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bench_util::scenario::{ScenarioResults, ScenarioRunner},
+    graph::{QueryStats, ResolverStrategy},
+};
+
+/// Averaged stats of all graph seeds and repetitions of one scenario using one strategy
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ComparisonRow {
+    pub name: String,
+    pub head: String,
+    pub pattern: String,
+    pub num_queries: usize,
+    pub strategy: ResolverStrategy,
+    /// Number of runs the stats are averaged over
+    pub runs: usize,
+    pub stats: QueryStats,
+    /// Time of the first strategy in the comparison divided by the time of this strategy,
+    /// `None` if this strategy took no measurable time
+    pub speedup: Option<f32>,
+}
+
+/// (name, head, pattern, num_queries)
+type GroupKey = (String, String, String, usize);
+type StrategyRuns = Vec<(ResolverStrategy, Vec<QueryStats>)>;
+
+/// Comparison table of the same workload resolved with different resolver strategies
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct StrategyComparison {
+    pub rows: Vec<ComparisonRow>,
+}
+
+impl ScenarioRunner {
+    /// Runs every scenario once for each strategy, ignoring the strategy in the scenario itself.
+    ///
+    /// The first strategy is used as the baseline for the speedup.
+    pub fn compare(&self, strategies: &[ResolverStrategy]) -> StrategyComparison {
+        let mut runner = ScenarioRunner::new().with_warmup(self.warmup());
+        for scenario in self.scenarios() {
+            for strategy in strategies {
+                let mut scenario = scenario.clone();
+                scenario.query.strategy = *strategy;
+                runner.push(scenario);
+            }
+        }
+        StrategyComparison::from_results(runner.run(), strategies)
+    }
+}
+
+impl StrategyComparison {
+    pub fn from_results(results: ScenarioResults, strategies: &[ResolverStrategy]) -> Self {
+        let mut groups: Vec<(GroupKey, StrategyRuns)> = Vec::new();
+        for r in results.results {
+            let key = (r.name, r.head, r.pattern, r.query.num_queries);
+            let group = match groups.iter().position(|(k, _)| *k == key) {
+                Some(idx) => &mut groups[idx].1,
+                None => {
+                    groups.push((key, Vec::new()));
+                    &mut groups.last_mut().unwrap().1
+                }
+            };
+            match group.iter_mut().find(|(s, _)| *s == r.query.strategy) {
+                Some((_, stats)) => stats.push(r.stats),
+                None => group.push((r.query.strategy, vec![r.stats])),
+            }
+        }
+
+        let mut rows = Vec::new();
+        for ((name, head, pattern, num_queries), mut group) in groups {
+            group.sort_by_key(|(s, _)| strategies.iter().position(|x| x == s));
+            let mut baseline = None;
+            for (strategy, stats) in group {
+                let runs = stats.len();
+                let stats = stats
+                    .into_iter()
+                    .fold(QueryStats::default(), |acc, s| acc + s)
+                    / runs;
+                let time = stats.time.as_secs_f32();
+                let baseline_time = *baseline.get_or_insert(time);
+                rows.push(ComparisonRow {
+                    name: name.clone(),
+                    head: head.clone(),
+                    pattern: pattern.clone(),
+                    num_queries,
+                    strategy,
+                    runs,
+                    stats,
+                    speedup: (time > 0.0).then(|| baseline_time / time),
+                });
+            }
+        }
+        Self { rows }
+    }
+
+    pub fn to_json_file(&self, path: &str) -> std::io::Result<()> {
+        let file = create_file(path)?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    pub fn write_csv(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        writeln!(
+            writer,
+            "name,head,pattern,num_queries,strategy,runs,time_us,speedup,edges_traversed,\
+            nodes_visited,cache_hits,cache_size,cache_size_estimate,graph_size"
+        )?;
+        for r in &self.rows {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                csv_field(&r.name, ','),
+                csv_field(&r.head, ','),
+                csv_field(&r.pattern, ','),
+                r.num_queries,
+                r.strategy,
+                r.runs,
+                r.stats.time.as_micros(),
+                r.speedup.map(|s| s.to_string()).unwrap_or_default(),
+                r.stats.edges_traversed,
+                r.stats.nodes_visited,
+                r.stats.cache_hits,
+                r.stats.cache_size,
+                r.stats.cache_size_estimate,
+                r.stats.graph_size,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for StrategyComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<12} {:<16} {:<16} {:>8} {:<10} {:>12} {:>8} {:>10} {:>10} {:>12} {:>11}",
+            "name",
+            "head",
+            "pattern",
+            "queries",
+            "strategy",
+            "time (us)",
+            "speedup",
+            "nodes",
+            "hits",
+            "cache (B)",
+            "cache ratio",
+        )?;
+        for r in &self.rows {
+            writeln!(
+                f,
+                "{:<12} {:<16} {:<16} {:>8} {:<10} {:>12} {:>8} {:>10} {:>10} {:>12} {:>11.2}",
+                r.name,
+                r.head,
+                r.pattern,
+                r.num_queries,
+                r.strategy,
+                r.stats.time.as_micros(),
+                r.speedup
+                    .map(|s| format!("{s:.2}"))
+                    .unwrap_or(String::from("-")),
+                r.stats.nodes_visited,
+                r.stats.cache_hits,
+                r.stats.cache_size,
+                r.stats.cache_size_estimate,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bench_util::{
+            bench::HeadGenerator,
+            scenario::{QuerySpec, ScenarioResult, ScenarioRunner},
+        },
+        generator::GraphPattern,
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn test_compare() {
+        let heads = [HeadGenerator::linear(5)];
+        let runner = ScenarioRunner::new().matrix(
+            "test",
+            &heads,
+            [GraphPattern::Linear(3), GraphPattern::Diamond(2, 1)],
+            0..2,
+            &[QuerySpec::new(4, ResolverStrategy::TopDownUncached)],
+            2,
+        );
+        let cmp = runner.compare(ResolverStrategy::ALL);
+        // one row per pattern per strategy, seeds and repetitions are averaged
+        assert_eq!(cmp.rows.len(), 2 * ResolverStrategy::ALL.len());
+        for r in &cmp.rows {
+            assert_eq!(r.runs, 2 * 2);
+            if r.strategy == ResolverStrategy::TopDownUncached {
+                assert!(r.speedup.is_none_or(|s| s == 1.0));
+                assert_eq!(r.stats.cache_hits, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_speedup_without_time() {
+        let result = |strategy, time| ScenarioResult {
+            name: String::from("a,b"),
+            head: String::from("linear"),
+            pattern: String::from("linear-3"),
+            graph_seed: 0,
+            query: QuerySpec::new(1, strategy),
+            repetition: 0,
+            stats: QueryStats {
+                time: Duration::from_micros(time),
+                ..Default::default()
+            },
+        };
+        let results = ScenarioResults {
+            results: vec![
+                result(ResolverStrategy::TopDownUncached, 10),
+                result(ResolverStrategy::TopDownCached, 0),
+            ],
+        };
+        let cmp = StrategyComparison::from_results(
+            results,
+            &[
+                ResolverStrategy::TopDownUncached,
+                ResolverStrategy::TopDownCached,
+            ],
+        );
+        assert_eq!(cmp.rows[0].speedup, Some(1.0));
+        assert_eq!(cmp.rows[1].speedup, None);
+
+        let mut csv = Vec::new();
+        cmp.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(
+            csv.lines()
+                .nth(2)
+                .unwrap()
+                .starts_with("\"a,b\",linear,linear-3,")
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng, rngs::SmallRng};
+use scopegraphs::{Storage, completeness::UncheckedCompleteness};
+
+use crate::{
+    LibGraph, SgData, SgLabel, SgProjection,
+    bench_util::{bench::HeadGenerator, construct_cached_graph},
+    generator::GraphPattern,
+    graph::{LibScopeGraph, QueryResult, ScopeGraph},
+    regex::dfs::RegexAutomaton,
+    scope::Scope,
+    util::DisplayVec,
+};
+
+/// Random graph that is resolved with both [`CachedScopeGraph`](crate::graph::CachedScopeGraph)
+/// and the scopegraphs crate, which serves as the reference implementation.
+///
+/// Everything is derived from `seed`, so a failing case can be reproduced with
+/// `DifferentialCase::generate(seed, ..)`.
+#[derive(Debug, Clone)]
+pub struct DifferentialCase {
+    pub seed: u64,
+    pub head: HeadGenerator,
+    /// Patterns that follow the head
+    pub patterns: Vec<GraphPattern>,
+    pub num_queries: usize,
+}
+
+/// Query for which the environments of both implementations differ
+#[derive(Debug)]
+pub struct Mismatch {
+    pub seed: u64,
+    pub start: Scope,
+    pub x: String,
+    /// (target scope, data) found by the scopegraphs crate
+    pub expected: Vec<(Scope, SgData)>,
+    /// (target scope, data) found by [`CachedScopeGraph`](crate::graph::CachedScopeGraph)
+    pub actual: Vec<(Scope, SgData)>,
+}
+
+impl std::fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fmt_envs = |envs: &[(Scope, SgData)]| {
+            envs.iter()
+                .map(|(s, d)| format!("{s}: {d}"))
+                .collect::<Vec<_>>()
+        };
+        write!(
+            f,
+            "seed {}: query for {} from {} found {}, expected {}",
+            self.seed,
+            self.x,
+            self.start,
+            DisplayVec(&fmt_envs(&self.actual)),
+            DisplayVec(&fmt_envs(&self.expected)),
+        )
+    }
+}
+
+impl DifferentialCase {
+    /// Generates a random acyclic graph.
+    ///
+    /// Cycles are left out on purpose, use [`DifferentialCase::with_cycle`] to add one.
+    pub fn generate(seed: u64, num_queries: usize) -> Self {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let head = match rng.random_bool(0.5) {
+            true => HeadGenerator::linear(rng.random_range(2..10)),
+            false => HeadGenerator::fan_chain(rng.random_range(1..4), rng.random_range(1..3)),
+        }
+        .with_seed(seed);
+
+        let patterns = (0..rng.random_range(1..4))
+            .map(|_| match rng.random_range(0..4) {
+                0 => GraphPattern::Linear(rng.random_range(1..4)),
+                1 => GraphPattern::Diamond(rng.random_range(2..4), rng.random_range(1..3)),
+                2 => GraphPattern::Tree(rng.random_range(2..4)),
+                _ => GraphPattern::ReverseTree(rng.random_range(1..3)),
+            })
+            .collect();
+
+        Self {
+            seed,
+            head,
+            patterns,
+            num_queries,
+        }
+    }
+
+    /// Appends a cycle of `size` scopes to the graph
+    pub fn with_cycle(mut self, size: usize) -> Self {
+        self.patterns.push(GraphPattern::Circle(size));
+        self
+    }
+
+    /// Resolves all queries with both implementations and returns the queries whose environments differ.
+    ///
+    /// Environments are compared by the scope and data they resolve to. Paths are not compared,
+    /// since the scopegraphs crate does not expose them.
+    pub fn run(&self) -> Vec<Mismatch> {
+        let mut pattern = self.head.pattern();
+        pattern.extend(self.patterns.iter().cloned());
+        let mut graph = construct_cached_graph(pattern);
+
+        let storage = Storage::new();
+        let lib_graph: LibGraph = unsafe { LibGraph::new(&storage, UncheckedCompleteness::new()) };
+        let mut lib_graph = LibScopeGraph::from_graph(lib_graph, &graph);
+
+        let mut scopes = graph.scope_iter().map(|(s, _)| *s).collect::<Vec<_>>();
+        scopes.sort_by_key(|s| s.id());
+
+        let order = self.head.order();
+        let matcher = RegexAutomaton::from_regex(self.head.reg());
+        let mut rng = SmallRng::seed_from_u64(self.seed);
+        let mut mismatches = Vec::new();
+        for _ in 0..self.num_queries {
+            let start = scopes[rng.random_range(0..scopes.len())];
+            let x = format!("x_{}", rng.random_range(self.head.var_range()));
+            let x_wfd: Arc<str> = Arc::from(x.as_str());
+
+            let actual = graph.query_proj(
+                start,
+                &matcher,
+                &order,
+                SgProjection::VarName,
+                x_wfd.clone(),
+            );
+            let expected =
+                lib_graph.query_proj(start, &matcher, &order, SgProjection::VarName, x_wfd);
+
+            let (actual, expected) = (env_targets(actual), env_targets(expected));
+            if actual != expected {
+                mismatches.push(Mismatch {
+                    seed: self.seed,
+                    start,
+                    x,
+                    expected,
+                    actual,
+                });
+            }
+        }
+        mismatches
+    }
+}
+
+fn env_targets(envs: Vec<QueryResult<SgLabel, SgData>>) -> Vec<(Scope, SgData)> {
+    let mut targets = envs
+        .into_iter()
+        .map(|qr| (qr.path.target(), qr.data.as_ref().clone()))
+        .collect::<Vec<_>>();
+    targets.sort_by_key(|(s, _)| s.id());
+    targets
+}
@@ -1,17 +1,24 @@
 pub mod bench;
+pub mod compare;
+#[cfg(feature = "test-util")]
+pub mod differential;
+pub mod scenario;
+pub mod workload;
 
 use std::sync::{Arc, Mutex, atomic::AtomicUsize};
 
 use crate::{
     LibGraph, SgData, SgLabel, SgProjection,
     generator::{GraphGenerator, GraphPattern},
-    graph::{CachedScopeGraph, QueryResult, ScopeGraph},
+    graph::{CachedScopeGraph, LibScopeGraph, QueryResult, ScopeGraph},
     order::LabelOrder,
     regex::dfs::RegexAutomaton,
     scope::Scope,
 };
 use rand::{Rng, SeedableRng, rngs::SmallRng};
-use scopegraphs::{label_order, query_regex, resolve::Resolve};
+use scopegraphs::{
+    Storage, completeness::UncheckedCompleteness, label_order, query_regex, resolve::Resolve,
+};
 
 const HEAD_RANGE: std::ops::RangeInclusive<usize> = 1..=20;
 const TAIL_RANGE: std::ops::RangeInclusive<usize> = 1..=20;
@@ -36,12 +43,19 @@ pub fn construct_graph(pattern: GraphPattern) -> (CachedScopeGraph<SgLabel, SgDa
     (graph, head_size, tail_size)
 }
 
-// pub fn construct_libgraph(storage: &Storage, pattern: Vec<GraphPattern>) -> LibGraph<'_> {
-//     let lib_graph: LibGraph = unsafe { LibGraph::new(storage, UncheckedCompleteness::new()) };
-//     GraphGenerator::new(lib_graph)
-//         .with_patterns(pattern)
-//         .build_sg()
-// }
+/// Constructs a graph of the scopegraphs crate with the same generator as the other graphs
+pub fn construct_lib_graph(
+    storage: &Storage,
+    pattern: impl IntoIterator<Item = GraphPattern>,
+) -> LibScopeGraph<'_> {
+    let _lock = SG_CREATION_LOCK.lock().unwrap();
+    let lib_graph: LibGraph = unsafe { LibGraph::new(storage, UncheckedCompleteness::new()) };
+    let g = GraphGenerator::with_graph(LibScopeGraph::new(lib_graph))
+        .with_patterns(pattern)
+        .build();
+    Scope::reset_counter();
+    g
+}
 
 static SG_CREATION_LOCK: Mutex<()> = Mutex::new(());
 
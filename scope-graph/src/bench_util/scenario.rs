@@ -0,0 +1,343 @@
+use std::{io::Write, sync::Arc};
+
+use graphing::{create_file, csv_field};
+use rand::{SeedableRng, rngs::SmallRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    SgProjection,
+    bench_util::{
+        Graph,
+        bench::{GraphParams, HeadGenerator, PatternBencher},
+    },
+    generator::GraphPattern,
+    graph::{QueryStats, ResolverStrategy, ScopeGraph},
+    scope::Scope,
+};
+
+/// Graph that is queried in a scenario.
+///
+/// The graph is the head, followed by the pattern and a random tail of tree branches.
+/// Queries start at the end of one of the tail branches.
+#[derive(Debug, Clone)]
+pub struct GraphSpec {
+    pub head: HeadGenerator,
+    pub pattern: GraphPattern,
+    /// Seed for the size of the tail
+    pub seed: u64,
+}
+
+/// Queries that are performed on the graph of a scenario.
+///
+/// Start scopes and the data that is looked for are sampled using `seed`,
+/// so every repetition performs exactly the same queries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuerySpec {
+    pub num_queries: usize,
+    pub strategy: ResolverStrategy,
+    pub seed: u64,
+}
+
+impl QuerySpec {
+    pub fn new(num_queries: usize, strategy: ResolverStrategy) -> Self {
+        Self {
+            num_queries,
+            strategy,
+            seed: 0,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub name: String,
+    pub graph: GraphSpec,
+    pub query: QuerySpec,
+    pub repetitions: usize,
+}
+
+/// Result of a single repetition of a scenario
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScenarioResult {
+    pub name: String,
+    pub head: String,
+    pub pattern: String,
+    pub graph_seed: u64,
+    pub query: QuerySpec,
+    pub repetition: usize,
+    /// Sum of the stats of all queries in this repetition.
+    ///
+    /// Cache and graph sizes are measured after the last query.
+    pub stats: QueryStats,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ScenarioResults {
+    pub results: Vec<ScenarioResult>,
+}
+
+impl ScenarioResults {
+    pub fn to_json_file(&self, path: &str) -> std::io::Result<()> {
+        let file = create_file(path)?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    /// Writes a flat table with one row per repetition
+    pub fn to_csv_file(&self, path: &str) -> std::io::Result<()> {
+        let mut writer = std::io::BufWriter::new(create_file(path)?);
+        self.write_csv(&mut writer)
+    }
+
+    pub fn write_csv(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        writeln!(
+            writer,
+            "name,head,pattern,graph_seed,query_seed,strategy,num_queries,repetition,\
+            time_us,edges_traversed,nodes_visited,cache_reads,cache_writes,cache_hits,cache_size,graph_size"
+        )?;
+        for r in &self.results {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                csv_field(&r.name, ','),
+                csv_field(&r.head, ','),
+                csv_field(&r.pattern, ','),
+                r.graph_seed,
+                r.query.seed,
+                r.query.strategy,
+                r.query.num_queries,
+                r.repetition,
+                r.stats.time.as_micros(),
+                r.stats.edges_traversed,
+                r.stats.nodes_visited,
+                r.stats.cache_reads,
+                r.stats.cache_writes,
+                r.stats.cache_hits,
+                r.stats.cache_size,
+                r.stats.graph_size,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs a list of scenarios, usually created with [`ScenarioRunner::matrix`].
+#[derive(Debug, Default)]
+pub struct ScenarioRunner {
+    scenarios: Vec<Scenario>,
+    /// Number of queries performed before every scenario (cache is reset afterwards)
+    warmup: usize,
+}
+
+impl ScenarioRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_warmup(mut self, warmup: usize) -> Self {
+        self.warmup = warmup;
+        self
+    }
+
+    pub fn push(&mut self, scenario: Scenario) {
+        self.scenarios.push(scenario);
+    }
+
+    pub fn scenarios(&self) -> &[Scenario] {
+        &self.scenarios
+    }
+
+    pub fn warmup(&self) -> usize {
+        self.warmup
+    }
+
+    /// Adds a scenario for every combination of head, pattern, graph seed and query spec.
+    pub fn matrix<'a>(
+        mut self,
+        name: &str,
+        heads: impl IntoIterator<Item = &'a HeadGenerator>,
+        patterns: impl IntoIterator<Item = GraphPattern>,
+        graph_seeds: impl IntoIterator<Item = u64>,
+        queries: &[QuerySpec],
+        repetitions: usize,
+    ) -> Self {
+        let heads = heads.into_iter().collect::<Vec<_>>();
+        let patterns = patterns.into_iter().collect::<Vec<_>>();
+        let graph_seeds = graph_seeds.into_iter().collect::<Vec<_>>();
+        for head in &heads {
+            for pattern in &patterns {
+                for seed in &graph_seeds {
+                    for query in queries {
+                        self.scenarios.push(Scenario {
+                            name: name.to_string(),
+                            graph: GraphSpec {
+                                head: (*head).clone(),
+                                pattern: pattern.clone(),
+                                seed: *seed,
+                            },
+                            query: query.clone(),
+                            repetitions,
+                        });
+                    }
+                }
+            }
+        }
+        self
+    }
+
+    pub fn run(&self) -> ScenarioResults {
+        let bar = indicatif::ProgressBar::new(self.scenarios.len() as u64);
+        let results = self
+            .scenarios
+            .iter()
+            .inspect(|_| bar.inc(1))
+            .flat_map(|s| self.run_scenario(s))
+            .collect();
+        bar.finish();
+        ScenarioResults { results }
+    }
+
+    fn run_scenario(&self, scenario: &Scenario) -> Vec<ScenarioResult> {
+        let spec = &scenario.graph;
+        let mut graph_rng = SmallRng::seed_from_u64(spec.seed);
+        let (mut graph, tail_idx) = PatternBencher::<()>::construct_variation(
+            &mut graph_rng,
+            spec.pattern.clone(),
+            &spec.head,
+        );
+
+        let perform_query = |graph: &mut Graph, rng: &mut SmallRng| {
+            let start_scope = Scope(tail_idx.sample_branch(rng));
+            let params = GraphParams::new(rng, &spec.head);
+            let x_wfd: Arc<str> = Arc::from(params.x.as_str());
+            graph
+                .query_with_strategy(
+                    scenario.query.strategy,
+                    start_scope,
+                    &params.matcher,
+                    &params.order,
+                    SgProjection::VarName,
+                    x_wfd,
+                )
+                .1
+        };
+
+        let mut warmup_rng =
+            SmallRng::seed_from_u64(scenario.query.seed.wrapping_add(u64::MAX / 2));
+        for _ in 0..self.warmup {
+            perform_query(&mut graph, &mut warmup_rng);
+        }
+
+        (0..scenario.repetitions)
+            .map(|repetition| {
+                graph.reset_cache();
+                let mut rng = SmallRng::seed_from_u64(scenario.query.seed);
+                let mut stats = QueryStats::default();
+                for _ in 0..scenario.query.num_queries {
+                    let s = perform_query(&mut graph, &mut rng);
+                    let sizes = (s.cache_size, s.cache_size_estimate, s.graph_size);
+                    stats = stats + s;
+                    // sizes are not cumulative, keep the ones after the last query
+                    (
+                        stats.cache_size,
+                        stats.cache_size_estimate,
+                        stats.graph_size,
+                    ) = sizes;
+                }
+                ScenarioResult {
+                    name: scenario.name.clone(),
+                    head: spec.head.kind().to_string(),
+                    pattern: spec.pattern.to_string(),
+                    graph_seed: spec.seed,
+                    query: scenario.query.clone(),
+                    repetition,
+                    stats,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix() {
+        let heads = [HeadGenerator::linear(5), HeadGenerator::fan_chain(2, 2)];
+        let queries = [
+            QuerySpec::new(3, ResolverStrategy::TopDownUncached),
+            QuerySpec::new(3, ResolverStrategy::TopDownCached),
+        ];
+        let runner = ScenarioRunner::new().matrix(
+            "test",
+            &heads,
+            [GraphPattern::Linear(3), GraphPattern::Tree(2)],
+            0..2,
+            &queries,
+            2,
+        );
+        assert_eq!(runner.scenarios().len(), 2 * 2 * 2 * 2);
+
+        let results = runner.run();
+        assert_eq!(results.results.len(), runner.scenarios().len() * 2);
+        assert!(results.results.iter().all(|r| r.stats.nodes_visited > 0));
+
+        let mut csv = Vec::new();
+        results.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), results.results.len() + 1);
+    }
+
+    #[test]
+    fn test_repetitions_are_identical() {
+        let scenario = Scenario {
+            name: String::from("test"),
+            graph: GraphSpec {
+                head: HeadGenerator::linear(10),
+                pattern: GraphPattern::Diamond(2, 1),
+                seed: 1,
+            },
+            query: QuerySpec::new(5, ResolverStrategy::TopDownCached).with_seed(3),
+            repetitions: 3,
+        };
+        let mut runner = ScenarioRunner::new();
+        runner.push(scenario);
+        let results = runner.run().results;
+        assert_eq!(results.len(), 3);
+        for r in &results[1..] {
+            assert_eq!(r.stats.nodes_visited, results[0].stats.nodes_visited);
+            assert_eq!(r.stats.cache_hits, results[0].stats.cache_hits);
+        }
+    }
+
+    #[test]
+    fn test_csv_quotes_fields() {
+        let results = ScenarioResults {
+            results: vec![ScenarioResult {
+                name: String::from("linear, cached"),
+                head: String::from("linear"),
+                pattern: String::from("diamond-2-1"),
+                graph_seed: 0,
+                query: QuerySpec::new(1, ResolverStrategy::TopDownCached),
+                repetition: 0,
+                stats: QueryStats::default(),
+            }],
+        };
+        let mut csv = Vec::new();
+        results.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(
+            csv.lines()
+                .nth(1)
+                .unwrap()
+                .starts_with("\"linear, cached\",linear,diamond-2-1,")
+        );
+    }
+}
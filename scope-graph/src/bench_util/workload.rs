@@ -0,0 +1,241 @@
+use std::{collections::HashMap, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    SgData,
+    data::ScopeGraphData,
+    graph::{CachedScopeGraph, QueryStats, ScopeGraph},
+    label::ScopeGraphLabel,
+    order::{LabelOrder, LabelOrderBuilder},
+    projection::ScopeGraphDataProjection,
+    regex::{Regex, dfs::RegexAutomaton},
+    scope::Scope,
+};
+
+/// Projection used to replay recorded queries, only scopes with data are matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReplayProjection {
+    /// Name of the data, for queries that look for a name
+    Name,
+    /// All data projects to the same value, for queries that match any data
+    Any,
+}
+
+impl ScopeGraphDataProjection<SgData> for ReplayProjection {
+    type Output = Option<Arc<str>>;
+
+    fn project(&self, data: &SgData) -> Self::Output {
+        if !data.variant_has_data() {
+            return None;
+        }
+        match self {
+            Self::Name => Some(Arc::from(data.name())),
+            Self::Any => Some(Arc::from("")),
+        }
+    }
+}
+
+/// Query of a [`Workload`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "Lbl: Serialize + for<'a> Deserialize<'a>")]
+pub struct WorkloadQuery<Lbl: ScopeGraphLabel> {
+    /// Index of the start scope in [`Workload::scopes`]
+    pub start: usize,
+    pub regex: Regex<Lbl>,
+    /// `(lhs, rhs)` for `lhs < rhs`
+    pub order: Vec<(Lbl, Lbl)>,
+    /// Name the data has to match, `None` if any data matches
+    pub wfd: Option<String>,
+}
+
+/// Graph with a list of queries on it, e.g. exported from the query log of a real project by `data-parse`.
+///
+/// Scopes are identified by their index, so the workload can be stored as JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "Lbl: Serialize + for<'a> Deserialize<'a>")]
+pub struct Workload<Lbl: ScopeGraphLabel> {
+    pub name: String,
+    /// Data of every scope, indexed by scope id
+    pub scopes: Vec<SgData>,
+    /// `(source, target, label)`
+    pub edges: Vec<(usize, usize, Lbl)>,
+    /// In the order they are performed
+    pub queries: Vec<WorkloadQuery<Lbl>>,
+}
+
+impl<Lbl> Workload<Lbl>
+where
+    Lbl: ScopeGraphLabel + Serialize + for<'a> Deserialize<'a>,
+{
+    pub fn to_json_file(&self, path: &str) -> std::io::Result<()> {
+        let file = graphing::create_file(path)?;
+        serde_json::to_writer(std::io::BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    pub fn from_json_file(path: &str) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+    }
+}
+
+impl<Lbl: ScopeGraphLabel> Workload<Lbl> {
+    pub fn build_graph(&self) -> CachedScopeGraph<Lbl, SgData> {
+        let mut graph = CachedScopeGraph::new();
+        for (id, data) in self.scopes.iter().enumerate() {
+            graph.add_scope(Scope(id), data.clone());
+        }
+        for (source, target, label) in &self.edges {
+            graph.add_edge(Scope(*source), Scope(*target), label.clone());
+        }
+        graph
+    }
+
+    /// Same workload with every label replaced by `f(label)`,
+    /// e.g. to store the labels of a specification as [`char`]s.
+    pub fn map_labels<M: ScopeGraphLabel>(&self, f: impl Fn(&Lbl) -> M) -> Workload<M> {
+        Workload {
+            name: self.name.clone(),
+            scopes: self.scopes.clone(),
+            edges: self.edges.iter().map(|(s, t, l)| (*s, *t, f(l))).collect(),
+            queries: self
+                .queries
+                .iter()
+                .map(|q| WorkloadQuery {
+                    start: q.start,
+                    regex: q.regex.map_labels(&f),
+                    order: q.order.iter().map(|(l, r)| (f(l), f(r))).collect(),
+                    wfd: q.wfd.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+struct CompiledQuery<Lbl: ScopeGraphLabel> {
+    start: Scope,
+    automaton: RegexAutomaton<Lbl>,
+    order: LabelOrder<Lbl>,
+    projection: ReplayProjection,
+    wfd: Option<Arc<str>>,
+}
+
+/// Graph and compiled queries of a [`Workload`], so a benchmark only measures the queries
+pub struct WorkloadRunner<Lbl: ScopeGraphLabel> {
+    graph: CachedScopeGraph<Lbl, SgData>,
+    queries: Vec<CompiledQuery<Lbl>>,
+}
+
+impl<Lbl: ScopeGraphLabel> WorkloadRunner<Lbl> {
+    pub fn new(workload: &Workload<Lbl>) -> Self {
+        // queries share a handful of orders and regexes, which are expensive to build
+        let mut orders = HashMap::new();
+        let mut automata = HashMap::new();
+        let queries = workload
+            .queries
+            .iter()
+            .map(|q| {
+                let order = orders
+                    .entry(q.order.clone())
+                    .or_insert_with_key(|order: &Vec<(Lbl, Lbl)>| {
+                        order
+                            .iter()
+                            .fold(LabelOrderBuilder::new(), |builder, (lhs, rhs)| {
+                                builder.push(lhs.clone(), rhs.clone())
+                            })
+                            .build()
+                    })
+                    .clone();
+                let automaton = automata
+                    .entry(q.regex.clone())
+                    .or_insert_with_key(|r: &Regex<Lbl>| r.clone().compile())
+                    .clone();
+                let (projection, wfd) = match &q.wfd {
+                    Some(name) => (ReplayProjection::Name, Some(Arc::from(name.as_str()))),
+                    None => (ReplayProjection::Any, Some(Arc::from(""))),
+                };
+                CompiledQuery {
+                    start: Scope(q.start),
+                    automaton,
+                    order,
+                    projection,
+                    wfd,
+                }
+            })
+            .collect();
+        Self {
+            graph: workload.build_graph(),
+            queries,
+        }
+    }
+
+    pub fn num_queries(&self) -> usize {
+        self.queries.len()
+    }
+
+    /// Empties the resolve cache, so the next run starts cold
+    pub fn reset_cache(&mut self) {
+        self.graph.reset_cache();
+    }
+
+    /// Performs all queries in order, without resetting the cache in between.
+    ///
+    /// Returns the sum of the stats of all queries.
+    pub fn run(&mut self, caching_enabled: bool) -> QueryStats {
+        let mut stats = QueryStats::default();
+        for q in &self.queries {
+            let (_, query_stats) = self.graph.query_proj_stats(
+                q.start,
+                &q.automaton,
+                &q.order,
+                q.projection,
+                q.wfd.clone(),
+                caching_enabled,
+            );
+            stats = stats + query_stats;
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SgLabel;
+
+    /// 0 -P-> 1 -D-> 2 (x)
+    fn workload() -> Workload<SgLabel> {
+        let query = |wfd: Option<&str>| WorkloadQuery {
+            start: 0,
+            regex: Regex::concat(Regex::kleene(SgLabel::Parent), SgLabel::Declaration),
+            order: vec![(SgLabel::Declaration, SgLabel::Parent)],
+            wfd: wfd.map(String::from),
+        };
+        Workload {
+            name: "test".to_string(),
+            scopes: vec![SgData::NoData, SgData::NoData, SgData::var("x", "int")],
+            edges: vec![(0, 1, SgLabel::Parent), (1, 2, SgLabel::Declaration)],
+            queries: vec![query(Some("x")), query(Some("y")), query(None)],
+        }
+    }
+
+    #[test]
+    fn test_workload_runner() {
+        let workload = workload();
+        let json = serde_json::to_string(&workload).unwrap();
+        let workload: Workload<SgLabel> = serde_json::from_str(&json).unwrap();
+
+        let mut runner = WorkloadRunner::new(&workload);
+        assert_eq!(runner.num_queries(), 3);
+        let stats = runner.run(true);
+        assert!(stats.edges_traversed > 0);
+
+        let chars = workload.map_labels(|l| l.char());
+        assert_eq!(chars.edges[0], (0, 1, SgLabel::Parent.char()));
+        assert_eq!(
+            chars.queries[0].regex.to_string(),
+            workload.queries[0].regex.to_string()
+        );
+    }
+}
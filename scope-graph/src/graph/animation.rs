@@ -0,0 +1,453 @@
+use graphing::{
+    RenderResult, Renderer,
+    mermaid::{
+        self, MermaidDiagram,
+        item::{ItemShape, MermaidItem},
+        theme::EdgeType,
+    },
+    plantuml::{EdgeDirection, PlantUmlDiagram, PlantUmlItem, escape_label},
+};
+
+use crate::{
+    BackgroundColor, ColorSet, ForeGroundColor,
+    data::ScopeGraphData,
+    graph::{GraphRenderOptions, QueryResult, ResolverObserver, ScopeGraph},
+    label::ScopeGraphLabel,
+    path::Path,
+    scope::Scope,
+    util::DisplayVec,
+};
+
+/// A single event emitted by the resolver, see [`ResolverObserver`]
+#[derive(Debug, Clone)]
+pub enum ResolutionStep<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    EnterScope(Path<Lbl>),
+    CacheHit(Path<Lbl>, Vec<QueryResult<Lbl, Data>>),
+    CacheWrite(Path<Lbl>, Vec<QueryResult<Lbl, Data>>),
+    EnvFound(Path<Lbl>, QueryResult<Lbl, Data>),
+    Shadowed {
+        shadowed: QueryResult<Lbl, Data>,
+        by: QueryResult<Lbl, Data>,
+    },
+}
+
+impl<Lbl, Data> ResolutionStep<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    /// Scope the step takes place in
+    pub fn scope(&self) -> Scope {
+        match self {
+            Self::EnterScope(p)
+            | Self::CacheHit(p, _)
+            | Self::CacheWrite(p, _)
+            | Self::EnvFound(p, _) => p.target(),
+            Self::Shadowed { shadowed, .. } => shadowed.path.target(),
+        }
+    }
+
+    /// Path the resolver traversed to get to this step, if there is one
+    pub fn path(&self) -> Option<&Path<Lbl>> {
+        match self {
+            Self::EnterScope(p)
+            | Self::CacheHit(p, _)
+            | Self::CacheWrite(p, _)
+            | Self::EnvFound(p, _) => Some(p),
+            Self::Shadowed { .. } => None,
+        }
+    }
+}
+
+impl<Lbl, Data> std::fmt::Display for ResolutionStep<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EnterScope(p) => write!(f, "enter scope {}", p.target()),
+            Self::CacheHit(p, envs) => {
+                write!(f, "cache hit in {}: {}", p.target(), DisplayVec(envs))
+            }
+            Self::CacheWrite(p, envs) => {
+                write!(f, "cache write in {}: {}", p.target(), DisplayVec(envs))
+            }
+            Self::EnvFound(_, env) => write!(f, "found {}", env),
+            Self::Shadowed { shadowed, by } => write!(f, "{} shadowed by {}", shadowed, by),
+        }
+    }
+}
+
+/// Records every step of a resolution, so it can be rendered as a sequence of diagrams.
+///
+/// Every frame shows the graph, the path the resolver is currently on, the environments found
+/// so far and the cache entries written so far. This is meant to be used to make slide animations
+/// of a query.
+///
+/// ```ignore
+/// let mut animation = ResolutionAnimation::new();
+/// graph.query_proj_observed(s, &reg, &order, proj, wfd, &mut animation);
+/// animation.render_uml_frames(&graph, "query", "output/animation")?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResolutionAnimation<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    steps: Vec<ResolutionStep<Lbl, Data>>,
+}
+
+impl<Lbl, Data> Default for ResolutionAnimation<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Lbl, Data> ResolverObserver<Lbl, Data> for ResolutionAnimation<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    fn on_enter_scope(&mut self, path: &Path<Lbl>) {
+        self.steps.push(ResolutionStep::EnterScope(path.clone()));
+    }
+
+    fn on_cache_hit(&mut self, path: &Path<Lbl>, envs: &[QueryResult<Lbl, Data>]) {
+        self.steps
+            .push(ResolutionStep::CacheHit(path.clone(), envs.to_vec()));
+    }
+
+    fn on_cache_write(&mut self, path: &Path<Lbl>, envs: &[QueryResult<Lbl, Data>]) {
+        self.steps
+            .push(ResolutionStep::CacheWrite(path.clone(), envs.to_vec()));
+    }
+
+    fn on_env_found(&mut self, path: &Path<Lbl>, env: &QueryResult<Lbl, Data>) {
+        self.steps
+            .push(ResolutionStep::EnvFound(path.clone(), env.clone()));
+    }
+
+    fn on_shadowed(&mut self, shadowed: &QueryResult<Lbl, Data>, by: &QueryResult<Lbl, Data>) {
+        self.steps.push(ResolutionStep::Shadowed {
+            shadowed: shadowed.clone(),
+            by: by.clone(),
+        });
+    }
+}
+
+/// State of the resolution after a number of steps
+struct Frame<'a, Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    step: &'a ResolutionStep<Lbl, Data>,
+    /// Latest cache entry per scope
+    cache: Vec<(Scope, &'a [QueryResult<Lbl, Data>])>,
+    /// Environments that are found and not (yet) shadowed
+    found: Vec<&'a QueryResult<Lbl, Data>>,
+}
+
+impl<Lbl, Data> ResolutionAnimation<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    pub fn new() -> Self {
+        Self { steps: Vec::new() }
+    }
+
+    pub fn steps(&self) -> &[ResolutionStep<Lbl, Data>] {
+        &self.steps
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Forget all recorded steps, so the animation can be reused for another query
+    pub fn clear(&mut self) {
+        self.steps.clear();
+    }
+
+    fn frames(&self) -> impl Iterator<Item = Frame<'_, Lbl, Data>> {
+        let mut cache: Vec<(Scope, &[QueryResult<Lbl, Data>])> = Vec::new();
+        let mut found: Vec<&QueryResult<Lbl, Data>> = Vec::new();
+        self.steps.iter().map(move |step| {
+            match step {
+                ResolutionStep::CacheWrite(p, envs) => {
+                    let scope = p.target();
+                    match cache.iter_mut().find(|(s, _)| *s == scope) {
+                        Some(entry) => entry.1 = envs,
+                        None => cache.push((scope, envs)),
+                    }
+                }
+                ResolutionStep::EnvFound(_, env) => found.push(env),
+                ResolutionStep::Shadowed { shadowed, .. } => {
+                    found.retain(|e| e.path != shadowed.path);
+                }
+                _ => (),
+            }
+            Frame {
+                step,
+                cache: cache.clone(),
+                found: found.clone(),
+            }
+        })
+    }
+
+    fn frame_title(&self, title: &str, idx: usize) -> String {
+        format!("{} (step {}/{})", title, idx + 1, self.steps.len())
+    }
+
+    /// Creates one PlantUML diagram per recorded step
+    pub fn uml_frames<G>(&self, graph: &G, title: &str) -> Vec<PlantUmlDiagram>
+    where
+        G: ScopeGraph<Lbl, Data>,
+    {
+        let options = GraphRenderOptions {
+            draw_caches: false,
+            ..Default::default()
+        };
+        let base = graph.as_uml_diagram(title, &options);
+
+        self.frames()
+            .enumerate()
+            .map(|(idx, frame)| {
+                let mut diagram = base.clone();
+                diagram.set_title(self.frame_title(title, idx));
+
+                diagram.extend(frame.cache.iter().map(|(scope, envs)| {
+                    let contents = envs
+                        .iter()
+                        .map(|e| escape_label(&e.to_string()))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    PlantUmlItem::note(
+                        scope.uml_id(),
+                        format!("<b>cache</b>\n{contents}"),
+                        EdgeDirection::Right,
+                    )
+                    .raw()
+                    .add_class("cache-entry")
+                    .add_class(BackgroundColor::get_class_name(scope.id()))
+                }));
+
+                for (i, env) in frame.found.iter().enumerate() {
+                    diagram.extend(
+                        env.path
+                            .as_uml(ForeGroundColor::get_class_name(i + 1), true),
+                    );
+                }
+
+                if let Some(path) = frame.step.path() {
+                    diagram.extend(path.as_uml(ForeGroundColor::get_class_name(0), false));
+                }
+                diagram.push(PlantUmlItem::note(
+                    frame.step.scope().uml_id(),
+                    frame.step.to_string(),
+                    EdgeDirection::Left,
+                ));
+                diagram
+            })
+            .collect()
+    }
+
+    /// Creates one Mermaid diagram per recorded step
+    pub fn mmd_frames<G>(&self, graph: &G, title: &str) -> Vec<MermaidDiagram>
+    where
+        G: ScopeGraph<Lbl, Data>,
+    {
+        let base = graph.as_mmd_diagram(title, false);
+
+        self.frames()
+            .enumerate()
+            .map(|(idx, frame)| {
+                let mut diagram = base.clone();
+                diagram.set_title(mermaid::escape_label(&format!(
+                    "{}: {}",
+                    self.frame_title(title, idx),
+                    frame.step
+                )));
+
+                for (scope, envs) in &frame.cache {
+                    let id = format!("cache_{}", scope.uml_id());
+                    let contents = mermaid::escape_label(&DisplayVec(envs).to_string());
+                    diagram.push(
+                        MermaidItem::node(&id, contents, ItemShape::Card)
+                            .raw()
+                            .add_class("cache-entry"),
+                    );
+                    diagram.push(
+                        MermaidItem::edge(scope.uml_id(), id, "", EdgeType::Dotted)
+                            .add_class("cache-edge"),
+                    );
+                }
+
+                for (i, env) in frame.found.iter().enumerate() {
+                    diagram.extend(
+                        env.path
+                            .as_mmd(ForeGroundColor::get_class_name(i + 1), true),
+                    );
+                }
+
+                if let Some(path) = frame.step.path() {
+                    diagram.extend(path.as_mmd(ForeGroundColor::get_class_name(0), false));
+                }
+                diagram
+            })
+            .collect()
+    }
+
+    /// Renders all frames to `{dir}/step_{idx}.puml`
+    pub fn render_uml_frames<G>(&self, graph: &G, title: &str, dir: &str) -> RenderResult<()>
+    where
+        G: ScopeGraph<Lbl, Data>,
+    {
+        for (idx, diagram) in self.uml_frames(graph, title).into_iter().enumerate() {
+            diagram.render_to_file(&format!("{dir}/step_{idx:03}.puml"))?;
+        }
+        Ok(())
+    }
+
+    /// Renders all frames to `{dir}/step_{idx}.md`
+    pub fn render_mmd_frames<G>(&self, graph: &G, title: &str, dir: &str) -> RenderResult<()>
+    where
+        G: ScopeGraph<Lbl, Data>,
+    {
+        for (idx, diagram) in self.mmd_frames(graph, title).into_iter().enumerate() {
+            diagram.render_to_file(&format!("{dir}/step_{idx:03}.md"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use graphing::Renderer;
+
+    use super::*;
+    use crate::{
+        SgData, SgLabel, SgProjection, graph::CachedScopeGraph, order::LabelOrderBuilder,
+        regex::Regex,
+    };
+
+    /// Query for x from s0 in s0 -P-> s1 -D-> x, returns the graph, the animation and [s0, s1, x]
+    fn record() -> (
+        CachedScopeGraph<SgLabel, SgData>,
+        ResolutionAnimation<SgLabel, SgData>,
+        [Scope; 3],
+    ) {
+        let mut graph = CachedScopeGraph::<SgLabel, SgData>::new();
+        let s0 = graph.add_scope_default();
+        let s1 = graph.add_scope_default();
+        graph.add_edge(s0, s1, SgLabel::Parent);
+        let x = graph.add_decl(s1, SgLabel::Declaration, SgData::var("x", "int"));
+
+        let regex = Regex::concat(Regex::kleene(SgLabel::Parent), SgLabel::Declaration).compile();
+        let lo = LabelOrderBuilder::new()
+            .push(SgLabel::Declaration, SgLabel::Parent)
+            .build();
+        let mut animation = ResolutionAnimation::new();
+        graph.query_proj_observed(
+            s0,
+            &regex,
+            &lo,
+            SgProjection::VarName,
+            Arc::from("x"),
+            &mut animation,
+        );
+        (graph, animation, [s0, s1, x])
+    }
+
+    #[test]
+    fn test_steps() {
+        let (_, animation, [s0, s1, x]) = record();
+        let steps = animation
+            .steps()
+            .iter()
+            .map(|s| match s {
+                ResolutionStep::EnterScope(_) => ("enter", s.scope()),
+                ResolutionStep::EnvFound(..) => ("found", s.scope()),
+                ResolutionStep::CacheWrite(..) => ("write", s.scope()),
+                ResolutionStep::CacheHit(..) => ("hit", s.scope()),
+                ResolutionStep::Shadowed { .. } => ("shadowed", s.scope()),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            steps,
+            vec![
+                ("enter", s0),
+                ("enter", s1),
+                ("enter", x),
+                ("found", x),
+                ("write", s1),
+                ("write", s0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_uml_frames() {
+        let (graph, animation, _) = record();
+        let frames = animation.uml_frames(&graph, "query");
+        assert_eq!(frames.len(), animation.len());
+        for (idx, (frame, step)) in frames.iter().zip(animation.steps()).enumerate() {
+            let uml = frame.render_to_string().unwrap();
+            assert!(uml.contains(&format!("\"query (step {}/6)\"", idx + 1)));
+            // the step is noted next to the scope it happens in
+            let note = format!("note left of {}\n\t{}", step.scope().uml_id(), step);
+            assert!(uml.contains(&note), "frame {idx}");
+            // the current path is highlighted
+            let path_len = step.path().map_or(0, |p| p.len() - 1);
+            assert_eq!(uml.matches("<<foreground-0>>").count(), path_len);
+        }
+        // cache entries are only shown once they are written
+        let last = frames.last().unwrap().render_to_string().unwrap();
+        assert_eq!(last.matches("<<cache-entry>>").count(), 2);
+        let first = frames[0].render_to_string().unwrap();
+        assert!(!first.contains("<<cache-entry>>"));
+    }
+
+    #[test]
+    fn test_mmd_frames() {
+        let (graph, animation, _) = record();
+        let frames = animation.mmd_frames(&graph, "query");
+        assert_eq!(frames.len(), animation.len());
+        for (idx, (frame, step)) in frames.iter().zip(animation.steps()).enumerate() {
+            let mmd = frame.render_to_string().unwrap();
+            let title = format!("query (step {}/6): {}", idx + 1, step);
+            assert!(
+                mmd.contains(&format!("title: \"{}\"", mermaid::escape_label(&title))),
+                "frame {idx}"
+            );
+            let path_len = step.path().map_or(0, |p| p.len() - 1);
+            let highlighted = mmd
+                .lines()
+                .filter(|l| l.starts_with("class edge") && l.ends_with(" foreground-0"))
+                .count();
+            assert_eq!(highlighted, path_len, "frame {idx}");
+        }
+        // cache entries hold paths, whose arrows are escaped
+        let last = frames.last().unwrap().render_to_string().unwrap();
+        assert_eq!(last.matches("class cache_").count(), 2);
+        assert!(last.contains("-D1-#gt; 1]"));
+        assert!(!last.contains("-D1-> 1]"));
+    }
+}
@@ -0,0 +1,486 @@
+use std::collections::HashMap;
+
+use deepsize::DeepSizeOf;
+
+use graphing::plantuml::{EdgeDirection, PlantUmlItem};
+
+use crate::{
+    data::ScopeGraphData,
+    debug_tracing,
+    graph::{Edge, QueryResult, QueryStats, ScopeData, ScopeGraph, ScopeMap},
+    label::ScopeGraphLabel,
+    order::LabelOrder,
+    path::{Path, ReversePath},
+    projection::ScopeGraphDataProjection,
+    regex::dfs::RegexAutomaton,
+    scope::Scope,
+    shadow::{LabelOrderShadowing, ShadowingPolicy},
+};
+
+/// Scope graph that resolves queries bottom-up.
+///
+/// Every scope caches all declarations that are reachable from it, together with the (acyclic)
+/// path to the declaration. These are propagated to parent scopes when edges or declarations are added,
+/// so a query only has to filter the declarations of the start scope and apply shadowing.
+/// This makes querying cheap, at the cost of (potentially exponential) memory and slower construction.
+#[derive(Debug)]
+pub struct BottomupScopeGraph<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    pub scopes: ScopeMap<Lbl, Data>,
+    /// All reachable declarations per scope
+    decls: HashMap<Scope, Vec<QueryResult<Lbl, Data>>>,
+    /// Scopes visited while propagating declarations
+    nodes_visited: usize,
+    /// Edges that declarations were propagated over
+    edges_traversed: usize,
+}
+
+impl<Lbl, Data> Default for BottomupScopeGraph<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Lbl, Data> BottomupScopeGraph<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    pub fn new() -> Self {
+        Self {
+            scopes: ScopeMap::new(),
+            decls: HashMap::new(),
+            nodes_visited: 0,
+            edges_traversed: 0,
+        }
+    }
+
+    /// Copies the scopes and edges of another scope graph, so both can be queried on the same graph
+    pub fn from_graph<G: ScopeGraph<Lbl, Data>>(graph: &G) -> Self {
+        let mut bottomup = Self::new();
+        for (s, d) in graph.scope_iter() {
+            bottomup.add_scope(*s, d.data.clone());
+        }
+        for (s, d) in graph.scope_iter() {
+            for edge in d.outgoing() {
+                bottomup.add_edge(*s, edge.target(), edge.lbl().clone());
+            }
+        }
+        bottomup
+    }
+
+    /// Returns number of scopes
+    pub fn size(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// All declarations reachable from `scope`, before filtering and shadowing
+    pub fn reachable_decls(&self, scope: Scope) -> &[QueryResult<Lbl, Data>] {
+        self.decls
+            .get(&scope)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Total number of cached paths
+    pub fn num_cached_paths(&self) -> usize {
+        self.decls.values().map(Vec::len).sum()
+    }
+
+    /// Size of the cached declarations in bytes
+    pub fn cache_size(&self) -> usize {
+        self.decls.deep_size_of()
+    }
+
+    /// Work done to propagate the declarations while the graph was built
+    pub fn build_stats(&self) -> QueryStats {
+        QueryStats {
+            nodes_visited: self.nodes_visited,
+            edges_traversed: self.edges_traversed,
+            cache_writes: self.num_cached_paths(),
+            ..Default::default()
+        }
+    }
+
+    /// Work done by a query from `scope`: every reachable declaration is visited,
+    /// and its path is traversed to match it against the regex
+    pub fn query_stats(&self, scope: Scope) -> QueryStats {
+        let decls = self.reachable_decls(scope);
+        QueryStats {
+            nodes_visited: decls.len(),
+            edges_traversed: decls.iter().map(|env| env.path.len() - 1).sum(),
+            cache_reads: 1,
+            cache_hits: usize::from(!decls.is_empty()),
+            ..Default::default()
+        }
+    }
+
+    /// Adds `envs` to `scope` and to every scope that can reach `scope`
+    fn propagate(&mut self, scope: Scope, envs: Vec<QueryResult<Lbl, Data>>) {
+        let mut worklist = vec![(scope, envs)];
+        while let Some((scope, envs)) = worklist.pop() {
+            if envs.is_empty() {
+                continue;
+            }
+            self.nodes_visited += 1;
+            self.edges_traversed += self.scopes[&scope].incoming().len();
+
+            for edge in self.scopes[&scope].incoming() {
+                let parent = edge.target();
+                let lifted = envs
+                    .iter()
+                    .filter(|env| !path_visits(&env.path, parent))
+                    .map(|env| env.step(edge.lbl().clone(), parent, 0))
+                    .collect::<Vec<_>>();
+                worklist.push((parent, lifted));
+            }
+
+            self.decls.entry(scope).or_default().extend(envs);
+        }
+    }
+
+    fn resolve<DEq, DWfd, Shadow>(
+        &self,
+        scope: Scope,
+        path_regex: &RegexAutomaton<Lbl>,
+        order: &LabelOrder<Lbl>,
+        data_equiv: DEq,
+        data_wellformedness: DWfd,
+        shadowing: Shadow,
+    ) -> Vec<QueryResult<Lbl, Data>>
+    where
+        DEq: Fn(&Data, &Data) -> bool,
+        DWfd: Fn(&Data) -> bool,
+        Shadow: ShadowingPolicy<Lbl, Data>,
+    {
+        let envs = self
+            .reachable_decls(scope)
+            .iter()
+            .filter(|env| data_wellformedness(&env.data))
+            .filter(|env| path_regex.is_match(&env.path.labels()))
+            .collect::<Vec<_>>();
+
+        envs.iter()
+            .filter(|candidate| {
+                !envs.iter().any(|preferred| {
+                    data_equiv(&preferred.data, &candidate.data)
+                        && is_preferred(order, &preferred.path, &candidate.path)
+                        && shadowing.shadows(preferred, candidate)
+                })
+            })
+            .map(|env| (*env).clone())
+            .collect()
+    }
+}
+
+/// Returns true if `preferred` has priority over `candidate` according to the label order.
+///
+/// Like the top-down resolvers, this is only the case when both paths leave the same scope through
+/// different labels. Paths that split at a scope using the same label are never compared.
+fn is_preferred<Lbl: ScopeGraphLabel>(
+    order: &LabelOrder<Lbl>,
+    preferred: &ReversePath<Lbl>,
+    candidate: &ReversePath<Lbl>,
+) -> bool {
+    // iterating the inner path goes from the start scope to the data
+    for (p, c) in preferred.as_ref().iter().zip(candidate.as_ref().iter()) {
+        match (p, c) {
+            (
+                Path::Step {
+                    label: l_p,
+                    target: s_p,
+                    ..
+                },
+                Path::Step {
+                    label: l_c,
+                    target: s_c,
+                    ..
+                },
+            ) => {
+                if s_p != s_c {
+                    return false;
+                }
+                if l_p != l_c {
+                    return order.label_is_less(l_p, l_c);
+                }
+            }
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Returns true if `scope` is part of `path`
+fn path_visits<Lbl: ScopeGraphLabel>(path: &ReversePath<Lbl>, scope: Scope) -> bool {
+    path.as_ref().iter().any(|p| p.target() == scope)
+}
+
+impl<Lbl, Data> ScopeGraph<Lbl, Data> for BottomupScopeGraph<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    /// Declarations are part of the graph, so there is no cache to reset
+    fn reset_cache(&mut self) {}
+
+    fn add_scope(&mut self, scope: Scope, data: Data) -> Scope {
+        debug_tracing!(trace, "Adding scope: {} with data: {}", scope, data);
+        let has_data = data.variant_has_data();
+        self.scopes.insert(scope, ScopeData::new(data.clone()));
+        if has_data {
+            self.propagate(scope, vec![QueryResult::start(scope, data)]);
+        }
+        scope
+    }
+
+    fn add_edge(&mut self, source: Scope, target: Scope, label: Lbl) {
+        debug_tracing!(
+            debug,
+            "Adding edge: {} -> {} with label: {}",
+            source,
+            target,
+            label
+        );
+
+        self.scopes
+            .get_mut(&source)
+            .expect("Attempting to add edge to non-existant scope")
+            .outgoing_mut()
+            .push(Edge::new(target, label.clone()));
+        self.scopes
+            .get_mut(&target)
+            .expect("Attempting to add edge to non-existant scope")
+            .incoming_mut()
+            .push(Edge::new(source, label.clone()));
+
+        let envs = self
+            .reachable_decls(target)
+            .iter()
+            .filter(|env| !path_visits(&env.path, source))
+            .map(|env| env.step(label.clone(), source, 0))
+            .collect();
+        self.propagate(source, envs);
+    }
+
+    fn get_scope(&self, scope: Scope) -> Option<&ScopeData<Lbl, Data>> {
+        self.scopes.get(&scope)
+    }
+
+    fn scope_iter<'a>(&'a self) -> impl Iterator<Item = (&'a Scope, &'a ScopeData<Lbl, Data>)>
+    where
+        Lbl: 'a,
+        Data: 'a,
+    {
+        self.scopes.iter()
+    }
+
+    fn extend(&mut self, other: Self) {
+        let edges = other
+            .scopes
+            .iter()
+            .flat_map(|(s, d)| {
+                d.outgoing()
+                    .iter()
+                    .map(move |e| (*s, e.target(), e.lbl().clone()))
+            })
+            .collect::<Vec<_>>();
+        for (s, d) in other.scopes {
+            self.add_scope(s, d.data);
+        }
+        // re-adding the edges propagates declarations between both graphs
+        for (source, target, label) in edges {
+            self.add_edge(source, target, label);
+        }
+    }
+
+    fn scope_holds_data(&self, scope: Scope) -> bool {
+        self.scopes
+            .get(&scope)
+            .map(|d| d.data.variant_has_data())
+            .unwrap_or_default()
+    }
+
+    fn query_with_policy<DEq, DWfd, Shadow>(
+        &mut self,
+        scope: Scope,
+        path_regex: &RegexAutomaton<Lbl>,
+        order: &LabelOrder<Lbl>,
+        data_equiv: DEq,
+        data_wellformedness: DWfd,
+        shadowing: Shadow,
+    ) -> Vec<QueryResult<Lbl, Data>>
+    where
+        DEq: for<'da, 'db> Fn(&'da Data, &'db Data) -> bool,
+        DWfd: for<'da> Fn(&'da Data) -> bool,
+        Shadow: ShadowingPolicy<Lbl, Data>,
+    {
+        self.resolve(
+            scope,
+            path_regex,
+            order,
+            data_equiv,
+            data_wellformedness,
+            shadowing,
+        )
+    }
+
+    fn query_proj_with_policy<Proj, Shadow>(
+        &mut self,
+        scope: Scope,
+        path_regex: &RegexAutomaton<Lbl>,
+        order: &LabelOrder<Lbl>,
+        data_proj: Proj,
+        proj_wfd: Proj::Output,
+        shadowing: Shadow,
+    ) -> Vec<QueryResult<Lbl, Data>>
+    where
+        Proj: ScopeGraphDataProjection<Data>,
+        Shadow: ShadowingPolicy<Lbl, Data>,
+    {
+        self.resolve(
+            scope,
+            path_regex,
+            order,
+            |d1, d2| data_proj.project(d1) == data_proj.project(d2),
+            |d| data_proj.project(d) == proj_wfd,
+            shadowing,
+        )
+    }
+
+    fn query_proj_many<Proj>(
+        &mut self,
+        scope: Scope,
+        path_regex: &RegexAutomaton<Lbl>,
+        order: &LabelOrder<Lbl>,
+        data_proj: Proj,
+        proj_wfds: &[Proj::Output],
+    ) -> Vec<Vec<QueryResult<Lbl, Data>>>
+    where
+        Proj: ScopeGraphDataProjection<Data>,
+    {
+        proj_wfds
+            .iter()
+            .map(|wfd| {
+                self.resolve(
+                    scope,
+                    path_regex,
+                    order,
+                    |d1, d2| data_proj.project(d1) == data_proj.project(d2),
+                    |d| data_proj.project(d) == *wfd,
+                    LabelOrderShadowing,
+                )
+            })
+            .collect()
+    }
+
+    fn generate_cache_uml(&self) -> Vec<PlantUmlItem> {
+        self.decls
+            .iter()
+            .filter(|(s, _)| !self.scope_holds_data(**s))
+            .map(|(s, envs)| {
+                let contents = envs
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                PlantUmlItem::note(s.uml_id(), contents, EdgeDirection::Right)
+                    .add_class("cache-entry")
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        SgData, SgLabel, SgProjection,
+        generator::{GraphGenerator, GraphPattern},
+        graph::CachedScopeGraph,
+        order::LabelOrderBuilder,
+        regex::Regex,
+    };
+
+    use super::*;
+
+    fn targets(envs: &[QueryResult<SgLabel, SgData>]) -> Vec<Scope> {
+        let mut targets = envs.iter().map(|e| e.path.target()).collect::<Vec<_>>();
+        targets.sort_by_key(|s| s.0);
+        targets
+    }
+
+    #[test]
+    fn test_shadowing() {
+        let mut graph = BottomupScopeGraph::<SgLabel, SgData>::new();
+        let s1 = graph.add_scope_default();
+        let s2 = graph.add_scope_default();
+        graph.add_edge(s1, s2, SgLabel::Parent);
+        let x1 = graph.add_decl(s1, SgLabel::Declaration, SgData::var("x", "int"));
+        let x2 = graph.add_decl(s2, SgLabel::Declaration, SgData::var("x", "int"));
+
+        let regex = Regex::concat(Regex::kleene(SgLabel::Parent), SgLabel::Declaration).compile();
+        let lo = LabelOrderBuilder::new()
+            .push(SgLabel::Declaration, SgLabel::Parent)
+            .build();
+        let envs = graph.query_proj(s1, &regex, &lo, SgProjection::VarName, Arc::from("x"));
+        assert_eq!(targets(&envs), vec![x1]);
+
+        // no order, so nothing is shadowed
+        let lo = LabelOrderBuilder::new().build();
+        let envs = graph.query_proj(s1, &regex, &lo, SgProjection::VarName, Arc::from("x"));
+        assert_eq!(targets(&envs), vec![x1, x2]);
+        assert_eq!(graph.reachable_decls(s1).len(), 2);
+    }
+
+    #[test]
+    fn test_same_as_cached() {
+        let patterns = [
+            GraphPattern::Decl(SgData::var("x", "int")),
+            GraphPattern::Linear(2),
+            GraphPattern::Decl(SgData::var("y", "int")),
+            GraphPattern::Circle(3),
+            GraphPattern::Decl(SgData::var("x", "int")),
+            GraphPattern::Diamond(2, 1),
+            GraphPattern::Tree(3),
+            GraphPattern::Linear(2),
+        ];
+        let mut cached = GraphGenerator::with_graph(CachedScopeGraph::<SgLabel, SgData>::new())
+            .with_patterns(patterns)
+            .build();
+        let mut bottomup = BottomupScopeGraph::from_graph(&cached);
+
+        let regex = Regex::concat(Regex::kleene(SgLabel::Parent), SgLabel::Declaration).compile();
+        let lo = LabelOrderBuilder::new()
+            .push(SgLabel::Declaration, SgLabel::Parent)
+            .build();
+        let scopes = cached
+            .scope_iter()
+            .filter(|(_, d)| !d.data.variant_has_data())
+            .map(|(s, _)| *s)
+            .collect::<Vec<_>>();
+        // the uncached resolver is used as reference, since that one does not depend on caching
+        // behaviour in cycles
+        for s in scopes {
+            for x in ["x", "y", "z"] {
+                let c_envs = cached.query(
+                    s,
+                    &regex,
+                    &lo,
+                    |d1: &SgData, d2: &SgData| d1.name() == d2.name(),
+                    |d: &SgData| d.name() == x,
+                );
+                let b_envs =
+                    bottomup.query_proj(s, &regex, &lo, SgProjection::VarName, Arc::from(x));
+                assert_eq!(targets(&c_envs), targets(&b_envs), "scope {s}, data {x}");
+            }
+        }
+    }
+}
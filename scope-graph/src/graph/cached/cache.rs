@@ -1,7 +1,8 @@
 use std::{cell::RefCell, fmt::Write, rc::Rc};
 
 use deepsize::DeepSizeOf;
-use graphing::plantuml::{EdgeDirection, PlantUmlItem};
+use graphing::plantuml::{EdgeDirection, PlantUmlItem, escape_label};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
 use crate::{
     BackgroundColor, ColorSet,
@@ -13,12 +14,14 @@ use crate::{
     path::Path,
     regex::{RegexState, dfs::RegexAutomaton},
     scope::Scope,
+    shadow::ShadowingPolicy,
 };
 
 pub type ProjHash = u64;
+pub type ShadowHash = u64;
 
-/// (label order, automaton, hash of the projection function)
-pub type ResolveCacheKey<Lbl> = (LabelOrder<Lbl>, RegexAutomaton<Lbl>, ProjHash);
+/// (label order, automaton, hash of the projection function, hash of the shadowing policy)
+pub type ResolveCacheKey<Lbl> = (LabelOrder<Lbl>, RegexAutomaton<Lbl>, ProjHash, ShadowHash);
 
 /// Cache for entire scope graph, across multiple queries.
 #[derive(Debug, Default, Clone)]
@@ -49,6 +52,21 @@ where
         self.cache.clear();
     }
 
+    /// Clone that does not share the query caches with `self`
+    pub(crate) fn deep_clone(&self) -> Self {
+        let cache = self
+            .cache
+            .iter()
+            .map(|(k, v)| {
+                let query_cache = QueryCache {
+                    cache: Rc::new(RefCell::new(v.cache.borrow().clone())),
+                };
+                (k.clone(), query_cache)
+            })
+            .collect();
+        Self { cache }
+    }
+
     pub fn into_std(
         self,
     ) -> std::collections::HashMap<ResolveCacheKey<Lbl>, StdQueryCacheMap<Lbl, Data>> {
@@ -61,18 +79,92 @@ where
             })
     }
 
+    /// Writes the cache to `path` as JSON.
+    ///
+    /// Projections and shadowing policies are part of the key as a hash, which is only stable
+    /// within the same build. The cache should be loaded by the same binary and only be used
+    /// for the graph it was filled for.
+    pub fn save(&self, path: &str) -> std::io::Result<()>
+    where
+        Lbl: Serialize,
+        Data: Serialize,
+    {
+        let saved = self
+            .cache
+            .iter()
+            .map(|(key, query_cache)| SavedQueryCache {
+                order: key.0.clone(),
+                automaton: key.1.clone(),
+                proj_hash: key.2,
+                shadow_hash: key.3,
+                entries: query_cache
+                    .cache
+                    .borrow()
+                    .iter()
+                    .map(|(k, v)| (*k, v.clone()))
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer(std::io::BufWriter::new(file), &saved)?;
+        Ok(())
+    }
+
+    /// Reads a cache that was written with [`ResolveCache::save`]
+    pub fn load(path: &str) -> std::io::Result<Self>
+    where
+        Lbl: DeserializeOwned,
+        Data: DeserializeOwned,
+    {
+        let file = std::fs::File::open(path)?;
+        let saved: Vec<SavedQueryCache<Lbl, Data>> =
+            serde_json::from_reader(std::io::BufReader::new(file))?;
+        let cache = saved
+            .into_iter()
+            .map(|s| {
+                let query_cache = QueryCache {
+                    cache: Rc::new(RefCell::new(s.entries.into_iter().collect())),
+                };
+                (
+                    (s.order, s.automaton, s.proj_hash, s.shadow_hash),
+                    query_cache,
+                )
+            })
+            .collect();
+        Ok(Self { cache })
+    }
+
     pub fn generate_uml<S: ScopeGraph<Lbl, Data>>(
         &self,
         graph: &S,
     ) -> impl Iterator<Item = PlantUmlItem> {
         self.cache.iter().flat_map(|(key, query_cache)| {
             let mut s = String::new();
-            writeln!(&mut s, "<b>({}, {})</b>", key.0, key.1).unwrap();
+            let target = escape_label(&key.1.to_string());
+            writeln!(&mut s, "<b>({}, {})</b>", key.0, target).unwrap();
             query_cache.generate_uml(graph, s)
         })
     }
 }
 
+/// Single entry of [`ResolveCache`] as it is stored on disk
+#[derive(Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "Lbl: Serialize, Data: Serialize",
+    deserialize = "Lbl: Deserialize<'de>, Data: Deserialize<'de>"
+))]
+struct SavedQueryCache<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    order: LabelOrder<Lbl>,
+    automaton: RegexAutomaton<Lbl>,
+    proj_hash: ProjHash,
+    shadow_hash: ShadowHash,
+    entries: Vec<(QueryCacheKey, EnvCache<Lbl, Data>)>,
+}
+
 pub type QueryCacheKey = (usize, Scope);
 pub type QueryCacheMap<Lbl, Data> = hashbrown::HashMap<QueryCacheKey, EnvCache<Lbl, Data>>;
 pub type StdQueryCacheMap<Lbl, Data> =
@@ -156,9 +248,10 @@ where
                     .group_by_hash()
                     .values()
                     .map(|envs| {
-                        let mut s = format!("<i>{}</i>:\n", env_cache.path);
+                        let path = escape_label(&env_cache.path.to_string());
+                        let mut s = format!("<i>{path}</i>:\n");
                         for e in envs {
-                            writeln!(&mut s, "  {e}").unwrap();
+                            writeln!(&mut s, "  {}", escape_label(&e.to_string())).unwrap();
                         }
                         s
                     })
@@ -169,6 +262,7 @@ where
 
                 Some(
                     PlantUmlItem::note(scope.uml_id(), contents, EdgeDirection::Right)
+                        .raw()
                         .add_class("cache-entry")
                         .add_class(BackgroundColor::get_class_name(scope.id())),
                 )
@@ -177,7 +271,11 @@ where
     }
 }
 
-#[derive(Debug, Clone, DeepSizeOf)]
+#[derive(Debug, Clone, DeepSizeOf, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "Lbl: Serialize, Data: Serialize",
+    deserialize = "Lbl: Deserialize<'de>, Data: Deserialize<'de>"
+))]
 pub struct EnvCache<Lbl, Data>
 where
     Lbl: ScopeGraphLabel,
@@ -218,8 +316,12 @@ where
     }
 }
 
-#[derive(Debug, Clone, DeepSizeOf)]
+#[derive(Debug, Clone, DeepSizeOf, Serialize, Deserialize)]
 #[repr(transparent)]
+#[serde(bound(
+    serialize = "Lbl: Serialize, Data: Serialize",
+    deserialize = "Lbl: Deserialize<'de>, Data: Deserialize<'de>"
+))]
 pub(crate) struct ProjEnvs<Lbl: ScopeGraphLabel, Data: ScopeGraphData> {
     inner: Vec<(ProjHash, QueryResult<Lbl, Data>)>,
 }
@@ -276,10 +378,26 @@ impl<Lbl: ScopeGraphLabel, Data: ScopeGraphData> ProjEnvs<Lbl, Data> {
         }
     }
 
-    pub fn shadow(&mut self, mut other: Self) {
-        other
-            .inner
-            .retain(|(proj, _)| !self.inner.iter().any(|(p, _)| *p == *proj));
+    /// Shadow `other` by `self`, `on_shadowed` is called for every removed environment
+    pub fn shadow(
+        &mut self,
+        mut other: Self,
+        policy: &impl ShadowingPolicy<Lbl, Data>,
+        mut on_shadowed: impl FnMut(&QueryResult<Lbl, Data>, &QueryResult<Lbl, Data>),
+    ) {
+        other.inner.retain(|(proj, qr)| {
+            let shadowed_by = self
+                .inner
+                .iter()
+                .find(|(p, preferred)| *p == *proj && policy.shadows(preferred, qr));
+            match shadowed_by {
+                Some((_, preferred)) => {
+                    on_shadowed(qr, preferred);
+                    false
+                }
+                None => true,
+            }
+        });
         self.extend(other);
     }
 
@@ -303,6 +421,10 @@ impl<Lbl: ScopeGraphLabel, Data: ScopeGraphData> ProjEnvs<Lbl, Data> {
         map
     }
 
+    pub fn clone_envs(&self) -> Vec<QueryResult<Lbl, Data>> {
+        self.inner.iter().map(|(_, e)| e).cloned().collect()
+    }
+
     pub fn clone_envs_by_hash(&self, hash: &ProjHash) -> Vec<QueryResult<Lbl, Data>> {
         self.inner
             .iter()
@@ -7,8 +7,10 @@ use crate::{
     data::ScopeGraphData,
     debug_tracing,
     graph::{
-        Edge, ScopeData, ScopeMap,
+        BottomupScopeGraph, Edge, Explanation, ResolverObserver, ResolverStrategy, ScopeData,
+        ScopeMap,
         circle::CachedCircleMatcher,
+        explain::ExplainObserver,
         resolve::{QueryStats, Resolver},
     },
     label::ScopeGraphLabel,
@@ -17,14 +19,20 @@ use crate::{
     projection::ScopeGraphDataProjection,
     regex::dfs::RegexAutomaton,
     scope::Scope,
+    shadow::{LabelOrderShadowing, ShadowingPolicy},
 };
 
 use super::{ScopeGraph, resolve::QueryResult};
 
 mod cache;
 mod resolve;
+mod snapshot;
+#[cfg(feature = "trace-queries")]
+mod trace;
 
+pub use cache::ResolveCache;
 pub(crate) use cache::*;
+pub use snapshot::SnapshotId;
 
 // type StdProjEnvs<Lbl, Data> = std::collections::HashMap<ProjHash, Vec<QueryResult<Lbl, Data>>>;
 // type StdQueryCache<Lbl, Data> = std::collections::HashMap<QueryCacheKey, StdProjEnvs<Lbl, Data>>;
@@ -41,6 +49,14 @@ where
     resolve_cache: ResolveCache<Lbl, Data>,
     #[serde(skip)]
     cycle_scope_cache: hashbrown::HashMap<Scope, bool>,
+    /// Built on the first [`ResolverStrategy::BottomUp`] query, dropped when the graph changes
+    #[serde(skip)]
+    bottomup: Option<BottomupScopeGraph<Lbl, Data>>,
+    #[serde(skip)]
+    snapshots: Vec<snapshot::Snapshot<Lbl, Data>>,
+    /// Changes since the first snapshot
+    #[serde(skip)]
+    journal: Vec<snapshot::GraphChange<Lbl, Data>>,
 }
 
 impl<Lbl, Data> CachedScopeGraph<Lbl, Data>
@@ -65,14 +81,23 @@ where
         DEq: for<'da, 'db> Fn(&'da Data, &'db Data) -> bool,
         DWfd: for<'da> Fn(&'da Data) -> bool,
     {
+        #[cfg(feature = "trace-queries")]
+        let span = trace::query_span("stats", scope, path_regex, order).entered();
         let mut resolver = Resolver::new(
             &self.scopes,
             path_regex,
             order,
             &data_equiv,
             &data_wellformedness,
+            LabelOrderShadowing,
         );
-        resolver.resolve(Path::start(scope))
+        let (envs, stats) = resolver.resolve(Path::start(scope));
+        #[cfg(feature = "trace-queries")]
+        {
+            span.record("num_envs", envs.len());
+            span.record("profiler", tracing::field::debug(&resolver.profiler));
+        }
+        (envs, stats)
     }
 
     pub fn query_proj_stats<Proj>(
@@ -87,10 +112,13 @@ where
     where
         Proj: ScopeGraphDataProjection<Data>,
     {
+        #[cfg(feature = "trace-queries")]
+        let span = trace::query_span("proj-stats", scope, path_regex, order).entered();
         let proj_hash = resolve::hash(&data_proj);
+        let shadow_hash = resolve::shadow_hash(&LabelOrderShadowing);
         let cache_entry =
             self.resolve_cache
-                .get_mut((order.clone(), path_regex.clone(), proj_hash));
+                .get_mut((order.clone(), path_regex.clone(), proj_hash, shadow_hash));
 
         let cycle_matcher = CachedCircleMatcher::new(&self.scopes, &mut self.cycle_scope_cache);
         let mut resolver = CachedResolver::new(
@@ -100,10 +128,15 @@ where
             path_regex,
             order,
             data_proj,
-            proj_wfd,
+            LabelOrderShadowing,
             caching_enabled,
         );
-        let (envs, mut stats) = resolver.resolve(Path::start(scope));
+        let (envs, mut stats) = resolver.resolve(Path::start(scope), &proj_wfd);
+        #[cfg(feature = "trace-queries")]
+        {
+            span.record("num_envs", envs.len());
+            span.record("profiler", tracing::field::debug(&resolver.profiler));
+        }
 
         let std_cache = self.resolve_cache.clone().into_std();
         stats.cache_size_estimate =
@@ -113,6 +146,137 @@ where
         (envs, stats)
     }
 
+    /// Resolves a query using a strategy that is chosen at runtime.
+    ///
+    /// [`ResolverStrategy::BottomUp`] builds a [`BottomupScopeGraph`] from this graph on first use,
+    /// which is kept until the graph or cache changes. The time and work of building it are added
+    /// to the stats of that first query, the build time is also recorded as `cache_store_time`.
+    pub fn query_with_strategy<Proj>(
+        &mut self,
+        strategy: ResolverStrategy,
+        scope: Scope,
+        path_regex: &RegexAutomaton<Lbl>,
+        order: &LabelOrder<Lbl>,
+        data_proj: Proj,
+        proj_wfd: Proj::Output,
+    ) -> (Vec<QueryResult<Lbl, Data>>, QueryStats)
+    where
+        Proj: ScopeGraphDataProjection<Data>,
+    {
+        match strategy {
+            ResolverStrategy::TopDownUncached => {
+                self.query_proj_stats(scope, path_regex, order, data_proj, proj_wfd, false)
+            }
+            ResolverStrategy::TopDownCached => {
+                self.query_proj_stats(scope, path_regex, order, data_proj, proj_wfd, true)
+            }
+            ResolverStrategy::BottomUp => {
+                let mut build_stats = QueryStats::default();
+                if self.bottomup.is_none() {
+                    let timer = std::time::Instant::now();
+                    let bottomup = BottomupScopeGraph::from_graph(self);
+                    let time = timer.elapsed();
+                    build_stats = QueryStats {
+                        time,
+                        cache_store_time: time,
+                        ..bottomup.build_stats()
+                    };
+                    self.bottomup = Some(bottomup);
+                }
+                let bottomup = self.bottomup.as_mut().unwrap();
+
+                let timer = std::time::Instant::now();
+                let envs = bottomup.query_proj(scope, path_regex, order, data_proj, proj_wfd);
+                let time = timer.elapsed();
+
+                let cache_size = bottomup.cache_size();
+                let graph_size = self.scopes.deep_size_of();
+                let stats = QueryStats {
+                    time,
+                    cache_size,
+                    graph_size,
+                    cache_size_estimate: cache_size as f32 / graph_size as f32,
+                    ..bottomup.query_stats(scope)
+                };
+                (envs, build_stats + stats)
+            }
+        }
+    }
+
+    /// Same as [`ScopeGraph::query_proj`], but calls the hooks of `observer` during resolution
+    pub fn query_proj_observed<Proj>(
+        &mut self,
+        scope: Scope,
+        path_regex: &RegexAutomaton<Lbl>,
+        order: &LabelOrder<Lbl>,
+        data_proj: Proj,
+        proj_wfd: Proj::Output,
+        observer: &mut dyn ResolverObserver<Lbl, Data>,
+    ) -> Vec<QueryResult<Lbl, Data>>
+    where
+        Proj: ScopeGraphDataProjection<Data>,
+    {
+        #[cfg(feature = "trace-queries")]
+        let span = trace::query_span("proj-observed", scope, path_regex, order).entered();
+        let proj_hash = resolve::hash(&data_proj);
+        let shadow_hash = resolve::shadow_hash(&LabelOrderShadowing);
+        let cache_entry =
+            self.resolve_cache
+                .get_mut((order.clone(), path_regex.clone(), proj_hash, shadow_hash));
+        let cycle_matcher = CachedCircleMatcher::new(&self.scopes, &mut self.cycle_scope_cache);
+        let mut resolver = CachedResolver::new(
+            &self.scopes,
+            cache_entry,
+            cycle_matcher,
+            path_regex,
+            order,
+            data_proj,
+            LabelOrderShadowing,
+            true,
+        )
+        .with_observer(observer);
+        let envs = resolver.resolve(Path::start(scope), &proj_wfd).0;
+        #[cfg(feature = "trace-queries")]
+        {
+            span.record("num_envs", envs.len());
+            span.record("profiler", tracing::field::debug(&resolver.profiler));
+        }
+        envs
+    }
+
+    /// Resolves a query and explains the result: which paths were explored, which environments
+    /// were found and why the other environments were discarded.
+    ///
+    /// The query is resolved without reading or writing the cache, so every path is explored.
+    pub fn explain<Proj>(
+        &mut self,
+        scope: Scope,
+        path_regex: &RegexAutomaton<Lbl>,
+        order: &LabelOrder<Lbl>,
+        data_proj: Proj,
+        proj_wfd: Proj::Output,
+    ) -> Explanation<Lbl, Data>
+    where
+        Proj: ScopeGraphDataProjection<Data> + Clone,
+    {
+        let mut observer = ExplainObserver::new();
+        let mut cache_entry = QueryCache::default();
+        let cycle_matcher = CachedCircleMatcher::new(&self.scopes, &mut self.cycle_scope_cache);
+        let mut resolver = CachedResolver::new(
+            &self.scopes,
+            &mut cache_entry,
+            cycle_matcher,
+            path_regex,
+            order,
+            data_proj.clone(),
+            LabelOrderShadowing,
+            false,
+        )
+        .with_observer(&mut observer);
+        let envs = resolver.resolve(Path::start(scope), &proj_wfd).0;
+        observer.into_explanation(scope, envs, |d| data_proj.project(d) == proj_wfd)
+    }
+
     pub(crate) fn map(&self) -> &ScopeMap<Lbl, Data> {
         &self.scopes
     }
@@ -126,11 +290,14 @@ where
     fn reset_cache(&mut self) {
         self.resolve_cache.clear();
         self.cycle_scope_cache.clear();
+        self.bottomup = None;
     }
 
     fn add_scope(&mut self, scope: Scope, data: Data) -> Scope {
         debug_tracing!(trace, "Adding scope: {} with data: {}", scope, data);
-        self.scopes.insert(scope, ScopeData::new(data));
+        self.bottomup = None;
+        let old = self.scopes.insert(scope, ScopeData::new(data));
+        self.record(snapshot::GraphChange::AddScope(scope, old));
         scope
     }
 
@@ -141,6 +308,9 @@ where
             target,
             label
         );
+        self.bottomup = None;
+        // the edge can close a cycle, whose scopes must not be cached
+        self.cycle_scope_cache.clear();
 
         let edge_to_parent = Edge::new(target, label.clone());
         self.scopes
@@ -155,6 +325,7 @@ where
             .expect("Attempting to add edge to non-existant scope")
             .incoming_mut()
             .push(edge_to_child);
+        self.record(snapshot::GraphChange::AddEdge(source, target));
     }
 
     fn get_scope(&self, scope: Scope) -> Option<&ScopeData<Lbl, Data>> {
@@ -170,7 +341,12 @@ where
     }
 
     fn extend(&mut self, other: Self) {
-        self.scopes.extend(other.scopes);
+        self.bottomup = None;
+        self.cycle_scope_cache.clear();
+        for (scope, data) in other.scopes {
+            let old = self.scopes.insert(scope, data);
+            self.record(snapshot::GraphChange::AddScope(scope, old));
+        }
     }
 
     fn scope_holds_data(&self, scope: Scope) -> bool {
@@ -180,43 +356,59 @@ where
             .unwrap_or_default()
     }
 
-    fn query<DEq, DWfd>(
+    fn query_with_policy<DEq, DWfd, Shadow>(
         &mut self,
         scope: Scope,
         path_regex: &RegexAutomaton<Lbl>,
         order: &LabelOrder<Lbl>,
         data_equiv: DEq,
         data_wellformedness: DWfd,
+        shadowing: Shadow,
     ) -> Vec<QueryResult<Lbl, Data>>
     where
         DEq: for<'da, 'db> Fn(&'da Data, &'db Data) -> bool,
         DWfd: for<'da> Fn(&'da Data) -> bool,
+        Shadow: ShadowingPolicy<Lbl, Data>,
     {
+        #[cfg(feature = "trace-queries")]
+        let span = trace::query_span("policy", scope, path_regex, order).entered();
         let mut resolver = Resolver::new(
             &self.scopes,
             path_regex,
             order,
             &data_equiv,
             &data_wellformedness,
+            shadowing,
         );
-        resolver.resolve(Path::start(scope)).0
+        let envs = resolver.resolve(Path::start(scope)).0;
+        #[cfg(feature = "trace-queries")]
+        {
+            span.record("num_envs", envs.len());
+            span.record("profiler", tracing::field::debug(&resolver.profiler));
+        }
+        envs
     }
 
-    fn query_proj<Proj>(
+    fn query_proj_with_policy<Proj, Shadow>(
         &mut self,
         scope: Scope,
         path_regex: &RegexAutomaton<Lbl>,
         order: &LabelOrder<Lbl>,
         data_proj: Proj,
         proj_wfd: Proj::Output,
+        shadowing: Shadow,
     ) -> Vec<QueryResult<Lbl, Data>>
     where
         Proj: ScopeGraphDataProjection<Data>,
+        Shadow: ShadowingPolicy<Lbl, Data>,
     {
+        #[cfg(feature = "trace-queries")]
+        let span = trace::query_span("proj", scope, path_regex, order).entered();
         let proj_hash = resolve::hash(&data_proj);
+        let shadow_hash = resolve::shadow_hash(&shadowing);
         let cache_entry =
             self.resolve_cache
-                .get_mut((order.clone(), path_regex.clone(), proj_hash));
+                .get_mut((order.clone(), path_regex.clone(), proj_hash, shadow_hash));
         let cycle_matcher = CachedCircleMatcher::new(&self.scopes, &mut self.cycle_scope_cache);
         let mut resolver = CachedResolver::new(
             &self.scopes,
@@ -225,19 +417,52 @@ where
             path_regex,
             order,
             data_proj,
-            proj_wfd,
+            shadowing,
             true,
         );
-        let envs = resolver.resolve(Path::start(scope)).0;
-        tracing::info!("{:?}", resolver.profiler);
-        tracing::info!(
-            "Resolved query: {}, {}, {}, found:",
-            scope,
+        let envs = resolver.resolve(Path::start(scope), &proj_wfd).0;
+        #[cfg(feature = "trace-queries")]
+        {
+            span.record("num_envs", envs.len());
+            span.record("profiler", tracing::field::debug(&resolver.profiler));
+        }
+        envs
+    }
+
+    fn query_proj_many<Proj>(
+        &mut self,
+        scope: Scope,
+        path_regex: &RegexAutomaton<Lbl>,
+        order: &LabelOrder<Lbl>,
+        data_proj: Proj,
+        proj_wfds: &[Proj::Output],
+    ) -> Vec<Vec<QueryResult<Lbl, Data>>>
+    where
+        Proj: ScopeGraphDataProjection<Data>,
+    {
+        #[cfg(feature = "trace-queries")]
+        let span = trace::query_span("proj-many", scope, path_regex, order).entered();
+        let proj_hash = resolve::hash(&data_proj);
+        let shadow_hash = resolve::shadow_hash(&LabelOrderShadowing);
+        let cache_entry =
+            self.resolve_cache
+                .get_mut((order.clone(), path_regex.clone(), proj_hash, shadow_hash));
+        let cycle_matcher = CachedCircleMatcher::new(&self.scopes, &mut self.cycle_scope_cache);
+        let mut resolver = CachedResolver::new(
+            &self.scopes,
+            cache_entry,
+            cycle_matcher,
             path_regex,
             order,
+            data_proj,
+            LabelOrderShadowing,
+            true,
         );
-        for qr in &envs {
-            tracing::info!("\t{}", qr);
+        let envs = resolver.resolve_many(Path::start(scope), proj_wfds).0;
+        #[cfg(feature = "trace-queries")]
+        {
+            span.record("num_envs", envs.iter().map(Vec::len).sum::<usize>());
+            span.record("profiler", tracing::field::debug(&resolver.profiler));
         }
         envs
     }
@@ -271,6 +496,9 @@ where
             scopes: ScopeMap::new(),
             resolve_cache: ResolveCache::new(),
             cycle_scope_cache: hashbrown::HashMap::new(),
+            bottomup: None,
+            snapshots: Vec::new(),
+            journal: Vec::new(),
         }
     }
 
@@ -282,6 +510,11 @@ where
         &self.resolve_cache
     }
 
+    /// Replace the cache, e.g. with one that was loaded using [`ResolveCache::load`]
+    pub fn set_cache(&mut self, cache: ResolveCache<Lbl, Data>) {
+        self.resolve_cache = cache;
+    }
+
     /// draw the path to the data in the cache for a specific scope
     pub fn cache_path_uml(&self, scope_num: usize) -> Vec<PlantUmlItem> {
         todo!()
@@ -319,3 +552,332 @@ where
         //     .collect::<Vec<_>>()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use graphing::Renderer;
+
+    use crate::{
+        SgData, SgLabel, SgProjection,
+        graph::{DiscardReason, ResolutionAnimation, ResolutionStep, ResolverStrategy},
+        order::LabelOrderBuilder,
+        regex::Regex,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_query_proj_many() {
+        let mut graph = CachedScopeGraph::<SgLabel, SgData>::new();
+        let s1 = graph.add_scope_default();
+        let s2 = graph.add_scope_default();
+        graph.add_edge(s1, s2, SgLabel::Parent);
+        let x = graph.add_decl(s2, SgLabel::Declaration, SgData::var("x", "int"));
+        let y = graph.add_decl(s1, SgLabel::Declaration, SgData::var("y", "int"));
+
+        let regex = Regex::concat(Regex::kleene(SgLabel::Parent), SgLabel::Declaration).compile();
+        let lo = LabelOrderBuilder::new()
+            .push(SgLabel::Declaration, SgLabel::Parent)
+            .build();
+        let wfds = ["x", "y", "z"].map(std::sync::Arc::from);
+        let envs = graph.query_proj_many(s1, &regex, &lo, SgProjection::VarName, &wfds);
+
+        assert_eq!(envs.len(), 3);
+        assert_eq!(envs[0].len(), 1);
+        assert_eq!(envs[0][0].path.target(), x);
+        assert_eq!(envs[1].len(), 1);
+        assert_eq!(envs[1][0].path.target(), y);
+        assert!(envs[2].is_empty());
+
+        // single queries should give the same result
+        graph.reset_cache();
+        for (wfd, many_env) in wfds.into_iter().zip(envs) {
+            let env = graph.query_proj(s1, &regex, &lo, SgProjection::VarName, wfd);
+            let targets = env.iter().map(|qr| qr.path.target()).collect::<Vec<_>>();
+            let many_targets = many_env
+                .iter()
+                .map(|qr| qr.path.target())
+                .collect::<Vec<_>>();
+            assert_eq!(targets, many_targets);
+        }
+    }
+
+    #[derive(Default)]
+    struct CountingObserver {
+        entered: usize,
+        found: usize,
+        cache_hits: usize,
+        shadowed: usize,
+    }
+
+    impl ResolverObserver<SgLabel, SgData> for CountingObserver {
+        fn on_enter_scope(&mut self, _: &Path<SgLabel>) {
+            self.entered += 1;
+        }
+
+        fn on_cache_hit(&mut self, _: &Path<SgLabel>, _: &[QueryResult<SgLabel, SgData>]) {
+            self.cache_hits += 1;
+        }
+
+        fn on_env_found(&mut self, _: &Path<SgLabel>, _: &QueryResult<SgLabel, SgData>) {
+            self.found += 1;
+        }
+
+        fn on_shadowed(
+            &mut self,
+            _: &QueryResult<SgLabel, SgData>,
+            _: &QueryResult<SgLabel, SgData>,
+        ) {
+            self.shadowed += 1;
+        }
+    }
+
+    #[test]
+    fn test_observer() {
+        let mut graph = CachedScopeGraph::<SgLabel, SgData>::new();
+        let s1 = graph.add_scope_default();
+        let s2 = graph.add_scope_default();
+        graph.add_edge(s1, s2, SgLabel::Parent);
+        graph.add_decl(s1, SgLabel::Declaration, SgData::var("x", "int"));
+        graph.add_decl(s2, SgLabel::Declaration, SgData::var("x", "int"));
+
+        let regex = Regex::concat(Regex::kleene(SgLabel::Parent), SgLabel::Declaration).compile();
+        let lo = LabelOrderBuilder::new()
+            .push(SgLabel::Declaration, SgLabel::Parent)
+            .build();
+        let wfd = std::sync::Arc::from("x");
+
+        let mut observer = CountingObserver::default();
+        let envs =
+            graph.query_proj_observed(s1, &regex, &lo, SgProjection::VarName, wfd, &mut observer);
+        assert_eq!(envs.len(), 1);
+        // s1, s2 and both declarations
+        assert_eq!(observer.entered, 4);
+        assert_eq!(observer.found, 2);
+        assert_eq!(observer.shadowed, 1);
+        assert_eq!(observer.cache_hits, 0);
+    }
+
+    #[test]
+    fn test_animation() {
+        let mut graph = CachedScopeGraph::<SgLabel, SgData>::new();
+        let s1 = graph.add_scope_default();
+        let s2 = graph.add_scope_default();
+        graph.add_edge(s1, s2, SgLabel::Parent);
+        graph.add_decl(s1, SgLabel::Declaration, SgData::var("x", "int"));
+        graph.add_decl(s2, SgLabel::Declaration, SgData::var("x", "int"));
+
+        let regex = Regex::concat(Regex::kleene(SgLabel::Parent), SgLabel::Declaration).compile();
+        let lo = LabelOrderBuilder::new()
+            .push(SgLabel::Declaration, SgLabel::Parent)
+            .build();
+        let wfd = std::sync::Arc::from("x");
+
+        let mut animation = ResolutionAnimation::new();
+        graph.query_proj_observed(s1, &regex, &lo, SgProjection::VarName, wfd, &mut animation);
+        let entered = animation
+            .steps()
+            .iter()
+            .filter(|s| matches!(s, ResolutionStep::EnterScope(_)))
+            .count();
+        assert_eq!(entered, 4);
+        assert!(
+            animation
+                .steps()
+                .iter()
+                .any(|s| matches!(s, ResolutionStep::CacheWrite(..)))
+        );
+
+        let frames = animation.uml_frames(&graph, "animation");
+        assert_eq!(frames.len(), animation.len());
+        let last = frames.last().unwrap().render().unwrap();
+        assert!(last.contains(&format!("step {0}/{0}", animation.len())));
+    }
+
+    #[test]
+    fn test_query_with_strategy() {
+        let mut graph = CachedScopeGraph::<SgLabel, SgData>::new();
+        let s1 = graph.add_scope_default();
+        let s2 = graph.add_scope_default();
+        let s3 = graph.add_scope_default();
+        graph.add_edge(s1, s2, SgLabel::Parent);
+        graph.add_edge(s2, s3, SgLabel::Parent);
+        let x = graph.add_decl(s3, SgLabel::Declaration, SgData::var("x", "int"));
+        graph.add_decl(s1, SgLabel::Declaration, SgData::var("y", "int"));
+
+        let regex = Regex::concat(Regex::kleene(SgLabel::Parent), SgLabel::Declaration).compile();
+        let lo = LabelOrderBuilder::new()
+            .push(SgLabel::Declaration, SgLabel::Parent)
+            .build();
+        for strategy in ResolverStrategy::ALL {
+            let (envs, _) = graph.query_with_strategy(
+                *strategy,
+                s1,
+                &regex,
+                &lo,
+                SgProjection::VarName,
+                std::sync::Arc::from("x"),
+            );
+            assert_eq!(envs.len(), 1, "{strategy}");
+            assert_eq!(envs[0].path.target(), x, "{strategy}");
+            assert_eq!(
+                strategy.to_string().parse::<ResolverStrategy>(),
+                Ok(*strategy)
+            );
+        }
+
+        // bottom-up graph is rebuilt after the graph changes
+        let x2 = graph.add_decl(s1, SgLabel::Declaration, SgData::var("x", "int"));
+        let mut query = || {
+            graph.query_with_strategy(
+                ResolverStrategy::BottomUp,
+                s1,
+                &regex,
+                &lo,
+                SgProjection::VarName,
+                std::sync::Arc::from("x"),
+            )
+        };
+        let (envs, build_stats) = query();
+        assert_eq!(envs.len(), 1);
+        assert_eq!(envs[0].path.target(), x2);
+
+        // the first query includes building the bottom-up graph
+        let (_, stats) = query();
+        // declarations x, y and x2, reached over 3, 1 and 1 edges
+        assert_eq!(stats.nodes_visited, 3);
+        assert_eq!(stats.edges_traversed, 5);
+        assert!(build_stats.nodes_visited > stats.nodes_visited);
+        assert!(build_stats.edges_traversed > stats.edges_traversed);
+        assert_eq!(stats.cache_store_time, std::time::Duration::ZERO);
+    }
+
+    #[test]
+    fn test_save_load_cache() {
+        let mut graph = CachedScopeGraph::<SgLabel, SgData>::new();
+        let s1 = graph.add_scope_default();
+        let s2 = graph.add_scope_default();
+        let s3 = graph.add_scope_default();
+        graph.add_edge(s1, s2, SgLabel::Parent);
+        graph.add_edge(s2, s3, SgLabel::Parent);
+        let x = graph.add_decl(s3, SgLabel::Declaration, SgData::var("x", "int"));
+
+        let regex = Regex::concat(Regex::kleene(SgLabel::Parent), SgLabel::Declaration).compile();
+        let lo = LabelOrderBuilder::new()
+            .push(SgLabel::Declaration, SgLabel::Parent)
+            .build();
+        let wfd: std::sync::Arc<str> = std::sync::Arc::from("x");
+        graph.query_proj(s1, &regex, &lo, SgProjection::VarName, wfd.clone());
+
+        let path = std::env::temp_dir().join(format!("resolve_cache_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        graph.cache().save(path).unwrap();
+        let loaded = ResolveCache::<SgLabel, SgData>::load(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        let mut fresh = CachedScopeGraph::<SgLabel, SgData>::new();
+        fresh.extend(graph);
+        fresh.set_cache(loaded);
+        let (envs, stats) =
+            fresh.query_proj_stats(s1, &regex, &lo, SgProjection::VarName, wfd, true);
+        assert_eq!(envs.len(), 1);
+        assert_eq!(envs[0].path.target(), x);
+        assert_eq!(envs[0].path.start_scope(), s1);
+        assert!(stats.cache_hits > 0);
+    }
+
+    #[test]
+    fn test_snapshot_rollback() {
+        let mut graph = CachedScopeGraph::<SgLabel, SgData>::new();
+        let s1 = graph.add_scope_default();
+        let s2 = graph.add_scope_default();
+        graph.add_edge(s1, s2, SgLabel::Parent);
+        let x = graph.add_decl(s2, SgLabel::Declaration, SgData::var("x", "int"));
+
+        let regex = Regex::concat(Regex::kleene(SgLabel::Parent), SgLabel::Declaration).compile();
+        let lo = LabelOrderBuilder::new()
+            .push(SgLabel::Declaration, SgLabel::Parent)
+            .build();
+        let wfd: std::sync::Arc<str> = std::sync::Arc::from("x");
+        let query = |graph: &mut CachedScopeGraph<SgLabel, SgData>| {
+            graph.query_proj(s1, &regex, &lo, SgProjection::VarName, wfd.clone())
+        };
+        assert_eq!(query(&mut graph)[0].path.target(), x);
+
+        let snapshot = graph.snapshot();
+        let num_scopes = graph.size();
+        let x2 = graph.add_decl(s1, SgLabel::Declaration, SgData::var("x", "int"));
+        graph.reset_cache();
+        assert_eq!(query(&mut graph)[0].path.target(), x2);
+
+        graph.rollback(snapshot);
+        assert_eq!(graph.size(), num_scopes);
+        assert_eq!(graph.get_scope(s1).unwrap().outgoing().len(), 1);
+        assert!(!graph.cache().cache.is_empty());
+        assert_eq!(query(&mut graph)[0].path.target(), x);
+
+        // snapshot is still valid after rolling back to it
+        graph.add_scope_default();
+        graph.rollback(snapshot);
+        assert_eq!(graph.size(), num_scopes);
+    }
+
+    #[test]
+    fn test_explain() {
+        let mut graph = CachedScopeGraph::<SgLabel, SgData>::new();
+        let s1 = graph.add_scope_default();
+        let s2 = graph.add_scope_default();
+        graph.add_edge(s1, s2, SgLabel::Parent);
+        let x1 = graph.add_decl(s1, SgLabel::Declaration, SgData::var("x", "int"));
+        let x2 = graph.add_decl(s2, SgLabel::Declaration, SgData::var("x", "int"));
+        graph.add_decl(s1, SgLabel::Declaration, SgData::var("y", "int"));
+        let y2 = graph.add_decl(s2, SgLabel::Declaration, SgData::var("y", "int"));
+
+        let regex = Regex::concat(Regex::kleene(SgLabel::Parent), SgLabel::Declaration).compile();
+        let lo = LabelOrderBuilder::new()
+            .push(SgLabel::Declaration, SgLabel::Parent)
+            .build();
+        let explanation = graph.explain(
+            s1,
+            &regex,
+            &lo,
+            SgProjection::VarName,
+            std::sync::Arc::from("x"),
+        );
+
+        assert_eq!(explanation.envs.len(), 1);
+        assert_eq!(explanation.envs[0].path.target(), x1);
+        // s1, s2 and the four declarations
+        assert_eq!(explanation.explored.len(), 6);
+
+        let shadowed = explanation
+            .discarded
+            .iter()
+            .find_map(|d| match &d.reason {
+                DiscardReason::Shadowed { by, rule } => Some((&d.env, by, rule)),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(shadowed.0.path.target(), x2);
+        assert_eq!(shadowed.1.path.target(), x1);
+        let rule = shadowed.2.as_ref().unwrap();
+        assert_eq!(rule.preferred, Some(SgLabel::Declaration));
+        assert_eq!(rule.shadowed, Some(SgLabel::Parent));
+        assert!(
+            explanation
+                .discarded
+                .iter()
+                .any(|d| matches!(d.reason, DiscardReason::NotWellFormed))
+        );
+        // y in s2 is shadowed, but only listed as not well-formed
+        assert_eq!(
+            explanation
+                .discarded
+                .iter()
+                .filter(|d| d.env.path.target() == y2)
+                .count(),
+            1
+        );
+        assert!(explanation.to_string().contains("D < P"));
+    }
+}
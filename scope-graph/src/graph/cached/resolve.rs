@@ -1,4 +1,5 @@
 use std::{
+    cell::RefCell,
     hash::{DefaultHasher, Hash, Hasher},
     time::Instant,
 };
@@ -8,7 +9,7 @@ use crate::{
     data::ScopeGraphData,
     debug_tracing,
     graph::{
-        ScopeMap,
+        ResolverObserver, ScopeMap,
         circle::CachedCircleMatcher,
         resolve::{QueryProfiler, QueryStats},
     },
@@ -18,6 +19,7 @@ use crate::{
     projection::ScopeGraphDataProjection,
     regex::{RegexState, dfs::RegexAutomaton},
     scope::Scope,
+    shadow::ShadowingPolicy,
     util::DisplayVec,
 };
 
@@ -30,14 +32,21 @@ pub(super) fn hash<T: Hash>(t: &T) -> u64 {
     hasher.finish()
 }
 
+/// Hash of a shadowing policy, includes the type name as the built-in policies are unit structs.
+#[inline(always)]
+pub(super) fn shadow_hash<T: Hash>(t: &T) -> u64 {
+    hash(&(std::any::type_name::<T>(), t))
+}
+
 // type ProjEnvs<Lbl, Data> = HashMap<ProjHash, SmallVec<[QueryResult<Lbl, Data>; 16]>>;
 
 // todo: reuse code from Resolver
-pub struct CachedResolver<'r, Lbl, Data, Proj>
+pub struct CachedResolver<'r, Lbl, Data, Proj, Shadow>
 where
     Lbl: ScopeGraphLabel,
     Data: ScopeGraphData,
     Proj: ScopeGraphDataProjection<Data>,
+    Shadow: ShadowingPolicy<Lbl, Data>,
 {
     // scopegraph contains cache
     scope_map: &'r ScopeMap<Lbl, Data>,
@@ -49,20 +58,20 @@ where
     lbl_order: &'r LabelOrder<Lbl>,
     /// Data projection function
     data_proj: Proj,
-    /// DProj output that results in well-formed data
-    ///
-    /// `DWfd := |data: &Data| data_proj(data) == proj_wfd`
-    proj_wfd: Proj::Output,
-    proj_wfd_hash: u64,
+    /// Decides which equivalent environments are shadowed
+    shadowing: Shadow,
+    /// Optional hooks that are called during resolution
+    observer: Option<RefCell<&'r mut dyn ResolverObserver<Lbl, Data>>>,
     pub profiler: QueryProfiler,
     caching_enabled: bool,
 }
 
-impl<'r, Lbl, Data, Proj> CachedResolver<'r, Lbl, Data, Proj>
+impl<'r, Lbl, Data, Proj, Shadow> CachedResolver<'r, Lbl, Data, Proj, Shadow>
 where
     Lbl: ScopeGraphLabel,
     Data: ScopeGraphData,
     Proj: ScopeGraphDataProjection<Data>,
+    Shadow: ShadowingPolicy<Lbl, Data>,
 {
     pub fn new(
         scope_graph: &'r ScopeMap<Lbl, Data>,
@@ -71,9 +80,9 @@ where
         path_re: &'r RegexAutomaton<Lbl>,
         lbl_order: &'r LabelOrder<Lbl>,
         data_proj: Proj,
-        proj_wfd: Proj::Output,
+        shadowing: Shadow,
         caching_enabled: bool,
-    ) -> CachedResolver<'r, Lbl, Data, Proj> {
+    ) -> CachedResolver<'r, Lbl, Data, Proj, Shadow> {
         Self {
             scope_map: scope_graph,
             cache,
@@ -81,19 +90,53 @@ where
             path_re,
             lbl_order,
             data_proj,
-            proj_wfd_hash: hash(&proj_wfd),
-            proj_wfd,
+            shadowing,
+            observer: None,
             profiler: QueryProfiler::new(),
             caching_enabled,
         }
     }
 
+    pub fn with_observer(mut self, observer: &'r mut dyn ResolverObserver<Lbl, Data>) -> Self {
+        self.observer = Some(RefCell::new(observer));
+        self
+    }
+
+    #[inline(always)]
+    fn notify(&self, f: impl FnOnce(&mut dyn ResolverObserver<Lbl, Data>)) {
+        if let Some(observer) = &self.observer {
+            let mut observer = observer.borrow_mut();
+            f(&mut **observer);
+        }
+    }
+
     /// Helper function to avoid the ugly field accessor syntax
     fn data_proj(&self, data: &Data) -> Proj::Output {
         self.data_proj.project(data)
     }
 
-    pub fn resolve(&mut self, path: Path<Lbl>) -> (Vec<QueryResult<Lbl, Data>>, QueryStats) {
+    /// Resolve the query for a single well-formedness value.
+    ///
+    /// `DWfd := |data: &Data| data_proj(data) == proj_wfd`
+    pub fn resolve(
+        &mut self,
+        path: Path<Lbl>,
+        proj_wfd: &Proj::Output,
+    ) -> (Vec<QueryResult<Lbl, Data>>, QueryStats) {
+        let (mut envs, stats) = self.resolve_many(path, std::slice::from_ref(proj_wfd));
+        (envs.pop().unwrap_or_default(), stats)
+    }
+
+    /// Resolve the query for multiple well-formedness values in a single traversal.
+    ///
+    /// Environments are collected for every projection hash anyway, so the paths are explored
+    /// (and cached) once, after which the results for each value in `proj_wfds` are picked out.
+    /// The returned vec has the same order as `proj_wfds`.
+    pub fn resolve_many(
+        &mut self,
+        path: Path<Lbl>,
+        proj_wfds: &[Proj::Output],
+    ) -> (Vec<Vec<QueryResult<Lbl, Data>>>, QueryStats) {
         debug_tracing!(
             info,
             "Resolving query: {}, {}, {}",
@@ -104,7 +147,10 @@ where
         self.profiler.start_time = Instant::now();
         let reg = RegexState::new(self.path_re);
         let all_envs = self.resolve_all(path.clone(), reg);
-        let envs = all_envs.clone_envs_by_hash(&self.proj_wfd_hash);
+        let envs = proj_wfds
+            .iter()
+            .map(|wfd| all_envs.clone_envs_by_hash(&hash(wfd)))
+            .collect();
         (envs, (&self.profiler).into())
     }
 
@@ -121,12 +167,14 @@ where
     fn get_env(&self, path: Path<Lbl>, reg: RegexState<'r, Lbl>) -> ProjEnvs<Lbl, Data> {
         // all edges where brzozowski derivative != 0
         self.profiler.inc_nodes_visited();
+        self.notify(|o| o.on_enter_scope(&path));
 
         debug_tracing!(debug, "Checking cache for path {}", path);
         let cached_env = self.get_cached_env(&path, &reg);
         if let Some(cached_env) = cached_env {
             debug_tracing!(debug, "Cache hit for {}", path);
             self.profiler.inc_cache_hits();
+            self.notify(|o| o.on_cache_hit(&path, &cached_env.clone_envs()));
             return cached_env;
         } else {
             // invalid cache entry: clear it
@@ -225,7 +273,9 @@ where
             LabelOrEnd::End => {
                 let data = &self.get_scope(path.target()).unwrap().data;
                 let hash = hash(&self.data_proj(data));
-                ProjEnvs::new_with_env(hash, QueryResult::start(path.target(), data.clone()))
+                let env = QueryResult::start(path.target(), data.clone());
+                self.notify(|o| o.on_env_found(path, &env));
+                ProjEnvs::new_with_env(hash, env)
             }
             // not yet at end
             LabelOrEnd::Label((label, partial_reg)) => {
@@ -272,7 +322,9 @@ where
         mut envs1: ProjEnvs<Lbl, Data>,
         envs2: ProjEnvs<Lbl, Data>,
     ) -> ProjEnvs<Lbl, Data> {
-        envs1.shadow(envs2);
+        envs1.shadow(envs2, &self.shadowing, |shadowed, by| {
+            self.notify(|o| o.on_shadowed(shadowed, by))
+        });
         envs1
     }
 
@@ -320,6 +372,7 @@ where
 
         // debug_tracing!(debug, "Caching envs {env_map} for path {path}");
         self.profiler.inc_cache_writes();
+        self.notify(|o| o.on_cache_write(path, &env_map.clone_envs()));
         let timer = Instant::now();
         self.cache.insert(reg, path, env_map);
         self.profiler.inc_cache_store_timer(timer.elapsed());
@@ -0,0 +1,110 @@
+use crate::{
+    data::ScopeGraphData,
+    graph::{CachedScopeGraph, ScopeData},
+    label::ScopeGraphLabel,
+    scope::Scope,
+};
+
+use super::ResolveCache;
+
+/// Handle to a snapshot taken with [`CachedScopeGraph::snapshot`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SnapshotId(usize);
+
+/// Mutation of the graph that can be undone.
+///
+/// Only recorded while there is at least one snapshot.
+#[derive(Debug)]
+pub(super) enum GraphChange<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    /// Scope was added, replacing the old data if it already existed
+    AddScope(Scope, Option<ScopeData<Lbl, Data>>),
+    /// Edge was pushed to the outgoing edges of the source and incoming edges of the target
+    AddEdge(Scope, Scope),
+}
+
+#[derive(Debug)]
+pub(super) struct Snapshot<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    /// Number of changes in the journal when the snapshot was taken
+    journal_len: usize,
+    resolve_cache: ResolveCache<Lbl, Data>,
+    cycle_scope_cache: hashbrown::HashMap<Scope, bool>,
+}
+
+impl<Lbl, Data> CachedScopeGraph<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    /// Captures the current scopes, edges and caches, so they can be restored with [`CachedScopeGraph::rollback`].
+    ///
+    /// Scopes and edges are not copied. Instead, all changes made after the snapshot are recorded
+    /// and undone on rollback. The caches are copied.
+    pub fn snapshot(&mut self) -> SnapshotId {
+        self.snapshots.push(Snapshot {
+            journal_len: self.journal.len(),
+            resolve_cache: self.resolve_cache.deep_clone(),
+            cycle_scope_cache: self.cycle_scope_cache.clone(),
+        });
+        SnapshotId(self.snapshots.len() - 1)
+    }
+
+    /// Restores the graph to the state at `id`.
+    ///
+    /// Snapshots taken after `id` are dropped, `id` itself stays valid so the graph can be rolled back to it again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` was dropped by an earlier rollback or [`CachedScopeGraph::clear_snapshots`].
+    pub fn rollback(&mut self, id: SnapshotId) {
+        assert!(
+            id.0 < self.snapshots.len(),
+            "Attempting to roll back to dropped snapshot {}",
+            id.0
+        );
+        self.snapshots.truncate(id.0 + 1);
+        let snapshot = &self.snapshots[id.0];
+
+        for change in self.journal.drain(snapshot.journal_len..).rev() {
+            match change {
+                GraphChange::AddScope(scope, Some(old)) => {
+                    self.scopes.insert(scope, old);
+                }
+                GraphChange::AddScope(scope, None) => {
+                    self.scopes.remove(&scope);
+                }
+                GraphChange::AddEdge(source, target) => {
+                    if let Some(s) = self.scopes.get_mut(&source) {
+                        s.outgoing_mut().pop();
+                    }
+                    if let Some(t) = self.scopes.get_mut(&target) {
+                        t.incoming_mut().pop();
+                    }
+                }
+            }
+        }
+
+        self.resolve_cache = snapshot.resolve_cache.deep_clone();
+        self.cycle_scope_cache = snapshot.cycle_scope_cache.clone();
+        self.bottomup = None;
+    }
+
+    /// Drops all snapshots and stops recording changes
+    pub fn clear_snapshots(&mut self) {
+        self.snapshots.clear();
+        self.journal.clear();
+    }
+
+    pub(super) fn record(&mut self, change: GraphChange<Lbl, Data>) {
+        if !self.snapshots.is_empty() {
+            self.journal.push(change);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tracing::{Span, field::Empty};
+
+use crate::{label::ScopeGraphLabel, order::LabelOrder, regex::dfs::RegexAutomaton, scope::Scope};
+
+/// Id of the next query, so events of different queries can be told apart
+static QUERY_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Span that covers a single query.
+///
+/// `num_envs` and `profiler` are recorded once the query is resolved.
+///
+/// The span is at debug level, timing release builds requires disabling the default `release-max-level-error` feature.
+pub(super) fn query_span<Lbl: ScopeGraphLabel>(
+    kind: &'static str,
+    scope: Scope,
+    path_regex: &RegexAutomaton<Lbl>,
+    order: &LabelOrder<Lbl>,
+) -> Span {
+    tracing::debug_span!(
+        "query",
+        kind,
+        id = QUERY_ID.fetch_add(1, Ordering::Relaxed),
+        scope = %scope,
+        regex = %path_regex,
+        order = %order,
+        num_envs = Empty,
+        profiler = Empty,
+    )
+}
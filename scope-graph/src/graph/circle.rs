@@ -272,24 +272,12 @@ impl<'sg, Lbl: ScopeGraphLabel, Data: ScopeGraphData> CachedCircleMatcher<'sg, L
         }
     }
 
+    /// Returns true if `scope` is part of a cycle, the cycles of the whole graph are found on first use
     pub fn contains(&self, scope: Scope) -> bool {
-        return false;
-        // let cache = self.cache.borrow();
         if !self.cache.borrow().contains_key(&scope) {
             self.populate_cache();
         }
         self.cache.borrow().get(&scope).cloned().unwrap_or_default()
-
-        // let (in_cycle, not_cycle) = CircleMatcher::scopes_in_cycle(self.map, scope);
-        // let mut cache_mut = self.cache.borrow_mut();
-        // for scope in in_cycle {
-        //     cache_mut.entry(scope).or_insert(true);
-        // }
-        // for scope in not_cycle {
-        //     cache_mut.entry(scope).or_insert(false);
-        // }
-        // cache_mut.get(&scope).cloned().unwrap_or_default()
-        // // contains
     }
 
     fn populate_cache(&self) {
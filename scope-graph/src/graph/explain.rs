@@ -0,0 +1,215 @@
+use crate::{
+    data::ScopeGraphData,
+    graph::{QueryResult, ResolverObserver},
+    label::ScopeGraphLabel,
+    path::Path,
+    scope::Scope,
+};
+
+/// Label order rule that caused an environment to be shadowed: `preferred < shadowed`.
+///
+/// `None` stands for the end of a path ($), which happens when one environment is found in the scope
+/// where the other path continues.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShadowRule<Lbl> {
+    pub preferred: Option<Lbl>,
+    pub shadowed: Option<Lbl>,
+}
+
+impl<Lbl> ShadowRule<Lbl>
+where
+    Lbl: ScopeGraphLabel,
+{
+    /// Finds the first step where the paths of `by` and `shadowed` diverge
+    fn between<Data: ScopeGraphData>(
+        by: &QueryResult<Lbl, Data>,
+        shadowed: &QueryResult<Lbl, Data>,
+    ) -> Option<Self> {
+        let by = by.path.labels();
+        let shadowed = shadowed.path.labels();
+        let idx = by.iter().zip(&shadowed).take_while(|(a, b)| a == b).count();
+        let rule = Self {
+            preferred: by.get(idx).cloned(),
+            shadowed: shadowed.get(idx).cloned(),
+        };
+        (rule.preferred.is_some() || rule.shadowed.is_some()).then_some(rule)
+    }
+}
+
+impl<Lbl> std::fmt::Display for ShadowRule<Lbl>
+where
+    Lbl: ScopeGraphLabel,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fmt_lbl = |l: &Option<Lbl>| match l {
+            Some(l) => l.to_string(),
+            None => String::from("$"),
+        };
+        write!(
+            f,
+            "{} < {}",
+            fmt_lbl(&self.preferred),
+            fmt_lbl(&self.shadowed)
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum DiscardReason<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    /// Data does not match the wellformedness condition
+    NotWellFormed,
+    /// Shadowed by an environment that is reached using a preferred label.
+    ///
+    /// `rule` is `None` if the paths do not diverge, e.g. with a custom shadowing policy.
+    Shadowed {
+        by: QueryResult<Lbl, Data>,
+        rule: Option<ShadowRule<Lbl>>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct DiscardedEnv<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    pub env: QueryResult<Lbl, Data>,
+    pub reason: DiscardReason<Lbl, Data>,
+}
+
+/// Derivation of the result of a query, created with [`CachedScopeGraph::explain`](crate::graph::CachedScopeGraph::explain).
+#[derive(Debug, Clone)]
+pub struct Explanation<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    pub start: Scope,
+    /// Every path the resolver stepped into, in order
+    pub explored: Vec<Path<Lbl>>,
+    /// Environments that are the result of the query
+    pub envs: Vec<QueryResult<Lbl, Data>>,
+    /// Environments that were reached, but are not part of the result
+    pub discarded: Vec<DiscardedEnv<Lbl, Data>>,
+}
+
+impl<Lbl, Data> std::fmt::Display for Explanation<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "query from {}", self.start)?;
+        writeln!(f, "explored {} paths:", self.explored.len())?;
+        for p in &self.explored {
+            writeln!(f, "  {p}")?;
+        }
+        writeln!(f, "found {} environments:", self.envs.len())?;
+        for env in &self.envs {
+            writeln!(f, "  {env}")?;
+        }
+        writeln!(f, "discarded {} environments:", self.discarded.len())?;
+        for d in &self.discarded {
+            match &d.reason {
+                DiscardReason::NotWellFormed => writeln!(f, "  {}: data not well-formed", d.env)?,
+                DiscardReason::Shadowed {
+                    by,
+                    rule: Some(rule),
+                } => writeln!(f, "  {}: shadowed by {} ({})", d.env, by, rule)?,
+                DiscardReason::Shadowed { by, rule: None } => {
+                    writeln!(f, "  {}: shadowed by {}", d.env, by)?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Observer that collects everything needed for an [`Explanation`]
+pub(crate) struct ExplainObserver<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    explored: Vec<Path<Lbl>>,
+    reached: Vec<QueryResult<Lbl, Data>>,
+    shadowed: Vec<(QueryResult<Lbl, Data>, QueryResult<Lbl, Data>)>,
+}
+
+impl<Lbl, Data> ExplainObserver<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    pub fn new() -> Self {
+        Self {
+            explored: Vec::new(),
+            reached: Vec::new(),
+            shadowed: Vec::new(),
+        }
+    }
+
+    /// `is_wellformed` should check the data in the same way as the query
+    pub fn into_explanation(
+        self,
+        start: Scope,
+        envs: Vec<QueryResult<Lbl, Data>>,
+        is_wellformed: impl Fn(&Data) -> bool,
+    ) -> Explanation<Lbl, Data> {
+        let mut discarded = self
+            .reached
+            .into_iter()
+            .filter(|env| !is_wellformed(&env.data))
+            .map(|env| DiscardedEnv {
+                env,
+                reason: DiscardReason::NotWellFormed,
+            })
+            .collect::<Vec<_>>();
+        // environments that are not well-formed are already listed above, even if they were also shadowed
+        discarded.extend(
+            self.shadowed
+                .into_iter()
+                .filter(|(shadowed, _)| is_wellformed(&shadowed.data))
+                .map(|(shadowed, by)| {
+                    let rule = ShadowRule::between(&by, &shadowed);
+                    DiscardedEnv {
+                        env: shadowed,
+                        reason: DiscardReason::Shadowed { by, rule },
+                    }
+                }),
+        );
+
+        Explanation {
+            start,
+            explored: self.explored,
+            envs,
+            discarded,
+        }
+    }
+}
+
+impl<Lbl, Data> ResolverObserver<Lbl, Data> for ExplainObserver<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    fn on_enter_scope(&mut self, path: &Path<Lbl>) {
+        self.explored.push(path.clone());
+    }
+
+    fn on_env_found(&mut self, path: &Path<Lbl>, env: &QueryResult<Lbl, Data>) {
+        // env only holds the last scope, keep the full path to it
+        self.reached.push(QueryResult {
+            path: path.into(),
+            data: env.data.clone(),
+        });
+    }
+
+    fn on_shadowed(&mut self, shadowed: &QueryResult<Lbl, Data>, by: &QueryResult<Lbl, Data>) {
+        self.shadowed.push((shadowed.clone(), by.clone()));
+    }
+}
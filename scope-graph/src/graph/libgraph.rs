@@ -0,0 +1,356 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use scopegraphs::{
+    RegexMatcher,
+    resolve::{EdgeOrData, Resolve},
+};
+
+use crate::{
+    LibGraph, LibScope, SgData, SgLabel,
+    data::ScopeGraphData,
+    graph::{Edge, QueryResult, ScopeData, ScopeGraph, ScopeMap},
+    order::LabelOrder,
+    path::{Path, ReversePath},
+    projection::ScopeGraphDataProjection,
+    regex::dfs::RegexAutomaton,
+    scope::Scope,
+    shadow::{LabelOrderShadowing, ShadowingPolicy},
+};
+
+/// Implements [`ScopeGraph`] for a graph of the scopegraphs crate, so it can be generated, queried
+/// and rendered in the same way as the other scope graphs.
+///
+/// All scopes and edges are mirrored in a local [`ScopeMap`], which is used for iterating and rendering.
+/// Queries are always resolved by the scopegraphs crate.
+///
+/// There are some limitations:
+/// * scopegraphs only shadows using the label order, queries with any other [`ShadowingPolicy`]
+///   than [`LabelOrderShadowing`] panic instead of silently returning label order results.
+/// * the steps of a resolved path are not public in scopegraphs, so the path in a [`QueryResult`]
+///   is not the path that was resolved. It is reconstructed from the mirrored edges as the shortest path
+///   to the declaration that is accepted by the regex, which can differ from the resolved path when the
+///   label order prefers a longer one. Results without a path in the mirror are logged as an error
+///   and left out, debug builds panic instead.
+pub struct LibScopeGraph<'s> {
+    graph: LibGraph<'s>,
+    mirror: ScopeMap<SgLabel, SgData>,
+    lib_scopes: HashMap<Scope, LibScope>,
+    /// Inverse of `lib_scopes`
+    local_scopes: HashMap<LibScope, Scope>,
+}
+
+impl<'s> LibScopeGraph<'s> {
+    pub fn new(graph: LibGraph<'s>) -> Self {
+        Self {
+            graph,
+            mirror: ScopeMap::new(),
+            lib_scopes: HashMap::new(),
+            local_scopes: HashMap::new(),
+        }
+    }
+
+    /// Copies all scopes and edges of `graph` into `lib_graph`, keeping the same scope ids.
+    pub fn from_graph<G>(lib_graph: LibGraph<'s>, graph: &G) -> Self
+    where
+        G: ScopeGraph<SgLabel, SgData>,
+    {
+        let mut g = Self::new(lib_graph);
+        for (s, d) in graph.scope_iter() {
+            g.add_scope(*s, d.data.clone());
+        }
+        for (s, d) in graph.scope_iter() {
+            for edge in d.outgoing() {
+                g.add_edge(*s, edge.target(), *edge.lbl());
+            }
+        }
+        g
+    }
+
+    /// Underlying scopegraphs graph
+    pub fn lib_graph(&self) -> &LibGraph<'s> {
+        &self.graph
+    }
+
+    /// Scope in the scopegraphs graph that corresponds to `scope`
+    pub fn lib_scope(&self, scope: Scope) -> Option<LibScope> {
+        self.lib_scopes.get(&scope).copied()
+    }
+
+    fn resolve<DEq, DWfd>(
+        &self,
+        scope: Scope,
+        path_regex: &RegexAutomaton<SgLabel>,
+        order: &LabelOrder<SgLabel>,
+        data_equiv: DEq,
+        data_wellformedness: DWfd,
+    ) -> Vec<QueryResult<SgLabel, SgData>>
+    where
+        DEq: Fn(&SgData, &SgData) -> bool,
+        DWfd: Fn(&SgData) -> bool,
+    {
+        let Some(lib_scope) = self.lib_scope(scope) else {
+            return Vec::new();
+        };
+
+        let query = self
+            .graph
+            .query()
+            .with_path_wellformedness(AutomatonMatcher::new(path_regex))
+            .with_label_order(
+                |l1: &EdgeOrData<SgLabel>, l2: &EdgeOrData<SgLabel>| match (l1, l2) {
+                    (EdgeOrData::Data, EdgeOrData::Edge(_)) => true,
+                    (EdgeOrData::Edge(l1), EdgeOrData::Edge(l2)) => order.label_is_less(l1, l2),
+                    _ => false,
+                },
+            )
+            .with_data_wellformedness(|d: &SgData| data_wellformedness(d))
+            .with_data_equivalence(|d1: &SgData, d2: &SgData| data_equiv(d1, d2));
+
+        let mut num_missing = 0;
+        let envs = query
+            .resolve(lib_scope)
+            .into_iter()
+            .filter_map(|resolved| {
+                let path = self
+                    .local_scopes
+                    .get(&resolved.path().target())
+                    .and_then(|target| self.reconstruct_path(scope, *target, path_regex));
+                let Some(path) = path else {
+                    num_missing += 1;
+                    return None;
+                };
+                Some(QueryResult {
+                    path,
+                    data: std::rc::Rc::new(resolved.data().clone()),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // the mirror has the same scopes and edges as the scopegraphs graph, so this means they diverged
+        if num_missing > 0 {
+            tracing::error!(
+                "Could not reconstruct the path of {num_missing} result(s) of a query from {scope}, they are left out"
+            );
+        }
+        debug_assert_eq!(
+            num_missing, 0,
+            "results of scopegraphs do not exist in the mirrored graph"
+        );
+        envs
+    }
+
+    /// Shortest path from `start` to `target` in the mirror that is accepted by `path_regex`
+    fn reconstruct_path(
+        &self,
+        start: Scope,
+        target: Scope,
+        path_regex: &RegexAutomaton<SgLabel>,
+    ) -> Option<ReversePath<SgLabel>> {
+        let mut queue = VecDeque::from([(Path::start(start), 0)]);
+        let mut seen = HashSet::from([(start, 0)]);
+        while let Some((path, state)) = queue.pop_front() {
+            if path.target() == target
+                && path_regex
+                    .get_node(state)
+                    .is_some_and(|n| n.value.is_nullable())
+            {
+                return Some(path.into());
+            }
+
+            let Some(node) = path_regex.get_node(state) else {
+                continue;
+            };
+            for edge in self.mirror[&path.target()].outgoing() {
+                let Some(next) = node.get_edge(edge.lbl()) else {
+                    continue;
+                };
+                if seen.insert((edge.target(), *next)) {
+                    queue.push_back((path.step(*edge.lbl(), edge.target(), *next), *next));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// scopegraphs always shadows using the label order, so other policies cannot be resolved.
+///
+/// Policies are not required to be `'static`, so they are compared by type name instead of `TypeId`.
+fn assert_label_order_shadowing<Shadow>() {
+    let policy = std::any::type_name::<Shadow>();
+    if policy != std::any::type_name::<LabelOrderShadowing>() {
+        unimplemented!("scopegraphs only supports LabelOrderShadowing, got {policy}");
+    }
+}
+
+/// Runs a [`RegexAutomaton`] as path wellformedness of a scopegraphs query
+#[derive(Clone)]
+struct AutomatonMatcher<'a> {
+    automaton: &'a RegexAutomaton<SgLabel>,
+    /// `None` if no more labels can be accepted
+    state: Option<usize>,
+}
+
+impl<'a> AutomatonMatcher<'a> {
+    fn new(automaton: &'a RegexAutomaton<SgLabel>) -> Self {
+        Self {
+            automaton,
+            state: (!automaton.is_empty()).then_some(0),
+        }
+    }
+}
+
+impl<'l> RegexMatcher<&'l SgLabel> for AutomatonMatcher<'_> {
+    fn step(&mut self, inp: &'l SgLabel) {
+        self.state = self
+            .state
+            .and_then(|s| self.automaton.get_node(s))
+            .and_then(|n| n.get_edge(inp).copied());
+    }
+
+    fn is_final(&self) -> bool {
+        self.state
+            .and_then(|s| self.automaton.get_node(s))
+            .is_some_and(|n| n.value.is_nullable())
+    }
+
+    fn is_accepting(&self) -> bool {
+        self.state.is_some()
+    }
+}
+
+impl ScopeGraph<SgLabel, SgData> for LibScopeGraph<'_> {
+    /// scopegraphs does not cache queries
+    fn reset_cache(&mut self) {}
+
+    fn add_scope(&mut self, scope: Scope, data: SgData) -> Scope {
+        let lib_scope = self.graph.add_scope(data.clone());
+        self.lib_scopes.insert(scope, lib_scope);
+        self.local_scopes.insert(lib_scope, scope);
+        self.mirror.insert(scope, ScopeData::new(data));
+        scope
+    }
+
+    fn add_edge(&mut self, source: Scope, target: Scope, label: SgLabel) {
+        let lib_source = self
+            .lib_scope(source)
+            .expect("Attempting to add edge to non-existant scope");
+        let lib_target = self
+            .lib_scope(target)
+            .expect("Attempting to add edge to non-existant scope");
+        self.graph.add_edge(lib_source, label, lib_target);
+
+        if let Some(s) = self.mirror.get_mut(&source) {
+            s.outgoing_mut().push(Edge::new(target, label))
+        }
+        if let Some(t) = self.mirror.get_mut(&target) {
+            t.incoming_mut().push(Edge::new(source, label))
+        }
+    }
+
+    fn get_scope(&self, scope: Scope) -> Option<&ScopeData<SgLabel, SgData>> {
+        self.mirror.get(&scope)
+    }
+
+    fn scope_iter<'a>(&'a self) -> impl Iterator<Item = (&'a Scope, &'a ScopeData<SgLabel, SgData>)>
+    where
+        SgLabel: 'a,
+        SgData: 'a,
+    {
+        self.mirror.iter()
+    }
+
+    fn extend(&mut self, other: Self) {
+        for (s, d) in &other.mirror {
+            self.add_scope(*s, d.data.clone());
+        }
+        for (s, d) in &other.mirror {
+            for edge in d.outgoing() {
+                self.add_edge(*s, edge.target(), *edge.lbl());
+            }
+        }
+    }
+
+    fn scope_holds_data(&self, scope: Scope) -> bool {
+        self.mirror
+            .get(&scope)
+            .map(|d| d.data.variant_has_data())
+            .unwrap_or_default()
+    }
+
+    /// Panics if `Shadow` is not [`LabelOrderShadowing`], paths are reconstructed, see [`LibScopeGraph`]
+    fn query_with_policy<DEq, DWfd, Shadow>(
+        &mut self,
+        scope: Scope,
+        path_regex: &RegexAutomaton<SgLabel>,
+        order: &LabelOrder<SgLabel>,
+        data_equiv: DEq,
+        data_wellformedness: DWfd,
+        _: Shadow,
+    ) -> Vec<QueryResult<SgLabel, SgData>>
+    where
+        DEq: for<'da, 'db> Fn(&'da SgData, &'db SgData) -> bool,
+        DWfd: for<'da> Fn(&'da SgData) -> bool,
+        Shadow: ShadowingPolicy<SgLabel, SgData>,
+    {
+        assert_label_order_shadowing::<Shadow>();
+        self.resolve(scope, path_regex, order, data_equiv, data_wellformedness)
+    }
+
+    /// Panics if `Shadow` is not [`LabelOrderShadowing`], paths are reconstructed, see [`LibScopeGraph`]
+    fn query_proj_with_policy<Proj, Shadow>(
+        &mut self,
+        scope: Scope,
+        path_regex: &RegexAutomaton<SgLabel>,
+        order: &LabelOrder<SgLabel>,
+        data_proj: Proj,
+        proj_wfd: Proj::Output,
+        _: Shadow,
+    ) -> Vec<QueryResult<SgLabel, SgData>>
+    where
+        Proj: ScopeGraphDataProjection<SgData>,
+        Shadow: ShadowingPolicy<SgLabel, SgData>,
+    {
+        assert_label_order_shadowing::<Shadow>();
+        self.resolve(
+            scope,
+            path_regex,
+            order,
+            |d1, d2| data_proj.project(d1) == data_proj.project(d2),
+            |d| data_proj.project(d) == proj_wfd,
+        )
+    }
+
+    fn query_proj_many<Proj>(
+        &mut self,
+        scope: Scope,
+        path_regex: &RegexAutomaton<SgLabel>,
+        order: &LabelOrder<SgLabel>,
+        data_proj: Proj,
+        proj_wfds: &[Proj::Output],
+    ) -> Vec<Vec<QueryResult<SgLabel, SgData>>>
+    where
+        Proj: ScopeGraphDataProjection<SgData>,
+    {
+        proj_wfds
+            .iter()
+            .map(|wfd| {
+                self.resolve(
+                    scope,
+                    path_regex,
+                    order,
+                    |d1, d2| data_proj.project(d1) == data_proj.project(d2),
+                    |d| data_proj.project(d) == *wfd,
+                )
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for LibScopeGraph<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LibScopeGraph")
+            .field("scopes", &self.mirror)
+            .finish_non_exhaustive()
+    }
+}
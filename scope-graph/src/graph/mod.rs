@@ -2,35 +2,43 @@ use std::collections::HashMap;
 
 use deepsize::DeepSizeOf;
 use graphing::{
-    Color,
-    mermaid::{
-        MermaidChartDirection, MermaidDiagram, MermaidStyleSheet,
-        item::{ItemShape, MermaidItem},
-        theme::{AnimationSpeed, AnimationStyle, EdgeType, ElementStyle, Size},
-    },
-    plantuml::{
-        EdgeDirection, NodeType, PlantUmlDiagram, PlantUmlItem,
-        theme::{
-            ElementCss, FontFamily, FontStyle, HorizontalAlignment, LineStyle, PlantUmlStyleSheet,
-        },
-    },
+    Diagram, DiagramEdge, DiagramNode, Direction, Shape,
+    mermaid::{MermaidChartDirection, MermaidDiagram, item::MermaidItem},
+    plantuml::{PlantUmlDiagram, PlantUmlItem},
 };
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    BackGroundEdgeColor, BackgroundColor, ColorSet, DRAW_CACHES, ForeGroundColor,
-    data::ScopeGraphData, debug_tracing, graph::circle::CircleMatcher, label::ScopeGraphLabel,
-    order::LabelOrder, projection::ScopeGraphDataProjection, regex::dfs::RegexAutomaton,
+    BackgroundColor, ColorSet, DRAW_CACHES,
+    data::ScopeGraphData,
+    debug_tracing,
+    graph::circle::CircleMatcher,
+    label::ScopeGraphLabel,
+    order::LabelOrder,
+    projection::ScopeGraphDataProjection,
+    regex::dfs::RegexAutomaton,
     scope::Scope,
+    shadow::{LabelOrderShadowing, ShadowingPolicy},
+    theme::ThemePreset,
 };
 
 // mod base;
+mod animation;
+mod bottomup;
 mod cached;
 mod circle;
+mod explain;
+mod libgraph;
+mod observer;
 mod resolve;
 
 // pub use base::*;
+pub use animation::{ResolutionAnimation, ResolutionStep};
+pub use bottomup::BottomupScopeGraph;
 pub use cached::*;
+pub use explain::{DiscardReason, DiscardedEnv, Explanation, ShadowRule};
+pub use libgraph::LibScopeGraph;
+pub use observer::{ResolverObserver, TracingObserver};
 pub use resolve::{QueryResult, QueryStats};
 
 #[derive(Clone, Copy, Default, Debug)]
@@ -41,6 +49,48 @@ pub enum LabelRenderStyle {
     Long,
 }
 
+/// Algorithm used to resolve a query, see [`CachedScopeGraph::query_with_strategy`]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResolverStrategy {
+    /// Top-down resolution without reading or writing the cache
+    TopDownUncached,
+    /// Top-down resolution, reusing environments of earlier queries
+    #[default]
+    TopDownCached,
+    /// Look up the precomputed declarations of [`BottomupScopeGraph`]
+    BottomUp,
+}
+
+impl ResolverStrategy {
+    pub const ALL: &[Self] = &[Self::TopDownUncached, Self::TopDownCached, Self::BottomUp];
+}
+
+impl std::fmt::Display for ResolverStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TopDownUncached => write!(f, "top-down-uncached"),
+            Self::TopDownCached => write!(f, "top-down-cached"),
+            Self::BottomUp => write!(f, "bottom-up"),
+        }
+    }
+}
+
+impl std::str::FromStr for ResolverStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top-down-uncached" | "uncached" => Ok(Self::TopDownUncached),
+            "top-down-cached" | "cached" => Ok(Self::TopDownCached),
+            "bottom-up" | "bottomup" => Ok(Self::BottomUp),
+            _ => Err(format!(
+                "unknown resolver strategy '{s}', expected one of: top-down-uncached, top-down-cached, bottom-up"
+            )),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct GraphRenderOptions {
     pub draw_caches: bool,
@@ -48,6 +98,9 @@ pub struct GraphRenderOptions {
     pub draw_types: bool,
     pub draw_node_label: bool,
     pub draw_colors: bool,
+    /// Adds a legend with the colors of the classes used in the diagram
+    pub draw_legend: bool,
+    pub theme: ThemePreset,
 }
 
 impl std::default::Default for GraphRenderOptions {
@@ -58,6 +111,8 @@ impl std::default::Default for GraphRenderOptions {
             draw_types: true,
             draw_node_label: true,
             draw_colors: true,
+            draw_legend: false,
+            theme: ThemePreset::default(),
         }
     }
 }
@@ -183,7 +238,32 @@ where
     ) -> Vec<QueryResult<Lbl, Data>>
     where
         DEq: for<'da, 'db> Fn(&'da Data, &'db Data) -> bool,
-        DWfd: for<'da> Fn(&'da Data) -> bool;
+        DWfd: for<'da> Fn(&'da Data) -> bool,
+    {
+        self.query_with_policy(
+            scope,
+            path_regex,
+            order,
+            data_equiv,
+            data_wellformedness,
+            LabelOrderShadowing,
+        )
+    }
+
+    /// Same as [`ScopeGraph::query`], but with a custom shadowing policy
+    fn query_with_policy<DEq, DWfd, Shadow>(
+        &mut self,
+        scope: Scope,
+        path_regex: &RegexAutomaton<Lbl>,
+        order: &LabelOrder<Lbl>,
+        data_equiv: DEq,
+        data_wellformedness: DWfd,
+        shadowing: Shadow,
+    ) -> Vec<QueryResult<Lbl, Data>>
+    where
+        DEq: for<'da, 'db> Fn(&'da Data, &'db Data) -> bool,
+        DWfd: for<'da> Fn(&'da Data) -> bool,
+        Shadow: ShadowingPolicy<Lbl, Data>;
 
     /// Query using a projection function and a wellformedness value for the projected data
     fn query_proj<Proj>(
@@ -194,6 +274,45 @@ where
         data_proj: Proj,
         proj_wfd: Proj::Output,
     ) -> Vec<QueryResult<Lbl, Data>>
+    where
+        Proj: ScopeGraphDataProjection<Data>,
+    {
+        self.query_proj_with_policy(
+            scope,
+            path_regex,
+            order,
+            data_proj,
+            proj_wfd,
+            LabelOrderShadowing,
+        )
+    }
+
+    /// Same as [`ScopeGraph::query_proj`], but with a custom shadowing policy
+    fn query_proj_with_policy<Proj, Shadow>(
+        &mut self,
+        scope: Scope,
+        path_regex: &RegexAutomaton<Lbl>,
+        order: &LabelOrder<Lbl>,
+        data_proj: Proj,
+        proj_wfd: Proj::Output,
+        shadowing: Shadow,
+    ) -> Vec<QueryResult<Lbl, Data>>
+    where
+        Proj: ScopeGraphDataProjection<Data>,
+        Shadow: ShadowingPolicy<Lbl, Data>;
+
+    /// Query for multiple wellformedness values of the projected data at once.
+    ///
+    /// Shares path exploration between the values, the result contains the environments for each
+    /// value in `proj_wfds`, in the same order.
+    fn query_proj_many<Proj>(
+        &mut self,
+        scope: Scope,
+        path_regex: &RegexAutomaton<Lbl>,
+        order: &LabelOrder<Lbl>,
+        data_proj: Proj,
+        proj_wfds: &[Proj::Output],
+    ) -> Vec<Vec<QueryResult<Lbl, Data>>>
     where
         Proj: ScopeGraphDataProjection<Data>;
 
@@ -231,67 +350,19 @@ where
         false
     }
 
-    fn as_uml_diagram(&self, title: &str, options: &GraphRenderOptions) -> PlantUmlDiagram {
-        let mut style_sheet: PlantUmlStyleSheet = [
-            ElementCss::new()
-                .background_color(Color::new_rgb(242, 232, 230))
-                .font_family(FontFamily::Monospace)
-                .as_selector("element"),
-            ElementCss::new()
-                .line_color(Color::BLACK)
-                .as_selector("arrow"),
-            ElementCss::new()
-                .font_size(24)
-                .font_style(FontStyle::Bold)
-                .round_corner(1000)
-                .horizontal_alignment(HorizontalAlignment::Center)
-                .as_class("scope"),
-            ElementCss::new()
-                .font_size(24)
-                .font_style(FontStyle::Bold)
-                .round_corner(10)
-                .shadowing(1)
-                .background_color(Color::new_rgb(245, 229, 220))
-                .as_class("data-scope"),
-            ElementCss::new()
-                .line_thickness(1.25)
-                .font_size(16)
-                .as_class("scope-edge"),
-            ElementCss::new()
-                .line_style(LineStyle::Dashed)
-                .as_class("query-edge"),
-            ElementCss::new()
-                .line_style(LineStyle::Dotted)
-                .line_color(Color::LIGHT_GRAY)
-                .as_class("cache-edge"),
-            ElementCss::new().font_size(11).as_class("cache-entry"),
-        ]
-        .into();
-        let fg = ForeGroundColor::uml_stylesheet();
-        let bg = BackgroundColor::uml_stylesheet();
-        let bg_line = BackGroundEdgeColor::uml_stylesheet();
-        style_sheet.merge(fg);
-        style_sheet.merge(bg);
-        style_sheet.merge(bg_line);
-
-        let mut diagram = PlantUmlDiagram::new(title);
-        diagram.set_style_sheet(style_sheet);
-        diagram.extend(self.generate_graph_uml(options));
-        if options.draw_caches {
-            diagram.extend(self.generate_cache_uml());
-        }
-        diagram
-    }
-
-    fn generate_graph_uml(&self, options: &GraphRenderOptions) -> Vec<PlantUmlItem> {
-        let scope_nodes = self.scope_iter().map(|(s, d)| {
-            let (node_type, class, contents) = match d.data.variant_has_data() {
+    /// Backend-agnostic diagram of the scopes and edges of this graph
+    fn as_diagram(&self, title: &str, options: &GraphRenderOptions) -> Diagram {
+        let mut diagram = Diagram::new(title);
+        for (s, d) in self.scope_iter() {
+            let node = match d.data.variant_has_data() {
                 true => {
                     let d_str = match options.draw_types {
                         true => d.data.render_with_type(),
                         false => d.data.render_string(),
                     };
-                    (NodeType::Card, "data-scope", format!("{} ⊢ {}", s, d_str))
+                    DiagramNode::new(s.uml_id(), format!("{} ⊢ {}", s, d_str))
+                        .with_shape(Shape::Card)
+                        .add_class("data-scope")
                 }
                 false => {
                     let contents = if options.draw_node_label {
@@ -299,31 +370,33 @@ where
                     } else {
                         String::from("0") // empty is not possible ugh
                     };
-                    (NodeType::Card, "scope", contents)
+                    let node = DiagramNode::new(s.uml_id(), contents)
+                        .with_shape(Shape::Circle)
+                        .add_class("scope");
+                    match options.draw_colors {
+                        true => node.add_class(BackgroundColor::get_class_name(s.0)),
+                        false => node,
+                    }
                 }
             };
-            let mut node = PlantUmlItem::node(s.uml_id(), contents, node_type).add_class(class);
-            if options.draw_colors {
-                node = node.add_class(BackgroundColor::get_class_name(s.0));
-            }
-            node
-        });
+            diagram.push_node(node);
+        }
 
         let mut decl_dir = 0;
-
-        let edges = self.scope_iter().flat_map(move |(s, d)| {
-            d.outgoing().iter().map(move |edge| {
+        for (s, d) in self.scope_iter() {
+            for edge in d.outgoing() {
+                // spread declarations around their scope
                 let dir = match self.scope_holds_data(edge.target()) {
                     true => {
                         decl_dir = (decl_dir + 1) % 4;
                         match decl_dir {
-                            0 => EdgeDirection::Bottom,
-                            1 => EdgeDirection::Left,
-                            2 => EdgeDirection::Right,
-                            _ => EdgeDirection::Up,
+                            0 => Direction::Down,
+                            1 => Direction::Left,
+                            2 => Direction::Right,
+                            _ => Direction::Up,
                         }
                     }
-                    false => EdgeDirection::Up,
+                    false => Direction::Up,
                 };
 
                 let lbl = match options.draw_labels {
@@ -332,12 +405,26 @@ where
                     LabelRenderStyle::Long => edge.lbl().str().to_string(),
                 };
 
-                PlantUmlItem::edge(s.uml_id(), edge.target().uml_id(), lbl, dir)
-                    .add_class("scope-edge")
-            })
-        });
+                diagram.push_edge(
+                    DiagramEdge::new(s.uml_id(), edge.target().uml_id(), lbl)
+                        .with_direction(dir)
+                        .add_class("scope-edge"),
+                );
+            }
+        }
+        diagram
+    }
 
-        scope_nodes.chain(edges).collect()
+    fn as_uml_diagram(&self, title: &str, options: &GraphRenderOptions) -> PlantUmlDiagram {
+        let mut diagram = PlantUmlDiagram::from(&self.as_diagram(title, options));
+        diagram.set_style_sheet(options.theme.uml_stylesheet());
+        if options.draw_legend {
+            diagram.add_legend();
+        }
+        if options.draw_caches {
+            diagram.extend(self.generate_cache_uml());
+        }
+        diagram
     }
 
     fn generate_cache_uml(&self) -> Vec<PlantUmlItem> {
@@ -345,43 +432,13 @@ where
     }
 
     fn as_mmd_diagram(&self, title: &str, draw_caches: bool) -> MermaidDiagram {
-        let mut style_sheet = MermaidStyleSheet::new()
-            .with_class(
-                "scope",
-                ElementStyle::new()
-                    .line_color(Color::DARK_GRAY)
-                    .font_size(Size::Pt(18))
-                    .margin(Size::Px(5))
-                    .padding(Size::Px(5)),
-            )
-            .with_class(
-                "data-scope",
-                ElementStyle::new()
-                    .line_color(Color::BLACK)
-                    .background_color(Color::new_rgb(242, 232, 175)),
-            )
-            .with_class("scope-edge", ElementStyle::new().line_thickness(2.5))
-            .with_class(
-                "query-edge",
-                ElementStyle::new()
-                    .line_thickness(1.5)
-                    .animation_style(AnimationStyle::Linear)
-                    .animation_speed(AnimationSpeed::Slow),
-            )
-            .with_class("cache-entry", ElementStyle::new().font_size(Size::Pt(8)))
-            .with_class("cache-edge", ElementStyle::new());
-
-        let fg = ForeGroundColor::mmd_stylesheet();
-        let bg = BackgroundColor::mmd_stylesheet();
-        let bg_line = BackGroundEdgeColor::mmd_stylesheet();
-        style_sheet.merge(fg);
-        style_sheet.merge(bg);
-        style_sheet.merge(bg_line);
-
-        let mut diagram = MermaidDiagram::new(title);
-        diagram.set_style_sheet(style_sheet);
+        let options = GraphRenderOptions {
+            draw_types: false,
+            ..Default::default()
+        };
+        let mut diagram = MermaidDiagram::from(&self.as_diagram(title, &options));
+        diagram.set_style_sheet(options.theme.mmd_stylesheet());
         diagram.set_direction(MermaidChartDirection::BottomTop);
-        diagram.extend(self.generate_graph_mmd());
         if draw_caches {
             diagram.extend(self.generate_cache_mmd());
         }
@@ -391,36 +448,4 @@ where
     fn generate_cache_mmd(&self) -> Vec<MermaidItem> {
         Vec::new()
     }
-
-    fn generate_graph_mmd(&self) -> Vec<MermaidItem> {
-        let scope_nodes = self
-            .scope_iter()
-            .map(|(s, d)| match d.data.variant_has_data() {
-                true => {
-                    let contents = format!("{} ⊢ {}", s, d.data.render_string());
-                    MermaidItem::node(s.uml_id(), contents, ItemShape::Rounded)
-                        .add_class("data-scope")
-                }
-                false => {
-                    let contents = s.to_string();
-                    MermaidItem::node(s.uml_id(), contents, ItemShape::Circle)
-                        .add_class("scope")
-                        .add_class(BackgroundColor::get_class_name(s.0))
-                }
-            });
-
-        let edges = self.scope_iter().flat_map(move |(s, d)| {
-            d.outgoing().iter().map(move |edge| {
-                MermaidItem::edge(
-                    s.uml_id(),
-                    edge.target().uml_id(),
-                    edge.lbl().char(),
-                    EdgeType::Thick,
-                )
-                .add_class("scope-edge")
-            })
-        });
-
-        scope_nodes.chain(edges).collect()
-    }
 }
@@ -0,0 +1,82 @@
+use crate::{
+    data::ScopeGraphData, graph::QueryResult, label::ScopeGraphLabel, path::Path, util::DisplayVec,
+};
+
+/// Hooks that are called by the cached resolver while it resolves a query.
+///
+/// All methods do nothing by default, so only the relevant ones have to be implemented.
+pub trait ResolverObserver<Lbl, Data>
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    /// Resolver steps into the target scope of `path`
+    fn on_enter_scope(&mut self, path: &Path<Lbl>) {
+        let _ = path;
+    }
+
+    /// Environments for the target scope of `path` were read from the cache
+    fn on_cache_hit(&mut self, path: &Path<Lbl>, envs: &[QueryResult<Lbl, Data>]) {
+        let _ = (path, envs);
+    }
+
+    /// Environments for the target scope of `path` are written to the cache
+    fn on_cache_write(&mut self, path: &Path<Lbl>, envs: &[QueryResult<Lbl, Data>]) {
+        let _ = (path, envs);
+    }
+
+    /// Reached the end of a path with data.
+    ///
+    /// This is called before the data is checked against the wellformedness condition.
+    fn on_env_found(&mut self, path: &Path<Lbl>, env: &QueryResult<Lbl, Data>) {
+        let _ = (path, env);
+    }
+
+    /// `shadowed` is removed since `by` is found via a label with a higher priority
+    fn on_shadowed(&mut self, shadowed: &QueryResult<Lbl, Data>, by: &QueryResult<Lbl, Data>) {
+        let _ = (shadowed, by);
+    }
+}
+
+/// Observer that does nothing
+impl<Lbl, Data> ResolverObserver<Lbl, Data> for ()
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+}
+
+/// Emits all resolver events as structured `tracing` events with the target `scope_graph::resolver`.
+///
+/// Entering a scope is logged at trace level, the other events at debug level.
+/// Release builds strip these unless the default `release-max-level-error` feature is disabled.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TracingObserver;
+
+const RESOLVER_TARGET: &str = "scope_graph::resolver";
+
+impl<Lbl, Data> ResolverObserver<Lbl, Data> for TracingObserver
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    fn on_enter_scope(&mut self, path: &Path<Lbl>) {
+        tracing::trace!(target: RESOLVER_TARGET, scope = %path.target(), path = %path, "enter scope");
+    }
+
+    fn on_cache_hit(&mut self, path: &Path<Lbl>, envs: &[QueryResult<Lbl, Data>]) {
+        tracing::debug!(target: RESOLVER_TARGET, scope = %path.target(), envs = %DisplayVec(envs), "cache hit");
+    }
+
+    fn on_cache_write(&mut self, path: &Path<Lbl>, envs: &[QueryResult<Lbl, Data>]) {
+        tracing::debug!(target: RESOLVER_TARGET, scope = %path.target(), envs = %DisplayVec(envs), "cache write");
+    }
+
+    fn on_env_found(&mut self, path: &Path<Lbl>, env: &QueryResult<Lbl, Data>) {
+        tracing::debug!(target: RESOLVER_TARGET, scope = %path.target(), env = %env, "env found");
+    }
+
+    fn on_shadowed(&mut self, shadowed: &QueryResult<Lbl, Data>, by: &QueryResult<Lbl, Data>) {
+        tracing::debug!(target: RESOLVER_TARGET, shadowed = %shadowed, by = %by, "shadowed");
+    }
+}
@@ -18,6 +18,7 @@ use crate::{
     path::{Path, ReversePath},
     regex::{RegexState, dfs::RegexAutomaton},
     scope::Scope,
+    shadow::ShadowingPolicy,
 };
 
 use super::ScopeData;
@@ -210,7 +211,11 @@ impl From<&QueryProfiler> for QueryStats {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, DeepSizeOf)]
+#[derive(Debug, Clone, PartialEq, Eq, DeepSizeOf, serde::Serialize, serde::Deserialize)]
+#[serde(bound(
+    serialize = "Lbl: serde::Serialize, Data: serde::Serialize",
+    deserialize = "Lbl: serde::Deserialize<'de>, Data: serde::Deserialize<'de>"
+))]
 pub struct QueryResult<Lbl, Data>
 where
     Lbl: ScopeGraphLabel + Clone,
@@ -262,12 +267,13 @@ where
     }
 }
 
-pub struct Resolver<'r, Lbl, Data, DEq, DWfd>
+pub struct Resolver<'r, Lbl, Data, DEq, DWfd, Shadow>
 where
     Lbl: ScopeGraphLabel,
     Data: ScopeGraphData,
     DEq: for<'da, 'db> Fn(&'da Data, &'db Data) -> bool,
     DWfd: for<'da> Fn(&'da Data) -> bool,
+    Shadow: ShadowingPolicy<Lbl, Data>,
 {
     // scopegraph contains cache
     pub scope_map: &'r ScopeMap<Lbl, Data>,
@@ -275,15 +281,17 @@ where
     pub lbl_order: &'r LabelOrder<Lbl>,
     pub data_eq: DEq,
     pub data_wfd: DWfd,
+    pub shadowing: Shadow,
     pub profiler: QueryProfiler,
 }
 
-impl<'r, Lbl, Data, DEq, DWfd> Resolver<'r, Lbl, Data, DEq, DWfd>
+impl<'r, Lbl, Data, DEq, DWfd, Shadow> Resolver<'r, Lbl, Data, DEq, DWfd, Shadow>
 where
     Lbl: ScopeGraphLabel,
     Data: ScopeGraphData,
     DEq: for<'da, 'db> Fn(&'da Data, &'db Data) -> bool,
     DWfd: for<'da> Fn(&'da Data) -> bool,
+    Shadow: ShadowingPolicy<Lbl, Data>,
 {
     pub fn new(
         scope_map: &'r ScopeMap<Lbl, Data>,
@@ -291,13 +299,15 @@ where
         lbl_order: &'r LabelOrder<Lbl>,
         data_eq: DEq,
         data_wfd: DWfd,
-    ) -> Resolver<'r, Lbl, Data, DEq, DWfd> {
+        shadowing: Shadow,
+    ) -> Resolver<'r, Lbl, Data, DEq, DWfd, Shadow> {
         Self {
             scope_map,
             path_re,
             lbl_order,
             data_eq,
             data_wfd,
+            shadowing,
             profiler: QueryProfiler::new(),
         }
     }
@@ -436,7 +446,10 @@ where
         mut a2: Vec<QueryResult<Lbl, Data>>,
     ) -> Vec<QueryResult<Lbl, Data>> {
         debug_tracing!(trace, "Shadowing...");
-        a2.retain(|qr2| !a1.iter().any(|qr1| (self.data_eq)(&qr1.data, &qr2.data)));
+        a2.retain(|qr2| {
+            !a1.iter()
+                .any(|qr1| (self.data_eq)(&qr1.data, &qr2.data) && self.shadowing.shadows(qr1, qr2))
+        });
 
         a1.append(&mut a2);
         a1
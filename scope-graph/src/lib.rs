@@ -29,7 +29,9 @@ pub mod graph;
 pub mod order;
 pub mod projection;
 pub mod regex;
+pub mod shadow;
 mod slides;
+pub mod theme;
 pub mod util;
 
 /// Enable circular path check in cached resolver
@@ -1,21 +1,16 @@
 use std::{io::Write, sync::Arc};
 
 use graphing::{
-    Color, Renderer,
-    plantuml::{
-        EdgeDirection, NodeType, PlantUmlDiagram, PlantUmlItem,
-        theme::{
-            ElementCss, FontFamily, FontStyle, HorizontalAlignment, LineStyle, PlantUmlStyleSheet,
-        },
-    },
+    Renderer,
+    plantuml::{EdgeDirection, NodeType, PlantUmlDiagram, PlantUmlItem},
 };
 use scope_graph::{
-    BackGroundEdgeColor, BackgroundColor, ColorSet, DRAW_CACHES, ForeGroundColor, SgData, SgLabel,
-    SgProjection,
+    BackgroundColor, ColorSet, DRAW_CACHES, ForeGroundColor, SgData, SgLabel, SgProjection,
     generator::{GraphGenerator, GraphPattern},
-    graph::{CachedScopeGraph, GraphRenderOptions, ScopeGraph},
+    graph::{CachedScopeGraph, GraphRenderOptions, ResolutionAnimation, ScopeGraph},
     order::LabelOrderBuilder,
     regex::{Regex, dfs::RegexAutomaton},
+    theme::ThemePreset,
 };
 
 pub type UsedScopeGraph = CachedScopeGraph<SgLabel, SgData>;
@@ -252,47 +247,7 @@ fn aron_example() {
 
     println!("env: {0:?}", env);
 
-    let mut style_sheet: PlantUmlStyleSheet = [
-        ElementCss::new()
-            .background_color(Color::new_rgb(242, 232, 230))
-            .font_family(FontFamily::Monospace)
-            .as_selector("element"),
-        ElementCss::new()
-            .line_color(Color::BLACK)
-            .as_selector("arrow"),
-        ElementCss::new()
-            .font_size(24)
-            .font_style(FontStyle::Bold)
-            .round_corner(1000)
-            .horizontal_alignment(HorizontalAlignment::Center)
-            .as_class("scope"),
-        ElementCss::new()
-            .font_size(24)
-            .font_style(FontStyle::Bold)
-            .round_corner(10)
-            .shadowing(1)
-            .background_color(Color::new_rgb(245, 229, 220))
-            .as_class("data-scope"),
-        ElementCss::new()
-            .line_thickness(1.25)
-            .font_size(16)
-            .as_class("scope-edge"),
-        ElementCss::new()
-            .line_style(LineStyle::Dashed)
-            .as_class("query-edge"),
-        ElementCss::new()
-            .line_style(LineStyle::Dotted)
-            .line_color(Color::LIGHT_GRAY)
-            .as_class("cache-edge"),
-        ElementCss::new().font_size(11).as_class("cache-entry"),
-    ]
-    .into();
-    let fg = ForeGroundColor::uml_stylesheet();
-    let bg = BackgroundColor::uml_stylesheet();
-    let bg_line = BackGroundEdgeColor::uml_stylesheet();
-    style_sheet.merge(fg);
-    style_sheet.merge(bg);
-    style_sheet.merge(bg_line);
+    let style_sheet = ThemePreset::default().uml_stylesheet();
 
     let mut diagram = PlantUmlDiagram::new("1st query");
     diagram.set_style_sheet(style_sheet);
@@ -399,6 +354,27 @@ fn aron_example() {
         EdgeDirection::Left,
     ));
     d2.render_to_file("output/aron2.puml").unwrap();
+
+    // same two queries, but one diagram per resolution step
+    graph.reset_cache();
+    for (idx, start) in [s1, s3].into_iter().enumerate() {
+        let mut animation = ResolutionAnimation::new();
+        graph.query_proj_observed(
+            start,
+            &reg,
+            &label_order,
+            SgProjection::VarName,
+            wfd.clone(),
+            &mut animation,
+        );
+        animation
+            .render_uml_frames(
+                &graph,
+                &format!("Query {} from scope {start}", idx + 1),
+                &format!("output/aron_steps/query{}", idx + 1),
+            )
+            .unwrap();
+    }
 }
 
 fn main() {
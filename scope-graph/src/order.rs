@@ -5,8 +5,12 @@ use std::{
 };
 
 use deepsize::DeepSizeOf;
+use serde::{Deserialize, Serialize};
 
-use crate::label::{LabelOrEnd, ScopeGraphLabel};
+use crate::{
+    label::{LabelOrEnd, ScopeGraphLabel},
+    path::{Path, ReversePath},
+};
 
 pub struct LabelOrderBuilder<Lbl>
 where
@@ -135,7 +139,7 @@ where
     }
 }
 
-#[derive(Clone, Debug, Hash, PartialEq, Eq, Default, DeepSizeOf)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Default, DeepSizeOf, Serialize, Deserialize)]
 pub struct LabelOrder<Lbl>
 where
     Lbl: ScopeGraphLabel,
@@ -162,6 +166,16 @@ where
         }
     }
 
+    /// Returns true if `lbl1 < lbl2`, ie `lbl1` has a higher priority
+    pub fn label_is_less(&self, lbl1: &Lbl, lbl2: &Lbl) -> bool {
+        self.is_less_internal(lbl1, lbl2)
+    }
+
+    /// Returns true if path `p1` is preferred over path `p2`, see [`PathOrd`]
+    pub fn path_is_less(&self, p1: &[Lbl], p2: &[Lbl]) -> bool {
+        PathOrd::new(self).labels_are_less(p1, p2)
+    }
+
     // returns true if lbl 1 is less than label2 (so higher priority)
     fn is_less_internal(&self, lbl1: &Lbl, lbl2: &Lbl) -> bool {
         let Some((_, less_thans)) = self.orders.iter().find(|(l, _)| l == lbl1) else {
@@ -171,6 +185,130 @@ where
     }
 }
 
+/// Paths that can be compared by [`PathOrd`]
+pub trait LabelPath<Lbl: ScopeGraphLabel> {
+    /// Labels of the path, in traversal order
+    fn path_labels(&self) -> Vec<Lbl>;
+}
+
+impl<Lbl: ScopeGraphLabel> LabelPath<Lbl> for Path<Lbl> {
+    fn path_labels(&self) -> Vec<Lbl> {
+        self.labels()
+    }
+}
+
+impl<Lbl: ScopeGraphLabel> LabelPath<Lbl> for ReversePath<Lbl> {
+    fn path_labels(&self) -> Vec<Lbl> {
+        self.labels()
+    }
+}
+
+impl<Lbl: ScopeGraphLabel> LabelPath<Lbl> for [Lbl] {
+    fn path_labels(&self) -> Vec<Lbl> {
+        self.to_vec()
+    }
+}
+
+impl<Lbl: ScopeGraphLabel> LabelPath<Lbl> for Vec<Lbl> {
+    fn path_labels(&self) -> Vec<Lbl> {
+        self.clone()
+    }
+}
+
+/// Compares paths using a label order.
+///
+/// Follows the lexicographic path order of Statix:
+/// paths are compared on the first label where they differ, where the end of a path is `$`.
+/// * `l1 < l2` in the label order: the path with `l1` is less (preferred).
+/// * `l1` and `l2` are not ordered: the paths are incomparable (`None`).
+/// * One path is a prefix of the other: `$` is compared to the next label of the longer path.
+///   Like the resolvers in this crate, `$` is always less than any label, so the prefix is less.
+///
+/// Only the labels are compared, scopes are ignored.
+/// For a total order that ignores the label order, see [`PathOrd::cmp_lexicographic`].
+pub struct PathOrd<'a, Lbl>
+where
+    Lbl: ScopeGraphLabel,
+{
+    order: &'a LabelOrder<Lbl>,
+}
+
+impl<'a, Lbl> PathOrd<'a, Lbl>
+where
+    Lbl: ScopeGraphLabel,
+{
+    pub fn new(order: &'a LabelOrder<Lbl>) -> Self {
+        Self { order }
+    }
+
+    /// Compare two paths by label order, `None` if they are incomparable.
+    pub fn partial_cmp<P>(&self, p1: &P, p2: &P) -> Option<std::cmp::Ordering>
+    where
+        P: LabelPath<Lbl> + ?Sized,
+    {
+        self.partial_cmp_labels(&p1.path_labels(), &p2.path_labels())
+    }
+
+    /// Returns true if `p1` is strictly less (preferred) than `p2`.
+    pub fn is_less<P>(&self, p1: &P, p2: &P) -> bool
+    where
+        P: LabelPath<Lbl> + ?Sized,
+    {
+        self.partial_cmp(p1, p2).is_some_and(|o| o.is_lt())
+    }
+
+    /// Compare two sequences of labels by label order
+    pub fn partial_cmp_labels(&self, p1: &[Lbl], p2: &[Lbl]) -> Option<std::cmp::Ordering> {
+        use std::cmp::Ordering;
+        let first_diff = p1.iter().zip(p2.iter()).find(|(l1, l2)| l1 != l2);
+        match first_diff {
+            Some((l1, l2)) if self.order.label_is_less(l1, l2) => Some(Ordering::Less),
+            Some((l1, l2)) if self.order.label_is_less(l2, l1) => Some(Ordering::Greater),
+            Some(_) => None,
+            // one is a prefix of the other, $ < l for any label l
+            None => Some(p1.len().cmp(&p2.len())),
+        }
+    }
+
+    fn labels_are_less(&self, p1: &[Lbl], p2: &[Lbl]) -> bool {
+        self.partial_cmp_labels(p1, p2).is_some_and(|o| o.is_lt())
+    }
+
+    /// Returns all paths for which no other path is strictly less.
+    ///
+    /// This is the `min` of a Statix query: the paths that would not be shadowed.
+    pub fn min_paths<'p, P>(&self, paths: &'p [P]) -> Vec<&'p P>
+    where
+        P: LabelPath<Lbl>,
+    {
+        let labels = paths.iter().map(|p| p.path_labels()).collect::<Vec<_>>();
+        paths
+            .iter()
+            .zip(labels.iter())
+            .filter(|(_, l1)| !labels.iter().any(|l2| self.labels_are_less(l2, l1)))
+            .map(|(p, _)| p)
+            .collect()
+    }
+
+    /// Total order on paths that only uses the `Ord` of the labels, not the label order.
+    ///
+    /// Useful for sorting paths deterministically.
+    pub fn cmp_lexicographic<P>(p1: &P, p2: &P) -> std::cmp::Ordering
+    where
+        P: LabelPath<Lbl> + ?Sized,
+    {
+        p1.path_labels().cmp(&p2.path_labels())
+    }
+
+    /// Sorts paths lexicographically, see [`PathOrd::cmp_lexicographic`]
+    pub fn sort_lexicographic<P>(paths: &mut [P])
+    where
+        P: LabelPath<Lbl>,
+    {
+        paths.sort_by_cached_key(|p| p.path_labels());
+    }
+}
+
 impl<Lbl> std::fmt::Display for LabelOrder<Lbl>
 where
     Lbl: ScopeGraphLabel,
@@ -203,8 +341,110 @@ where
 mod tests {
     use std::cmp::Ordering;
 
+    use rand::{Rng, SeedableRng, rngs::SmallRng};
+
     use super::*;
 
+    const NUM_CASES: usize = 500;
+    const LABELS: [char; 4] = ['a', 'b', 'c', 'd'];
+
+    /// a < b, a < c, b < d (so also a < d)
+    fn test_order() -> LabelOrder<char> {
+        LabelOrderBuilder::new()
+            .push('a', 'b')
+            .push('a', 'c')
+            .push('b', 'd')
+            .build()
+    }
+
+    fn random_path(rng: &mut SmallRng) -> Vec<char> {
+        let len = rng.random_range(0..6);
+        (0..len)
+            .map(|_| LABELS[rng.random_range(0..LABELS.len())])
+            .collect()
+    }
+
+    #[test]
+    fn test_path_ord_statix() {
+        // min $ < P, P < Q
+        let order = LabelOrderBuilder::new().push('P', 'Q').build();
+        let ord = PathOrd::new(&order);
+        // $ < P, so a path ending earlier is preferred
+        assert!(ord.is_less(&vec![], &vec!['P']));
+        assert!(ord.is_less(&vec!['P'], &vec!['P', 'P']));
+        // first differing label decides, regardless of length
+        assert!(ord.is_less(&vec!['P', 'P', 'P'], &vec!['Q']));
+        assert!(ord.is_less(&vec!['P', 'P'], &vec!['P', 'Q']));
+        // unordered labels are incomparable
+        assert_eq!(ord.partial_cmp(&vec!['P', 'R'], &vec!['P', 'Q']), None);
+        assert_eq!(
+            ord.partial_cmp(&vec!['P'], &vec!['P']),
+            Some(Ordering::Equal)
+        );
+
+        let path = Path::start(0).step('P', 1, 0).step('Q', 2, 0);
+        let rev = ReversePath::from(&path);
+        assert_eq!(ord.partial_cmp(&path, &path), Some(Ordering::Equal));
+        assert!(order.path_is_less(&['P'], &rev.labels()));
+    }
+
+    #[test]
+    fn test_path_ord_properties() {
+        let order = test_order();
+        let ord = PathOrd::new(&order);
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        for _ in 0..NUM_CASES {
+            let p1 = random_path(&mut rng);
+            let p2 = random_path(&mut rng);
+            let p3 = random_path(&mut rng);
+
+            // irreflexive
+            assert!(!ord.is_less(&p1, &p1), "{p1:?} < {p1:?}");
+            // antisymmetric
+            assert_eq!(
+                ord.partial_cmp(&p1, &p2),
+                ord.partial_cmp(&p2, &p1).map(Ordering::reverse),
+                "{p1:?} {p2:?}"
+            );
+            // transitive
+            if ord.is_less(&p1, &p2) && ord.is_less(&p2, &p3) {
+                assert!(ord.is_less(&p1, &p3), "{p1:?} < {p2:?} < {p3:?}");
+            }
+            // a prefix is always preferred, since $ < l
+            let mut extended = p1.clone();
+            extended.push(LABELS[rng.random_range(0..LABELS.len())]);
+            assert!(ord.is_less(&p1, &extended), "{p1:?} {extended:?}");
+            // a smaller first label wins, regardless of the rest of the path
+            let mut p_a = vec!['a'];
+            p_a.extend(p1.iter());
+            let mut p_b = vec!['b'];
+            p_b.extend(p2.iter());
+            assert!(ord.is_less(&p_a, &p_b), "{p_a:?} {p_b:?}");
+        }
+    }
+
+    #[test]
+    fn test_min_paths() {
+        let order = test_order();
+        let ord = PathOrd::new(&order);
+        let mut rng = SmallRng::seed_from_u64(1);
+
+        for _ in 0..NUM_CASES {
+            let mut paths = (0..rng.random_range(1..8))
+                .map(|_| random_path(&mut rng))
+                .collect::<Vec<_>>();
+            let min = ord.min_paths(&paths);
+            assert!(!min.is_empty());
+            for m in &min {
+                assert!(!paths.iter().any(|p| ord.is_less(p, *m)));
+            }
+
+            PathOrd::<char>::sort_lexicographic(&mut paths);
+            assert!(paths.is_sorted());
+        }
+    }
+
     #[test]
     fn test_inference() {
         let order = LabelOrderBuilder::new()
@@ -10,6 +10,7 @@ use graphing::{
     mermaid::{item::MermaidItem, theme::EdgeType},
     plantuml::{EdgeDirection, PlantUmlItem},
 };
+use serde::{Deserialize, Serialize};
 
 use crate::{
     label::ScopeGraphLabel, path::segment::PathSegment, scope::Scope, util::ContainsContainer,
@@ -18,7 +19,14 @@ use crate::{
 /// Path enum "starts" at the target scope, ie its in reverse order
 ///
 /// This holds a path using a pointer to the head path segment.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, DeepSizeOf)]
+///
+/// Serialized as a flat list of steps, see [`PathSteps`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, DeepSizeOf, Serialize, Deserialize)]
+#[serde(
+    into = "PathSteps<Lbl>",
+    from = "PathSteps<Lbl>",
+    bound(serialize = "Lbl: Serialize", deserialize = "Lbl: Deserialize<'de>")
+)]
 pub enum Path<Lbl>
 where
     Lbl: ScopeGraphLabel,
@@ -154,6 +162,19 @@ where
         }
     }
 
+    /// Labels of this path, in the order they were traversed (so starting at `start_scope()`)
+    pub fn labels(&self) -> Vec<Lbl> {
+        let mut labels = self
+            .iter()
+            .filter_map(|p| match p {
+                Self::Start(_) => None,
+                Self::Step { label, .. } => Some(label.clone()),
+            })
+            .collect::<Vec<_>>();
+        labels.reverse();
+        labels
+    }
+
     pub fn parent(&self) -> Option<&Self> {
         match self {
             Self::Start(_) => None,
@@ -315,6 +336,50 @@ where
     }
 }
 
+/// Flat representation of a [`Path`], used for (de)serialization.
+///
+/// Serializing the linked segments directly would nest as deep as the path is long.
+#[derive(Serialize, Deserialize)]
+struct PathSteps<Lbl> {
+    start: Scope,
+    /// (label, target, automaton index) in the order they were taken
+    steps: Vec<(Lbl, Scope, usize)>,
+}
+
+impl<Lbl: ScopeGraphLabel> From<Path<Lbl>> for PathSteps<Lbl> {
+    fn from(value: Path<Lbl>) -> Self {
+        let mut steps = Vec::with_capacity(value.len());
+        let mut current = &value;
+        while let Path::Step {
+            label,
+            target,
+            automaton_idx,
+            from,
+            ..
+        } = current
+        {
+            steps.push((label.clone(), *target, *automaton_idx));
+            current = from;
+        }
+        steps.reverse();
+        Self {
+            start: current.target(),
+            steps,
+        }
+    }
+}
+
+impl<Lbl: ScopeGraphLabel> From<PathSteps<Lbl>> for Path<Lbl> {
+    fn from(value: PathSteps<Lbl>) -> Self {
+        value
+            .steps
+            .into_iter()
+            .fold(Path::start(value.start), |p, (label, target, idx)| {
+                p.step(label, target, idx)
+            })
+    }
+}
+
 impl<Lbl> std::fmt::Display for Path<Lbl>
 where
     Lbl: ScopeGraphLabel + Clone,
@@ -349,8 +414,9 @@ impl<'a, Lbl: ScopeGraphLabel> Iterator for PathIterator<'a, Lbl> {
 /// This is more efficient for the cache
 ///
 /// Internally, this is the exact same structure, however the "start scope" now refers to the tail instead
-#[derive(Debug, Clone, PartialEq, Eq, DeepSizeOf)]
+#[derive(Debug, Clone, PartialEq, Eq, DeepSizeOf, Serialize, Deserialize)]
 #[repr(transparent)]
+#[serde(bound(serialize = "Lbl: Serialize", deserialize = "Lbl: Deserialize<'de>"))]
 pub struct ReversePath<Lbl>(Path<Lbl>)
 where
     Lbl: ScopeGraphLabel + Clone;
@@ -459,6 +525,18 @@ where
         self.0.partially_contains(&other.0)
     }
 
+    /// Labels of this path, starting at `start_scope()`
+    pub fn labels(&self) -> Vec<Lbl> {
+        // stored in reverse, so the head is the first step of the path
+        self.0
+            .iter()
+            .filter_map(|p| match p {
+                Path::Start(_) => None,
+                Path::Step { label, .. } => Some(label.clone()),
+            })
+            .collect()
+    }
+
     /// Step forward (p -> new p)
     #[inline(always)]
     pub fn step(&self, label: Lbl, scope: Scope, automaton_idx: usize) -> Self {
@@ -491,8 +569,10 @@ mod tests {
             .step('c', Scope(2), 0)
             .step('d', Scope(3), 0);
         println!("{}", path);
-        let rev = ReversePath::from(path);
+        let rev = ReversePath::from(path.clone());
         println!("{}", rev);
+        assert_eq!(path.labels(), vec!['c', 'd']);
+        assert_eq!(rev.labels(), vec!['c', 'd']);
     }
 
     #[test]
@@ -0,0 +1,57 @@
+use deepsize::DeepSizeOf;
+use serde::{Deserialize, Serialize};
+
+use crate::label::ScopeGraphLabel;
+
+/// Named set of labels, which matches any of its labels when used in a [`Regex`](super::Regex).
+///
+/// ```ignore
+/// // lexical = P|Q
+/// let lexical = LabelClass::new("lexical", [SgLabel::Parent, SgLabel::Extend]);
+/// let regex = Regex::concat(Regex::kleene(lexical), SgLabel::Declaration);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, DeepSizeOf)]
+pub struct LabelClass<Lbl>
+where
+    Lbl: ScopeGraphLabel,
+{
+    name: String,
+    /// Sorted and without duplicates
+    labels: Vec<Lbl>,
+}
+
+impl<Lbl> LabelClass<Lbl>
+where
+    Lbl: ScopeGraphLabel,
+{
+    pub fn new(name: impl ToString, labels: impl IntoIterator<Item = Lbl>) -> Self {
+        let mut labels = labels.into_iter().collect::<Vec<_>>();
+        labels.sort();
+        labels.dedup();
+        Self {
+            name: name.to_string(),
+            labels,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn labels(&self) -> &[Lbl] {
+        &self.labels
+    }
+
+    pub fn contains(&self, label: &Lbl) -> bool {
+        self.labels.binary_search(label).is_ok()
+    }
+}
+
+impl<Lbl> std::fmt::Display for LabelClass<Lbl>
+where
+    Lbl: ScopeGraphLabel,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}]", self.name)
+    }
+}
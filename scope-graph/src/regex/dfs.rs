@@ -9,18 +9,21 @@ use graphing::{
     },
     plantuml::{EdgeDirection, NodeType, PlantUmlDiagram, PlantUmlItem},
 };
+use serde::{Deserialize, Serialize};
 
 use crate::label::ScopeGraphLabel;
 
-use super::Regex;
+use super::{LabelClass, Regex};
 
-#[derive(Clone, Debug, Hash, PartialEq, Eq, DeepSizeOf)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, DeepSizeOf, Serialize, Deserialize)]
 pub struct AutomatonNode<Lbl>
 where
     Lbl: ScopeGraphLabel,
 {
     pub value: Regex<Lbl>,
     pub edges: Vec<(Lbl, usize)>,
+    /// Transitions for all labels of a class, if they all lead to the same node
+    pub class_edges: Vec<(LabelClass<Lbl>, usize)>,
 }
 
 impl<Lbl> AutomatonNode<Lbl>
@@ -31,6 +34,7 @@ where
         Self {
             value: val,
             edges: Vec::new(),
+            class_edges: Vec::new(),
         }
     }
 
@@ -39,10 +43,26 @@ where
             .iter()
             .find(|(l, _)| l == lbl)
             .map(|(_, idx)| idx)
+            .or_else(|| {
+                self.class_edges
+                    .iter()
+                    .find(|(c, _)| c.contains(lbl))
+                    .map(|(_, idx)| idx)
+            })
+    }
+
+    /// All outgoing transitions with the label or class name they are taken on
+    fn edge_labels(&self) -> impl Iterator<Item = (String, usize)> + '_ {
+        let edges = self.edges.iter().map(|(l, idx)| (l.to_string(), *idx));
+        let class_edges = self
+            .class_edges
+            .iter()
+            .map(|(c, idx)| (c.to_string(), *idx));
+        edges.chain(class_edges)
     }
 }
 
-#[derive(Clone, Debug, Hash, PartialEq, Eq, DeepSizeOf)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, DeepSizeOf, Serialize, Deserialize)]
 pub struct RegexAutomaton<Lbl>
 where
     Lbl: ScopeGraphLabel,
@@ -75,6 +95,7 @@ where
             }
 
             let alfabet = key.leading_labels();
+            let mut edges = Vec::with_capacity(alfabet.len());
             // println!("(key, alfabet): {0:?}", (&key, &alfabet));
             for a in &alfabet {
                 let derivative = key.derivative(a).reduce();
@@ -88,9 +109,30 @@ where
                     self.get_node_idx(&derivative).unwrap()
                 };
 
-                let node = self.get_node_mut(&key).unwrap();
-                node.edges.push(((*a).clone(), derivative_idx));
+                edges.push(((*a).clone(), derivative_idx));
             }
+
+            // replace the edges of a class by a single edge if they all go to the same node
+            let mut class_edges = Vec::new();
+            for class in key.classes() {
+                let mut targets = class.labels().iter().map(|l| {
+                    edges
+                        .iter()
+                        .find(|(a, _)| a == l)
+                        .map(|(_, idx): &(Lbl, usize)| *idx)
+                });
+                let Some(Some(target)) = targets.next() else {
+                    continue;
+                };
+                if targets.all(|t| t == Some(target)) {
+                    edges.retain(|(a, _)| !class.contains(a));
+                    class_edges.push((class.clone(), target));
+                }
+            }
+
+            let node = self.get_node_mut(&key).unwrap();
+            node.edges.extend(edges);
+            node.class_edges.extend(class_edges);
         }
     }
 
@@ -188,10 +230,10 @@ where
 
         let edges = self.node_vec.iter().enumerate().flat_map(|(idx, node)| {
             let from = Self::node_key(idx);
-            node.edges.iter().map(move |(lbl, target_idx)| {
-                let to = Self::node_key(*target_idx);
+            node.edge_labels().map(move |(lbl, target_idx)| {
+                let to = Self::node_key(target_idx);
 
-                MermaidItem::edge(&from, to, lbl.to_string(), EdgeType::Solid)
+                MermaidItem::edge(&from, to, lbl, EdgeType::Solid)
             })
         });
 
@@ -201,6 +243,44 @@ where
         diagram
     }
 
+    /// Graphviz DOT representation of the automaton.
+    ///
+    /// Every state is labelled with its index and the derivative it represents, accepting states
+    /// are drawn with a double circle.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph RegexAutomaton {\n");
+        dot.push_str("    rankdir=LR;\n");
+        dot.push_str("    node [shape=circle];\n");
+        dot.push_str("    start [shape=point];\n");
+        for (idx, node) in self.node_vec.iter().enumerate() {
+            let shape = if node.value.is_nullable() {
+                "doublecircle"
+            } else {
+                "circle"
+            };
+            let label = format!("{idx}\\n{}", node.value).replace('"', "\\\"");
+            dot.push_str(&format!(
+                "    {} [label=\"{label}\", shape={shape}];\n",
+                Self::node_key(idx)
+            ));
+        }
+        if !self.is_empty() {
+            dot.push_str(&format!("    start -> {};\n", Self::node_key(0)));
+        }
+        for (idx, node) in self.node_vec.iter().enumerate() {
+            for (lbl, target_idx) in node.edge_labels() {
+                dot.push_str(&format!(
+                    "    {} -> {} [label=\"{}\"];\n",
+                    Self::node_key(idx),
+                    Self::node_key(target_idx),
+                    lbl.replace('"', "\\\"")
+                ));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
     pub fn to_uml(&self) -> PlantUmlDiagram {
         let mut diagram = PlantUmlDiagram::new("Regex Automata");
 
@@ -210,15 +290,15 @@ where
 
         let edges = self.node_vec.iter().enumerate().flat_map(|(idx, node)| {
             let from = Self::node_key(idx);
-            node.edges.iter().map(move |(lbl, target_idx)| {
-                let to = Self::node_key(*target_idx);
+            node.edge_labels().map(move |(lbl, target_idx)| {
+                let to = Self::node_key(target_idx);
                 let dir = if from == to {
                     EdgeDirection::Right
                 } else {
                     EdgeDirection::Unspecified
                 };
 
-                PlantUmlItem::edge(&from, to, lbl.to_string(), dir)
+                PlantUmlItem::edge(&from, to, lbl, dir)
             })
         });
 
@@ -267,6 +347,17 @@ mod tests {
         println!("{:?}", timer.elapsed());
     }
 
+    #[test]
+    fn test_to_dot() {
+        let automata = RegexAutomaton::from_regex(Regex::concat(Regex::kleene('P'), 'D'));
+        let dot = automata.to_dot();
+        assert!(dot.starts_with("digraph"));
+        assert!(dot.contains("start -> n0;"));
+        // P*D -D-> eps, which is the only accepting state
+        assert_eq!(dot.matches("doublecircle").count(), 1);
+        assert!(dot.contains("n0 -> n0 [label=\"P\"];"));
+    }
+
     #[test]
     fn test_is_match() {
         let regex = Regex::kleene('a');
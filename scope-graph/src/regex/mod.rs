@@ -1,16 +1,18 @@
+mod class;
 pub mod dfs;
 mod partial;
 
+pub use class::LabelClass;
 use deepsize::DeepSizeOf;
 use dfs::RegexAutomaton;
-pub use partial::RegexState;
+pub use partial::{MatchState, RegexState};
 use serde::{Deserialize, Serialize};
 
 use crate::label::ScopeGraphLabel;
 
 /// Regular expressions with labels
 ///
-/// todo: allow easy way to match any label, without having to do an OR of all labels by
+/// Use a [`LabelClass`] to match any label out of a set, instead of an OR of all labels.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, DeepSizeOf)]
 pub enum Regex<Lbl>
 where
@@ -22,6 +24,8 @@ where
     ZeroSet,
     /// `a`
     Character(Lbl),
+    /// `[class]`, matches any label in the class
+    Class(LabelClass<Lbl>),
     /// r . s
     Concat(Box<Self>, Box<Self>),
     /// r*
@@ -45,6 +49,7 @@ where
             Self::EmptyString => write!(f, "ε"),
             Self::ZeroSet => write!(f, "∅"),
             Self::Character(c) => write!(f, "{}", c.char()),
+            Self::Class(c) => write!(f, "{c}"),
             Self::Concat(r, s) => write!(f, "{r}{s}"), // r dot s
             Self::KleeneStar(r) => write!(f, "{r}*"),
             Self::Or(r, s) => write!(f, "({r}+{s})"),
@@ -64,6 +69,15 @@ where
     }
 }
 
+impl<Lbl> From<LabelClass<Lbl>> for Regex<Lbl>
+where
+    Lbl: ScopeGraphLabel,
+{
+    fn from(c: LabelClass<Lbl>) -> Self {
+        Self::Class(c)
+    }
+}
+
 impl<Lbl> Regex<Lbl>
 where
     Lbl: ScopeGraphLabel,
@@ -114,6 +128,22 @@ where
         Self::concat(r.clone(), Self::kleene(r))
     }
 
+    /// Same regex with every label replaced by `f(label)`
+    pub fn map_labels<M: ScopeGraphLabel>(&self, f: &impl Fn(&Lbl) -> M) -> Regex<M> {
+        match self {
+            Self::EmptyString => Regex::EmptyString,
+            Self::ZeroSet => Regex::ZeroSet,
+            Self::Character(l) => Regex::Character(f(l)),
+            Self::Class(c) => Regex::Class(LabelClass::new(c.name(), c.labels().iter().map(f))),
+            Self::Concat(r, s) => Regex::concat(r.map_labels(f), s.map_labels(f)),
+            Self::KleeneStar(r) => Regex::kleene(r.map_labels(f)),
+            Self::QuestionMark(r) => Regex::question(r.map_labels(f)),
+            Self::Or(r, s) => Regex::or(r.map_labels(f), s.map_labels(f)),
+            Self::And(r, s) => Regex::and(r.map_labels(f), s.map_labels(f)),
+            Self::Neg(r) => Regex::neg(r.map_labels(f)),
+        }
+    }
+
     pub fn is_nullable(&self) -> bool {
         self.v() == Regex::EmptyString
     }
@@ -127,7 +157,7 @@ where
         match self {
             Self::EmptyString => Self::EmptyString,
             Self::ZeroSet => Self::ZeroSet,
-            Self::Character(_) => Self::ZeroSet,
+            Self::Character(_) | Self::Class(_) => Self::ZeroSet,
             Self::And(r, s) | Self::Concat(r, s) => match (r.v(), s.v()) {
                 (Self::EmptyString, Self::EmptyString) => Self::EmptyString,
                 _ => Self::ZeroSet,
@@ -154,6 +184,8 @@ where
             Self::ZeroSet => Self::ZeroSet,
             Self::Character(a) if dim == a => Self::EmptyString,
             Self::Character(_) => Self::ZeroSet, // dim != a
+            Self::Class(c) if c.contains(dim) => Self::EmptyString,
+            Self::Class(_) => Self::ZeroSet,
             Self::Concat(r, s) => {
                 let lhs = Regex::concat(r.derivative(dim), *s.clone());
                 let rhs = Regex::concat(r.v(), s.derivative(dim));
@@ -174,6 +206,7 @@ where
             Self::Character(l) => {
                 vec![l]
             }
+            Self::Class(c) => c.labels().iter().collect(),
             Self::Concat(r, s) | Self::Or(r, s) | Self::And(r, s) => {
                 let mut v = Vec::new();
                 v.append(&mut r.unique_labels());
@@ -186,6 +219,22 @@ where
         v
     }
 
+    /// Returns all label classes in the regex
+    fn classes(&self) -> Vec<&LabelClass<Lbl>> {
+        let mut v = match self {
+            Self::EmptyString | Self::ZeroSet | Self::Character(_) => Vec::new(),
+            Self::Class(c) => vec![c],
+            Self::Concat(r, s) | Self::Or(r, s) | Self::And(r, s) => {
+                let mut v = r.classes();
+                v.append(&mut s.classes());
+                v
+            }
+            Self::KleeneStar(r) | Self::QuestionMark(r) | Self::Neg(r) => r.classes(),
+        };
+        v.dedup();
+        v
+    }
+
     /// Returns all leading labels in the regex
     ///
     /// Leading labels are the labels that are not trivially the empty set.
@@ -207,6 +256,7 @@ where
             Self::Character(l) => {
                 vec![l]
             }
+            Self::Class(c) => c.labels().iter().collect(),
             // in concat and and, lhs is always considered first
             Self::Concat(r, s) | Self::And(r, s) => {
                 let mut v = Vec::new();
@@ -236,6 +286,8 @@ where
             Self::EmptyString => Self::EmptyString,
             Self::ZeroSet => Self::ZeroSet,
             Self::Character(_) => self,
+            Self::Class(ref c) if c.labels().is_empty() => Self::ZeroSet,
+            Self::Class(_) => self,
             Self::And(r, s) | Self::Concat(r, s) => match (r.reduce(), s.reduce()) {
                 (Self::ZeroSet, _) | (_, Self::ZeroSet) => Self::ZeroSet,
                 (Self::EmptyString, s) => s,
@@ -286,4 +338,26 @@ mod tests {
         let leading = r.leading_labels();
         println!("leading: {0:?}", leading);
     }
+
+    #[test]
+    fn test_label_class() {
+        let lexical = LabelClass::new("lexical", ['P', 'Q']);
+        let automaton = Regex::concat(Regex::kleene(lexical), 'D').compile();
+        assert!(automaton.is_match(&['P', 'Q', 'P', 'D']));
+        assert!(automaton.is_match(&['D']));
+        assert!(!automaton.is_match(&['P', 'E', 'D']));
+
+        // P and Q are stored as a single transition
+        let start = automaton.get_node(0).unwrap();
+        assert_eq!(start.class_edges.len(), 1);
+        assert_eq!(start.edges.len(), 1);
+
+        // labels of the class that lead to different nodes get their own transitions
+        let lexical = LabelClass::new("lexical", ['P', 'Q']);
+        let automaton = Regex::or(Regex::concat(lexical, 'D'), Regex::concat('P', 'P')).compile();
+        assert!(automaton.is_match(&['Q', 'D']));
+        assert!(automaton.is_match(&['P', 'P']));
+        assert!(!automaton.is_match(&['Q', 'P']));
+        assert!(automaton.get_node(0).unwrap().class_edges.is_empty());
+    }
 }
@@ -1,7 +1,27 @@
 use crate::label::ScopeGraphLabel;
 
-use super::dfs::RegexAutomaton;
+use super::{Regex, dfs::RegexAutomaton};
 
+/// Result of feeding a label to a [`RegexState`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatchState {
+    /// The labels so far match the regex
+    Accepting,
+    /// The labels so far do not match the regex, but more labels could make them match
+    Partial,
+    /// The label can not lead to a match, the state is left unchanged
+    Rejected,
+}
+
+/// Position in a compiled [`RegexAutomaton`], for matching labels one at a time.
+///
+/// ```ignore
+/// let automaton = Regex::concat(Regex::kleene(SgLabel::Parent), SgLabel::Declaration).compile();
+/// let mut state = RegexState::new(&automaton);
+/// assert_eq!(state.advance(&SgLabel::Parent), MatchState::Partial);
+/// assert_eq!(state.advance(&SgLabel::Declaration), MatchState::Accepting);
+/// assert_eq!(state.advance(&SgLabel::Parent), MatchState::Rejected);
+/// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct RegexState<'a, Lbl>
 where
@@ -60,4 +80,90 @@ where
             .get_node(self.idx)
             .is_some_and(|node| node.value.is_nullable())
     }
+
+    /// Feeds a single label to the automaton.
+    ///
+    /// If the label is rejected, the state does not change, so a different label can be tried.
+    pub fn advance(&mut self, label: &Lbl) -> MatchState {
+        let next = self
+            .automata
+            .get_node(self.idx)
+            .and_then(|node| node.get_edge(label))
+            .copied();
+        match next {
+            Some(next) if !self.is_dead(next) => {
+                self.prev_idx = self.idx;
+                self.idx = next;
+                self.match_state()
+            }
+            _ => MatchState::Rejected,
+        }
+    }
+
+    /// Feeds labels until one is rejected, returns the state after the last label.
+    pub fn advance_all<'l>(&mut self, labels: impl IntoIterator<Item = &'l Lbl>) -> MatchState
+    where
+        Lbl: 'l,
+    {
+        let mut state = self.match_state();
+        for label in labels {
+            state = self.advance(label);
+            if state == MatchState::Rejected {
+                break;
+            }
+        }
+        state
+    }
+
+    /// Whether the labels fed so far match the regex
+    pub fn match_state(&self) -> MatchState {
+        if self.is_dead(self.idx) {
+            MatchState::Rejected
+        } else if self.is_accepting() {
+            MatchState::Accepting
+        } else {
+            MatchState::Partial
+        }
+    }
+
+    /// Go back to the start of the automaton
+    pub fn reset(&mut self) {
+        self.idx = 0;
+        self.prev_idx = 0;
+    }
+
+    pub fn automaton(&self) -> &'a RegexAutomaton<Lbl> {
+        self.automata
+    }
+
+    /// State that can never lead to a match
+    fn is_dead(&self, idx: usize) -> bool {
+        self.automata
+            .get_node(idx)
+            .is_none_or(|node| node.value == Regex::ZeroSet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance() {
+        let automaton = Regex::concat(Regex::kleene('P'), 'D').compile();
+        let mut state = RegexState::new(&automaton);
+        assert_eq!(state.match_state(), MatchState::Partial);
+        assert_eq!(state.advance(&'P'), MatchState::Partial);
+        assert_eq!(state.advance(&'Q'), MatchState::Rejected);
+        assert_eq!(state.advance(&'D'), MatchState::Accepting);
+        assert_eq!(state.advance(&'D'), MatchState::Rejected);
+        assert_eq!(state.match_state(), MatchState::Accepting);
+
+        state.reset();
+        assert_eq!(state.advance_all(&['P', 'P', 'D']), MatchState::Accepting);
+        state.reset();
+        assert_eq!(state.advance_all(&['P', 'E', 'D']), MatchState::Rejected);
+        // stops at the rejected label
+        assert_eq!(state.match_state(), MatchState::Partial);
+    }
 }
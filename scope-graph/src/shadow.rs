@@ -0,0 +1,74 @@
+use crate::{data::ScopeGraphData, graph::QueryResult, label::ScopeGraphLabel};
+
+/// Decides whether an environment shadows another environment.
+///
+/// Resolvers call this for every pair of *equivalent* environments (same projected data), where
+/// `preferred` was found through a label with higher priority than `candidate`.
+/// Only the candidate can be shadowed, preferred environments are always kept.
+///
+/// The policy is part of the cache key of a query, so it is required to be hashable.
+pub trait ShadowingPolicy<Lbl, Data>: std::hash::Hash + Eq
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    /// Returns true if `candidate` should be removed in favour of `preferred`
+    fn shadows(
+        &self,
+        preferred: &QueryResult<Lbl, Data>,
+        candidate: &QueryResult<Lbl, Data>,
+    ) -> bool;
+}
+
+/// Default shadowing: environments found via a label with higher priority always shadow
+/// equivalent environments found via a lower priority label.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LabelOrderShadowing;
+
+impl<Lbl, Data> ShadowingPolicy<Lbl, Data> for LabelOrderShadowing
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    #[inline(always)]
+    fn shadows(&self, _: &QueryResult<Lbl, Data>, _: &QueryResult<Lbl, Data>) -> bool {
+        true
+    }
+}
+
+/// Only shadows environments with a path that is at least as long as the preferred path.
+///
+/// Shorter paths through a lower priority label are kept.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShortestPathShadowing;
+
+impl<Lbl, Data> ShadowingPolicy<Lbl, Data> for ShortestPathShadowing
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    fn shadows(
+        &self,
+        preferred: &QueryResult<Lbl, Data>,
+        candidate: &QueryResult<Lbl, Data>,
+    ) -> bool {
+        candidate.path.len() >= preferred.path.len()
+    }
+}
+
+/// Never shadows, returns all reachable environments.
+///
+/// This is Statix' `project *`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReturnAllShadowing;
+
+impl<Lbl, Data> ShadowingPolicy<Lbl, Data> for ReturnAllShadowing
+where
+    Lbl: ScopeGraphLabel,
+    Data: ScopeGraphData,
+{
+    #[inline(always)]
+    fn shadows(&self, _: &QueryResult<Lbl, Data>, _: &QueryResult<Lbl, Data>) -> bool {
+        false
+    }
+}
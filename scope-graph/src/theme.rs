@@ -0,0 +1,370 @@
+use graphing::{
+    Color, DIFF_ADDED_CLASS, DIFF_CHANGED_CLASS, DIFF_REMOVED_CLASS,
+    mermaid::{
+        MermaidStyleSheet,
+        theme::{
+            AnimationSpeed, AnimationStyle, ElementStyle, LineStyle as MermaidLineStyle, Size,
+        },
+    },
+    plantuml::theme::{
+        ElementCss, FontFamily, FontStyle, HorizontalAlignment, LineStyle, PlantUmlStyleSheet,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{BackGroundEdgeColor, BackgroundColor, ColorSet, ForeGroundColor};
+
+/// Named set of styles for the classes used when drawing scope graphs
+/// (`scope`, `data-scope`, `scope-edge`, the color classes of [`ColorSet`], ...).
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemePreset {
+    #[default]
+    Default,
+    /// Black and white, paths are told apart by their line style instead of their color
+    PublicationGrayscale,
+    /// Light text and lines on dark elements
+    Dark,
+}
+
+impl ThemePreset {
+    pub const ALL: &[Self] = &[Self::Default, Self::PublicationGrayscale, Self::Dark];
+
+    pub fn uml_stylesheet(&self) -> PlantUmlStyleSheet {
+        let (element_bg, data_bg, text, line, faint) = match self {
+            Self::Default => (
+                Color::new_rgb(242, 232, 230),
+                Color::new_rgb(245, 229, 220),
+                Color::BLACK,
+                Color::BLACK,
+                Color::LIGHT_GRAY,
+            ),
+            Self::PublicationGrayscale => (
+                Color::WHITE,
+                Color::LIGHT_GRAY,
+                Color::BLACK,
+                Color::BLACK,
+                Color::DARK_GRAY,
+            ),
+            Self::Dark => (
+                Color::new_rgb_u32(0x2B2B2B),
+                Color::new_rgb_u32(0x4A3B36),
+                Color::LIGHT_GRAY,
+                Color::LIGHT_GRAY,
+                Color::new_rgb_u32(0x6B6B6B),
+            ),
+        };
+
+        let mut style_sheet: PlantUmlStyleSheet = [
+            ElementCss::new()
+                .background_color(element_bg)
+                .font_color(text)
+                .font_family(FontFamily::Monospace)
+                .as_selector("element"),
+            ElementCss::new().line_color(line).as_selector("arrow"),
+            ElementCss::new()
+                .font_size(24)
+                .font_style(FontStyle::Bold)
+                .round_corner(1000)
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .as_class("scope"),
+            ElementCss::new()
+                .font_size(24)
+                .font_style(FontStyle::Bold)
+                .round_corner(10)
+                .shadowing(1)
+                .background_color(data_bg)
+                .as_class("data-scope"),
+            ElementCss::new()
+                .line_thickness(1.25)
+                .font_size(16)
+                .as_class("scope-edge"),
+            ElementCss::new()
+                .line_style(LineStyle::Dashed)
+                .as_class("query-edge"),
+            ElementCss::new()
+                .line_style(LineStyle::Dotted)
+                .line_color(faint)
+                .as_class("cache-edge"),
+            ElementCss::new().font_size(11).as_class("cache-entry"),
+        ]
+        .into();
+
+        match self {
+            Self::Default => {
+                style_sheet.merge(ForeGroundColor::uml_stylesheet());
+                style_sheet.merge(BackgroundColor::uml_stylesheet());
+                style_sheet.merge(BackGroundEdgeColor::uml_stylesheet());
+            }
+            Self::PublicationGrayscale => {
+                // the colors of a set can map to the same gray, so vary the line style as well
+                let line_styles = [
+                    LineStyle::Solid,
+                    LineStyle::Dashed,
+                    LineStyle::Dotted,
+                    LineStyle::LongDashed,
+                ];
+                style_sheet.extend((0..ForeGroundColor::COLORS.len()).map(|i| {
+                    ElementCss::new()
+                        .line_color(grayscale(ForeGroundColor::get_color(i)))
+                        .line_style(line_styles[i % line_styles.len()])
+                        .as_class(ForeGroundColor::get_class_name(i))
+                }));
+                style_sheet.extend((0..BackgroundColor::COLORS.len()).map(|i| {
+                    ElementCss::new()
+                        .background_color(grayscale(BackgroundColor::get_color(i)))
+                        .as_class(BackgroundColor::get_class_name(i))
+                }));
+                style_sheet.extend((0..BackGroundEdgeColor::COLORS.len()).map(|i| {
+                    ElementCss::new()
+                        .line_color(grayscale(BackGroundEdgeColor::get_color(i)))
+                        .line_thickness(1.25)
+                        .as_class(BackGroundEdgeColor::get_class_name(i))
+                }));
+            }
+            Self::Dark => {
+                // the light background colors would make the text unreadable
+                style_sheet.merge(ForeGroundColor::uml_stylesheet());
+                style_sheet.extend((0..ForeGroundColor::COLORS.len()).map(|i| {
+                    ElementCss::new()
+                        .background_color(darken(ForeGroundColor::get_color(i)))
+                        .as_class(BackgroundColor::get_class_name(i))
+                }));
+                style_sheet.extend((0..ForeGroundColor::COLORS.len()).map(|i| {
+                    ElementCss::new()
+                        .line_color(darken(ForeGroundColor::get_color(i)))
+                        .line_thickness(1.25)
+                        .as_class(BackGroundEdgeColor::get_class_name(i))
+                }));
+            }
+        }
+        let (added, removed, changed) = self.diff_colors();
+        style_sheet.extend([
+            ElementCss::new()
+                .line_color(added)
+                .line_thickness(2.5)
+                .as_class(DIFF_ADDED_CLASS),
+            ElementCss::new()
+                .line_color(removed)
+                .line_style(LineStyle::Dotted)
+                .as_class(DIFF_REMOVED_CLASS),
+            ElementCss::new()
+                .line_color(changed)
+                .line_style(LineStyle::Dashed)
+                .as_class(DIFF_CHANGED_CLASS),
+        ]);
+        style_sheet
+    }
+
+    pub fn mmd_stylesheet(&self) -> MermaidStyleSheet {
+        let (scope_line, data_line, data_bg) = match self {
+            Self::Default => (
+                Color::DARK_GRAY,
+                Color::BLACK,
+                Color::new_rgb(242, 232, 175),
+            ),
+            Self::PublicationGrayscale => (Color::BLACK, Color::BLACK, Color::LIGHT_GRAY),
+            Self::Dark => (
+                Color::LIGHT_GRAY,
+                Color::LIGHT_GRAY,
+                Color::new_rgb_u32(0x4A3B36),
+            ),
+        };
+
+        let mut style_sheet = MermaidStyleSheet::new()
+            .with_class(
+                "scope",
+                ElementStyle::new()
+                    .line_color(scope_line)
+                    .font_size(Size::Pt(18))
+                    .margin(Size::Px(5))
+                    .padding(Size::Px(5)),
+            )
+            .with_class(
+                "data-scope",
+                ElementStyle::new()
+                    .line_color(data_line)
+                    .background_color(data_bg),
+            )
+            .with_class("scope-edge", ElementStyle::new().line_thickness(2.5))
+            .with_class(
+                "query-edge",
+                ElementStyle::new()
+                    .line_thickness(1.5)
+                    .animation_style(AnimationStyle::Linear)
+                    .animation_speed(AnimationSpeed::Slow),
+            )
+            .with_class("cache-entry", ElementStyle::new().font_size(Size::Pt(8)))
+            .with_class("cache-edge", ElementStyle::new());
+
+        match self {
+            Self::Default => {
+                style_sheet.merge(ForeGroundColor::mmd_stylesheet());
+                style_sheet.merge(BackgroundColor::mmd_stylesheet());
+                style_sheet.merge(BackGroundEdgeColor::mmd_stylesheet());
+            }
+            Self::PublicationGrayscale => {
+                style_sheet.merge(
+                    (0..ForeGroundColor::COLORS.len())
+                        .map(|i| {
+                            let style = ElementStyle::new()
+                                .line_color(grayscale(ForeGroundColor::get_color(i)));
+                            (ForeGroundColor::get_class_name(i), style)
+                        })
+                        .collect(),
+                );
+                style_sheet.merge(
+                    (0..BackgroundColor::COLORS.len())
+                        .map(|i| {
+                            let style = ElementStyle::new()
+                                .background_color(grayscale(BackgroundColor::get_color(i)));
+                            (BackgroundColor::get_class_name(i), style)
+                        })
+                        .collect(),
+                );
+                style_sheet.merge(
+                    (0..BackGroundEdgeColor::COLORS.len())
+                        .map(|i| {
+                            let style = ElementStyle::new()
+                                .background_color(grayscale(BackGroundEdgeColor::get_color(i)))
+                                .line_thickness(1.25);
+                            (BackGroundEdgeColor::get_class_name(i), style)
+                        })
+                        .collect(),
+                );
+            }
+            Self::Dark => {
+                style_sheet.merge(ForeGroundColor::mmd_stylesheet());
+                style_sheet.merge(
+                    (0..ForeGroundColor::COLORS.len())
+                        .map(|i| {
+                            let style = ElementStyle::new()
+                                .background_color(darken(ForeGroundColor::get_color(i)));
+                            (BackgroundColor::get_class_name(i), style)
+                        })
+                        .collect(),
+                );
+                style_sheet.merge(
+                    (0..ForeGroundColor::COLORS.len())
+                        .map(|i| {
+                            let style = ElementStyle::new()
+                                .background_color(darken(ForeGroundColor::get_color(i)))
+                                .line_thickness(1.25);
+                            (BackGroundEdgeColor::get_class_name(i), style)
+                        })
+                        .collect(),
+                );
+            }
+        }
+        let (added, removed, changed) = self.diff_colors();
+        style_sheet.merge(
+            [
+                (
+                    DIFF_ADDED_CLASS.to_string(),
+                    ElementStyle::new().line_color(added).line_thickness(2.5),
+                ),
+                (
+                    DIFF_REMOVED_CLASS.to_string(),
+                    ElementStyle::new()
+                        .line_color(removed)
+                        .line_style(MermaidLineStyle::Dotted),
+                ),
+                (
+                    DIFF_CHANGED_CLASS.to_string(),
+                    ElementStyle::new()
+                        .line_color(changed)
+                        .line_style(MermaidLineStyle::Dashed),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        style_sheet
+    }
+}
+
+impl ThemePreset {
+    /// Line colors of the classes of [`graphing::Diagram::diff`], as (added, removed, changed)
+    fn diff_colors(&self) -> (Color, Color, Color) {
+        match self {
+            // the line styles are different already
+            Self::PublicationGrayscale => (Color::BLACK, Color::DARK_GRAY, Color::BLACK),
+            Self::Default | Self::Dark => (Color::GREEN, Color::RED, Color::ORANGE),
+        }
+    }
+}
+
+impl std::fmt::Display for ThemePreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::PublicationGrayscale => write!(f, "publication-grayscale"),
+            Self::Dark => write!(f, "dark"),
+        }
+    }
+}
+
+impl std::str::FromStr for ThemePreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(Self::Default),
+            "publication-grayscale" | "grayscale" => Ok(Self::PublicationGrayscale),
+            "dark" => Ok(Self::Dark),
+            _ => Err(format!(
+                "unknown theme '{s}', expected one of: default, publication-grayscale, dark"
+            )),
+        }
+    }
+}
+
+/// Gray with the same perceived brightness as `color`
+fn grayscale(color: Color) -> Color {
+    let (r, g, b) = color.rgb();
+    let luma = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+    let l = luma.round() as u8;
+    Color::new_rgb(l, l, l)
+}
+
+/// Dark shade of `color` that light text is readable on
+fn darken(color: Color) -> Color {
+    let (r, g, b) = color.rgb();
+    Color::new_rgb(r / 3, g / 3, b / 3)
+}
+
+#[cfg(test)]
+mod tests {
+    use graphing::Renderer;
+
+    use super::*;
+
+    #[test]
+    fn test_name_roundtrip() {
+        for theme in ThemePreset::ALL {
+            assert_eq!(theme.to_string().parse::<ThemePreset>(), Ok(*theme));
+        }
+        assert!("neon".parse::<ThemePreset>().is_err());
+    }
+
+    #[test]
+    fn test_presets_define_graph_classes() {
+        for theme in ThemePreset::ALL {
+            let mut uml = graphing::plantuml::PlantUmlDiagram::new("theme");
+            uml.set_style_sheet(theme.uml_stylesheet());
+            let uml = uml.render().unwrap();
+
+            let mut mmd = graphing::mermaid::MermaidDiagram::new("theme");
+            mmd.set_style_sheet(theme.mmd_stylesheet());
+            let mmd = mmd.render().unwrap();
+
+            for class in ["data-scope", "scope-edge", "background-0", "foreground-0"] {
+                assert!(uml.contains(&format!(".{class}")), "{theme}: {class}");
+                assert!(
+                    mmd.contains(&format!("classDef {class} ")),
+                    "{theme}: {class}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,36 @@
+// Compares the resolution of `CachedScopeGraph` against the scopegraphs crate on random graphs
+
+use scope_graph::bench_util::differential::DifferentialCase;
+
+const NUM_SEEDS: u64 = 200;
+const QUERIES_PER_GRAPH: usize = 20;
+
+#[test]
+fn test_same_as_scopegraphs() {
+    let mismatches = (0..NUM_SEEDS)
+        .flat_map(|seed| DifferentialCase::generate(seed, QUERIES_PER_GRAPH).run())
+        .collect::<Vec<_>>();
+    for m in &mismatches {
+        println!("{m}");
+    }
+    assert!(
+        mismatches.is_empty(),
+        "{} queries resolved differently",
+        mismatches.len()
+    );
+}
+
+#[test]
+fn test_same_as_scopegraphs_cyclic() {
+    let mismatches = (0..NUM_SEEDS)
+        .flat_map(|seed| {
+            DifferentialCase::generate(seed, QUERIES_PER_GRAPH)
+                .with_cycle(3)
+                .run()
+        })
+        .collect::<Vec<_>>();
+    for m in &mismatches {
+        println!("{m}");
+    }
+    assert!(mismatches.is_empty());
+}
@@ -14,6 +14,7 @@ use scope_graph::{
     projection::ScopeGraphDataProjection,
     regex::{Regex, dfs::RegexAutomaton},
     scope::Scope,
+    shadow::ReturnAllShadowing,
 };
 use serde::Serialize;
 
@@ -801,33 +802,70 @@ fn test_label_order_respected() {
 // ]] analysis succeeds
 //    run evaluate-test to SUCCEEDS()
 
-// #[test]
-// fn test_all_is_respected() {
-//     let mut graph = CachedScopeGraph::<TestLabel, TestData>::new();
-//     let s0 = graph.add_scope_default();
-//     let s1 = graph.add_scope_default();
-//     let s2 = graph.add_scope_default();
-//     let s3 = graph.add_scope_default();
-//     graph.add_edge(s0, s1, TestLabel::P);
-//     graph.add_edge(s0, s2, TestLabel::P);
-//     graph.add_edge(s1, s3, TestLabel::P);
-//     graph.add_edge(s2, s3, TestLabel::P);
-//     graph.as_mmd_diagram("test_all_is_respected", DRAW_CACHES)
-//     .render_to_file("output/tests/test_all_is_respected.md").unwrap();
-//     let regex: RegexAutomaton<TestLabel> = Regex::kleene(TestLabel::P)
-//     .compile();
-//     regex.to_mmd().render_to_file("output/tests/test_all_is_respected_regex.md").unwrap();
-
-//     let lo = LabelOrderBuilder::new().build();
-//     let envs = graph.query_proj(s0,
-//         &regex,
-//         &lo,
-//         TestProjection::Name,
-//         String::from("x"),
-//
-//     );
-//     assert!(!envs.is_empty());
-// }
+#[test]
+fn test_all_is_respected() {
+    let mut graph = CachedScopeGraph::<TestLabel, TestData>::new();
+    let s0 = graph.add_scope_default();
+    let s1 = graph.add_scope_default();
+    let s2 = graph.add_scope_default();
+    let s3 = graph.add_scope_default();
+    let s3_decl = graph.add_decl(s3, TestLabel::D, TestData::var("x"));
+    graph.add_edge(s0, s1, TestLabel::P);
+    graph.add_edge(s0, s2, TestLabel::P);
+    graph.add_edge(s1, s3, TestLabel::P);
+    graph.add_edge(s2, s3, TestLabel::P);
+    graph
+        .as_mmd_diagram("test_all_is_respected", false)
+        .render_to_file("output/tests/test_all_is_respected.md")
+        .unwrap();
+    let regex: RegexAutomaton<TestLabel> =
+        Regex::concat(Regex::kleene(TestLabel::P), TestLabel::D).compile();
+
+    let lo = LabelOrderBuilder::new().build();
+    let envs = graph.query_proj_with_policy(
+        s0,
+        &regex,
+        &lo,
+        TestProjection::Name,
+        String::from("x"),
+        ReturnAllShadowing,
+    );
+    // both paths via s1 and s2
+    assert_eq!(envs.len(), 2);
+    for env in envs {
+        assert!(env.path.target() == s3_decl);
+    }
+}
+
+// project * also returns environments that would otherwise be shadowed
+#[test]
+fn test_all_is_respected_with_order() {
+    let mut graph = CachedScopeGraph::<TestLabel, TestData>::new();
+    let s0 = graph.add_scope_default();
+    let s1 = graph.add_scope_default();
+    let _ = graph.add_decl(s0, TestLabel::D, TestData::var("x"));
+    let _ = graph.add_decl(s1, TestLabel::D, TestData::var("x"));
+    graph.add_edge(s0, s1, TestLabel::P);
+
+    let regex: RegexAutomaton<TestLabel> =
+        Regex::concat(Regex::kleene(TestLabel::P), TestLabel::D).compile();
+    let lo = LabelOrderBuilder::new()
+        .push(TestLabel::D, TestLabel::P)
+        .build();
+
+    let envs = graph.query_proj(s0, &regex, &lo, TestProjection::Name, String::from("x"));
+    assert_eq!(envs.len(), 1);
+
+    let envs = graph.query_proj_with_policy(
+        s0,
+        &regex,
+        &lo,
+        TestProjection::Name,
+        String::from("x"),
+        ReturnAllShadowing,
+    );
+    assert_eq!(envs.len(), 2);
+}
 
 // test project target and data is respected [[
 //   resolve {s0 s1 s2 s3}